@@ -0,0 +1,121 @@
+#ifndef ENT_RS_H
+#define ENT_RS_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/*
+ Success.
+ */
+#define ENT_OK 0
+
+/*
+ A required pointer was null.
+ */
+#define ENT_ERR_NULL -1
+
+/*
+ The analysis panicked.
+ */
+#define ENT_ERR_PANIC -2
+
+/*
+ An [`EntAccumulator`] behind an opaque C pointer.
+ */
+typedef struct ent_accumulator_t ent_accumulator_t;
+
+/*
+ Statistics of `ent`, as in [`EntStats`] without the frequency tables.
+ */
+typedef struct ent_stats_t {
+  /*
+   Shannon entropy in bits per byte (or bit).
+   */
+  double entropy;
+  /*
+   Ideal compression percentage based on entropy.
+   */
+  double compression_percent;
+  /*
+   Chi-square test value.
+   */
+  double chisquare;
+  /*
+   p-value of the chi-square test, as in [`EntStats::p_value`].
+   */
+  double p_value;
+  /*
+   Arithmetic mean; NaN for empty input.
+   */
+  double mean;
+  /*
+   Monte Carlo estimate of Pi; 0 below 6 bytes.
+   */
+  double pi_estimate;
+  /*
+   Serial correlation coefficient; -99999.0 when undefined.
+   */
+  double serial_correlation;
+} ent_stats_t;
+
+/*
+ Byte-mode statistics of `len` bytes at `data` into `out`.
+
+ # Safety
+
+ `data` must point to `len` readable bytes (it may be null if `len` is
+ 0), and `out` to writable storage for one `ent_stats_t`.
+ */
+int32_t ent_analyze(const uint8_t *data, size_t len, struct ent_stats_t *out);
+
+/*
+ Bit-mode statistics of `len` bytes at `data` into `out`.
+
+ # Safety
+
+ As for [`ent_analyze`].
+ */
+int32_t ent_analyze_bits(const uint8_t *data, size_t len, struct ent_stats_t *out);
+
+/*
+ A new streaming accumulator, to be released with
+ [`ent_accumulator_free`].
+ */
+struct ent_accumulator_t *ent_accumulator_new(bool bit_mode);
+
+/*
+ Feed `len` bytes at `data` to `acc`.
+
+ # Safety
+
+ `acc` must come from [`ent_accumulator_new`] and not have been freed;
+ `data` must point to `len` readable bytes (it may be null if `len` is 0).
+ */
+int32_t ent_accumulator_update(struct ent_accumulator_t *acc, const uint8_t *data, size_t len);
+
+/*
+ Statistics of everything fed to `acc` so far into `out`.
+
+ # Safety
+
+ `acc` must come from [`ent_accumulator_new`] and not have been freed;
+ `out` must point to writable storage for one `ent_stats_t`.
+ */
+int32_t ent_accumulator_stats(const struct ent_accumulator_t *acc, struct ent_stats_t *out);
+
+/*
+ Release an accumulator; null is ignored.
+
+ # Safety
+
+ `acc` must be null or come from [`ent_accumulator_new`], and must not be
+ used afterwards.
+ */
+void ent_accumulator_free(struct ent_accumulator_t *acc);
+
+#endif  /* ENT_RS_H */
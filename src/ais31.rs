@@ -0,0 +1,489 @@
+//! BSI AIS 31 test procedures A and B.
+//!
+//! AIS 31 is the German scheme for evaluating physical random number
+//! generators. Procedure A checks that internal random numbers behave like
+//! ideal ones with the basic tests T0 to T5; procedure B checks that the raw
+//! noise has enough entropy with tests T6 to T8. Each procedure passes if
+//! all its tests pass, and may be repeated once on fresh data if exactly one
+//! test failed.
+//!
+//! Both procedures read bits most significant first and use fresh bits for
+//! each test. Procedure A needs [`PROCEDURE_A_LEN`] bytes; procedure B
+//! about 1 MB, depending on the data.
+//!
+//! ```rust,no_run
+//! use ent_rs::ais31::{procedure_a, Verdict};
+//! let data = std::fs::read("trng.bin").unwrap();
+//! let result = procedure_a(&data).unwrap();
+//! assert_eq!(result.verdict(), Verdict::Passed);
+//! ```
+
+use std::fmt;
+
+use crate::fips140::{self, BLOCK_LEN};
+
+/// Number of 48-bit strings compared by the disjointness test T0.
+pub const DISJOINTNESS_STRINGS: usize = 1 << 16;
+
+/// Number of 20,000-bit sequences tested with T1 to T5.
+pub const PROCEDURE_A_SEQUENCES: usize = 257;
+
+/// Bytes consumed by procedure A: T0's strings, then the sequences.
+pub const PROCEDURE_A_LEN: usize = DISJOINTNESS_STRINGS * 6 + PROCEDURE_A_SEQUENCES * BLOCK_LEN;
+
+/// Shortest run that fails the long run test T4.
+pub const LONG_RUN: usize = 34;
+
+/// Smallest Coron test value passing T8.
+pub const ENTROPY_THRESHOLD: f64 = 7.976;
+
+/// Largest test value passing the homogeneity test T7.
+const HOMOGENEITY_THRESHOLD: f64 = 15.13;
+
+/// Samples per distribution in T6 and T7.
+const SAMPLES: usize = 100_000;
+
+/// Initialization and test words of Coron's test T8.
+const CORON_INIT: usize = 2560;
+const CORON_WORDS: usize = 256_000;
+
+/// Shift searched by the autocorrelation test T5.
+const MAX_SHIFT: usize = 5000;
+
+/// Outcome of a test procedure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Verdict {
+    /// Every test passed.
+    Passed,
+    /// Exactly one test failed; the procedure may be repeated once with
+    /// fresh data, and passes if the repetition has no failures.
+    Repeat,
+    /// More than one test failed.
+    Failed,
+}
+
+impl Verdict {
+    fn from_failures(failures: usize) -> Self {
+        match failures {
+            0 => Verdict::Passed,
+            1 => Verdict::Repeat,
+            _ => Verdict::Failed,
+        }
+    }
+}
+
+/// Failure of [`procedure_a`] or [`procedure_b`]: the data ran out before
+/// `test` had all the bits it needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InsufficientData {
+    /// The test that ran out, such as `"T7b"`.
+    pub test: &'static str,
+    /// Bytes available.
+    pub available: usize,
+}
+
+impl fmt::Display for InsufficientData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} bytes are not enough for AIS 31 test {}",
+            self.available, self.test
+        )
+    }
+}
+
+impl std::error::Error for InsufficientData {}
+
+/// Results of procedure A.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcedureAResult {
+    /// Whether the 2^16 48-bit strings of T0 are pairwise distinct.
+    pub disjointness_passed: bool,
+    /// Sequences tested with T1 to T5.
+    pub sequences: usize,
+    /// Failed sequences for each of T1 (monobit), T2 (poker), T3 (runs),
+    /// T4 (long run) and T5 (autocorrelation).
+    pub failures: [usize; 5],
+}
+
+impl ProcedureAResult {
+    /// Failed basic tests, counting every failed sequence separately.
+    pub fn total_failures(&self) -> usize {
+        !self.disjointness_passed as usize + self.failures.iter().sum::<usize>()
+    }
+
+    /// Outcome of the procedure.
+    pub fn verdict(&self) -> Verdict {
+        Verdict::from_failures(self.total_failures())
+    }
+}
+
+/// Run procedure A on the first [`PROCEDURE_A_LEN`] bytes of `data`: T0 on
+/// 2^16 48-bit strings, then T1 to T5 on each of 257 20,000-bit sequences.
+pub fn procedure_a(data: &[u8]) -> Result<ProcedureAResult, InsufficientData> {
+    if data.len() < PROCEDURE_A_LEN {
+        return Err(InsufficientData {
+            test: if data.len() < DISJOINTNESS_STRINGS * 6 {
+                "T0"
+            } else {
+                "T1"
+            },
+            available: data.len(),
+        });
+    }
+    let (strings, sequences) = data.split_at(DISJOINTNESS_STRINGS * 6);
+    let mut failures = [0; 5];
+    for sequence in sequences
+        .chunks_exact(BLOCK_LEN)
+        .take(PROCEDURE_A_SEQUENCES)
+    {
+        let passed = test_sequence(sequence.try_into().unwrap());
+        for (failed, passed) in failures.iter_mut().zip(passed) {
+            *failed += !passed as usize;
+        }
+    }
+    Ok(ProcedureAResult {
+        disjointness_passed: disjointness(strings),
+        sequences: PROCEDURE_A_SEQUENCES,
+        failures,
+    })
+}
+
+/// T0: whether consecutive 48-bit strings of `data` are pairwise distinct.
+pub fn disjointness(data: &[u8]) -> bool {
+    let mut strings: Vec<u64> = data
+        .chunks_exact(6)
+        .map(|s| s.iter().fold(0, |acc, &b| acc << 8 | b as u64))
+        .collect();
+    let len = strings.len();
+    strings.sort_unstable();
+    strings.dedup();
+    strings.len() == len
+}
+
+/// Whether one 20,000-bit sequence passes T1 to T5, in that order.
+fn test_sequence(block: &[u8; BLOCK_LEN]) -> [bool; 5] {
+    let stats = fips140::test_block(block);
+    let runs_passed = stats.runs.iter().all(|counts| {
+        counts
+            .iter()
+            .zip(&RUN_INTERVALS)
+            .all(|(c, r)| r.contains(c))
+    });
+    [
+        stats.ones > 9654 && stats.ones < 10_346,
+        stats.poker > 1.03 && stats.poker < 57.4,
+        runs_passed,
+        stats.longest_run < LONG_RUN,
+        autocorrelation_passed(block),
+    ]
+}
+
+/// Passing numbers of runs of length 1 to 5 and 6 or more in T3.
+const RUN_INTERVALS: [std::ops::RangeInclusive<usize>; 6] = [
+    2267..=2733,
+    1079..=1421,
+    502..=748,
+    223..=402,
+    90..=223,
+    90..=223,
+];
+
+/// T5: find the shift `τ ≤ 5000` whose autocorrelation over the first
+/// 10,000 bits deviates most from 2500, then test that shift on the second
+/// 10,000 bits.
+fn autocorrelation_passed(block: &[u8; BLOCK_LEN]) -> bool {
+    let (first, second) = block.split_at(BLOCK_LEN / 2);
+    let first = words(first);
+    let worst = (1..=MAX_SHIFT)
+        .max_by_key(|&shift| autocorrelation(&first, shift).abs_diff(MAX_SHIFT / 2))
+        .unwrap();
+    let z = autocorrelation(&words(second), worst);
+    z > 2326 && z < 2674
+}
+
+/// Bits as 64-bit words, first bit most significant.
+fn words(bytes: &[u8]) -> Vec<u64> {
+    bytes
+        .chunks(8)
+        .map(|chunk| {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            u64::from_be_bytes(word)
+        })
+        .collect()
+}
+
+/// `Σ b_j ⊕ b_(j+τ)` for `j` in `0..5000`.
+fn autocorrelation(bits: &[u64], shift: usize) -> usize {
+    let word_at = |offset: usize| {
+        let (i, s) = (offset / 64, offset % 64);
+        let next = bits.get(i + 1).copied().unwrap_or(0);
+        if s == 0 {
+            bits[i]
+        } else {
+            bits[i] << s | next >> (64 - s)
+        }
+    };
+    let full = MAX_SHIFT / 64;
+    let mut count = (0..full)
+        .map(|i| (bits[i] ^ word_at(i * 64 + shift)).count_ones() as usize)
+        .sum::<usize>();
+    let rest = MAX_SHIFT % 64;
+    let mask = !(u64::MAX >> rest);
+    count += ((bits[full] ^ word_at(full * 64 + shift)) & mask).count_ones() as usize;
+    count
+}
+
+/// Results of procedure B.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProcedureBResult {
+    /// T6a: proportion of ones among 100,000 bits; passes within 0.025 of
+    /// one half.
+    pub ones_fraction: f64,
+    /// T6b: proportion of ones following a 0 and following a 1, each over
+    /// 100,000 bit pairs; passes within 0.02 of one half.
+    pub transition_fractions: [f64; 2],
+    /// T7a: homogeneity test values for the third bit of 3-bit words whose
+    /// first bit differs, after `0` and after `1`; pass below 15.13.
+    pub homogeneity_3: [f64; 2],
+    /// T7b: likewise for the fourth bit of 4-bit words, after `00`, `01`,
+    /// `10` and `11`.
+    pub homogeneity_4: [f64; 4],
+    /// T8: Coron's entropy test value over 8-bit words; passes above
+    /// [`ENTROPY_THRESHOLD`].
+    pub coron: f64,
+}
+
+impl ProcedureBResult {
+    /// Whether T6a, T6b, T7a, T7b and T8 passed, in that order.
+    pub fn passed(&self) -> [bool; 5] {
+        let near_half = |p: f64, a: f64| (p - 0.5).abs() < a;
+        let homogeneous = |t: &f64| *t < HOMOGENEITY_THRESHOLD;
+        [
+            near_half(self.ones_fraction, 0.025),
+            self.transition_fractions
+                .iter()
+                .all(|&p| near_half(p, 0.02)),
+            self.homogeneity_3.iter().all(homogeneous),
+            self.homogeneity_4.iter().all(homogeneous),
+            self.coron > ENTROPY_THRESHOLD,
+        ]
+    }
+
+    /// Number of failed tests.
+    pub fn total_failures(&self) -> usize {
+        self.passed().iter().filter(|&&p| !p).count()
+    }
+
+    /// Outcome of the procedure.
+    pub fn verdict(&self) -> Verdict {
+        Verdict::from_failures(self.total_failures())
+    }
+}
+
+/// Run procedure B on `data`: T6a, T6b, T7a, T7b and T8, each on the bits
+/// following those of the previous test.
+///
+/// T6b and T7 read words until every prefix has been seen 100,000 times,
+/// so the amount of data used depends on its distribution.
+pub fn procedure_b(data: &[u8]) -> Result<ProcedureBResult, InsufficientData> {
+    let mut bits = BitCursor { data, pos: 0 };
+    let ran_out = |test| InsufficientData {
+        test,
+        available: data.len(),
+    };
+
+    let mut ones = 0;
+    for _ in 0..SAMPLES {
+        ones += bits.take(1).ok_or_else(|| ran_out("T6a"))?;
+    }
+    let ones_fraction = ones as f64 / SAMPLES as f64;
+
+    let fraction = |ones: usize| ones as f64 / SAMPLES as f64;
+    let t6b = conditional_ones(&mut bits, 1).ok_or_else(|| ran_out("T6b"))?;
+    let t7a = conditional_ones(&mut bits, 2).ok_or_else(|| ran_out("T7a"))?;
+    let t7b = conditional_ones(&mut bits, 3).ok_or_else(|| ran_out("T7b"))?;
+
+    // Prefixes differing only in their first bit are compared.
+    let compare = |ones: &[usize]| {
+        let half = ones.len() / 2;
+        (0..half)
+            .map(|p| homogeneity(ones[p], ones[p + half]))
+            .collect::<Vec<_>>()
+    };
+
+    let start = bits.pos.div_ceil(8);
+    let words = data
+        .get(start..start + CORON_INIT + CORON_WORDS)
+        .ok_or_else(|| ran_out("T8"))?;
+
+    Ok(ProcedureBResult {
+        ones_fraction,
+        transition_fractions: [fraction(t6b[0]), fraction(t6b[1])],
+        homogeneity_3: compare(&t7a).try_into().unwrap(),
+        homogeneity_4: compare(&t7b).try_into().unwrap(),
+        coron: coron(words),
+    })
+}
+
+/// Read `prefix_len + 1`-bit words until each prefix has occurred 100,000
+/// times, and count the ones in the last bit after each prefix. Words whose
+/// prefix is already complete are skipped. `None` if the data runs out.
+fn conditional_ones(bits: &mut BitCursor, prefix_len: usize) -> Option<Vec<usize>> {
+    let prefixes = 1 << prefix_len;
+    let mut seen = vec![0; prefixes];
+    let mut ones = vec![0; prefixes];
+    let mut complete = 0;
+    while complete < prefixes {
+        let word = bits.take(prefix_len + 1)?;
+        let prefix = word >> 1;
+        if seen[prefix] < SAMPLES {
+            seen[prefix] += 1;
+            ones[prefix] += word & 1;
+            complete += (seen[prefix] == SAMPLES) as usize;
+        }
+    }
+    Some(ones)
+}
+
+/// Chi-square homogeneity test value of two samples of 100,000 bits with
+/// `a` and `b` ones.
+fn homogeneity(a: usize, b: usize) -> f64 {
+    let n = SAMPLES as f64;
+    let p1 = (a + b) as f64 / (2.0 * n);
+    let p0 = 1.0 - p1;
+    [a, b]
+        .iter()
+        .map(|&ones| {
+            let ones = ones as f64;
+            (ones - n * p1).powi(2) / (n * p1) + (n - ones - n * p0).powi(2) / (n * p0)
+        })
+        .sum()
+}
+
+/// T8: Coron's test value over the first 2560 + 256,000 bytes of `words`,
+/// an unbiased estimate of the entropy per byte: close to 8 for ideal random
+/// bytes.
+///
+/// # Panics
+///
+/// If `words` is shorter than that.
+pub fn coron(words: &[u8]) -> f64 {
+    let words = &words[..CORON_INIT + CORON_WORDS];
+    // g(i) = Σ_(k<i) 1/k / ln 2, for every distance i up to the word count.
+    let mut g = Vec::with_capacity(words.len() + 1);
+    g.extend([0.0, 0.0]);
+    for k in 1..words.len() {
+        g.push(g[k] + 1.0 / k as f64);
+    }
+    let mut last = [0; 256];
+    let mut sum = 0.0;
+    for (n, &w) in words.iter().enumerate().map(|(i, w)| (i + 1, w)) {
+        if n > CORON_INIT {
+            sum += g[n - last[w as usize]];
+        }
+        last[w as usize] = n;
+    }
+    sum / CORON_WORDS as f64 / std::f64::consts::LN_2
+}
+
+/// Sequential reader of bits, most significant first.
+struct BitCursor<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl BitCursor<'_> {
+    /// The next `n ≤ 8` bits as an integer, first bit most significant.
+    fn take(&mut self, n: usize) -> Option<usize> {
+        if self.pos + n > self.data.len() * 8 {
+            return None;
+        }
+        let mut value = 0;
+        for i in self.pos..self.pos + n {
+            value = value << 1 | (self.data[i / 8] >> (7 - i % 8) & 1) as usize;
+        }
+        self.pos += n;
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_bytes(len: usize, seed: u64) -> Vec<u8> {
+        let mut x = seed;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_procedure_a_on_random_and_repeating_data() {
+        let data = random_bytes(PROCEDURE_A_LEN, 0x9E37_79B9_7F4A_7C15);
+        let result = procedure_a(&data).unwrap();
+        assert_eq!(result.verdict(), Verdict::Passed, "{result:?}");
+
+        // A source that repeats itself every 64 KiB fails T0, and a stuck
+        // bit every byte fails the autocorrelation test of every sequence.
+        let mut repeating = data.clone();
+        let (head, tail) = repeating.split_at_mut(6144);
+        tail[..6144].copy_from_slice(head);
+        for b in &mut repeating[DISJOINTNESS_STRINGS * 6..] {
+            *b &= 0xfe;
+        }
+        let result = procedure_a(&repeating).unwrap();
+        assert!(!result.disjointness_passed);
+        assert_eq!(result.failures[4], PROCEDURE_A_SEQUENCES);
+        assert_eq!(result.verdict(), Verdict::Failed);
+
+        let err = procedure_a(&data[..1000]).unwrap_err();
+        assert_eq!(err.test, "T0");
+    }
+
+    #[test]
+    fn test_autocorrelation_matches_naive_count() {
+        let block = random_bytes(BLOCK_LEN, 3);
+        let bits = words(&block);
+        let bit = |i: usize| (block[i / 8] >> (7 - i % 8)) & 1;
+        for shift in [1, 63, 64, 65, 1000, 5000] {
+            let naive = (0..MAX_SHIFT).filter(|&j| bit(j) != bit(j + shift)).count();
+            assert_eq!(autocorrelation(&bits, shift), naive);
+        }
+    }
+
+    #[test]
+    fn test_procedure_b_on_random_and_correlated_data() {
+        let data = random_bytes(1 << 20, 0x2545_F491_4F6C_DD1D);
+        let result = procedure_b(&data).unwrap();
+        assert_eq!(result.verdict(), Verdict::Passed, "{result:?}");
+        assert!((result.coron - 8.0).abs() < 0.01);
+
+        // Each bit repeats the previous one 55% of the time.
+        let noise = random_bytes(1 << 23, 5);
+        let mut previous = 0;
+        let correlated: Vec<u8> = noise
+            .chunks(8)
+            .map(|chunk| {
+                chunk.iter().fold(0u8, |byte, &r| {
+                    let bit = if r < 141 { previous } else { 1 - previous };
+                    previous = bit;
+                    byte << 1 | bit
+                })
+            })
+            .collect();
+        let result = procedure_b(&correlated).unwrap();
+        assert!(!result.passed()[1]);
+        assert!(result.ones_fraction > 0.475 && result.ones_fraction < 0.525);
+
+        let err = procedure_b(&data[..20_000]).unwrap_err();
+        assert_eq!(err.test, "T6b");
+    }
+}
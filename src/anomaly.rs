@@ -0,0 +1,209 @@
+//! Windowed anomaly detection against a known-good baseline.
+//!
+//! ```rust
+//! use ent_rs::anomaly::{AnomalyDetector, AnomalyKind, Tolerances};
+//! use ent_rs::EntStats;
+//! let normal: Vec<u8> = (0..=255).cycle().take(4096).collect();
+//! let detector = AnomalyDetector::new(EntStats::from_data(&normal, false), Tolerances::default());
+//!
+//! let mut traffic = normal.clone();
+//! traffic.extend([0u8; 1024]);
+//! let events = detector.scan(&traffic, 1024);
+//! assert!(events.iter().any(|a| a.kind == AnomalyKind::EntropyDrop && a.offset == 4096));
+//! ```
+
+use crate::window::{windowed_stats, WindowAnalyzer, Windowing};
+use crate::EntStats;
+
+/// Category of a detected anomaly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnomalyKind {
+    /// Entropy fell below the baseline by more than the tolerance.
+    EntropyDrop,
+    /// Arithmetic mean moved away from the baseline by more than the tolerance.
+    BiasSpike,
+    /// Serial correlation moved away from the baseline by more than the tolerance.
+    CorrelationSpike,
+}
+
+/// A window whose statistics left the tolerance band.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Anomaly {
+    /// What was detected.
+    pub kind: AnomalyKind,
+    /// Offset of the window's first byte in the stream.
+    pub offset: u64,
+    /// Window length in bytes.
+    pub len: usize,
+    /// Observed value of the metric.
+    pub observed: f64,
+    /// Baseline value of the metric.
+    pub expected: f64,
+}
+
+/// Allowed deviation of each metric from the baseline.
+///
+/// Small windows naturally fluctuate more than large ones, so bands should be
+/// chosen for the window size being monitored.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerances {
+    /// Allowed entropy drop in bits per byte.
+    pub entropy_drop: f64,
+    /// Allowed absolute deviation of the mean byte value.
+    pub mean_deviation: f64,
+    /// Allowed absolute deviation of the serial correlation coefficient.
+    pub correlation_deviation: f64,
+}
+
+impl Default for Tolerances {
+    fn default() -> Self {
+        Tolerances {
+            entropy_drop: 0.5,
+            mean_deviation: 10.0,
+            correlation_deviation: 0.2,
+        }
+    }
+}
+
+/// Compares windowed statistics against baseline statistics.
+#[derive(Debug, Clone)]
+pub struct AnomalyDetector {
+    baseline: EntStats,
+    tolerances: Tolerances,
+}
+
+impl AnomalyDetector {
+    /// Create a detector for data that normally looks like `baseline`.
+    pub fn new(baseline: EntStats, tolerances: Tolerances) -> Self {
+        AnomalyDetector {
+            baseline,
+            tolerances,
+        }
+    }
+
+    /// Baseline statistics the detector compares against.
+    pub fn baseline(&self) -> &EntStats {
+        &self.baseline
+    }
+
+    /// Check the statistics of one window starting at `offset`.
+    pub fn check(&self, offset: u64, len: usize, stats: &EntStats) -> Vec<Anomaly> {
+        let base = &self.baseline;
+        let tol = &self.tolerances;
+        let mut events = Vec::new();
+        let mut emit = |kind, observed, expected| {
+            events.push(Anomaly {
+                kind,
+                offset,
+                len,
+                observed,
+                expected,
+            })
+        };
+
+        if base.entropy - stats.entropy > tol.entropy_drop {
+            emit(AnomalyKind::EntropyDrop, stats.entropy, base.entropy);
+        }
+        if (stats.mean - base.mean).abs() > tol.mean_deviation {
+            emit(AnomalyKind::BiasSpike, stats.mean, base.mean);
+        }
+        // The -99999.0 sentinel marks an undefined correlation (constant data).
+        let defined = |scc: f64| scc != -99999.0;
+        if defined(stats.serial_correlation)
+            && defined(base.serial_correlation)
+            && (stats.serial_correlation - base.serial_correlation).abs()
+                > tol.correlation_deviation
+        {
+            emit(
+                AnomalyKind::CorrelationSpike,
+                stats.serial_correlation,
+                base.serial_correlation,
+            );
+        }
+        events
+    }
+
+    /// Check the current contents of a live [`WindowAnalyzer`].
+    pub fn check_live(&self, window: &WindowAnalyzer) -> Vec<Anomaly> {
+        self.check(window.start_offset(), window.len(), &window.current_stats())
+    }
+
+    /// Check consecutive non-overlapping windows of `data`; a trailing partial
+    /// window is checked as well.
+    pub fn scan(&self, data: &[u8], window: usize) -> Vec<Anomaly> {
+        data.chunks(window.max(1))
+            .enumerate()
+            .flat_map(|(i, chunk)| {
+                let offset = (i * window.max(1)) as u64;
+                self.check(offset, chunk.len(), &EntStats::from_data(chunk, false))
+            })
+            .collect()
+    }
+
+    /// Check complete windows of `data` placed by `windowing`.
+    ///
+    /// With overlapping windows one anomalous region is reported by every
+    /// window that covers it, so counts of events are not comparable with
+    /// those from [`scan`](Self::scan).
+    pub fn scan_with(&self, data: &[u8], windowing: Windowing) -> Vec<Anomaly> {
+        windowed_stats(data, windowing, false)
+            .into_iter()
+            .flat_map(|w| self.check(w.offset as u64, windowing.window(), &w.stats))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn baseline() -> AnomalyDetector {
+        let normal: Vec<u8> = (0..=255u8).cycle().take(8192).collect();
+        // Shuffle the ramp so adjacent bytes are not correlated.
+        let normal: Vec<u8> = normal
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| b.wrapping_mul(167).wrapping_add((i / 256) as u8))
+            .collect();
+        AnomalyDetector::new(EntStats::from_data(&normal, false), Tolerances::default())
+    }
+
+    #[test]
+    fn test_no_anomalies_on_baseline_like_data() {
+        let detector = baseline();
+        let stats = detector.baseline().clone();
+        assert!(detector.check(0, 8192, &stats).is_empty());
+    }
+
+    #[test]
+    fn test_bias_spike_detected() {
+        let detector = baseline();
+        let biased: Vec<u8> = (0..=255u8).cycle().take(2048).map(|b| b | 0x80).collect();
+        let events: Vec<_> = detector
+            .scan(&biased, 1024)
+            .into_iter()
+            .filter(|a| a.kind == AnomalyKind::BiasSpike)
+            .collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].offset, 1024);
+        assert!(events[0].observed > events[0].expected);
+
+        let overlapping = detector
+            .scan_with(&biased, Windowing::overlapping(1024, 256))
+            .into_iter()
+            .filter(|a| a.kind == AnomalyKind::BiasSpike)
+            .count();
+        assert_eq!(overlapping, 5);
+    }
+
+    #[test]
+    fn test_correlation_spike_and_live_window() {
+        let detector = baseline();
+        let mut live = WindowAnalyzer::new(512);
+        live.extend(&(0..=255u8).cycle().take(1500).collect::<Vec<_>>());
+        let events = detector.check_live(&live);
+        assert!(events
+            .iter()
+            .any(|a| a.kind == AnomalyKind::CorrelationSpike && a.offset == 988));
+    }
+}
@@ -0,0 +1,112 @@
+//! Streaming analysis of tokio `AsyncRead` sources.
+//!
+//! Network services and TRNG daemons can analyze a payload or stream as it
+//! arrives, chunk by chunk, without a blocking read tying up a runtime
+//! thread.
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite};
+
+use crate::{instrument, EntAccumulator, EntStats};
+
+/// Bytes read from the source at a time.
+const CHUNK_SIZE: usize = 64 << 10;
+
+impl EntStats {
+    /// Like [`EntStats::from_data`] on everything `reader` yields until end
+    /// of file. Stops at the first read error and returns it.
+    ///
+    /// ```rust
+    /// # async fn payload_entropy(body: &[u8]) -> std::io::Result<f64> {
+    /// use ent_rs::EntStats;
+    /// let stats = EntStats::from_async_reader(body, false).await?;
+    /// # Ok(stats.entropy)
+    /// # }
+    /// ```
+    pub async fn from_async_reader<R>(mut reader: R, bit_mode: bool) -> io::Result<Self>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let timer = instrument::Timer::start();
+        let mut acc = EntAccumulator::new(bit_mode);
+        let mut buf = vec![0u8; CHUNK_SIZE];
+        loop {
+            match reader.read(&mut buf).await {
+                Ok(0) => break,
+                Ok(n) => acc.update(&buf[..n]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        timer.finish("from_async_reader", acc.len());
+        Ok(acc.finalize())
+    }
+}
+
+/// Lets `tokio::io::copy` feed an accumulator, like the [`io::Write`]
+/// implementation does synchronously.
+impl AsyncWrite for EntAccumulator {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().update(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::task::Waker;
+
+    use tokio::io::AsyncWriteExt;
+
+    use super::*;
+
+    /// Drive a future whose I/O is always ready, as in-memory readers are.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_async_reader_matches_from_data() {
+        let data: Vec<u8> = (0..CHUNK_SIZE as u32 * 2 + 77)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+            .collect();
+        let streamed = block_on(EntStats::from_async_reader(&data[..], false)).unwrap();
+        let direct = EntStats::from_data(&data, false);
+        assert_eq!(streamed.entropy, direct.entropy);
+        assert_eq!(streamed.chisquare, direct.chisquare);
+        assert_eq!(streamed.serial_correlation, direct.serial_correlation);
+    }
+
+    #[test]
+    fn test_accumulator_is_async_writer() {
+        let mut acc = EntAccumulator::new(true);
+        block_on(async {
+            acc.write_all(&[0x0F; 100]).await.unwrap();
+            acc.shutdown().await.unwrap();
+        });
+        assert_eq!(acc.len(), 100);
+        assert_eq!(acc.stats().entropy, 1.0);
+    }
+}
@@ -0,0 +1,359 @@
+//! Drift detection against a corpus of known-good captures.
+//!
+//! A [`Baseline`] aggregates the statistics of many captures from a healthy
+//! source: the mean and variance of each metric across captures and the
+//! pooled byte histogram. New samples are scored against it, so "is this
+//! capture like the others?" is answered in standard deviations of the
+//! source's own variation rather than against the ideal of uniform noise.
+//!
+//! ```rust
+//! use ent_rs::baseline::Baseline;
+//! let captures: Vec<Vec<u8>> = (0..20u32)
+//!     .map(|k| (0..4096u32).map(|i| ((i * 31 + k * 7) % 200) as u8).collect())
+//!     .collect();
+//! let baseline = Baseline::from_captures(&captures);
+//! assert_eq!(baseline.captures(), 20);
+//!
+//! let drifted: Vec<u8> = (0..4096u32).map(|i| (i % 256) as u8).collect();
+//! let score = baseline.score(&drifted);
+//! assert!(score.histogram_p_value < 1e-6);
+//! ```
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+use crate::{byte_counts, chisquare_p_value, EntStats};
+
+/// Running mean and variance of one metric across captures (Welford).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetricSummary {
+    /// Number of captures contributing.
+    pub count: usize,
+    /// Mean across captures.
+    pub mean: f64,
+    /// Sum of squared deviations from the mean.
+    pub m2: f64,
+}
+
+impl MetricSummary {
+    /// Add one observation.
+    pub fn add(&mut self, x: f64) {
+        self.count += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    /// Sample variance; zero with fewer than two observations.
+    pub fn variance(&self) -> f64 {
+        if self.count < 2 {
+            0.0
+        } else {
+            self.m2 / (self.count - 1) as f64
+        }
+    }
+
+    /// Sample standard deviation.
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// Standard score of `x`: 0 if it equals the mean, infinite if it differs
+    /// from a metric that never varied, NaN without observations.
+    pub fn z_score(&self, x: f64) -> f64 {
+        if self.count == 0 {
+            return f64::NAN;
+        }
+        let deviation = x - self.mean;
+        if deviation == 0.0 {
+            0.0
+        } else {
+            deviation / self.std_dev()
+        }
+    }
+}
+
+/// Aggregated statistics of known-good captures, analyzed in byte mode.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Baseline {
+    /// Shannon entropy per capture.
+    pub entropy: MetricSummary,
+    /// Chi-square statistic per capture.
+    pub chisquare: MetricSummary,
+    /// Arithmetic mean per capture.
+    pub mean: MetricSummary,
+    /// Monte Carlo Pi estimate per capture.
+    pub pi_estimate: MetricSummary,
+    /// Serial correlation per capture; captures where it is undefined are
+    /// skipped.
+    pub serial_correlation: MetricSummary,
+    /// Byte counts pooled over all captures.
+    pub histogram: Vec<u64>,
+}
+
+impl Default for Baseline {
+    fn default() -> Self {
+        Baseline {
+            entropy: MetricSummary::default(),
+            chisquare: MetricSummary::default(),
+            mean: MetricSummary::default(),
+            pi_estimate: MetricSummary::default(),
+            serial_correlation: MetricSummary::default(),
+            histogram: vec![0; 256],
+        }
+    }
+}
+
+/// How a sample compares with a [`Baseline`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BaselineScore {
+    /// Standard score of the sample's entropy.
+    pub entropy: f64,
+    /// Standard score of the sample's chi-square statistic.
+    pub chisquare: f64,
+    /// Standard score of the sample's mean.
+    pub mean: f64,
+    /// Standard score of the sample's Pi estimate.
+    pub pi_estimate: f64,
+    /// Standard score of the sample's serial correlation, `None` if it is
+    /// undefined for the sample.
+    pub serial_correlation: Option<f64>,
+    /// p-value of a chi-square homogeneity test between the sample's byte
+    /// histogram and the pooled baseline histogram.
+    pub histogram_p_value: f64,
+}
+
+impl BaselineScore {
+    /// Largest absolute standard score over all metrics.
+    pub fn max_abs_z(&self) -> f64 {
+        [self.entropy, self.chisquare, self.mean, self.pi_estimate]
+            .into_iter()
+            .chain(self.serial_correlation)
+            .map(f64::abs)
+            .fold(0.0, f64::max)
+    }
+
+    /// Whether any metric is more than `z_limit` standard deviations from the
+    /// baseline or the histograms differ at significance `alpha`.
+    pub fn drifted(&self, z_limit: f64, alpha: f64) -> bool {
+        self.max_abs_z() > z_limit || self.histogram_p_value < alpha
+    }
+}
+
+impl Baseline {
+    /// An empty baseline.
+    pub fn new() -> Self {
+        Baseline::default()
+    }
+
+    /// A baseline built from `captures`.
+    pub fn from_captures<C: AsRef<[u8]>>(captures: &[C]) -> Self {
+        let mut baseline = Baseline::new();
+        for capture in captures {
+            baseline.add(capture.as_ref());
+        }
+        baseline
+    }
+
+    /// Number of captures added.
+    pub fn captures(&self) -> usize {
+        self.entropy.count
+    }
+
+    /// Add a known-good capture.
+    pub fn add(&mut self, capture: &[u8]) {
+        let stats = EntStats::from_data(capture, false);
+        self.entropy.add(stats.entropy);
+        self.chisquare.add(stats.chisquare);
+        self.mean.add(stats.mean);
+        self.pi_estimate.add(stats.pi_estimate);
+        if stats.serial_correlation != -99999.0 {
+            self.serial_correlation.add(stats.serial_correlation);
+        }
+        for (pooled, count) in self.histogram.iter_mut().zip(byte_counts(capture)) {
+            *pooled += count as u64;
+        }
+    }
+
+    /// Score `sample` against the baseline.
+    pub fn score(&self, sample: &[u8]) -> BaselineScore {
+        let stats = EntStats::from_data(sample, false);
+        BaselineScore {
+            entropy: self.entropy.z_score(stats.entropy),
+            chisquare: self.chisquare.z_score(stats.chisquare),
+            mean: self.mean.z_score(stats.mean),
+            pi_estimate: self.pi_estimate.z_score(stats.pi_estimate),
+            serial_correlation: (stats.serial_correlation != -99999.0)
+                .then(|| self.serial_correlation.z_score(stats.serial_correlation)),
+            histogram_p_value: self.histogram_p_value(&byte_counts(sample)),
+        }
+    }
+
+    /// Chi-square test of homogeneity between two histograms; byte values
+    /// absent from both do not count towards the degrees of freedom.
+    fn histogram_p_value(&self, sample: &[usize; 256]) -> f64 {
+        let n1 = self.histogram.iter().sum::<u64>() as f64;
+        let n2 = sample.iter().sum::<usize>() as f64;
+        if n1 == 0.0 || n2 == 0.0 {
+            return f64::NAN;
+        }
+        let mut chisq = 0.0;
+        let mut used = 0;
+        for (&a, &b) in self.histogram.iter().zip(sample) {
+            let (a, b) = (a as f64, b as f64);
+            if a + b == 0.0 {
+                continue;
+            }
+            used += 1;
+            let e1 = (a + b) * n1 / (n1 + n2);
+            let e2 = (a + b) * n2 / (n1 + n2);
+            chisq += (a - e1).powi(2) / e1 + (b - e2).powi(2) / e2;
+        }
+        chisquare_p_value(chisq, (used - 1) as f64)
+    }
+
+    /// Write the baseline in a line-based text format readable by
+    /// [`load`](Self::load).
+    pub fn save<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "{HEADER}")?;
+        for (name, metric) in self.metrics() {
+            writeln!(out, "{name} {} {} {}", metric.count, metric.mean, metric.m2)?;
+        }
+        let counts: Vec<String> = self.histogram.iter().map(u64::to_string).collect();
+        writeln!(out, "histogram {}", counts.join(" "))
+    }
+
+    /// Read a baseline written by [`save`](Self::save).
+    pub fn load<R: BufRead>(input: R) -> io::Result<Self> {
+        let mut lines = input.lines();
+        match lines.next().transpose()? {
+            Some(line) if line == HEADER => {}
+            _ => return Err(invalid("not an ent-rs baseline")),
+        }
+        let mut baseline = Baseline::new();
+        for line in lines {
+            let line = line?;
+            let mut fields = line.split_whitespace();
+            let Some(name) = fields.next() else {
+                continue;
+            };
+            if name == "histogram" {
+                let counts = fields
+                    .map(|f| f.parse::<u64>().map_err(|_| invalid("bad histogram count")))
+                    .collect::<io::Result<Vec<_>>>()?;
+                if counts.len() != 256 {
+                    return Err(invalid("histogram needs 256 counts"));
+                }
+                baseline.histogram = counts;
+                continue;
+            }
+            let metric = baseline
+                .metric_mut(name)
+                .ok_or_else(|| invalid(format!("unknown metric '{name}'")))?;
+            let mut next = || fields.next().ok_or_else(|| invalid("missing field"));
+            *metric = MetricSummary {
+                count: next()?.parse().map_err(|_| invalid("bad count"))?,
+                mean: next()?.parse().map_err(|_| invalid("bad mean"))?,
+                m2: next()?.parse().map_err(|_| invalid("bad m2"))?,
+            };
+        }
+        Ok(baseline)
+    }
+
+    fn metrics(&self) -> [(&'static str, &MetricSummary); 5] {
+        [
+            ("entropy", &self.entropy),
+            ("chisquare", &self.chisquare),
+            ("mean", &self.mean),
+            ("pi_estimate", &self.pi_estimate),
+            ("serial_correlation", &self.serial_correlation),
+        ]
+    }
+
+    fn metric_mut(&mut self, name: &str) -> Option<&mut MetricSummary> {
+        match name {
+            "entropy" => Some(&mut self.entropy),
+            "chisquare" => Some(&mut self.chisquare),
+            "mean" => Some(&mut self.mean),
+            "pi_estimate" => Some(&mut self.pi_estimate),
+            "serial_correlation" => Some(&mut self.serial_correlation),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for BaselineScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "z: entropy {:+.2}, chi-square {:+.2}, mean {:+.2}, pi {:+.2}",
+            self.entropy, self.chisquare, self.mean, self.pi_estimate
+        )?;
+        if let Some(z) = self.serial_correlation {
+            write!(f, ", serial correlation {z:+.2}")?;
+        }
+        write!(f, "; histogram p-value {:.4}", self.histogram_p_value)
+    }
+}
+
+const HEADER: &str = "ent-rs baseline v1";
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capture(seed: u64, len: usize) -> Vec<u8> {
+        let mut x = seed | 1;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_welford_matches_two_pass_variance() {
+        let xs = [7.9, 7.95, 7.97, 7.91, 7.99];
+        let mut summary = MetricSummary::default();
+        xs.iter().for_each(|&x| summary.add(x));
+        let mean = xs.iter().sum::<f64>() / 5.0;
+        let var = xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / 4.0;
+        assert!((summary.mean - mean).abs() < 1e-12);
+        assert!((summary.variance() - var).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_scores_separate_good_and_drifted_samples() {
+        let captures: Vec<Vec<u8>> = (1..=30).map(|k| capture(k * 0x9E37, 8192)).collect();
+        let baseline = Baseline::from_captures(&captures);
+
+        let good = baseline.score(&capture(999, 8192));
+        assert!(!good.drifted(5.0, 1e-4), "{good}");
+
+        // A stuck-bit fault: bit 0 always set.
+        let faulty: Vec<u8> = capture(1234, 8192).iter().map(|b| b | 1).collect();
+        let bad = baseline.score(&faulty);
+        assert!(bad.drifted(5.0, 1e-4), "{bad}");
+        assert!(bad.histogram_p_value < 1e-10);
+    }
+
+    #[test]
+    fn test_save_load_round_trip() {
+        let captures: Vec<Vec<u8>> = (1..=5).map(|k| capture(k, 1000)).collect();
+        let baseline = Baseline::from_captures(&captures);
+        let mut text = Vec::new();
+        baseline.save(&mut text).unwrap();
+        let loaded = Baseline::load(&text[..]).unwrap();
+        assert_eq!(loaded, baseline);
+        assert!(Baseline::load(&b"something else\n"[..]).is_err());
+    }
+}
@@ -0,0 +1,197 @@
+//! Histogram binning for wide symbols (words, floats).
+//!
+//! A `u32` or `f64` alphabet is far too large to estimate directly, so values
+//! are first aggregated into a fixed number of bins and entropy/chi-square are
+//! computed over the bins.
+//!
+//! ```rust
+//! use ent_rs::binning::{Binning, BinnedStats};
+//! let samples: Vec<f64> = (0..10_000).map(|i| (i % 100) as f64).collect();
+//! let stats = BinnedStats::from_values(&samples, &Binning::linear(10));
+//! assert!((stats.entropy - stats.max_entropy).abs() < 1e-9);
+//! ```
+
+use crate::{chisquare_from_counts, chisquare_p_value, entropy_from_counts};
+
+/// Spacing of bin edges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scale {
+    /// Equal-width bins.
+    Linear,
+    /// Bins of equal width in `log(value)`; non-positive values are out of range.
+    Log,
+}
+
+/// Binning configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binning {
+    /// Number of bins (at least 1).
+    pub bins: usize,
+    /// Inclusive value range; `None` uses the minimum and maximum of the data.
+    pub range: Option<(f64, f64)>,
+    /// Spacing of bin edges.
+    pub scale: Scale,
+}
+
+impl Binning {
+    /// `bins` equal-width bins over the data range.
+    pub fn linear(bins: usize) -> Self {
+        Binning {
+            bins: bins.max(1),
+            range: None,
+            scale: Scale::Linear,
+        }
+    }
+
+    /// `bins` logarithmically spaced bins over the positive data range.
+    pub fn log(bins: usize) -> Self {
+        Binning {
+            bins: bins.max(1),
+            range: None,
+            scale: Scale::Log,
+        }
+    }
+
+    /// Use a fixed value range instead of the data range.
+    pub fn with_range(mut self, min: f64, max: f64) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    fn resolve_range(&self, values: &[f64]) -> (f64, f64) {
+        let extent = values
+            .iter()
+            .fold(None, |extent, &v| self.extend_range(extent, v));
+        self.resolve_range_from(extent)
+    }
+
+    /// Grow the data extent `extent` (min, max) to include `value` if it can
+    /// be binned in this scale.
+    pub(crate) fn extend_range(
+        &self,
+        extent: Option<(f64, f64)>,
+        value: f64,
+    ) -> Option<(f64, f64)> {
+        if !value.is_finite() || (self.scale == Scale::Log && value <= 0.0) {
+            return extent;
+        }
+        Some(match extent {
+            Some((lo, hi)) => (lo.min(value), hi.max(value)),
+            None => (value, value),
+        })
+    }
+
+    /// The configured range, or the data extent when none is configured.
+    pub(crate) fn resolve_range_from(&self, extent: Option<(f64, f64)>) -> (f64, f64) {
+        self.range.or(extent).unwrap_or((0.0, 0.0))
+    }
+
+    pub(crate) fn bin_of(&self, value: f64, (min, max): (f64, f64)) -> Option<usize> {
+        if !(value >= min && value <= max) {
+            return None;
+        }
+        let (value, min, max) = match self.scale {
+            Scale::Linear => (value, min, max),
+            Scale::Log if value > 0.0 && min > 0.0 => (value.ln(), min.ln(), max.ln()),
+            Scale::Log => return None,
+        };
+        if max <= min {
+            return Some(0);
+        }
+        let pos = ((value - min) / (max - min) * self.bins as f64) as usize;
+        Some(pos.min(self.bins - 1))
+    }
+}
+
+/// Entropy and chi-square statistics over a binned histogram.
+#[derive(Debug, Clone)]
+pub struct BinnedStats {
+    /// Value range the bins cover.
+    pub range: (f64, f64),
+    /// Observations per bin.
+    pub counts: Vec<usize>,
+    /// Values outside the range (including NaN and, in log scale, non-positive values).
+    pub out_of_range: usize,
+    /// Shannon entropy in bits per bin.
+    pub entropy: f64,
+    /// Largest possible entropy, `log2(bins)`.
+    pub max_entropy: f64,
+    /// Chi-square statistic against a uniform distribution over the bins.
+    pub chisquare: f64,
+    /// p-value of the chi-square test with `bins - 1` degrees of freedom.
+    pub p_value: f64,
+}
+
+impl BinnedStats {
+    /// Bin `values` and compute entropy and chi-square over the bins.
+    pub fn from_values<T: Copy + Into<f64>>(values: &[T], binning: &Binning) -> Self {
+        let values: Vec<f64> = values.iter().map(|&v| v.into()).collect();
+        let bins = binning.bins.max(1);
+        let binning = Binning {
+            bins,
+            ..binning.clone()
+        };
+        let range = binning.resolve_range(&values);
+
+        let mut counts = vec![0usize; bins];
+        let mut out_of_range = 0;
+        for &v in &values {
+            match binning.bin_of(v, range) {
+                Some(i) => counts[i] += 1,
+                None => out_of_range += 1,
+            }
+        }
+        BinnedStats::from_counts(range, counts, out_of_range)
+    }
+
+    /// Statistics of an already binned histogram.
+    pub(crate) fn from_counts(range: (f64, f64), counts: Vec<usize>, out_of_range: usize) -> Self {
+        let bins = counts.len();
+        let entropy = entropy_from_counts(&counts);
+        // The statistic only: its p-value is the exact tail, not `ent`'s
+        // normal approximation.
+        let (chisquare, _) = chisquare_from_counts(&counts);
+        let p_value = chisquare_p_value(chisquare, (bins - 1) as f64);
+
+        BinnedStats {
+            range,
+            counts,
+            out_of_range,
+            entropy,
+            max_entropy: (bins as f64).log2(),
+            chisquare,
+            p_value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear_binning_uniform_words() {
+        let words: Vec<u32> = (0..65_536u32).map(|i| i.wrapping_mul(65_537)).collect();
+        let stats = BinnedStats::from_values(&words, &Binning::linear(16));
+        assert_eq!(stats.counts.len(), 16);
+        assert_eq!(stats.out_of_range, 0);
+        assert!(stats.entropy > 3.99);
+        assert!(stats.p_value > 0.01);
+    }
+
+    #[test]
+    fn test_log_binning_excludes_non_positive() {
+        let values = [-1.0f64, 0.0, 1.0, 10.0, 100.0, 1000.0];
+        let stats = BinnedStats::from_values(&values, &Binning::log(4));
+        assert_eq!(stats.out_of_range, 2);
+        assert_eq!(stats.counts, vec![1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn test_fixed_range_counts_out_of_range() {
+        let values = [0.5f32, 1.5, 2.5, 10.0];
+        let stats = BinnedStats::from_values(&values, &Binning::linear(3).with_range(0.0, 3.0));
+        assert_eq!(stats.counts, vec![1, 1, 1]);
+        assert_eq!(stats.out_of_range, 1);
+    }
+}
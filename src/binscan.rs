@@ -0,0 +1,309 @@
+//! Per-section entropy of executables.
+//!
+//! [`section_entropy`] parses an ELF, PE or Mach-O file with `goblin` and
+//! reports statistics for every section stored in the file, plus any overlay:
+//! bytes appended after everything the headers describe, a common hiding
+//! place for encrypted payloads. A packed `.text` section or an overlay
+//! classified as [`ContentClass::Encrypted`] is worth a closer look.
+//!
+//! ```rust,no_run
+//! use ent_rs::binscan::section_entropy;
+//! let report = section_entropy("/usr/bin/ls").unwrap();
+//! for section in &report.sections {
+//!     println!("{:<20} {:>10} {:.4}", section.name, section.size, section.stats.entropy);
+//! }
+//! if let Some(overlay) = &report.overlay {
+//!     println!("overlay of {} bytes at {}", overlay.size, overlay.offset);
+//! }
+//! ```
+
+use std::fmt;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+
+use goblin::{elf, mach, pe, Object};
+
+use crate::classify::{classify, ContentClass};
+use crate::EntStats;
+
+/// Executable format of an analyzed file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinaryFormat {
+    /// ELF, as on Linux and most other Unix systems.
+    Elf,
+    /// PE/COFF, as on Windows.
+    Pe,
+    /// Single-architecture Mach-O, as on macOS and iOS.
+    MachO,
+}
+
+/// Statistics of one section, or of the overlay.
+#[derive(Debug, Clone)]
+pub struct SectionEntropy {
+    /// Section name; Mach-O names are `segment,section`, and the overlay is
+    /// named `<overlay>`.
+    pub name: String,
+    /// File offset of the section's data.
+    pub offset: u64,
+    /// Bytes of the section stored in the file.
+    pub size: u64,
+    /// Whether the section is marked as containing executable code.
+    pub executable: bool,
+    /// Byte-mode statistics of the section's data.
+    pub stats: EntStats,
+    /// Classification of the section's data.
+    pub class: ContentClass,
+}
+
+/// Sections of an executable, in header order, and its overlay.
+#[derive(Debug, Clone)]
+pub struct BinaryReport {
+    /// Format of the file.
+    pub format: BinaryFormat,
+    /// Every section with data in the file; uninitialized sections such as
+    /// `.bss` are skipped.
+    pub sections: Vec<SectionEntropy>,
+    /// Data after the end of the last header, section or segment, if any.
+    /// A PE certificate table counts as part of the image.
+    pub overlay: Option<SectionEntropy>,
+}
+
+/// Failure of [`section_entropy`].
+#[derive(Debug)]
+pub enum BinscanError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The file is not a well-formed executable.
+    Parse(String),
+    /// The file is valid but not a supported format, e.g. an archive or a
+    /// universal Mach-O.
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for BinscanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinscanError::Io(err) => write!(f, "{err}"),
+            BinscanError::Parse(msg) => write!(f, "malformed executable: {msg}"),
+            BinscanError::Unsupported(kind) => write!(f, "unsupported file type: {kind}"),
+        }
+    }
+}
+
+impl std::error::Error for BinscanError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BinscanError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for BinscanError {
+    fn from(err: io::Error) -> Self {
+        BinscanError::Io(err)
+    }
+}
+
+/// Parse the executable at `path` and analyze its sections.
+pub fn section_entropy<P: AsRef<Path>>(path: P) -> Result<BinaryReport, BinscanError> {
+    section_entropy_of(&std::fs::read(path)?)
+}
+
+/// Like [`section_entropy`] for an executable already in memory.
+///
+/// Sections extending past the end of `data` are truncated to it.
+pub fn section_entropy_of(data: &[u8]) -> Result<BinaryReport, BinscanError> {
+    let object = Object::parse(data).map_err(|err| BinscanError::Parse(err.to_string()))?;
+    let (format, layout) = match object {
+        Object::Elf(elf) => (BinaryFormat::Elf, elf_layout(&elf)),
+        Object::PE(pe) => (BinaryFormat::Pe, pe_layout(&pe)),
+        Object::Mach(mach::Mach::Binary(macho)) => (BinaryFormat::MachO, macho_layout(&macho)?),
+        Object::Mach(mach::Mach::Fat(_)) => {
+            return Err(BinscanError::Unsupported("universal Mach-O"));
+        }
+        Object::TE(_) => return Err(BinscanError::Unsupported("TE image")),
+        Object::COFF(_) => return Err(BinscanError::Unsupported("COFF object")),
+        Object::Archive(_) => return Err(BinscanError::Unsupported("archive")),
+        _ => return Err(BinscanError::Unsupported("unknown format")),
+    };
+
+    let analyze = |name: String, range: Range<u64>, executable: bool| {
+        let start = (range.start as usize).min(data.len());
+        let end = usize::try_from(range.end).map_or(data.len(), |e| e.min(data.len()));
+        let bytes = &data[start..end.max(start)];
+        let stats = EntStats::from_data(bytes, false);
+        SectionEntropy {
+            name,
+            offset: start as u64,
+            size: bytes.len() as u64,
+            executable,
+            class: classify(&stats),
+            stats,
+        }
+    };
+    let sections = layout
+        .sections
+        .into_iter()
+        .filter(|s| s.range.end > s.range.start)
+        .map(|s| analyze(s.name, s.range, s.executable))
+        .collect();
+    let overlay = (layout.image_end < data.len() as u64).then(|| {
+        analyze(
+            "<overlay>".to_string(),
+            layout.image_end..data.len() as u64,
+            false,
+        )
+    });
+    Ok(BinaryReport {
+        format,
+        sections,
+        overlay,
+    })
+}
+
+/// A section's location, before analysis.
+struct RawSection {
+    name: String,
+    range: Range<u64>,
+    executable: bool,
+}
+
+/// Sections of a file and the end of everything its headers account for.
+struct Layout {
+    sections: Vec<RawSection>,
+    image_end: u64,
+}
+
+fn elf_layout(elf: &elf::Elf) -> Layout {
+    let header = &elf.header;
+    let mut image_end = (header.e_ehsize as u64)
+        .max(header.e_phoff + header.e_phnum as u64 * header.e_phentsize as u64)
+        .max(header.e_shoff + header.e_shnum as u64 * header.e_shentsize as u64);
+    for ph in &elf.program_headers {
+        image_end = image_end.max(ph.p_offset + ph.p_filesz);
+    }
+    let mut sections = Vec::new();
+    for sh in &elf.section_headers {
+        if sh.sh_type == elf::section_header::SHT_NOBITS {
+            continue;
+        }
+        let range = sh.sh_offset..sh.sh_offset + sh.sh_size;
+        image_end = image_end.max(range.end);
+        sections.push(RawSection {
+            name: elf.shdr_strtab.get_at(sh.sh_name).unwrap_or("").to_string(),
+            range,
+            executable: sh.sh_flags & elf::section_header::SHF_EXECINSTR as u64 != 0,
+        });
+    }
+    Layout {
+        sections,
+        image_end,
+    }
+}
+
+fn pe_layout(pe: &pe::PE) -> Layout {
+    let optional = pe.header.optional_header.as_ref();
+    let mut image_end = optional.map_or(0, |o| o.windows_fields.size_of_headers as u64);
+    // The certificate table's "virtual address" is a file offset.
+    if let Some(certs) = optional.and_then(|o| o.data_directories.get_certificate_table()) {
+        image_end = image_end.max(certs.virtual_address as u64 + certs.size as u64);
+    }
+    let mut sections = Vec::new();
+    for section in &pe.sections {
+        let start = section.pointer_to_raw_data as u64;
+        let range = start..start + section.size_of_raw_data as u64;
+        image_end = image_end.max(range.end);
+        let name = section
+            .real_name
+            .clone()
+            .or_else(|| section.name().ok().map(str::to_string))
+            .unwrap_or_default();
+        sections.push(RawSection {
+            name,
+            range,
+            executable: section.characteristics & pe::section_table::IMAGE_SCN_MEM_EXECUTE != 0,
+        });
+    }
+    Layout {
+        sections,
+        image_end,
+    }
+}
+
+fn macho_layout(macho: &mach::MachO) -> Result<Layout, BinscanError> {
+    use mach::constants::{
+        SECTION_TYPE, S_ATTR_PURE_INSTRUCTIONS, S_ATTR_SOME_INSTRUCTIONS, S_GB_ZEROFILL,
+        S_THREAD_LOCAL_ZEROFILL, S_ZEROFILL,
+    };
+    let mut image_end = 0;
+    let mut sections = Vec::new();
+    for segment in &macho.segments {
+        image_end = image_end.max(segment.fileoff + segment.filesize);
+        let segment_sections = segment
+            .sections()
+            .map_err(|err| BinscanError::Parse(err.to_string()))?;
+        for (section, _) in segment_sections {
+            let kind = section.flags & SECTION_TYPE;
+            if matches!(kind, S_ZEROFILL | S_GB_ZEROFILL | S_THREAD_LOCAL_ZEROFILL) {
+                continue;
+            }
+            let start = section.offset as u64;
+            sections.push(RawSection {
+                name: format!(
+                    "{},{}",
+                    section.segname().unwrap_or(""),
+                    section.name().unwrap_or("")
+                ),
+                range: start..start + section.size,
+                executable: section.flags & (S_ATTR_PURE_INSTRUCTIONS | S_ATTR_SOME_INSTRUCTIONS)
+                    != 0,
+            });
+        }
+    }
+    Ok(Layout {
+        sections,
+        image_end,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_own_executable_sections_and_overlay() {
+        let mut image = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+        let report = section_entropy_of(&image).unwrap();
+        assert_eq!(report.format, BinaryFormat::Elf);
+        assert!(report.overlay.is_none());
+        let text = report.sections.iter().find(|s| s.name == ".text").unwrap();
+        assert!(text.executable);
+        assert_eq!(text.class, ContentClass::Code);
+        assert!(report.sections.iter().all(|s| s.name != ".bss"));
+
+        let image_len = image.len() as u64;
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        image.extend((0..65_536).map(|_| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            (x >> 56) as u8
+        }));
+        let overlay = section_entropy_of(&image).unwrap().overlay.unwrap();
+        assert_eq!((overlay.offset, overlay.size), (image_len, 65_536));
+        assert_eq!(overlay.class, ContentClass::Encrypted);
+    }
+
+    #[test]
+    fn test_rejects_non_executables() {
+        assert!(matches!(
+            section_entropy_of(b"just some text, not an executable"),
+            Err(BinscanError::Parse(_) | BinscanError::Unsupported(_))
+        ));
+        let err = section_entropy("/nonexistent/ent-rs-binscan").unwrap_err();
+        assert!(matches!(err, BinscanError::Io(_)));
+    }
+}
@@ -0,0 +1,257 @@
+//! Result caching keyed by the SHA-256 of the input.
+//!
+//! Repeated scans over mostly unchanged corpora only need to analyze new or
+//! modified inputs. Results are stored as JSON under a key made of the input
+//! digest and a description of the analysis, in memory or in a directory.
+//!
+//! ```rust
+//! use ent_rs::cache::{Cache, MemoryStore};
+//! let mut cache = Cache::new(MemoryStore::default());
+//! let first = cache.stats(b"some input", false).unwrap();
+//! let again = cache.stats(b"some input", false).unwrap();
+//! assert_eq!(first.entropy, again.entropy);
+//! assert_eq!((cache.hits(), cache.misses()), (1, 1));
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::digest::InputDigest;
+use crate::EntStats;
+
+/// Identifies one analysis of one input.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    /// Hex SHA-256 of the input.
+    pub digest: String,
+    /// Description of the analysis and its configuration, e.g. `ent-bytes-v0.1.1`.
+    pub config: String,
+}
+
+impl CacheKey {
+    /// Key for analyzing `data` with the analysis described by `config`.
+    pub fn new(data: &[u8], config: &str) -> Self {
+        CacheKey::from_digest(&InputDigest::new(data, false), config)
+    }
+
+    /// Key for the input described by `input`, e.g. the
+    /// [`SuiteReport::input`](crate::suite::SuiteReport::input) of an earlier
+    /// run. The input's mode is not part of the key; include it in `config`.
+    pub fn from_digest(input: &InputDigest, config: &str) -> Self {
+        CacheKey {
+            digest: input.sha256_hex().expect("the cache feature enables sha2"),
+            config: config.to_string(),
+        }
+    }
+
+    /// File name used by [`DirectoryStore`]; characters other than ASCII
+    /// alphanumerics, `.`, `-` and `_` in the config are replaced by `_`.
+    pub fn file_name(&self) -> String {
+        let config: String = self
+            .config
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        format!("{}.{config}.json", self.digest)
+    }
+}
+
+/// Storage backend for cached results.
+pub trait CacheStore {
+    /// Stored JSON for `key`, if any.
+    fn load(&mut self, key: &CacheKey) -> io::Result<Option<String>>;
+    /// Store JSON for `key`, replacing any previous value.
+    fn store(&mut self, key: &CacheKey, json: &str) -> io::Result<()>;
+}
+
+/// In-memory store; contents are lost when it is dropped.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    entries: HashMap<CacheKey, String>,
+}
+
+impl MemoryStore {
+    /// Number of stored results.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether nothing is stored.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl CacheStore for MemoryStore {
+    fn load(&mut self, key: &CacheKey) -> io::Result<Option<String>> {
+        Ok(self.entries.get(key).cloned())
+    }
+
+    fn store(&mut self, key: &CacheKey, json: &str) -> io::Result<()> {
+        self.entries.insert(key.clone(), json.to_string());
+        Ok(())
+    }
+}
+
+/// One JSON file per result in a directory.
+#[derive(Debug, Clone)]
+pub struct DirectoryStore {
+    dir: PathBuf,
+}
+
+impl DirectoryStore {
+    /// Use `dir` for storage, creating it if needed.
+    pub fn new<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(DirectoryStore {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    /// The storage directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl CacheStore for DirectoryStore {
+    fn load(&mut self, key: &CacheKey) -> io::Result<Option<String>> {
+        match fs::read_to_string(self.dir.join(key.file_name())) {
+            Ok(json) => Ok(Some(json)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn store(&mut self, key: &CacheKey, json: &str) -> io::Result<()> {
+        // Write then rename so concurrent readers never see a partial file.
+        let path = self.dir.join(key.file_name());
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, &path)
+    }
+}
+
+/// Computes results through a [`CacheStore`], reusing stored ones.
+#[derive(Debug, Clone)]
+pub struct Cache<S> {
+    store: S,
+    hits: usize,
+    misses: usize,
+}
+
+impl<S: CacheStore> Cache<S> {
+    /// Cache backed by `store`.
+    pub fn new(store: S) -> Self {
+        Cache {
+            store,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// The backing store.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Lookups answered from the store.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Lookups that had to compute the result.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// The stored result for `data` under `config`, or `compute(data)`,
+    /// which is then stored.
+    ///
+    /// Entries that fail to parse (e.g. written by an incompatible version)
+    /// are treated as missing and overwritten.
+    pub fn get_or_compute<T, F>(&mut self, data: &[u8], config: &str, compute: F) -> io::Result<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce(&[u8]) -> T,
+    {
+        let key = CacheKey::new(data, config);
+        if let Some(value) = self
+            .store
+            .load(&key)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+        {
+            self.hits += 1;
+            return Ok(value);
+        }
+        self.misses += 1;
+        let value = compute(data);
+        let json = serde_json::to_string(&value).map_err(io::Error::other)?;
+        self.store.store(&key, &json)?;
+        Ok(value)
+    }
+
+    /// Cached [`EntStats::from_data`].
+    ///
+    /// The key includes the crate version, so results are recomputed after
+    /// an upgrade.
+    pub fn stats(&mut self, data: &[u8], bit_mode: bool) -> io::Result<EntStats> {
+        let mode = if bit_mode { "bits" } else { "bytes" };
+        let config = format!("ent-{mode}-v{}", env!("CARGO_PKG_VERSION"));
+        self.get_or_compute(data, &config, |data| EntStats::from_data(data, bit_mode))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directory_store_round_trip() {
+        let dir = std::env::temp_dir().join(format!("ent-rs-cache-{}", std::process::id()));
+        // chi-square below 255 dof, so p_value is NaN and serialized as null.
+        let data: Vec<u8> = (0..=255u8).cycle().take(4096).collect();
+
+        let mut cache = Cache::new(DirectoryStore::new(&dir).unwrap());
+        let computed = cache.stats(&data, false).unwrap();
+        assert!(computed.p_value.is_nan());
+
+        let mut reopened = Cache::new(DirectoryStore::new(&dir).unwrap());
+        let cached = reopened.stats(&data, false).unwrap();
+        assert_eq!(reopened.hits(), 1);
+        assert_eq!(cached.entropy, computed.entropy);
+        assert!(cached.p_value.is_nan());
+        assert_eq!(cached.byte_frequencies, computed.byte_frequencies);
+
+        // Bit mode is a different analysis.
+        reopened.stats(&data, true).unwrap();
+        assert_eq!(reopened.misses(), 1);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_corrupt_entry_is_recomputed() {
+        let mut cache = Cache::new(MemoryStore::default());
+        let key = CacheKey::new(b"abc", "custom/v1");
+        assert!(key.file_name().ends_with(".custom_v1.json"));
+        cache.store.store(&key, "not json").unwrap();
+        let n: usize = cache
+            .get_or_compute(b"abc", "custom/v1", |d| d.len())
+            .unwrap();
+        assert_eq!(n, 3);
+        assert_eq!(cache.misses(), 1);
+        assert_eq!(cache.get_or_compute(b"abc", "custom/v1", |_| 0).unwrap(), 3);
+    }
+}
@@ -0,0 +1,143 @@
+//! Candidate file-carving boundaries from entropy change points.
+//!
+//! With the `magic` feature, embedded magic-byte hits are merged into the
+//! hints: a nearby entropy boundary snaps to the signature offset and gains
+//! confidence, and signatures without an entropy boundary become hints too.
+//!
+//! ```rust
+//! use ent_rs::carving::carving_hints;
+//! use ent_rs::classify::ContentClass;
+//! let mut image = vec![0u8; 8192];
+//! image.extend((0..=255u8).cycle().take(8192).map(|b| b.wrapping_mul(167)));
+//! let hints = carving_hints(&image, 1024, 2.0);
+//! assert_eq!(hints[0].offset, 8192);
+//! assert_eq!(hints[0].before, ContentClass::Padding);
+//! ```
+
+use crate::classify::{classify_data, ContentClass};
+use crate::profile::change_points;
+
+/// A candidate boundary between two differently-looking regions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CarvingHint {
+    /// Offset of the boundary.
+    pub offset: usize,
+    /// Confidence in `0.0..=1.0` that a file or section starts or ends here.
+    pub confidence: f64,
+    /// Classification of the window before the boundary.
+    pub before: ContentClass,
+    /// Classification of the window after the boundary.
+    pub after: ContentClass,
+    /// Name of the file signature found at the boundary, if any.
+    pub signature: Option<&'static str>,
+}
+
+/// Suggest carving boundaries where entropy changes by at least `min_delta`
+/// bits per byte between adjacent `window`-sized blocks.
+pub fn carving_hints(data: &[u8], window: usize, min_delta: f64) -> Vec<CarvingHint> {
+    let window = window.max(1);
+    let mut hints: Vec<CarvingHint> = change_points(data, window, min_delta)
+        .into_iter()
+        .map(|point| {
+            let (before, after) = classify_around(data, point.offset, window);
+            // A full 8-bit swing is certain; a class change adds weight.
+            let mut confidence = (point.delta().abs() / 8.0).min(1.0) * 0.75;
+            if before != after {
+                confidence += 0.25;
+            }
+            CarvingHint {
+                offset: point.offset,
+                confidence,
+                before,
+                after,
+                signature: None,
+            }
+        })
+        .collect();
+
+    #[cfg(feature = "magic")]
+    merge_signatures(data, window, &mut hints);
+
+    hints.sort_by_key(|h| h.offset);
+    hints
+}
+
+fn classify_around(data: &[u8], offset: usize, window: usize) -> (ContentClass, ContentClass) {
+    let before = &data[offset.saturating_sub(window)..offset];
+    let after = &data[offset..(offset + window).min(data.len())];
+    (classify_data(before), classify_data(after))
+}
+
+#[cfg(feature = "magic")]
+fn merge_signatures(data: &[u8], window: usize, hints: &mut Vec<CarvingHint>) {
+    for hit in crate::signature::find_signatures(data) {
+        let name = Some(hit.signature.name);
+        let nearby = hints
+            .iter_mut()
+            .find(|h| h.signature.is_none() && h.offset.abs_diff(hit.offset) <= window);
+        match nearby {
+            Some(hint) => {
+                hint.offset = hit.offset;
+                hint.signature = name;
+                hint.confidence = (hint.confidence + 0.5).min(1.0);
+            }
+            None => {
+                let (before, after) = classify_around(data, hit.offset, window);
+                hints.push(CarvingHint {
+                    offset: hit.offset,
+                    confidence: 0.5,
+                    before,
+                    after,
+                    signature: name,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_bytes(len: usize) -> Vec<u8> {
+        let mut x = 0x2545_f491_4f6c_dd1du64;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_hints_classify_both_sides() {
+        let mut image = b"The quick brown fox jumps over the lazy dog. ".repeat(200);
+        image.truncate(8192);
+        image.extend(random_bytes(8192));
+        let hints = carving_hints(&image, 1024, 1.5);
+        assert_eq!(hints.len(), 1);
+        assert_eq!(hints[0].offset, 8192);
+        assert_eq!(hints[0].before, ContentClass::Text);
+        assert_eq!(hints[0].after, ContentClass::Encrypted);
+        assert!(hints[0].confidence > 0.5);
+    }
+
+    #[test]
+    fn test_no_hints_on_uniform_input() {
+        assert!(carving_hints(&random_bytes(16384), 1024, 1.5).is_empty());
+    }
+
+    #[cfg(feature = "magic")]
+    #[test]
+    fn test_signature_snaps_boundary() {
+        let mut image = vec![0u8; 8000];
+        image.extend(b"PK\x03\x04");
+        image.extend(random_bytes(8188));
+        let hints = carving_hints(&image, 1024, 1.5);
+        assert_eq!(hints[0].offset, 8000);
+        assert_eq!(hints[0].signature, Some("ZIP"));
+        assert!(hints[0].confidence > 0.9);
+    }
+}
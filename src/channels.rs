@@ -0,0 +1,173 @@
+//! Per-channel analysis of interleaved multi-channel data.
+//!
+//! Mixing channels masks each one's bias: RGBA pixels with an opaque alpha
+//! channel, or a multi-ADC capture where one converter is stuck, can still
+//! look reasonable as a whole. Splitting the frames into channels shows each
+//! channel on its own and how strongly the channels track each other.
+//!
+//! ```rust
+//! use ent_rs::channels::{analyze_channels, Interleave};
+//! // RGBA pixels: noisy colour, constant alpha.
+//! let pixels: Vec<u8> = (0..4096u32)
+//!     .flat_map(|i| {
+//!         let v = i.wrapping_mul(2_654_435_761).to_le_bytes();
+//!         [v[1], v[2], v[3], 255]
+//!     })
+//!     .collect();
+//! let analysis = analyze_channels(&pixels, Interleave::new(4), false);
+//! assert!(analysis.channels[0].entropy > 7.9);
+//! assert_eq!(analysis.channels[3].entropy, 0.0);
+//! ```
+
+use crate::pair::pearson;
+use crate::EntStats;
+
+/// Layout of interleaved frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interleave {
+    /// Number of channels analyzed, one byte each at the start of a frame.
+    pub channels: usize,
+    /// Bytes per frame; any bytes after the channels are skipped.
+    pub stride: usize,
+}
+
+impl Interleave {
+    /// `channels` one-byte channels per frame, with no padding.
+    pub fn new(channels: usize) -> Self {
+        Interleave {
+            channels,
+            stride: channels,
+        }
+    }
+
+    /// Frames of `stride` bytes, e.g. 4 for RGB pixels padded to RGBX.
+    pub fn with_stride(mut self, stride: usize) -> Self {
+        self.stride = stride;
+        self
+    }
+}
+
+/// Statistics of each channel and their pairwise correlation.
+#[derive(Debug, Clone)]
+pub struct ChannelAnalysis {
+    /// Statistics of each channel in order.
+    pub channels: Vec<EntStats>,
+    /// Pearson correlation between channels `i` and `j` at `[i][j]`, over
+    /// complete frames; 1 on the diagonal and NaN where a channel is constant.
+    pub correlation: Vec<Vec<f64>>,
+}
+
+impl ChannelAnalysis {
+    /// Channel pair `(i, j)`, `i < j`, with the largest absolute
+    /// correlation, if any pair is defined.
+    pub fn max_correlation(&self) -> Option<(usize, usize, f64)> {
+        let n = self.channels.len();
+        (0..n)
+            .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+            .map(|(i, j)| (i, j, self.correlation[i][j]))
+            .filter(|(_, _, r)| !r.is_nan())
+            .max_by(|a, b| a.2.abs().total_cmp(&b.2.abs()))
+    }
+}
+
+/// Split `data` into one buffer per channel.
+///
+/// A trailing partial frame contributes to the channels it covers.
+///
+/// # Panics
+///
+/// If `layout` has no channels or a stride shorter than its channels.
+pub fn deinterleave(data: &[u8], layout: Interleave) -> Vec<Vec<u8>> {
+    assert!(
+        layout.channels > 0 && layout.stride >= layout.channels,
+        "invalid interleave layout {layout:?}"
+    );
+    (0..layout.channels)
+        .map(|c| {
+            data.iter()
+                .skip(c)
+                .step_by(layout.stride)
+                .copied()
+                .collect()
+        })
+        .collect()
+}
+
+/// Analyze each channel of `data` separately.
+///
+/// # Panics
+///
+/// If `layout` has no channels or a stride shorter than its channels.
+pub fn analyze_channels(data: &[u8], layout: Interleave, bit_mode: bool) -> ChannelAnalysis {
+    let split = deinterleave(data, layout);
+    let frames = data.len() / layout.stride;
+    let correlation = (0..split.len())
+        .map(|i| {
+            (0..split.len())
+                .map(|j| {
+                    if i == j {
+                        1.0
+                    } else {
+                        pearson(&split[i][..frames], &split[j][..frames])
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    ChannelAnalysis {
+        channels: split
+            .iter()
+            .map(|channel| EntStats::from_data(channel, bit_mode))
+            .collect(),
+        correlation,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noise(len: usize, seed: u64) -> Vec<u8> {
+        let mut x = seed;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_deinterleave_with_padding_and_partial_frame() {
+        let data = [1, 2, 3, 0, 4, 5, 6, 0, 7, 8];
+        let split = deinterleave(&data, Interleave::new(3).with_stride(4));
+        assert_eq!(split, [vec![1, 4, 7], vec![2, 5, 8], vec![3, 6]]);
+    }
+
+    #[test]
+    fn test_correlated_channels() {
+        // Two ADCs sampling the same signal with independent noise, and a
+        // third unrelated one.
+        let signal = noise(20_000, 3);
+        let jitter = noise(20_000, 5);
+        let other = noise(20_000, 7);
+        let data: Vec<u8> = (0..20_000)
+            .flat_map(|i| [signal[i], signal[i].saturating_add(jitter[i] % 8), other[i]])
+            .collect();
+        let analysis = analyze_channels(&data, Interleave::new(3), false);
+        assert_eq!(analysis.channels.len(), 3);
+        let (i, j, r) = analysis.max_correlation().unwrap();
+        assert_eq!((i, j), (0, 1));
+        assert!(r > 0.99);
+        assert!(analysis.correlation[0][2].abs() < 0.05);
+        assert_eq!(analysis.correlation[2][0], analysis.correlation[0][2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid interleave layout")]
+    fn test_stride_shorter_than_channels() {
+        deinterleave(&[0; 8], Interleave::new(4).with_stride(2));
+    }
+}
@@ -0,0 +1,111 @@
+//! Coarse content classification from byte statistics.
+//!
+//! ```rust
+//! use ent_rs::classify::{classify_data, ContentClass};
+//! assert_eq!(classify_data(&[0u8; 4096]), ContentClass::Padding);
+//! assert_eq!(classify_data(b"plain old ASCII text, nothing to see here."), ContentClass::Text);
+//! ```
+
+use crate::{chisquare_p_value, EntStats};
+
+/// Likely kind of content, judged from its byte distribution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContentClass {
+    /// Constant or nearly constant bytes (zero fill, erased flash).
+    Padding,
+    /// Printable ASCII text.
+    Text,
+    /// Structured binary data such as machine code or tables.
+    Code,
+    /// High entropy that is measurably non-uniform, typical of compressed data.
+    Compressed,
+    /// Bytes indistinguishable from uniform random, typical of ciphertext.
+    Encrypted,
+}
+
+/// Fraction of printable bytes above which data counts as text.
+const TEXT_FRACTION: f64 = 0.95;
+
+/// Classify data from its byte-mode statistics.
+///
+/// Bit-mode statistics carry no byte frequencies, so they can only be
+/// told apart by entropy and never classify as [`ContentClass::Text`].
+pub fn classify(stats: &EntStats) -> ContentClass {
+    let freqs = stats.byte_frequencies.as_deref().unwrap_or(&[]);
+    let len = freqs.iter().map(|&(_, count, _)| count).sum::<usize>();
+    // Entropy of a short sample is capped by its length.
+    let ceiling = if len > 0 {
+        (len as f64).log2().min(8.0)
+    } else {
+        8.0
+    };
+    let entropy = if stats.bit_frequencies.is_some() {
+        stats.entropy * 8.0
+    } else {
+        stats.entropy
+    };
+
+    if entropy < 1.0 {
+        return ContentClass::Padding;
+    }
+    let printable = freqs
+        .iter()
+        .filter(|&&(b, _, _)| matches!(b, 0x20..=0x7E | b'\t' | b'\n' | b'\r'))
+        .map(|&(_, _, fraction)| fraction)
+        .sum::<f64>();
+    if printable >= TEXT_FRACTION {
+        return ContentClass::Text;
+    }
+    if entropy < 0.85 * ceiling {
+        return ContentClass::Code;
+    }
+    if len > 0 && chisquare_p_value(stats.chisquare, 255.0) >= 0.001 {
+        ContentClass::Encrypted
+    } else {
+        ContentClass::Compressed
+    }
+}
+
+/// Classify a byte slice.
+pub fn classify_data(data: &[u8]) -> ContentClass {
+    classify(&EntStats::from_data(data, false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_bytes(len: usize) -> Vec<u8> {
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_classify_random_as_encrypted() {
+        assert_eq!(classify_data(&random_bytes(65536)), ContentClass::Encrypted);
+        assert_eq!(classify_data(&random_bytes(512)), ContentClass::Encrypted);
+    }
+
+    #[test]
+    fn test_classify_skewed_high_entropy_as_compressed() {
+        // Nearly uniform, but with a strong excess of a few values.
+        let mut data = random_bytes(65536);
+        data.extend([0u8; 2048]);
+        assert_eq!(classify_data(&data), ContentClass::Compressed);
+    }
+
+    #[test]
+    fn test_classify_structured_binary_as_code() {
+        let data: Vec<u8> = (0..8192u32)
+            .map(|i| [0x48, 0x89, 0xE5, 0x00, (i % 16) as u8][i as usize % 5])
+            .collect();
+        assert_eq!(classify_data(&data), ContentClass::Code);
+    }
+}
@@ -0,0 +1,173 @@
+//! Collision entropy and collision counts.
+//!
+//! A source can be biased toward a handful of values and still show close
+//! to 8 bits of Shannon entropy. Collisions, pairs of samples with the same
+//! value, are more sensitive to that bias: [`collision_stats`] compares how
+//! many pairs collide with how many would for uniform random bytes.
+//!
+//! ```rust
+//! use ent_rs::collision::collision_stats;
+//! // Every value occurs, but 0 makes up a tenth of the data.
+//! let data: Vec<u8> = (0u32..100_000)
+//!     .map(|i| if i % 10 == 0 { 0 } else { (i.wrapping_mul(2_654_435_761) >> 24) as u8 })
+//!     .collect();
+//! let stats = collision_stats(&data);
+//! assert!(stats.entropy < 6.5);
+//! assert!(stats.value_collisions.z_score > 10.0);
+//! ```
+
+use crate::renyi::renyi_entropy_from_counts;
+use crate::special::erfc;
+use crate::{byte_counts, SQRT_2};
+
+/// Probability that two uniform random bytes are equal.
+const UNIFORM_RATE: f64 = 1.0 / 256.0;
+
+/// Observed and expected counts of one kind of collision.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CollisionCount {
+    /// Pairs of samples compared.
+    pub pairs: u64,
+    /// Pairs whose two samples are equal.
+    pub observed: u64,
+    /// Equal pairs expected for uniform random bytes, `pairs / 256`.
+    pub expected: f64,
+    /// `(observed - expected)` in standard deviations; positive means more
+    /// collisions than random data, i.e. a biased source.
+    pub z_score: f64,
+    /// Two-sided p-value of the z-score under the normal approximation.
+    pub p_value: f64,
+}
+
+impl CollisionCount {
+    fn new(pairs: u64, observed: u64) -> Self {
+        let expected = pairs as f64 * UNIFORM_RATE;
+        // The pair indicators are pairwise uncorrelated under uniformity, so
+        // the variance is that of a binomial count.
+        let sd = (expected * (1.0 - UNIFORM_RATE)).sqrt();
+        let z_score = if sd > 0.0 {
+            (observed as f64 - expected) / sd
+        } else {
+            f64::NAN
+        };
+        CollisionCount {
+            pairs,
+            observed,
+            expected,
+            z_score,
+            p_value: erfc(z_score.abs() / SQRT_2),
+        }
+    }
+
+    /// Fraction of pairs that collide; about 1/256 for random bytes.
+    pub fn rate(&self) -> f64 {
+        self.observed as f64 / self.pairs as f64
+    }
+}
+
+/// Collision entropy and collision counts of a byte sequence.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CollisionStats {
+    /// Collision entropy, `-log2 Σ p²`, of the byte frequencies, in bits per
+    /// byte.
+    pub entropy: f64,
+    /// Collisions among all pairs of positions, `n (n - 1) / 2` of them.
+    pub value_collisions: CollisionCount,
+    /// Adjacent bytes with equal values, `n - 1` pairs.
+    pub adjacent_repeats: CollisionCount,
+}
+
+/// Collision entropy of `data` in bits per byte, the Rényi entropy of
+/// order 2.
+pub fn collision_entropy(data: &[u8]) -> f64 {
+    renyi_entropy_from_counts(&byte_counts(data), 2.0)
+}
+
+/// Collision entropy and collision counts of `data`.
+///
+/// Counts of fewer than 2 bytes compare no pairs; their z-scores and
+/// p-values are NaN.
+pub fn collision_stats(data: &[u8]) -> CollisionStats {
+    let counts = byte_counts(data);
+    let n = data.len() as u64;
+    let equal_pairs = counts
+        .iter()
+        .map(|&c| c as u64 * (c as u64).saturating_sub(1) / 2)
+        .sum();
+    let repeats = data.windows(2).filter(|w| w[0] == w[1]).count() as u64;
+    CollisionStats {
+        entropy: renyi_entropy_from_counts(&counts, 2.0),
+        value_collisions: CollisionCount::new(n * n.saturating_sub(1) / 2, equal_pairs),
+        adjacent_repeats: CollisionCount::new(n.saturating_sub(1), repeats),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_bytes(len: usize) -> Vec<u8> {
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_counts_on_small_input() {
+        let stats = collision_stats(b"aaab");
+        // Pairs: 6, of which the three "a" pairs collide; two adjacent repeats.
+        assert_eq!(
+            (
+                stats.value_collisions.pairs,
+                stats.value_collisions.observed
+            ),
+            (6, 3)
+        );
+        assert_eq!(
+            (
+                stats.adjacent_repeats.pairs,
+                stats.adjacent_repeats.observed
+            ),
+            (3, 2)
+        );
+        // Σ p² = 9/16 + 1/16
+        assert!((stats.entropy - -(10.0f64 / 16.0).log2()).abs() < 1e-12);
+        assert_eq!(stats.entropy, collision_entropy(b"aaab"));
+    }
+
+    #[test]
+    fn test_random_data_matches_expectation() {
+        let stats = collision_stats(&random_bytes(100_000));
+        assert!(stats.value_collisions.z_score.abs() < 4.0);
+        assert!(stats.adjacent_repeats.z_score.abs() < 4.0);
+        assert!((stats.value_collisions.rate() * 256.0 - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_bias_invisible_to_shannon_is_flagged() {
+        // Half the bytes are drawn from 64 values, the rest uniformly: Shannon
+        // entropy stays high, but collisions are far more frequent.
+        let data: Vec<u8> = random_bytes(100_000)
+            .chunks(2)
+            .flat_map(|p| [p[0] & 0x3f, p[1]])
+            .collect();
+        let stats = collision_stats(&data);
+        assert!(crate::EntStats::from_data(&data, false).entropy > 7.5);
+        assert!(stats.value_collisions.z_score > 100.0);
+        assert!(stats.value_collisions.p_value < 1e-10);
+        assert!(stats.entropy < 7.5);
+    }
+
+    #[test]
+    fn test_short_input_has_no_pairs() {
+        let stats = collision_stats(b"x");
+        assert_eq!(stats.value_collisions.pairs, 0);
+        assert!(stats.value_collisions.z_score.is_nan());
+    }
+}
@@ -0,0 +1,159 @@
+//! Evaluation of conditioning functions applied to raw noise.
+//!
+//! Mirrors the SP 800-90B treatment of conditioned output: the entropy of
+//! each input block is estimated from the raw data, the entropy the
+//! conditioning function can deliver per output block is derived from it with
+//! the 90B output-entropy formula, and both are reported next to statistics of
+//! the actual output.
+//!
+//! ```rust
+//! use ent_rs::conditioning::evaluate_conditioning;
+//! // A toy conditioner folding 8-byte blocks into one byte.
+//! let raw: Vec<u8> = (0..65_536u32).map(|i| (i.wrapping_mul(2_654_435_761) >> 28) as u8).collect();
+//! let report = evaluate_conditioning(&raw, 8, |block| vec![block.iter().fold(0, |a, b| a ^ b)]);
+//! assert_eq!(report.output_block_bytes, 1);
+//! assert!(report.output_block_entropy <= 8.0);
+//! ```
+
+use std::f64::consts::LN_2;
+
+use crate::sp800_90b::most_common_value;
+use crate::EntStats;
+
+/// Raw vs conditioned statistics and entropy accounting.
+#[derive(Debug, Clone)]
+pub struct ConditioningReport {
+    /// Statistics of the raw input.
+    pub input: EntStats,
+    /// Statistics of the concatenated conditioned output.
+    pub output: EntStats,
+    /// Bytes per conditioning input block.
+    pub input_block_bytes: usize,
+    /// Bytes per conditioning output block.
+    pub output_block_bytes: usize,
+    /// Min-entropy of the raw data in bits per byte (90B most common value).
+    pub input_min_entropy: f64,
+    /// Min-entropy of the output in bits per byte (90B most common value).
+    pub output_min_entropy: f64,
+    /// Entropy in bits credited to each input block.
+    pub input_block_entropy: f64,
+    /// Entropy in bits per output block allowed by the 90B output-entropy formula.
+    pub output_block_entropy: f64,
+    /// Entropy lost per block, `input_block_entropy - output_block_entropy`.
+    pub entropy_loss: f64,
+}
+
+/// Condition `raw` in blocks of `block_len` bytes with `conditioner` and
+/// report input vs output entropy.
+///
+/// A trailing partial block is not conditioned. The conditioner's narrowest
+/// internal width is assumed to equal its output width, which holds for hash
+/// functions and CBC-MAC.
+pub fn evaluate_conditioning<F>(
+    raw: &[u8],
+    block_len: usize,
+    mut conditioner: F,
+) -> ConditioningReport
+where
+    F: FnMut(&[u8]) -> Vec<u8>,
+{
+    let block_len = block_len.max(1);
+    let mut output = Vec::new();
+    let mut output_block_bytes = 0;
+    for block in raw.chunks_exact(block_len) {
+        let out = conditioner(block);
+        output_block_bytes = out.len();
+        output.extend(out);
+    }
+
+    let input_min_entropy = most_common_value(raw);
+    let n_in = (block_len * 8) as f64;
+    let n_out = (output_block_bytes * 8) as f64;
+    let input_block_entropy = (input_min_entropy * block_len as f64).min(n_in);
+    let output_block_entropy = output_entropy(n_in, n_out, n_out, input_block_entropy);
+
+    ConditioningReport {
+        input: EntStats::from_data(raw, false),
+        output: EntStats::from_data(&output, false),
+        input_block_bytes: block_len,
+        output_block_bytes,
+        input_min_entropy,
+        output_min_entropy: most_common_value(&output),
+        input_block_entropy,
+        output_block_entropy,
+        entropy_loss: input_block_entropy - output_block_entropy,
+    }
+}
+
+/// SP 800-90B §3.1.5.1.2 `Output_Entropy(n_in, n_out, nw, h_in)`.
+///
+/// All quantities are in bits. Evaluated in the log domain so wide inputs
+/// (hundreds of bits) do not overflow.
+pub fn output_entropy(n_in: f64, n_out: f64, nw: f64, h_in: f64) -> f64 {
+    if n_out <= 0.0 || h_in <= 0.0 {
+        return 0.0;
+    }
+    let n = n_out.min(nw);
+    let p_high = (-h_in).exp2();
+    // log2(P_low) with P_low = (1 - P_high) / (2^n_in - 1).
+    let log_p_low = (1.0 - p_high).log2() - n_in - (-(-n_in).exp2()).ln_1p() / LN_2;
+    let log_psi = log2_add(n_in - n + log_p_low, p_high.log2());
+    let log_u = (n_in - n) + (1.0 + (2.0 * n * LN_2 / (n_in - n).exp2()).sqrt()).log2();
+    let log_omega = log_u + log_p_low;
+    (-log_psi.max(log_omega)).min(n_out).min(h_in)
+}
+
+/// `log2(2^a + 2^b)` without overflow.
+fn log2_add(a: f64, b: f64) -> f64 {
+    let (hi, lo) = if a > b { (a, b) } else { (b, a) };
+    if lo == f64::NEG_INFINITY {
+        return hi;
+    }
+    hi + (lo - hi).exp2().ln_1p() / LN_2
+}
+
+/// SHA-256 conditioning function for use with [`evaluate_conditioning`].
+#[cfg(feature = "sha2")]
+pub fn sha256(block: &[u8]) -> Vec<u8> {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(block).to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_entropy_limits() {
+        // Full-entropy input wider than the output: output is (almost) full entropy.
+        let h = output_entropy(512.0, 256.0, 256.0, 512.0);
+        assert!(h > 255.99 && h <= 256.0);
+        // Input entropy far below the output width caps the output.
+        let h = output_entropy(512.0, 256.0, 256.0, 64.0);
+        assert!((h - 64.0).abs() < 1e-6);
+        assert_eq!(output_entropy(512.0, 256.0, 256.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_folding_conditioner_loses_entropy() {
+        let raw: Vec<u8> = (0..65_536u32).map(|i| (i % 4) as u8).collect();
+        let report = evaluate_conditioning(&raw, 4, |block| block[..1].to_vec());
+        assert_eq!(report.output_block_bytes, 1);
+        assert!(report.input_block_entropy > 7.9);
+        assert!(report.output_block_entropy <= 8.0);
+        assert!(report.output_min_entropy < 0.1);
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn test_sha256_conditioning_of_biased_input() {
+        let raw: Vec<u8> = (0..64 * 512u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 30) as u8)
+            .collect();
+        let report = evaluate_conditioning(&raw, 64, sha256);
+        assert_eq!(report.output_block_bytes, 32);
+        assert!(report.input_min_entropy < 2.1);
+        assert!(report.output.entropy > 7.9);
+        assert!(report.entropy_loss >= 0.0);
+    }
+}
@@ -0,0 +1,228 @@
+//! Reading hardware RNG devices with timeout handling.
+//!
+//! Devices such as `/dev/hwrng` or a serial-attached TRNG can stall, and a
+//! blocking `read` cannot be interrupted portably. Reads therefore run on a
+//! background thread and the caller waits for each chunk with a timeout; on
+//! timeout the thread is left to finish (or block) on its own.
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use ent_rs::device::analyze_device;
+//! let stats = analyze_device("/dev/hwrng", 1 << 20, Duration::from_secs(5)).unwrap();
+//! println!("Entropy: {:.4}", stats.entropy);
+//! ```
+//!
+//! [`evaluate_device`] answers "is my dongle broken?" in one call:
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use ent_rs::device::evaluate_device;
+//! use ent_rs::randomness::RandomnessReport;
+//! let battery = RandomnessReport::builder().without_diehard();
+//! let report = evaluate_device("/dev/hwrng", 1 << 20, Duration::from_secs(5), &battery).unwrap();
+//! println!("{report}");
+//! assert!(report.passed());
+//! ```
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::randomness::{RandomnessReport, RandomnessReportBuilder};
+use crate::window::WindowAnalyzer;
+use crate::EntStats;
+
+/// Size of each read issued to the device.
+pub const CHUNK_SIZE: usize = 4096;
+
+/// Read `len` bytes from `path`, calling `on_chunk` as data arrives.
+///
+/// Fails with [`io::ErrorKind::TimedOut`] if no data arrives for `timeout`,
+/// and with [`io::ErrorKind::UnexpectedEof`] if the device ends early.
+pub fn read_chunks<P, F>(path: P, len: usize, timeout: Duration, mut on_chunk: F) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    F: FnMut(&[u8]),
+{
+    let mut file = File::open(path)?;
+    let (tx, rx) = mpsc::sync_channel::<io::Result<Vec<u8>>>(4);
+    thread::spawn(move || {
+        let mut remaining = len;
+        while remaining > 0 {
+            let mut buf = vec![0u8; remaining.min(CHUNK_SIZE)];
+            let result = match file.read(&mut buf) {
+                Ok(0) => Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "device returned end of file",
+                )),
+                Ok(n) => {
+                    buf.truncate(n);
+                    remaining -= n;
+                    Ok(buf)
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => Err(e),
+            };
+            let failed = result.is_err();
+            if tx.send(result).is_err() || failed {
+                return;
+            }
+        }
+    });
+
+    let mut received = 0;
+    while received < len {
+        let chunk = match rx.recv_timeout(timeout) {
+            Ok(chunk) => chunk?,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("no data from device within {timeout:?} after {received} bytes"),
+                ))
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                return Err(io::Error::other("device reader stopped"))
+            }
+        };
+        received += chunk.len();
+        on_chunk(&chunk);
+    }
+    Ok(())
+}
+
+/// Read exactly `len` bytes from a device.
+pub fn read_device<P: AsRef<Path>>(path: P, len: usize, timeout: Duration) -> io::Result<Vec<u8>> {
+    let mut data = Vec::with_capacity(len);
+    read_chunks(path, len, timeout, |chunk| data.extend_from_slice(chunk))?;
+    Ok(data)
+}
+
+/// Read `len` bytes from a device and compute byte-mode statistics.
+pub fn analyze_device<P: AsRef<Path>>(
+    path: P,
+    len: usize,
+    timeout: Duration,
+) -> io::Result<EntStats> {
+    let data = read_device(path, len, timeout)?;
+    Ok(EntStats::from_data(&data, false))
+}
+
+/// Read `len` bytes from a device into a live [`WindowAnalyzer`].
+pub fn feed_device<P: AsRef<Path>>(
+    path: P,
+    len: usize,
+    timeout: Duration,
+    window: &mut WindowAnalyzer,
+) -> io::Result<()> {
+    read_chunks(path, len, timeout, |chunk| window.extend(chunk))
+}
+
+/// Evaluation of a hardware RNG device by [`evaluate_device`].
+#[derive(Debug, Clone)]
+pub struct DeviceReport {
+    /// The device read.
+    pub path: PathBuf,
+    /// Bytes read.
+    pub len: usize,
+    /// Time taken to read them.
+    pub elapsed: Duration,
+    /// Byte-mode statistics of the output.
+    pub stats: EntStats,
+    /// Verdicts of the battery run over the output.
+    pub report: RandomnessReport,
+}
+
+impl DeviceReport {
+    /// Whether every test of the battery passed or was skipped.
+    pub fn passed(&self) -> bool {
+        self.report.passed()
+    }
+
+    /// Read rate in bytes per second.
+    pub fn throughput(&self) -> f64 {
+        self.len as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+impl fmt::Display for DeviceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: {} bytes in {:.3} s ({:.0} bytes/s), entropy {:.6} bits per byte",
+            self.path.display(),
+            self.len,
+            self.elapsed.as_secs_f64(),
+            self.throughput(),
+            self.stats.entropy
+        )?;
+        write!(f, "{}", self.report)
+    }
+}
+
+/// Read `len` bytes from a device, as [`read_device`] does, and run
+/// `battery` over them.
+pub fn evaluate_device<P: AsRef<Path>>(
+    path: P,
+    len: usize,
+    timeout: Duration,
+    battery: &RandomnessReportBuilder,
+) -> io::Result<DeviceReport> {
+    let path = path.as_ref();
+    let start = Instant::now();
+    let data = read_device(path, len, timeout)?;
+    let elapsed = start.elapsed();
+    Ok(DeviceReport {
+        path: path.to_path_buf(),
+        len,
+        elapsed,
+        stats: EntStats::from_data(&data, false),
+        report: battery.run(&data),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_regular_file_as_device() {
+        let path = std::env::temp_dir().join("ent-rs-device-test.bin");
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i * 31) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let read = read_device(&path, 9_000, Duration::from_secs(5)).unwrap();
+        assert_eq!(read, data[..9_000]);
+
+        let mut window = WindowAnalyzer::new(1024);
+        feed_device(&path, 5_000, Duration::from_secs(5), &mut window).unwrap();
+        assert_eq!(window.start_offset(), 5_000 - 1024);
+
+        let err = read_device(&path, 20_000, Duration::from_secs(5)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_evaluate_device_runs_battery() {
+        let path = std::env::temp_dir().join("ent-rs-device-evaluate-test.bin");
+        // A stuck-at-one-value source.
+        std::fs::write(&path, [0xA5u8; 50_000]).unwrap();
+        let battery = RandomnessReportBuilder::none().with_ent().with_fips140();
+        let report = evaluate_device(&path, 40_000, Duration::from_secs(5), &battery).unwrap();
+        assert_eq!(report.len, 40_000);
+        assert_eq!(report.stats.entropy, 0.0);
+        assert!(!report.passed());
+        assert!(report.throughput() > 0.0);
+        assert!(report
+            .to_string()
+            .starts_with(&format!("{}: 40000 bytes", path.display())));
+
+        let err = evaluate_device(&path, 60_000, Duration::from_secs(5), &battery).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
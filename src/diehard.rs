@@ -0,0 +1,726 @@
+//! Tests from Marsaglia's Diehard battery.
+//!
+//! Except for [`count_ones_stream`], which reads bytes, the tests read the
+//! input as big-endian 32-bit integers, the unit the original battery works
+//! in; a trailing partial integer is ignored.
+//!
+//! ```rust
+//! use ent_rs::diehard::{birthday_spacings, count_ones_stream};
+//! // A Weyl sequence: evenly spread values, hence far too regular spacings.
+//! let data: Vec<u8> = (0u32..300 * 512)
+//!     .flat_map(|i| i.wrapping_mul(2_654_435_761).to_be_bytes())
+//!     .collect();
+//! assert!(birthday_spacings(&data).p_value < 1e-6);
+//! assert!(count_ones_stream(&data).p_value < 1e-6);
+//! ```
+
+use crate::second_level::ks_uniform;
+use crate::special::erfc;
+use crate::{chisquare_p_value, SQRT_2};
+
+/// Birthdays per sample of the birthday spacings test.
+pub const BIRTHDAYS: usize = 512;
+
+/// Bits per birthday: the top 24 bits of each integer, so a "year" has 2^24
+/// days.
+pub const BIRTHDAY_BITS: u32 = 24;
+
+/// Classes of repeated spacings counted by the birthday spacings test: 0 to
+/// 5, and 6 or more.
+const SPACING_CLASSES: usize = 7;
+
+/// Result of the birthday spacings test.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BirthdaySpacingsResult {
+    /// Samples of [`BIRTHDAYS`] integers tested.
+    pub samples: usize,
+    /// Expected repeated spacings per sample, `λ = m³ / 4n = 2`.
+    pub lambda: f64,
+    /// Samples with 0, 1, ..., 5 and at least 6 repeated spacings.
+    pub counts: [usize; SPACING_CLASSES],
+    /// Chi-square statistic against the Poisson distribution with mean
+    /// `lambda`, with 6 degrees of freedom.
+    pub chi_square: f64,
+    /// Upper-tail p-value.
+    pub p_value: f64,
+}
+
+/// Diehard birthday spacings test: choose 512 birthdays in a year of 2^24
+/// days, sort them, and count the spacings between adjacent birthdays that
+/// occur more than once. For random birthdays that count is asymptotically
+/// Poisson with mean 2.
+///
+/// Each sample uses 2 KiB of input. At least 300 samples (600 KiB) keep
+/// every class's expected count above 5; the p-value is NaN without any.
+pub fn birthday_spacings(data: &[u8]) -> BirthdaySpacingsResult {
+    let lambda = (BIRTHDAYS as f64).powi(3) / (4.0 * (BIRTHDAY_BITS as f64).exp2());
+    let mut counts = [0; SPACING_CLASSES];
+    let mut birthdays = Vec::with_capacity(BIRTHDAYS);
+    let mut spacings = Vec::with_capacity(BIRTHDAYS);
+    for sample in data.chunks_exact(4 * BIRTHDAYS) {
+        birthdays.clear();
+        birthdays.extend(integers(sample).map(|x| x >> (32 - BIRTHDAY_BITS)));
+        birthdays.sort_unstable();
+        spacings.clear();
+        spacings.extend(birthdays.windows(2).map(|w| w[1] - w[0]));
+        spacings.sort_unstable();
+        let repeats = spacings.windows(2).filter(|w| w[0] == w[1]).count();
+        counts[repeats.min(SPACING_CLASSES - 1)] += 1;
+    }
+
+    let samples = counts.iter().sum::<usize>();
+    let n = samples as f64;
+    let mut tail = 1.0;
+    let mut pmf = (-lambda).exp();
+    let mut chi_square = 0.0;
+    for (k, &observed) in counts.iter().enumerate() {
+        let p = if k == SPACING_CLASSES - 1 { tail } else { pmf };
+        chi_square += (observed as f64 - n * p).powi(2) / (n * p);
+        tail -= pmf;
+        pmf *= lambda / (k + 1) as f64;
+    }
+    let p_value = if samples == 0 {
+        f64::NAN
+    } else {
+        chisquare_p_value(chi_square, (SPACING_CLASSES - 1) as f64)
+    };
+    BirthdaySpacingsResult {
+        samples,
+        lambda,
+        counts,
+        chi_square,
+        p_value,
+    }
+}
+
+/// Letters of the count-the-1s tests.
+const LETTERS: usize = 5;
+
+/// Probability of each letter: a byte with at most 2, 3, 4, 5, or at least 6
+/// ones.
+const LETTER_PROBABILITIES: [f64; LETTERS] = [
+    37.0 / 256.0,
+    56.0 / 256.0,
+    70.0 / 256.0,
+    56.0 / 256.0,
+    37.0 / 256.0,
+];
+
+/// Result of a count-the-1s test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CountOnesResult {
+    /// Letters, and overlapping words of each length, counted.
+    pub letters: usize,
+    /// Pearson statistic of the 3125 five-letter word counts.
+    pub q5: f64,
+    /// Pearson statistic of the 625 four-letter word counts.
+    pub q4: f64,
+    /// `Q5 - Q4`, chi-square with 2500 degrees of freedom for random data.
+    pub statistic: f64,
+    /// Upper-tail p-value.
+    pub p_value: f64,
+}
+
+/// Diehard count-the-1s test on a stream of bytes: each byte becomes a
+/// letter by its number of ones (at most 2, 3, 4, 5, or at least 6), and
+/// the frequencies of overlapping 5-letter words are tested.
+///
+/// Words wrap around the end of the input. Diehard uses 256,000 bytes;
+/// below about 100,000 the rarest words are expected fewer than 5 times.
+pub fn count_ones_stream(data: &[u8]) -> CountOnesResult {
+    let letters: Vec<u8> = data.iter().map(|&b| letter(b)).collect();
+    count_letter_words(&letters)
+}
+
+/// Diehard count-the-1s test on specific bytes: the letter of each 32-bit
+/// integer is taken from its byte `(x >> shift) as u8`, so `shift` 24
+/// takes the first byte and 0 the last.
+///
+/// Diehard runs this for every `shift` from 0 to 24 over 256,000 integers.
+///
+/// # Panics
+///
+/// If `shift` exceeds 24.
+pub fn count_ones_bytes(data: &[u8], shift: u32) -> CountOnesResult {
+    assert!(shift <= 24, "shift must be in 0..=24");
+    let letters: Vec<u8> = integers(data).map(|x| letter((x >> shift) as u8)).collect();
+    count_letter_words(&letters)
+}
+
+fn letter(byte: u8) -> u8 {
+    match byte.count_ones() {
+        0..=2 => 0,
+        ones => (ones - 2).min(4) as u8,
+    }
+}
+
+/// `Q5 - Q4` over the cyclic overlapping words of `letters`.
+fn count_letter_words(letters: &[u8]) -> CountOnesResult {
+    let len = letters.len();
+    let pearson = |word_len: u32| {
+        let cells = LETTERS.pow(word_len);
+        let mut counts = vec![0usize; cells];
+        let mut word = 0;
+        let cyclic = letters
+            .iter()
+            .chain(&letters[..(word_len as usize - 1).min(len)]);
+        for (i, &l) in cyclic.enumerate() {
+            word = (word * LETTERS + l as usize) % cells;
+            if i + 1 >= word_len as usize {
+                counts[word] += 1;
+            }
+        }
+        counts
+            .iter()
+            .enumerate()
+            .map(|(mut word, &observed)| {
+                let mut p = 1.0;
+                for _ in 0..word_len {
+                    p *= LETTER_PROBABILITIES[word % LETTERS];
+                    word /= LETTERS;
+                }
+                let expected = len as f64 * p;
+                (observed as f64 - expected).powi(2) / expected
+            })
+            .sum::<f64>()
+    };
+    let (q5, q4) = (pearson(5), pearson(4));
+    let statistic = q5 - q4;
+    let dof = (LETTERS.pow(5) - LETTERS.pow(4)) as f64;
+    CountOnesResult {
+        letters: len,
+        q5,
+        q4,
+        statistic,
+        p_value: chisquare_p_value(statistic, dof),
+    }
+}
+
+/// Classes of squeeze lengths: at most 6 steps, 7 to 47, and 48 or more.
+const SQUEEZE_CLASSES: usize = 43;
+
+/// Squeezes stop after this many steps, whether or not they reached 1.
+const MAX_SQUEEZE: usize = 48;
+
+/// Probability of each squeeze length class, from Diehard. From `k` a step
+/// goes to a uniform value in `1..=k`, so the length from 2^31 is 1 plus a
+/// sum of independent geometric variables, one for each of 2 to 2^31.
+const SQUEEZE_PROBABILITIES: [f64; SQUEEZE_CLASSES] = [
+    0.00002103, 0.00005779, 0.00017554, 0.00046732, 0.00110783, 0.00236784, 0.00460944, 0.00824116,
+    0.01362781, 0.02096849, 0.03017612, 0.04080197, 0.05204203, 0.06283828, 0.07205637, 0.07869451,
+    0.08206755, 0.08191935, 0.07844008, 0.07219412, 0.06398679, 0.05470931, 0.04519852, 0.03613661,
+    0.02800028, 0.02105567, 0.01538652, 0.01094020, 0.00757796, 0.00511956, 0.00337726, 0.00217787,
+    0.00137439, 0.00084970, 0.00051518, 0.00030666, 0.00017939, 0.00010324, 0.00005851, 0.00003269,
+    0.00001803, 0.00000982, 0.00001121,
+];
+
+/// Result of the squeeze test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SqueezeResult {
+    /// Complete squeezes.
+    pub squeezes: usize,
+    /// Squeezes of at most 6 steps, of 7, 8, ..., 47, and of 48 or more.
+    pub counts: [usize; SQUEEZE_CLASSES],
+    /// Chi-square statistic of `counts`, with 42 degrees of freedom.
+    pub chi_square: f64,
+    /// Upper-tail p-value.
+    pub p_value: f64,
+}
+
+/// Diehard squeeze test: starting from `k = 2^31`, replace `k` by
+/// `⌈k·U⌉` for uniform `U` until it reaches 1, and test the distribution of
+/// the number of steps taken, which averages about 23.6.
+///
+/// Each integer `x` gives `U = (x + 1) / 2^32`, so `k` never drops to 0; a
+/// squeeze still above 1 after 48 steps is cut short and counted in the
+/// last class. Diehard uses 100,000 squeezes, about 9.4 MB of input. A
+/// trailing incomplete squeeze is ignored, and the p-value is NaN without
+/// a complete one.
+pub fn squeeze(data: &[u8]) -> SqueezeResult {
+    let mut counts = [0; SQUEEZE_CLASSES];
+    let mut k = 1u64 << 31;
+    let mut steps = 0;
+    for x in integers(data) {
+        k = (k * (x as u64 + 1)).div_ceil(1 << 32);
+        steps += 1;
+        if k == 1 || steps == MAX_SQUEEZE {
+            counts[steps.clamp(6, MAX_SQUEEZE) - 6] += 1;
+            k = 1 << 31;
+            steps = 0;
+        }
+    }
+
+    let squeezes = counts.iter().sum::<usize>();
+    let n = squeezes as f64;
+    let chi_square = counts
+        .iter()
+        .zip(SQUEEZE_PROBABILITIES)
+        .map(|(&observed, p)| (observed as f64 - n * p).powi(2) / (n * p))
+        .sum::<f64>();
+    let p_value = if squeezes == 0 {
+        f64::NAN
+    } else {
+        chisquare_p_value(chi_square, (SQUEEZE_CLASSES - 1) as f64)
+    };
+    SqueezeResult {
+        squeezes,
+        counts,
+        chi_square,
+        p_value,
+    }
+}
+
+/// Classes of game lengths counted by the craps test: 1 to 20 throws, and
+/// 21 or more.
+const THROW_CLASSES: usize = 21;
+
+/// Probability of winning a game of craps, 244/495.
+const CRAPS_WIN: f64 = 244.0 / 495.0;
+
+/// Result of the craps test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CrapsResult {
+    /// Complete games played.
+    pub games: usize,
+    /// Games won.
+    pub wins: usize,
+    /// Deviation of `wins` from `244 / 495` of the games in standard
+    /// deviations.
+    pub wins_z_score: f64,
+    /// Two-sided p-value of the wins z-score.
+    pub wins_p_value: f64,
+    /// Games of 1, 2, ..., 20 and at least 21 throws.
+    pub throws: [usize; THROW_CLASSES],
+    /// Chi-square statistic of `throws`, with 20 degrees of freedom.
+    pub throws_chi_square: f64,
+    /// Upper-tail p-value of the throws chi-square.
+    pub throws_p_value: f64,
+}
+
+/// Diehard craps test: play games of craps, with each die `1 + ⌊6x / 2^32⌋`
+/// for an integer `x`, and test both the number of wins and the number of
+/// throws per game.
+///
+/// A game is won by a first throw of 7 or 11 and lost by 2, 3 or 12; any
+/// other sum is the point, and the dice are thrown again until they show
+/// the point, a win, or 7, a loss. Each throw takes two integers and a
+/// game averages 3.38 throws, so Diehard's 200,000 games use about 5.4 MB.
+/// A trailing incomplete game is ignored, and the p-values are NaN without
+/// a complete one.
+pub fn craps(data: &[u8]) -> CrapsResult {
+    let die = |x: u32| 1 + ((6 * x as u64) >> 32) as u32;
+    let mut games = 0;
+    let mut wins = 0;
+    let mut throws = [0; THROW_CLASSES];
+    let mut point = None;
+    let mut count = 0;
+    let dice = integers(data).collect::<Vec<_>>();
+    for pair in dice.chunks_exact(2) {
+        let sum = die(pair[0]) + die(pair[1]);
+        count += 1;
+        let won = match point {
+            None => match sum {
+                7 | 11 => Some(true),
+                2 | 3 | 12 => Some(false),
+                _ => {
+                    point = Some(sum);
+                    None
+                }
+            },
+            Some(p) if sum == p => Some(true),
+            Some(_) if sum == 7 => Some(false),
+            Some(_) => None,
+        };
+        if let Some(won) = won {
+            games += 1;
+            wins += won as usize;
+            throws[count.min(THROW_CLASSES) - 1] += 1;
+            point = None;
+            count = 0;
+        }
+    }
+
+    let n = games as f64;
+    let wins_z_score = (wins as f64 - n * CRAPS_WIN) / (n * CRAPS_WIN * (1.0 - CRAPS_WIN)).sqrt();
+    let throws_chi_square = throws
+        .iter()
+        .zip(throw_probabilities())
+        .map(|(&observed, p)| (observed as f64 - n * p).powi(2) / (n * p))
+        .sum::<f64>();
+    let throws_p_value = if games == 0 {
+        f64::NAN
+    } else {
+        chisquare_p_value(throws_chi_square, (THROW_CLASSES - 1) as f64)
+    };
+    CrapsResult {
+        games,
+        wins,
+        wins_z_score,
+        wins_p_value: erfc(wins_z_score.abs() / SQRT_2),
+        throws,
+        throws_chi_square,
+        throws_p_value,
+    }
+}
+
+/// Probability that a game of craps takes 1, 2, ..., 20 and at least 21
+/// throws. A game with a point that `w` of the 36 throws make ends on each
+/// later throw with probability `(w + 6) / 36`, 7 making the other 6.
+fn throw_probabilities() -> [f64; THROW_CLASSES] {
+    let mut probabilities = [0.0; THROW_CLASSES];
+    // 7 and 11, or 2, 3 and 12, on the first throw.
+    probabilities[0] = 12.0 / 36.0;
+    // Points 4 and 10, 5 and 9, 6 and 8 are made in 3, 4 and 5 ways.
+    for ways in [3.0, 4.0, 5.0] {
+        let end = (ways + 6.0) / 36.0;
+        let mut p = 2.0 * ways / 36.0;
+        for probability in &mut probabilities[1..THROW_CLASSES - 1] {
+            *probability += p * end;
+            p *= 1.0 - end;
+        }
+        probabilities[THROW_CLASSES - 1] += p;
+    }
+    probabilities
+}
+
+/// Side of the parking lot.
+const LOT_SIZE: f64 = 100.0;
+
+/// Parking attempts per trial of the parking lot test.
+pub const PARKING_ATTEMPTS: usize = 12_000;
+
+/// Mean and standard deviation of the cars parked in one trial, from
+/// Marsaglia's simulations.
+const PARKED_MEAN: f64 = 3523.0;
+const PARKED_SD: f64 = 21.9;
+
+/// Result of the parking lot test.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParkingLotResult {
+    /// Cars parked in each trial of [`PARKING_ATTEMPTS`] attempts.
+    pub parked: Vec<usize>,
+    /// Mean cars parked per trial; about 3523 for random data.
+    pub mean: f64,
+    /// Deviation of the mean from 3523 in standard errors.
+    pub z_score: f64,
+    /// Two-sided p-value of the z-score.
+    pub p_value: f64,
+}
+
+/// Diehard parking lot test: try 12,000 times to park a car at a random
+/// point of a 100 × 100 lot, failing when it would be within 1 of a parked
+/// car on both axes, and count the successes, normally distributed with
+/// mean 3523 and standard deviation 21.9.
+///
+/// Diehard's description speaks of circular cars, but its code, whose
+/// distribution this test uses, parks unit squares. Each attempt reads two
+/// integers as coordinates, so a trial uses 96,000 bytes; the p-value
+/// combines every complete trial and is NaN without any.
+pub fn parking_lot(data: &[u8]) -> ParkingLotResult {
+    let coordinate = |x: u32| x as f64 / 2f64.powi(32) * LOT_SIZE;
+    let mut parked = Vec::new();
+    // Cars by the unit cell of their position; a car can only crash into
+    // cars in its own or the eight surrounding cells.
+    let cells = LOT_SIZE as usize;
+    let mut grid: Vec<Vec<(f64, f64)>> = vec![Vec::new(); cells * cells];
+    for trial in data.chunks_exact(8 * PARKING_ATTEMPTS) {
+        grid.iter_mut().for_each(Vec::clear);
+        let mut count = 0;
+        let points = integers(trial).collect::<Vec<_>>();
+        for point in points.chunks_exact(2) {
+            let (x, y) = (coordinate(point[0]), coordinate(point[1]));
+            let (cx, cy) = (x as usize, y as usize);
+            let crash = (cx.saturating_sub(1)..=(cx + 1).min(cells - 1)).any(|i| {
+                (cy.saturating_sub(1)..=(cy + 1).min(cells - 1)).any(|j| {
+                    grid[i * cells + j]
+                        .iter()
+                        .any(|&(px, py)| (x - px).abs() <= 1.0 && (y - py).abs() <= 1.0)
+                })
+            });
+            if !crash {
+                grid[cx * cells + cy].push((x, y));
+                count += 1;
+            }
+        }
+        parked.push(count);
+    }
+
+    let trials = parked.len() as f64;
+    let mean = parked.iter().sum::<usize>() as f64 / trials;
+    let z_score = (mean - PARKED_MEAN) / (PARKED_SD / trials.sqrt());
+    ParkingLotResult {
+        parked,
+        mean,
+        z_score,
+        p_value: erfc(z_score.abs() / SQRT_2),
+    }
+}
+
+/// Points per sample of the minimum distance test.
+pub const DISTANCE_POINTS: usize = 8000;
+
+/// Points per sample of the 3D spheres test.
+pub const SPHERE_POINTS: usize = 4000;
+
+/// Result of the minimum distance or 3D spheres test.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NearestNeighborResult {
+    /// Smallest distance between two points of each sample.
+    pub min_distances: Vec<f64>,
+    /// p-value of each sample's smallest distance.
+    pub sample_p_values: Vec<f64>,
+    /// Kolmogorov-Smirnov statistic of the sample p-values against uniform.
+    pub ks_statistic: f64,
+    /// p-value of the Kolmogorov-Smirnov statistic.
+    pub p_value: f64,
+}
+
+/// Diehard minimum distance test: place 8000 points in a 10,000 × 10,000
+/// square and find the smallest distance `d` between two of them. `d²` is
+/// close to exponential with mean 0.995.
+///
+/// Like the Monte Carlo estimate of Pi, this reads the data as coordinates;
+/// each point takes two integers, so a sample uses 64,000 bytes. The
+/// samples' p-values are combined with a Kolmogorov-Smirnov test; Diehard
+/// uses 100 samples. The p-value is NaN without a complete sample.
+pub fn minimum_distance(data: &[u8]) -> NearestNeighborResult {
+    nearest_neighbor::<2>(data, DISTANCE_POINTS, 10_000.0, |d| {
+        1.0 - (-d * d / 0.995).exp()
+    })
+}
+
+/// Diehard 3D spheres test: place 4000 points in a cube of side 1000 and
+/// find the smallest distance `r` between two of them, the largest radius
+/// for which spheres around every point do not overlap. `r³` is close to
+/// exponential with mean 30.
+///
+/// Each point takes three integers, so a sample uses 48,000 bytes; Diehard
+/// uses 20 samples.
+pub fn spheres_3d(data: &[u8]) -> NearestNeighborResult {
+    nearest_neighbor::<3>(data, SPHERE_POINTS, 1000.0, |r| {
+        1.0 - (-r.powi(3) / 30.0).exp()
+    })
+}
+
+/// Smallest distances of samples of `points` points in a `D`-dimensional
+/// cube of side `side`, tested with `cdf`, their distribution.
+fn nearest_neighbor<const D: usize>(
+    data: &[u8],
+    points: usize,
+    side: f64,
+    cdf: impl Fn(f64) -> f64,
+) -> NearestNeighborResult {
+    let scale = side / 2f64.powi(32);
+    let mut min_distances = Vec::new();
+    for sample in data.chunks_exact(4 * D * points) {
+        let coordinates: Vec<u32> = integers(sample).collect();
+        let mut sample: Vec<[f64; D]> = coordinates
+            .chunks_exact(D)
+            .map(|c| std::array::from_fn(|k| c[k] as f64 * scale))
+            .collect();
+        min_distances.push(closest_pair(&mut sample));
+    }
+    let sample_p_values: Vec<f64> = min_distances.iter().map(|&d| cdf(d)).collect();
+    let (ks_statistic, p_value) = ks_uniform(&sample_p_values);
+    NearestNeighborResult {
+        min_distances,
+        sample_p_values,
+        ks_statistic,
+        p_value,
+    }
+}
+
+/// Smallest Euclidean distance between two of `points`, found by sweeping
+/// over the points in order of their first coordinate.
+fn closest_pair<const D: usize>(points: &mut [[f64; D]]) -> f64 {
+    points.sort_unstable_by(|a, b| a[0].total_cmp(&b[0]));
+    let mut best_sq = f64::INFINITY;
+    for (i, a) in points.iter().enumerate() {
+        for b in &points[i + 1..] {
+            if (b[0] - a[0]).powi(2) >= best_sq {
+                break;
+            }
+            let dist_sq = a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum::<f64>();
+            best_sq = best_sq.min(dist_sq);
+        }
+    }
+    best_sq.sqrt()
+}
+
+/// The big-endian 32-bit integers of `data`.
+fn integers(data: &[u8]) -> impl Iterator<Item = u32> + '_ {
+    data.chunks_exact(4)
+        .map(|b| u32::from_be_bytes(b.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_bytes(len: usize, seed: u64) -> Vec<u8> {
+        let mut x = seed;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_birthday_spacings_on_random_data() {
+        let result = birthday_spacings(&random_bytes(1000 * 4 * BIRTHDAYS, 1));
+        assert_eq!(result.samples, 1000);
+        assert_eq!(result.lambda, 2.0);
+        // Poisson(2): about 135 samples without repeats, 271 with one.
+        assert!((100..170).contains(&result.counts[0]), "{result:?}");
+        assert!((220..320).contains(&result.counts[1]), "{result:?}");
+        assert!(result.p_value > 0.001, "{result:?}");
+    }
+
+    #[test]
+    fn test_birthday_spacings_flags_coarse_values() {
+        // Only the top 20 bits vary: a year of 2^20 days has 16 times as
+        // many repeated spacings.
+        let data: Vec<u8> = random_bytes(500 * 4 * BIRTHDAYS, 2)
+            .chunks(4)
+            .flat_map(|b| [b[0], b[1], b[2] & 0xf0, 0])
+            .collect();
+        let result = birthday_spacings(&data);
+        assert_eq!(result.counts[6], 500);
+        assert!(result.p_value < 1e-10);
+        assert!(birthday_spacings(&[]).p_value.is_nan());
+    }
+
+    #[test]
+    fn test_count_ones_letters_and_random_data() {
+        assert_eq!(
+            [0x00, 0x03, 0x07, 0x0f, 0x1f, 0x3f, 0xff].map(letter),
+            [0, 0, 1, 2, 3, 4, 4]
+        );
+        let data = random_bytes(256_000, 3);
+        let stream = count_ones_stream(&data);
+        assert_eq!(stream.letters, 256_000);
+        assert!(stream.p_value > 0.001, "{stream:?}");
+        let bytes = count_ones_bytes(&random_bytes(4 * 100_000, 6), 7);
+        assert_eq!(bytes.letters, 100_000);
+        assert!(bytes.p_value > 0.001, "{bytes:?}");
+    }
+
+    #[test]
+    fn test_count_ones_flags_dependent_bytes() {
+        // Each byte's complement follows it, so letters come in mirrored
+        // pairs: fine letter frequencies, but impossible words.
+        let data: Vec<u8> = random_bytes(128_000, 4)
+            .into_iter()
+            .flat_map(|b| [b, !b])
+            .collect();
+        assert!(count_ones_stream(&data).p_value < 1e-10);
+        // The low byte of every integer is constant.
+        let data: Vec<u8> = random_bytes(400_000, 5)
+            .chunks(4)
+            .flat_map(|b| [b[0], b[1], b[2], 0x0f])
+            .collect();
+        assert!(count_ones_bytes(&data, 0).p_value < 1e-10);
+        assert!(count_ones_bytes(&data, 8).p_value > 0.001);
+    }
+
+    #[test]
+    fn test_squeeze() {
+        assert!((SQUEEZE_PROBABILITIES.iter().sum::<f64>() - 1.0).abs() < 1e-7);
+        let result = squeeze(&random_bytes(2_000_000, 11));
+        assert!((20_000..22_000).contains(&result.squeezes), "{result:?}");
+        assert!(result.p_value > 0.001, "{result:?}");
+        // U = 1 never shrinks k: every squeeze is cut short after 48 steps.
+        let result = squeeze(&[0xff; 4 * 48 * 10 + 7]);
+        assert_eq!(result.squeezes, 10);
+        assert_eq!(result.counts[SQUEEZE_CLASSES - 1], 10);
+        assert!(result.p_value < 1e-10);
+        // U below 1/2: squeezes end too soon.
+        let halved: Vec<u8> = random_bytes(400_000, 12)
+            .chunks(4)
+            .flat_map(|b| [b[0] & 0x7f, b[1], b[2], b[3]])
+            .collect();
+        assert!(squeeze(&halved).p_value < 1e-10);
+        assert!(squeeze(&[]).p_value.is_nan());
+    }
+
+    #[test]
+    fn test_craps() {
+        let probabilities = throw_probabilities();
+        assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        // Two throws: a point made in w ways, then it or 7, for each of the
+        // six points: 2 (3 · 9 + 4 · 10 + 5 · 11) / 36².
+        assert!((probabilities[1] - 244.0 / 1296.0).abs() < 1e-12);
+        let result = craps(&random_bytes(1_000_000, 13));
+        assert!((35_000..39_000).contains(&result.games), "{result:?}");
+        assert!(result.wins_p_value > 0.001, "{result:?}");
+        assert!(result.throws_p_value > 0.001, "{result:?}");
+        // Every die shows 1 + ⌊6 · 0.75⌋ = 5: each game is a 10 made at once.
+        let result = craps(&[0xc0; 4 * 2 * 100 + 3]);
+        assert_eq!((result.games, result.wins), (50, 50));
+        assert_eq!(result.throws[1], 50);
+        assert!(result.wins_p_value < 1e-10);
+        assert!(result.throws_p_value < 1e-10);
+        assert!(craps(&[]).throws_p_value.is_nan());
+    }
+
+    #[test]
+    fn test_parking_lot() {
+        let result = parking_lot(&random_bytes(10 * 8 * PARKING_ATTEMPTS + 5, 7));
+        assert_eq!(result.parked.len(), 10);
+        assert!((result.mean - 3523.0).abs() < 50.0, "{result:?}");
+        assert!(result.p_value > 0.001, "{result:?}");
+        // Coordinates with 4 bits of resolution: a 16 × 16 grid of spots
+        // more than 1 apart, every one of which gets a car.
+        let coarse: Vec<u8> = random_bytes(8 * PARKING_ATTEMPTS, 8)
+            .into_iter()
+            .map(|b| b & 0xf0)
+            .collect();
+        let result = parking_lot(&coarse);
+        assert_eq!(result.parked, [256]);
+        assert!(result.p_value < 1e-10);
+        assert!(parking_lot(&[]).p_value.is_nan());
+    }
+
+    #[test]
+    fn test_minimum_distance_and_spheres() {
+        let data = random_bytes(20 * 4 * 2 * DISTANCE_POINTS, 9);
+        let result = minimum_distance(&data);
+        assert_eq!(result.min_distances.len(), 20);
+        assert!(result.p_value > 0.001, "{result:?}");
+        let result = spheres_3d(&data);
+        assert_eq!(result.min_distances.len(), 26);
+        assert!(result.p_value > 0.001, "{result:?}");
+
+        // Coordinates with 4 bits of resolution: points share positions.
+        let coarse: Vec<u8> = data
+            .chunks(4)
+            .flat_map(|b| [b[0] & 0xf0, 0, 0, 0])
+            .collect();
+        let result = minimum_distance(&coarse);
+        assert!(result.min_distances.iter().all(|&d| d == 0.0));
+        assert!(result.p_value < 1e-10);
+        assert!(spheres_3d(&coarse).p_value < 1e-10);
+    }
+
+    #[test]
+    fn test_closest_pair_matches_brute_force() {
+        let data = random_bytes(4 * 3 * 500, 10);
+        let points: Vec<[f64; 3]> = integers(&data)
+            .collect::<Vec<_>>()
+            .chunks(3)
+            .map(|c| [c[0] as f64, c[1] as f64, c[2] as f64])
+            .collect();
+        let mut brute = f64::INFINITY;
+        for (i, a) in points.iter().enumerate() {
+            for b in &points[..i] {
+                let d = a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum::<f64>();
+                brute = brute.min(d.sqrt());
+            }
+        }
+        assert_eq!(closest_pair(&mut points.clone()), brute);
+    }
+}
@@ -0,0 +1,345 @@
+//! Block-aligned comparison of two versions of a binary.
+//!
+//! Blocks of the old version are located anywhere in the new version with a
+//! rolling checksum, so insertions and deletions do not throw the rest of the
+//! file out of alignment. Matches are extended byte by byte in both
+//! directions. What remains of the new version are the changed regions,
+//! each classified by content: vetting a firmware update, a changed region
+//! that is new code is expected, one that is a new encrypted blob deserves a
+//! closer look.
+//!
+//! ```rust
+//! use ent_rs::classify::ContentClass;
+//! use ent_rs::diff::diff_binaries;
+//! let old: Vec<u8> = (0..16384u32).map(|i| [0x48, 0x89, 0xE5, (i % 16) as u8][i as usize % 4]).collect();
+//! let mut new = old.clone();
+//! new.splice(8192..8192, [0u8; 4096]);
+//! let diff = diff_binaries(&old, &new, 512);
+//! assert_eq!(diff.changed.len(), 1);
+//! assert_eq!(diff.changed[0].offset, 8192);
+//! assert_eq!(diff.changed[0].class, ContentClass::Padding);
+//! assert!(diff.changed[0].is_insertion());
+//! ```
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::classify::{classify, ContentClass};
+use crate::EntStats;
+
+/// Most candidate blocks verified per checksum hit, bounding the cost of
+/// highly repetitive inputs such as padding.
+const MAX_CANDIDATES: usize = 16;
+
+/// A run of bytes common to both versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    /// Offset in the old version.
+    pub old_offset: usize,
+    /// Offset in the new version.
+    pub new_offset: usize,
+    /// Length of the run.
+    pub len: usize,
+}
+
+/// A region of the new version with no counterpart in the old one.
+#[derive(Debug, Clone)]
+pub struct ChangedRegion {
+    /// Offset in the new version.
+    pub offset: usize,
+    /// Length of the region.
+    pub len: usize,
+    /// Bytes of the old version this region took the place of; empty for a
+    /// pure insertion.
+    pub replaces: Range<usize>,
+    /// Statistics of the region.
+    pub stats: EntStats,
+    /// Classification of the region.
+    pub class: ContentClass,
+    /// Classification of the replaced bytes, if any.
+    pub old_class: Option<ContentClass>,
+}
+
+impl ChangedRegion {
+    /// Whether the region was inserted without replacing anything.
+    pub fn is_insertion(&self) -> bool {
+        self.replaces.is_empty()
+    }
+
+    /// Range of the region in the new version.
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.len
+    }
+}
+
+/// Result of [`diff_binaries`].
+#[derive(Debug, Clone)]
+pub struct BinaryDiff {
+    /// Length of the old version.
+    pub old_len: usize,
+    /// Length of the new version.
+    pub new_len: usize,
+    /// Common runs, ordered by offset in the new version.
+    pub matches: Vec<Match>,
+    /// Regions of the new version not found in the old one.
+    pub changed: Vec<ChangedRegion>,
+    /// Ranges of the old version not found in the new one.
+    pub removed: Vec<Range<usize>>,
+}
+
+impl BinaryDiff {
+    /// Total length of the changed regions.
+    pub fn changed_bytes(&self) -> usize {
+        self.changed.iter().map(|r| r.len).sum()
+    }
+
+    /// Fraction of the new version that also occurs in the old one.
+    pub fn unchanged_fraction(&self) -> f64 {
+        if self.new_len == 0 {
+            return 1.0;
+        }
+        1.0 - self.changed_bytes() as f64 / self.new_len as f64
+    }
+
+    /// Changed regions of the given class.
+    pub fn changed_of(&self, class: ContentClass) -> impl Iterator<Item = &ChangedRegion> {
+        self.changed.iter().filter(move |r| r.class == class)
+    }
+}
+
+/// Compare `old` and `new`, aligning them on `block`-sized pieces of `old`.
+///
+/// Smaller blocks find shorter common runs at the cost of more spurious
+/// checksum hits; a few hundred bytes suits most firmware images.
+pub fn diff_binaries(old: &[u8], new: &[u8], block: usize) -> BinaryDiff {
+    let block = block.max(1);
+    let matches = find_matches(old, new, block);
+
+    let mut changed = Vec::new();
+    let mut new_pos = 0;
+    let mut old_pos = 0;
+    for m in matches.iter().copied().chain([Match {
+        old_offset: old.len(),
+        new_offset: new.len(),
+        len: 0,
+    }]) {
+        if m.new_offset > new_pos {
+            // Out-of-order matches leave nothing to replace.
+            let replaces = old_pos..m.old_offset.max(old_pos);
+            let stats = EntStats::from_data(&new[new_pos..m.new_offset], false);
+            changed.push(ChangedRegion {
+                offset: new_pos,
+                len: m.new_offset - new_pos,
+                class: classify(&stats),
+                old_class: (!replaces.is_empty())
+                    .then(|| classify(&EntStats::from_data(&old[replaces.clone()], false))),
+                replaces,
+                stats,
+            });
+        }
+        new_pos = m.new_offset + m.len;
+        old_pos = m.old_offset + m.len;
+    }
+
+    BinaryDiff {
+        old_len: old.len(),
+        new_len: new.len(),
+        removed: uncovered(old.len(), &matches),
+        matches,
+        changed,
+    }
+}
+
+fn find_matches(old: &[u8], new: &[u8], block: usize) -> Vec<Match> {
+    let mut index: HashMap<u32, Vec<usize>> = HashMap::new();
+    for offset in (0..old.len().saturating_sub(block - 1)).step_by(block) {
+        let sum = Checksum::new(&old[offset..offset + block]).value();
+        index.entry(sum).or_default().push(offset);
+    }
+
+    let mut matches: Vec<Match> = Vec::new();
+    // Where the next block would be if the previous match continued.
+    let mut expected = 0;
+    let mut pos = 0;
+    let mut sum = None;
+    while pos + block <= new.len() {
+        let window = &new[pos..pos + block];
+        let checksum = sum.get_or_insert_with(|| Checksum::new(window));
+        let found = index.get(&checksum.value()).and_then(|offsets| {
+            let verified = |&&o: &&usize| &old[o..o + block] == window;
+            offsets
+                .iter()
+                .find(|&&o| o == expected && &old[o..o + block] == window)
+                .or_else(|| offsets.iter().take(MAX_CANDIDATES).find(verified))
+        });
+        let Some(&old_offset) = found else {
+            if pos + block < new.len() {
+                checksum.roll(new[pos], new[pos + block], block);
+            }
+            pos += 1;
+            continue;
+        };
+
+        let floor = matches.last().map_or(0, |m| m.new_offset + m.len);
+        let back = (1..=old_offset.min(pos - floor))
+            .take_while(|&k| old[old_offset - k] == new[pos - k])
+            .count();
+        let forward = old[old_offset..]
+            .iter()
+            .zip(&new[pos..])
+            .take_while(|(a, b)| a == b)
+            .count();
+        let m = Match {
+            old_offset: old_offset - back,
+            new_offset: pos - back,
+            len: back + forward,
+        };
+        match matches.last_mut() {
+            Some(last)
+                if last.new_offset + last.len == m.new_offset
+                    && last.old_offset + last.len == m.old_offset =>
+            {
+                last.len += m.len
+            }
+            _ => matches.push(m),
+        }
+        pos = m.new_offset + m.len;
+        expected = m.old_offset + m.len;
+        sum = None;
+    }
+    matches
+}
+
+/// Ranges of `0..len` in the old version covered by no match.
+fn uncovered(len: usize, matches: &[Match]) -> Vec<Range<usize>> {
+    let mut covered: Vec<Range<usize>> = matches
+        .iter()
+        .map(|m| m.old_offset..m.old_offset + m.len)
+        .collect();
+    covered.sort_by_key(|r| r.start);
+    let mut gaps = Vec::new();
+    let mut pos = 0;
+    covered.push(len..len);
+    for range in covered {
+        if range.start > pos {
+            gaps.push(pos..range.start);
+        }
+        pos = pos.max(range.end);
+    }
+    gaps
+}
+
+/// Adler-style rolling checksum of a block.
+struct Checksum {
+    a: u16,
+    b: u16,
+}
+
+impl Checksum {
+    fn new(block: &[u8]) -> Self {
+        let mut sum = Checksum { a: 0, b: 0 };
+        for (i, &byte) in block.iter().enumerate() {
+            sum.a = sum.a.wrapping_add(byte as u16);
+            sum.b = sum
+                .b
+                .wrapping_add(((block.len() - i) as u16).wrapping_mul(byte as u16));
+        }
+        sum
+    }
+
+    /// Slide the block one byte: drop `out` from the front, append `incoming`.
+    fn roll(&mut self, out: u8, incoming: u8, len: usize) {
+        self.a = self
+            .a
+            .wrapping_sub(out as u16)
+            .wrapping_add(incoming as u16);
+        self.b = self
+            .b
+            .wrapping_sub((len as u16).wrapping_mul(out as u16))
+            .wrapping_add(self.a);
+    }
+
+    fn value(&self) -> u32 {
+        (self.b as u32) << 16 | self.a as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_bytes(len: usize, seed: u64) -> Vec<u8> {
+        let mut x = seed | 1;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 56) as u8
+            })
+            .collect()
+    }
+
+    /// Bytes from a small alphabet: structured like code, but not periodic.
+    fn code(len: usize) -> Vec<u8> {
+        random_bytes(len, 77)
+            .into_iter()
+            .map(|b| [0x48, 0x89, 0xE5, 0x00, 0x0F, 0xC3, 0x8B, 0x45][b as usize % 8] ^ (b >> 6))
+            .collect()
+    }
+
+    #[test]
+    fn test_rolling_checksum_matches_fresh() {
+        let data = random_bytes(300, 3);
+        let mut sum = Checksum::new(&data[..64]);
+        for pos in 1..=data.len() - 64 {
+            sum.roll(data[pos - 1], data[pos + 63], 64);
+            assert_eq!(sum.value(), Checksum::new(&data[pos..pos + 64]).value());
+        }
+    }
+
+    #[test]
+    fn test_identical_versions() {
+        let data = code(10_000);
+        let diff = diff_binaries(&data, &data, 256);
+        assert!(diff.changed.is_empty() && diff.removed.is_empty());
+        assert_eq!(
+            diff.matches,
+            [Match {
+                old_offset: 0,
+                new_offset: 0,
+                len: 10_000
+            }]
+        );
+        assert_eq!(diff.unchanged_fraction(), 1.0);
+    }
+
+    #[test]
+    fn test_replaced_region_is_classified() {
+        let old = code(32_768);
+        let mut new = old.clone();
+        // Swap some code for an encrypted blob of a different size.
+        new.splice(10_000..14_000, random_bytes(6_000, 9));
+        let diff = diff_binaries(&old, &new, 512);
+        assert_eq!(diff.changed.len(), 1);
+        let region = &diff.changed[0];
+        assert_eq!(region.class, ContentClass::Encrypted);
+        assert_eq!(region.old_class, Some(ContentClass::Code));
+        // Exact up to bytes the blob shares with the code by chance.
+        assert!(region.offset <= 10_000 && region.offset >= 9_990);
+        assert!(region.range().end >= 15_990 && region.range().end <= 16_000);
+        assert_eq!(diff.removed, std::slice::from_ref(&region.replaces));
+        assert_eq!(diff.changed_of(ContentClass::Encrypted).count(), 1);
+    }
+
+    #[test]
+    fn test_deletion_and_moved_block() {
+        let old = random_bytes(20_000, 5);
+        let mut new = old[..5_000].to_vec();
+        new.extend_from_slice(&old[15_000..]);
+        new.extend_from_slice(&old[8_000..9_000]);
+        let diff = diff_binaries(&old, &new, 128);
+        assert!(diff.changed.is_empty());
+        assert_eq!(diff.removed, [5_000..8_000, 9_000..15_000]);
+    }
+}
@@ -0,0 +1,146 @@
+//! Identification of analyzed inputs.
+//!
+//! Reports record the length and analysis mode of their input and, with the
+//! `sha2` feature, its SHA-256, so results in an audit trail can be tied to
+//! the exact bytes they describe.
+//!
+//! ```rust
+//! use ent_rs::digest::InputDigest;
+//! let digest = InputDigest::new(b"abc", false);
+//! assert_eq!(digest.len, 3);
+//! # #[cfg(feature = "sha2")]
+//! assert!(digest.sha256_hex().unwrap().starts_with("ba7816bf"));
+//! ```
+
+use std::fmt;
+use std::io::{self, Read};
+
+#[cfg(feature = "sha2")]
+use sha2::{Digest, Sha256};
+
+/// Length, mode and (with the `sha2` feature) SHA-256 of an analyzed input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InputDigest {
+    /// Number of bytes analyzed.
+    pub len: u64,
+    /// Whether the input was analyzed as bits rather than bytes.
+    pub bit_mode: bool,
+    /// SHA-256 of the bytes analyzed; `None` without the `sha2` feature.
+    pub sha256: Option<[u8; 32]>,
+}
+
+impl InputDigest {
+    /// Digest of `data` analyzed in the given mode.
+    pub fn new(data: &[u8], bit_mode: bool) -> Self {
+        let mut hasher = InputHasher::new();
+        hasher.update(data);
+        hasher.finish(bit_mode)
+    }
+
+    /// The SHA-256 as lowercase hex.
+    pub fn sha256_hex(&self) -> Option<String> {
+        self.sha256
+            .map(|hash| hash.iter().map(|b| format!("{b:02x}")).collect())
+    }
+}
+
+impl fmt::Display for InputDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = if self.bit_mode { "bit" } else { "byte" };
+        write!(f, "{} bytes, {mode} mode", self.len)?;
+        if let Some(hex) = self.sha256_hex() {
+            write!(f, ", SHA-256 {hex}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Incremental [`InputDigest`] for inputs seen in pieces.
+#[derive(Debug, Clone, Default)]
+pub struct InputHasher {
+    len: u64,
+    #[cfg(feature = "sha2")]
+    sha256: Sha256,
+}
+
+impl InputHasher {
+    /// A hasher that has seen no input.
+    pub fn new() -> Self {
+        InputHasher::default()
+    }
+
+    /// Add the next piece of input.
+    pub fn update(&mut self, data: &[u8]) {
+        self.len += data.len() as u64;
+        #[cfg(feature = "sha2")]
+        self.sha256.update(data);
+    }
+
+    /// Digest of everything added so far.
+    pub fn finish(self, bit_mode: bool) -> InputDigest {
+        InputDigest {
+            len: self.len,
+            bit_mode,
+            #[cfg(feature = "sha2")]
+            sha256: Some(self.sha256.finalize().into()),
+            #[cfg(not(feature = "sha2"))]
+            sha256: None,
+        }
+    }
+}
+
+/// Reader adapter that digests everything read through it.
+#[derive(Debug)]
+pub struct DigestReader<R> {
+    inner: R,
+    hasher: InputHasher,
+}
+
+impl<R: Read> DigestReader<R> {
+    /// Wrap `inner`.
+    pub fn new(inner: R) -> Self {
+        DigestReader {
+            inner,
+            hasher: InputHasher::new(),
+        }
+    }
+
+    /// Digest of the bytes read so far.
+    pub fn finish(self, bit_mode: bool) -> InputDigest {
+        self.hasher.finish(bit_mode)
+    }
+}
+
+impl<R: Read> Read for DigestReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reader_matches_one_shot_digest() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i * 7) as u8).collect();
+        let mut reader = DigestReader::new(io::BufReader::with_capacity(64, &data[..]));
+        io::copy(&mut reader, &mut io::sink()).unwrap();
+        let streamed = reader.finish(true);
+        assert_eq!(streamed, InputDigest::new(&data, true));
+        assert_eq!(streamed.len, 10_000);
+        assert!(streamed.to_string().starts_with("10000 bytes, bit mode"));
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn test_sha256_known_answer() {
+        assert_eq!(
+            InputDigest::new(b"abc", false).sha256_hex().unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
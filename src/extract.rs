@@ -0,0 +1,112 @@
+//! Randomness extractors and before/after reports.
+//!
+//! ```rust
+//! use ent_rs::extract::von_neumann_report;
+//! // A source stuck at 75% ones
+//! let biased: Vec<u8> = (0..4096u32).map(|i| if i % 2 == 0 { 0b1110_1101 } else { 0b0111_1011 }).collect();
+//! let report = von_neumann_report(&biased, true);
+//! assert!(report.raw.entropy < 0.85);
+//! assert!(report.yield_ratio <= 0.25);
+//! ```
+
+use crate::EntStats;
+
+/// Von Neumann debiasing extractor.
+///
+/// Bits are taken most-significant first in non-overlapping pairs; `01`
+/// outputs `0`, `10` outputs `1`, and `00`/`11` output nothing. Output bits
+/// are packed most-significant first and a trailing partial byte is dropped.
+/// For independent bits with any fixed bias the output is unbiased.
+pub fn von_neumann(data: &[u8]) -> Vec<u8> {
+    von_neumann_bits(data).0
+}
+
+/// Extracted bytes plus the number of output bits produced (including
+/// those in the dropped partial byte).
+fn von_neumann_bits(data: &[u8]) -> (Vec<u8>, usize) {
+    let mut out = Vec::with_capacity(data.len() / 8);
+    let mut acc = 0u8;
+    let mut filled = 0;
+    let mut produced = 0;
+    for &b in data {
+        for shift in (0..8).step_by(2).rev() {
+            let pair = (b >> shift) & 0b11;
+            if pair == 0b01 || pair == 0b10 {
+                acc = (acc << 1) | (pair >> 1);
+                filled += 1;
+                produced += 1;
+                if filled == 8 {
+                    out.push(acc);
+                    acc = 0;
+                    filled = 0;
+                }
+            }
+        }
+    }
+    (out, produced)
+}
+
+/// Statistics of raw input and of its extracted output.
+#[derive(Debug, Clone)]
+pub struct ExtractionReport {
+    /// Statistics of the raw input.
+    pub raw: EntStats,
+    /// Statistics of the extractor output.
+    pub extracted: EntStats,
+    /// Number of input bits consumed.
+    pub input_bits: usize,
+    /// Number of output bits produced.
+    pub output_bits: usize,
+    /// `output_bits / input_bits`; at most 0.25 for the von Neumann extractor.
+    pub yield_ratio: f64,
+}
+
+/// Run the von Neumann extractor and report statistics before and after.
+pub fn von_neumann_report(data: &[u8], bit_mode: bool) -> ExtractionReport {
+    let (extracted, output_bits) = von_neumann_bits(data);
+    let input_bits = data.len() * 8;
+    ExtractionReport {
+        raw: EntStats::from_data(data, bit_mode),
+        extracted: EntStats::from_data(&extracted, bit_mode),
+        input_bits,
+        output_bits,
+        yield_ratio: output_bits as f64 / input_bits as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_von_neumann_pairs() {
+        // 01 10 00 11 | 10 10 01 01 -> 0 1 | 1 1 0 0 -> 011100.. (6 bits, no full byte)
+        assert!(von_neumann(&[0b0110_0011, 0b1010_0101]).is_empty());
+        // 16 alternating pairs "10" -> 16 one-bits
+        assert_eq!(von_neumann(&[0xAA; 4]), vec![0xFF, 0xFF]);
+        assert_eq!(von_neumann(&[0x55; 2]), vec![0x00]);
+    }
+
+    #[test]
+    fn test_report_debiases_independent_biased_bits() {
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        let mut next = || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+        // Each bit is one with probability 3/4.
+        let data: Vec<u8> = (0..50_000)
+            .map(|_| {
+                let (a, b) = (next() as u8, next() as u8);
+                a | b
+            })
+            .collect();
+        let report = von_neumann_report(&data, true);
+        assert!(report.raw.entropy < 0.85);
+        assert!(report.extracted.entropy > 0.999);
+        // Expected yield is p(1 - p) = 3/16.
+        assert!((report.yield_ratio - 3.0 / 16.0).abs() < 0.01);
+    }
+}
@@ -0,0 +1,235 @@
+//! C API for linking the analysis into C and C++ tools.
+//!
+//! Build a static or shared library with
+//! `cargo rustc --release --lib --features ffi --crate-type staticlib` (or
+//! `cdylib`); they are not declared in `Cargo.toml`, as Cargo would build
+//! them for every dependent crate, `no_std` ones included.
+//! `include/ent_rs.h` declares these functions and is regenerated from this
+//! module with
+//! `cbindgen --config cbindgen.toml --output include/ent_rs.h src/ffi.rs`.
+//!
+//! ```c
+//! #include "ent_rs.h"
+//!
+//! ent_stats_t stats;
+//! if (ent_analyze(buf, len, &stats) == ENT_OK)
+//!     printf("entropy %f bits per byte\n", stats.entropy);
+//! ```
+//!
+//! Functions return [`ENT_OK`] or a negative error code and never unwind
+//! into the caller.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+
+use crate::{EntAccumulator, EntStats};
+
+/// Success.
+pub const ENT_OK: i32 = 0;
+/// A required pointer was null.
+pub const ENT_ERR_NULL: i32 = -1;
+/// The analysis panicked.
+pub const ENT_ERR_PANIC: i32 = -2;
+
+/// Statistics of `ent`, as in [`EntStats`] without the frequency tables.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct EntStatsFfi {
+    /// Shannon entropy in bits per byte (or bit).
+    pub entropy: f64,
+    /// Ideal compression percentage based on entropy.
+    pub compression_percent: f64,
+    /// Chi-square test value.
+    pub chisquare: f64,
+    /// p-value of the chi-square test, as in [`EntStats::p_value`].
+    pub p_value: f64,
+    /// Arithmetic mean; NaN for empty input.
+    pub mean: f64,
+    /// Monte Carlo estimate of Pi; 0 below 6 bytes.
+    pub pi_estimate: f64,
+    /// Serial correlation coefficient; -99999.0 when undefined.
+    pub serial_correlation: f64,
+}
+
+impl From<&EntStats> for EntStatsFfi {
+    fn from(stats: &EntStats) -> Self {
+        EntStatsFfi {
+            entropy: stats.entropy,
+            compression_percent: stats.compression_percent,
+            chisquare: stats.chisquare,
+            p_value: stats.p_value,
+            mean: stats.mean,
+            pi_estimate: stats.pi_estimate,
+            serial_correlation: stats.serial_correlation,
+        }
+    }
+}
+
+/// Byte-mode statistics of `len` bytes at `data` into `out`.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes (it may be null if `len` is
+/// 0), and `out` to writable storage for one `ent_stats_t`.
+#[no_mangle]
+pub unsafe extern "C" fn ent_analyze(data: *const u8, len: usize, out: *mut EntStatsFfi) -> i32 {
+    analyze(data, len, false, out)
+}
+
+/// Bit-mode statistics of `len` bytes at `data` into `out`.
+///
+/// # Safety
+///
+/// As for [`ent_analyze`].
+#[no_mangle]
+pub unsafe extern "C" fn ent_analyze_bits(
+    data: *const u8,
+    len: usize,
+    out: *mut EntStatsFfi,
+) -> i32 {
+    analyze(data, len, true, out)
+}
+
+/// An [`EntAccumulator`] behind an opaque C pointer.
+#[derive(Debug)]
+pub struct Accumulator(EntAccumulator);
+
+/// A new streaming accumulator, to be released with
+/// [`ent_accumulator_free`].
+#[no_mangle]
+pub extern "C" fn ent_accumulator_new(bit_mode: bool) -> *mut Accumulator {
+    Box::into_raw(Box::new(Accumulator(EntAccumulator::new(bit_mode))))
+}
+
+/// Feed `len` bytes at `data` to `acc`.
+///
+/// # Safety
+///
+/// `acc` must come from [`ent_accumulator_new`] and not have been freed;
+/// `data` must point to `len` readable bytes (it may be null if `len` is 0).
+#[no_mangle]
+pub unsafe extern "C" fn ent_accumulator_update(
+    acc: *mut Accumulator,
+    data: *const u8,
+    len: usize,
+) -> i32 {
+    let Some(acc) = acc.as_mut() else {
+        return ENT_ERR_NULL;
+    };
+    let Some(data) = bytes(data, len) else {
+        return ENT_ERR_NULL;
+    };
+    guard(|| acc.0.update(data))
+}
+
+/// Statistics of everything fed to `acc` so far into `out`.
+///
+/// # Safety
+///
+/// `acc` must come from [`ent_accumulator_new`] and not have been freed;
+/// `out` must point to writable storage for one `ent_stats_t`.
+#[no_mangle]
+pub unsafe extern "C" fn ent_accumulator_stats(
+    acc: *const Accumulator,
+    out: *mut EntStatsFfi,
+) -> i32 {
+    let (Some(acc), false) = (acc.as_ref(), out.is_null()) else {
+        return ENT_ERR_NULL;
+    };
+    guard(|| out.write(EntStatsFfi::from(&acc.0.stats())))
+}
+
+/// Release an accumulator; null is ignored.
+///
+/// # Safety
+///
+/// `acc` must be null or come from [`ent_accumulator_new`], and must not be
+/// used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn ent_accumulator_free(acc: *mut Accumulator) {
+    if !acc.is_null() {
+        drop(Box::from_raw(acc));
+    }
+}
+
+unsafe fn analyze(data: *const u8, len: usize, bit_mode: bool, out: *mut EntStatsFfi) -> i32 {
+    let Some(data) = bytes(data, len) else {
+        return ENT_ERR_NULL;
+    };
+    if out.is_null() {
+        return ENT_ERR_NULL;
+    }
+    guard(|| out.write(EntStatsFfi::from(&EntStats::from_data(data, bit_mode))))
+}
+
+/// The `len` bytes at `data`; `None` if `data` is null and `len` is not 0.
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> Option<&'a [u8]> {
+    match (data.is_null(), len) {
+        (_, 0) => Some(&[]),
+        (true, _) => None,
+        (false, _) => Some(slice::from_raw_parts(data, len)),
+    }
+}
+
+/// Run `f`, turning a panic into [`ENT_ERR_PANIC`].
+fn guard(f: impl FnOnce()) -> i32 {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(()) => ENT_OK,
+        Err(_) => ENT_ERR_PANIC,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use super::*;
+
+    #[test]
+    fn test_analyze_matches_ent_stats() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i * 31 % 251) as u8).collect();
+        let mut out = EntStatsFfi::default();
+        assert_eq!(
+            unsafe { ent_analyze(data.as_ptr(), data.len(), &mut out) },
+            ENT_OK
+        );
+        // Debug, as NaN fields compare unequal.
+        let expected = EntStatsFfi::from(&EntStats::from_data(&data, false));
+        assert_eq!(format!("{out:?}"), format!("{expected:?}"));
+        assert_eq!(
+            unsafe { ent_analyze_bits(data.as_ptr(), data.len(), &mut out) },
+            ENT_OK
+        );
+        assert!(out.entropy <= 1.0);
+
+        assert_eq!(unsafe { ent_analyze(ptr::null(), 0, &mut out) }, ENT_OK);
+        assert_eq!(
+            unsafe { ent_analyze(ptr::null(), 1, &mut out) },
+            ENT_ERR_NULL
+        );
+        assert_eq!(
+            unsafe { ent_analyze(data.as_ptr(), 1, ptr::null_mut()) },
+            ENT_ERR_NULL
+        );
+    }
+
+    #[test]
+    fn test_accumulator_streams() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i * 7 % 256) as u8).collect();
+        let acc = ent_accumulator_new(false);
+        for chunk in data.chunks(999) {
+            let status = unsafe { ent_accumulator_update(acc, chunk.as_ptr(), chunk.len()) };
+            assert_eq!(status, ENT_OK);
+        }
+        let mut out = EntStatsFfi::default();
+        assert_eq!(unsafe { ent_accumulator_stats(acc, &mut out) }, ENT_OK);
+        let expected = EntStatsFfi::from(&EntStats::from_data(&data, false));
+        assert_eq!(format!("{out:?}"), format!("{expected:?}"));
+        unsafe { ent_accumulator_free(acc) };
+        unsafe { ent_accumulator_free(ptr::null_mut()) };
+        assert_eq!(
+            unsafe { ent_accumulator_stats(ptr::null(), &mut out) },
+            ENT_ERR_NULL
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! Discrete Fourier transform of any length.
+//!
+//! Powers of two use an iterative radix-2 FFT; other lengths are turned into
+//! a power-of-two convolution with Bluestein's chirp-z algorithm, so every
+//! length costs O(n log n) time and a few buffers of at most 4n values.
+
+use std::f64::consts::PI;
+use std::ops::{Add, Mul, Sub};
+
+/// A complex number.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Self {
+        Complex { re, im }
+    }
+
+    /// `e^(iθ)`.
+    fn from_angle(theta: f64) -> Self {
+        let (sin, cos) = theta.sin_cos();
+        Complex::new(cos, sin)
+    }
+
+    fn conj(self) -> Self {
+        Complex::new(self.re, -self.im)
+    }
+
+    pub fn abs(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+
+    fn add(self, rhs: Complex) -> Complex {
+        Complex::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+
+    fn sub(self, rhs: Complex) -> Complex {
+        Complex::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+
+    fn mul(self, rhs: Complex) -> Complex {
+        Complex::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+
+/// `X_k = Σ x_j e^(-2πijk/n)` for every `k` in `0..n`.
+pub(crate) fn dft(input: &[Complex]) -> Vec<Complex> {
+    let n = input.len();
+    if n.is_power_of_two() {
+        let mut buf = input.to_vec();
+        fft_in_place(&mut buf, false);
+        return buf;
+    }
+    if n == 0 {
+        return Vec::new();
+    }
+
+    // X_k = w_k Σ (x_j w_j) conj(w_(k-j)) with the chirp w_k = e^(-πik²/n),
+    // a convolution evaluated with power-of-two FFTs. k² is reduced modulo
+    // 2n, the chirp's period, to keep the angles accurate for large n.
+    let chirp: Vec<Complex> = (0..n as u64)
+        .map(|k| Complex::from_angle(-PI * ((k * k) % (2 * n as u64)) as f64 / n as f64))
+        .collect();
+    let len = (2 * n - 1).next_power_of_two();
+    let mut a = vec![Complex::default(); len];
+    for ((a, &x), &w) in a.iter_mut().zip(input).zip(&chirp) {
+        *a = x * w;
+    }
+    let mut b = vec![Complex::default(); len];
+    b[0] = chirp[0].conj();
+    for k in 1..n {
+        b[k] = chirp[k].conj();
+        b[len - k] = chirp[k].conj();
+    }
+    fft_in_place(&mut a, false);
+    fft_in_place(&mut b, false);
+    for (a, &b) in a.iter_mut().zip(&b) {
+        *a = *a * b;
+    }
+    fft_in_place(&mut a, true);
+    let scale = 1.0 / len as f64;
+    a.truncate(n);
+    for (x, &w) in a.iter_mut().zip(&chirp) {
+        *x = *x * w * Complex::new(scale, 0.0);
+    }
+    a
+}
+
+/// Unnormalized radix-2 FFT of a power-of-two length buffer; the inverse
+/// transform uses `e^(+2πijk/n)`.
+fn fft_in_place(buf: &mut [Complex], inverse: bool) {
+    let n = buf.len();
+    if n <= 1 {
+        return;
+    }
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if i < j {
+            buf.swap(i, j);
+        }
+    }
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let twiddles: Vec<Complex> = (0..n / 2)
+        .map(|k| Complex::from_angle(sign * 2.0 * PI * k as f64 / n as f64))
+        .collect();
+    let mut size = 2;
+    while size <= n {
+        let half = size / 2;
+        let step = n / size;
+        for chunk in buf.chunks_exact_mut(size) {
+            for k in 0..half {
+                let t = chunk[k + half] * twiddles[k * step];
+                let u = chunk[k];
+                chunk[k] = u + t;
+                chunk[k + half] = u - t;
+            }
+        }
+        size *= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naive_dft(input: &[Complex]) -> Vec<Complex> {
+        let n = input.len();
+        (0..n)
+            .map(|k| {
+                input
+                    .iter()
+                    .enumerate()
+                    .fold(Complex::default(), |sum, (j, &x)| {
+                        let theta = -2.0 * PI * ((j * k) % n) as f64 / n as f64;
+                        sum + x * Complex::from_angle(theta)
+                    })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_matches_naive_dft_for_all_lengths() {
+        let mut state = 0x9E37_79B9u32;
+        for n in [0, 1, 2, 3, 5, 8, 10, 64, 100, 127, 1000] {
+            let input: Vec<Complex> = (0..n)
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 17;
+                    state ^= state << 5;
+                    Complex::new(state as f64 / u32::MAX as f64 - 0.5, (state % 7) as f64)
+                })
+                .collect();
+            let fast = dft(&input);
+            let naive = naive_dft(&input);
+            assert_eq!(fast.len(), n);
+            for (f, s) in fast.iter().zip(&naive) {
+                assert!((*f - *s).abs() < 1e-8 * n as f64, "n = {n}: {f:?} vs {s:?}");
+            }
+        }
+    }
+}
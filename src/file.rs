@@ -0,0 +1,180 @@
+//! Analyzing files by path.
+//!
+//! [`EntStats::from_file`] streams the file through an [`EntAccumulator`],
+//! so memory use stays constant however large the file is. With the `mmap`
+//! feature, regular files are memory-mapped instead of read: the data is
+//! never copied into user-space buffers, and the kernel pages it in and out
+//! as needed, so files larger than RAM work too. With the `parallel`
+//! feature as well, the mapped file is analyzed on all cores. Pipes,
+//! devices, and files that cannot be mapped fall back to buffered reads.
+//!
+//! ```rust,no_run
+//! use ent_rs::file::FileOptions;
+//! use ent_rs::EntStats;
+//! let options = FileOptions {
+//!     offset: 512,
+//!     ..FileOptions::default()
+//! };
+//! let stats = EntStats::from_file("disk.img", &options).unwrap();
+//! println!("Entropy: {:.4}", stats.entropy);
+//! ```
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::{instrument, EntAccumulator, EntStats};
+
+/// Bytes read, or mapped bytes processed, per step.
+const CHUNK_SIZE: usize = 1 << 20;
+
+/// What part of a file to analyze, and how.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileOptions {
+    /// Analyze bits instead of bytes.
+    pub bit_mode: bool,
+    /// Bytes to skip at the start of the file.
+    pub offset: u64,
+    /// Most bytes to analyze after `offset`; `None` reads to the end.
+    pub length: Option<u64>,
+}
+
+impl EntStats {
+    /// Analyze the file at `path`.
+    ///
+    /// With the `mmap` feature, the file is memory-mapped when possible. A
+    /// mapped file that another process truncates during the analysis can
+    /// crash the process with `SIGBUS`; analyze a copy if that can happen.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display()))
+    )]
+    pub fn from_file<P: AsRef<Path>>(path: P, options: &FileOptions) -> io::Result<Self> {
+        let file = File::open(path)?;
+        #[cfg(feature = "mmap")]
+        if let Some(stats) = mapped(&file, options)? {
+            return Ok(stats);
+        }
+        read(file, options)
+    }
+}
+
+/// Statistics of the mapped range, or `None` if the file cannot be mapped.
+#[cfg(feature = "mmap")]
+fn mapped(file: &File, options: &FileOptions) -> io::Result<Option<EntStats>> {
+    let metadata = file.metadata()?;
+    if !metadata.is_file() {
+        return Ok(None);
+    }
+    let timer = instrument::Timer::start();
+    let available = metadata.len().saturating_sub(options.offset);
+    let len = options.length.map_or(available, |l| l.min(available));
+    let mut acc = EntAccumulator::new(options.bit_mode);
+    if len == 0 {
+        return Ok(Some(acc.finalize()));
+    }
+    // Wider than the address space, e.g. a large file on a 32-bit target.
+    let Ok(len) = usize::try_from(len) else {
+        return Ok(None);
+    };
+    // SAFETY: the map is only read, and only while `file` is open. Concurrent
+    // truncation by another process is documented on `from_file`.
+    let map = match unsafe {
+        memmap2::MmapOptions::new()
+            .offset(options.offset)
+            .len(len)
+            .map(file)
+    } {
+        Ok(map) => map,
+        Err(_) => return Ok(None),
+    };
+    #[cfg(unix)]
+    let _ = map.advise(memmap2::Advice::Sequential);
+    #[cfg(feature = "parallel")]
+    acc.par_update(&map);
+    #[cfg(not(feature = "parallel"))]
+    for chunk in map.chunks(CHUNK_SIZE) {
+        acc.update(chunk);
+    }
+    timer.finish("from_file", acc.len());
+    Ok(Some(acc.finalize()))
+}
+
+fn read(mut file: File, options: &FileOptions) -> io::Result<EntStats> {
+    let timer = instrument::Timer::start();
+    // Character devices and pipes cannot seek; read past the offset instead.
+    if options.offset > 0 && file.seek(SeekFrom::Start(options.offset)).is_err() {
+        io::copy(&mut (&mut file).take(options.offset), &mut io::sink())?;
+    }
+    let mut input = file.take(options.length.unwrap_or(u64::MAX));
+    let mut acc = EntAccumulator::new(options.bit_mode);
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    loop {
+        match input.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => acc.update(&buf[..n]),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    timer.finish("from_file", acc.len());
+    Ok(acc.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_from_file_ranges_match_from_data() {
+        let data: Vec<u8> = (0u32..100_000)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+            .collect();
+        let path = std::env::temp_dir().join(format!("ent-rs-file-{}", std::process::id()));
+        File::create(&path).unwrap().write_all(&data).unwrap();
+
+        for (offset, length, range) in [
+            (0, None, 0..data.len()),
+            (4097, Some(10_000), 4097..14_097),
+            (99_990, Some(1000), 99_990..data.len()),
+        ] {
+            let options = FileOptions {
+                bit_mode: offset % 2 == 1,
+                offset,
+                length,
+            };
+            let stats = EntStats::from_file(&path, &options).unwrap();
+            let expected = EntStats::from_data(&data[range], options.bit_mode);
+            assert_eq!(stats.entropy, expected.entropy);
+            assert_eq!(stats.byte_frequencies, expected.byte_frequencies);
+            assert_eq!(stats.bit_frequencies, expected.bit_frequencies);
+            assert_eq!(stats.pi_estimate, expected.pi_estimate);
+        }
+        let options = FileOptions {
+            offset: 200_000,
+            ..FileOptions::default()
+        };
+        let past_end = EntStats::from_file(&path, &options).unwrap();
+        let counts = past_end.byte_frequencies.unwrap();
+        assert!(counts.iter().all(|&(_, count, _)| count == 0));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_read_fallback_matches() {
+        let data = b"fallback path for pipes and devices".repeat(100);
+        let path = std::env::temp_dir().join(format!("ent-rs-read-{}", std::process::id()));
+        File::create(&path).unwrap().write_all(&data).unwrap();
+        let options = FileOptions {
+            offset: 7,
+            ..FileOptions::default()
+        };
+        let stats = read(File::open(&path).unwrap(), &options).unwrap();
+        assert_eq!(
+            stats.serial_correlation,
+            EntStats::from_data(&data[7..], false).serial_correlation
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,297 @@
+//! FIPS 140-2 power-up and continuous RNG tests.
+//!
+//! The statistical tests of FIPS 140-2 §4.9.1 run on blocks of 20,000 bits,
+//! with fixed pass intervals: monobit, poker, runs and long run. Together
+//! with the continuous test of §4.9.2, which rejects any 32-bit word equal
+//! to the one before it, they are the checks `rngd` applies to hardware RNG
+//! output before feeding it to the kernel.
+//!
+//! ```rust
+//! use ent_rs::fips140::{check, BLOCK_LEN};
+//! let stuck = vec![0x5Au8; 4 * BLOCK_LEN];
+//! let status = check(&stuck);
+//! assert_eq!(status.blocks, 4);
+//! assert_eq!(status.poker_failures, 4);
+//! assert!(!status.passed());
+//! ```
+
+#[cfg(not(any(feature = "std", test)))]
+use alloc::boxed::Box;
+use core::ops::RangeInclusive;
+
+#[cfg(not(any(feature = "std", test)))]
+use crate::float::Float;
+
+/// Bits per test block.
+pub const BLOCK_BITS: usize = 20_000;
+
+/// Bytes per test block.
+pub const BLOCK_LEN: usize = BLOCK_BITS / 8;
+
+/// Shortest run that fails the long run test.
+pub const LONG_RUN: usize = 26;
+
+/// Passing number of ones.
+const MONOBIT_INTERVAL: RangeInclusive<usize> = 9_726..=10_274;
+
+/// Passing poker statistic, bounds excluded.
+const POKER_BOUNDS: (f64, f64) = (2.16, 46.17);
+
+/// Passing number of runs of length 1, 2, 3, 4, 5 and 6 or more, the same
+/// for runs of zeros and of ones.
+const RUN_INTERVALS: [RangeInclusive<usize>; 6] = [
+    2_315..=2_685,
+    1_114..=1_386,
+    527..=723,
+    240..=384,
+    103..=209,
+    103..=209,
+];
+
+/// Statistics of one 20,000-bit block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockResult {
+    /// Number of one bits; passes within 9,726 to 10,274.
+    pub ones: usize,
+    /// `16 / 5000 · Σ f(i)² - 5000` over the counts of the 16 nibble values;
+    /// passes strictly between 2.16 and 46.17.
+    pub poker: f64,
+    /// Runs of zeros (`runs[0]`) and of ones (`runs[1]`) by length 1 to 5,
+    /// with longer runs counted in the last element.
+    pub runs: [[usize; 6]; 2],
+    /// Longest run of either bit; passes below [`LONG_RUN`].
+    pub longest_run: usize,
+}
+
+impl BlockResult {
+    /// Whether the monobit test passed.
+    pub fn monobit_passed(&self) -> bool {
+        MONOBIT_INTERVAL.contains(&self.ones)
+    }
+
+    /// Whether the poker test passed.
+    pub fn poker_passed(&self) -> bool {
+        self.poker > POKER_BOUNDS.0 && self.poker < POKER_BOUNDS.1
+    }
+
+    /// Whether every run count is within its interval.
+    pub fn runs_passed(&self) -> bool {
+        self.runs.iter().all(|counts| {
+            counts
+                .iter()
+                .zip(&RUN_INTERVALS)
+                .all(|(c, r)| r.contains(c))
+        })
+    }
+
+    /// Whether the long run test passed.
+    pub fn long_run_passed(&self) -> bool {
+        self.longest_run < LONG_RUN
+    }
+
+    /// Whether all four tests passed.
+    pub fn passed(&self) -> bool {
+        self.monobit_passed() && self.poker_passed() && self.runs_passed() && self.long_run_passed()
+    }
+}
+
+/// Run the monobit, poker, runs and long run tests on one block, bits most
+/// significant first.
+pub fn test_block(block: &[u8; BLOCK_LEN]) -> BlockResult {
+    let ones = crate::simd::count_ones(block) as usize;
+
+    let mut nibbles = [0u32; 16];
+    for &b in block {
+        nibbles[(b >> 4) as usize] += 1;
+        nibbles[(b & 0xf) as usize] += 1;
+    }
+    let segments = (BLOCK_BITS / 4) as f64;
+    let sum_sq = nibbles.iter().map(|&f| (f as f64).powi(2)).sum::<f64>();
+    let poker = 16.0 / segments * sum_sq - segments;
+
+    let mut runs = [[0; 6]; 2];
+    let mut longest_run = 0;
+    let mut record = |bit: u8, len: usize| {
+        runs[bit as usize][len.min(6) - 1] += 1;
+        longest_run = longest_run.max(len);
+    };
+    let mut current = block[0] >> 7;
+    let mut len = 0;
+    for &b in block {
+        for shift in (0..8).rev() {
+            let bit = (b >> shift) & 1;
+            if bit == current {
+                len += 1;
+            } else {
+                record(current, len);
+                current = bit;
+                len = 1;
+            }
+        }
+    }
+    record(current, len);
+
+    BlockResult {
+        ones,
+        poker,
+        runs,
+        longest_run,
+    }
+}
+
+/// Failure counts of the FIPS 140-2 tests over a sequence of blocks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Fips140Status {
+    /// Complete blocks tested.
+    pub blocks: usize,
+    /// Blocks that failed at least one statistical test.
+    pub failed_blocks: usize,
+    /// Blocks that failed the monobit test.
+    pub monobit_failures: usize,
+    /// Blocks that failed the poker test.
+    pub poker_failures: usize,
+    /// Blocks that failed the runs test.
+    pub runs_failures: usize,
+    /// Blocks that failed the long run test.
+    pub long_run_failures: usize,
+    /// 32-bit words equal to the previous word.
+    pub continuous_failures: usize,
+}
+
+impl Fips140Status {
+    /// Whether no test failed.
+    pub fn passed(&self) -> bool {
+        self.failed_blocks == 0 && self.continuous_failures == 0
+    }
+}
+
+/// Streaming FIPS 140-2 tests over a live byte stream.
+///
+/// Bytes are collected into 20,000-bit blocks; a trailing partial block is
+/// not tested. The continuous test runs on every complete 32-bit word.
+#[derive(Debug, Clone)]
+pub struct Fips140Tests {
+    block: Box<[u8; BLOCK_LEN]>,
+    filled: usize,
+    previous_word: Option<u32>,
+    status: Fips140Status,
+}
+
+impl Default for Fips140Tests {
+    fn default() -> Self {
+        Fips140Tests::new()
+    }
+}
+
+impl Fips140Tests {
+    /// Create tests with no data seen.
+    pub fn new() -> Self {
+        Fips140Tests {
+            block: Box::new([0; BLOCK_LEN]),
+            filled: 0,
+            previous_word: None,
+            status: Fips140Status::default(),
+        }
+    }
+
+    /// Feed every byte of `data`.
+    pub fn extend(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let take = (BLOCK_LEN - self.filled).min(data.len());
+            self.block[self.filled..self.filled + take].copy_from_slice(&data[..take]);
+            let words_before = self.filled / 4;
+            self.filled += take;
+            data = &data[take..];
+            for word in self.block[words_before * 4..self.filled / 4 * 4].chunks_exact(4) {
+                let word = u32::from_be_bytes(word.try_into().unwrap());
+                if self.previous_word == Some(word) {
+                    self.status.continuous_failures += 1;
+                }
+                self.previous_word = Some(word);
+            }
+            if self.filled == BLOCK_LEN {
+                self.record(test_block(&self.block));
+                self.filled = 0;
+            }
+        }
+    }
+
+    fn record(&mut self, result: BlockResult) {
+        let status = &mut self.status;
+        status.blocks += 1;
+        status.failed_blocks += !result.passed() as usize;
+        status.monobit_failures += !result.monobit_passed() as usize;
+        status.poker_failures += !result.poker_passed() as usize;
+        status.runs_failures += !result.runs_passed() as usize;
+        status.long_run_failures += !result.long_run_passed() as usize;
+    }
+
+    /// Failure counts so far.
+    pub fn status(&self) -> Fips140Status {
+        self.status
+    }
+}
+
+/// Run the FIPS 140-2 tests over every complete block of `data`.
+pub fn check(data: &[u8]) -> Fips140Status {
+    let mut tests = Fips140Tests::new();
+    tests.extend(data);
+    tests.status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_bytes(len: usize) -> Vec<u8> {
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_random_blocks_pass_and_streaming_matches_batch() {
+        let data = random_bytes(10 * BLOCK_LEN + 1234);
+        let status = check(&data);
+        assert_eq!(status.blocks, 10);
+        assert!(status.passed(), "{status:?}");
+        let mut tests = Fips140Tests::new();
+        for chunk in data.chunks(777) {
+            tests.extend(chunk);
+        }
+        assert_eq!(tests.status(), status);
+    }
+
+    #[test]
+    fn test_block_statistics() {
+        // 11110000 repeated: balanced, but only 4-bit runs and two nibbles.
+        let result = test_block(&[0xF0; BLOCK_LEN]);
+        assert_eq!(result.ones, 10_000);
+        assert!(result.monobit_passed());
+        assert_eq!(result.runs, [[0, 0, 0, 2500, 0, 0]; 2]);
+        assert!(!result.runs_passed());
+        assert_eq!(result.poker, 35_000.0);
+        assert!(!result.poker_passed());
+        assert_eq!(result.longest_run, 4);
+        assert!(result.long_run_passed());
+    }
+
+    #[test]
+    fn test_long_run_and_continuous_failures() {
+        let mut data = random_bytes(2 * BLOCK_LEN);
+        // A 32-bit run of zeros, and a repeated word, in the second block.
+        data[BLOCK_LEN + 100..BLOCK_LEN + 104].fill(0);
+        data[BLOCK_LEN + 200..BLOCK_LEN + 208].copy_from_slice(&[1, 2, 3, 4, 1, 2, 3, 4]);
+        let status = check(&data);
+        assert_eq!(status.long_run_failures, 1);
+        assert_eq!(status.failed_blocks, 1);
+        assert_eq!(status.continuous_failures, 1);
+        assert_eq!(status.monobit_failures, 0);
+    }
+}
@@ -0,0 +1,359 @@
+//! Deterministic fixed-point statistics.
+//!
+//! The floating-point statistics of [`EntStats`](crate::EntStats) depend on
+//! the platform's `log2` and on summation order, so their last bits may
+//! differ between targets and compiler versions. [`FixedStats`] derives the
+//! same histogram-based metrics with integer arithmetic only: counts and sums
+//! are exact, logarithms and square roots are computed bit by bit, and every
+//! result is a [`Fixed`] with [`FRAC_BITS`] fractional bits. The same input
+//! gives bit-identical results everywhere, which makes them suitable as
+//! certification evidence.
+//!
+//! The chi-square p-value needs the incomplete gamma function and is not
+//! provided; derive it from [`Fixed::to_f64`] if needed.
+//!
+//! ```rust
+//! use ent_rs::fixed::FixedStats;
+//! let data: Vec<u8> = (0..=255).cycle().take(4096).collect();
+//! let stats = FixedStats::from_data(&data, false);
+//! assert_eq!(stats.entropy.to_string(), "8.000000");
+//! assert_eq!(format!("{:.2}", stats.mean), "127.50");
+//! ```
+
+use std::fmt;
+
+use crate::monte_carlo_hit;
+
+/// Number of fractional bits of [`Fixed`].
+pub const FRAC_BITS: u32 = 32;
+
+const ONE: i128 = 1 << FRAC_BITS;
+
+/// A signed fixed-point number with [`FRAC_BITS`] fractional bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fixed(i128);
+
+impl Fixed {
+    /// The number `raw / 2^FRAC_BITS`.
+    pub fn from_raw(raw: i128) -> Self {
+        Fixed(raw)
+    }
+
+    /// The integer `n`.
+    pub fn from_int(n: i64) -> Self {
+        Fixed((n as i128) << FRAC_BITS)
+    }
+
+    /// The underlying integer, `self * 2^FRAC_BITS`.
+    pub fn raw(self) -> i128 {
+        self.0
+    }
+
+    /// Nearest `f64`; the conversion is correctly rounded and therefore
+    /// also deterministic.
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / ONE as f64
+    }
+}
+
+impl fmt::Display for Fixed {
+    /// Decimal representation truncated to the requested precision
+    /// (default 6 digits), computed without floating point.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = f.precision().unwrap_or(6);
+        let magnitude = self.0.unsigned_abs();
+        let mut s = (magnitude >> FRAC_BITS).to_string();
+        if digits > 0 {
+            s.push('.');
+            let mut frac = magnitude & (ONE as u128 - 1);
+            for _ in 0..digits {
+                frac *= 10;
+                s.push(char::from(b'0' + (frac >> FRAC_BITS) as u8));
+                frac &= ONE as u128 - 1;
+            }
+        }
+        f.pad_integral(self.0 >= 0, "", &s)
+    }
+}
+
+/// Statistics computed with integer arithmetic only.
+///
+/// The metrics match those of [`EntStats`](crate::EntStats) to about
+/// `2^-30`. For empty input all metrics are zero except the serial
+/// correlation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FixedStats {
+    /// Shannon entropy in bits per byte (or bit).
+    pub entropy: Fixed,
+    /// Ideal compression percentage based on entropy.
+    pub compression_percent: Fixed,
+    /// Chi-square test value.
+    pub chisquare: Fixed,
+    /// Arithmetic mean of all data bytes.
+    pub mean: Fixed,
+    /// In bit mode, the arithmetic mean of all data bits.
+    pub bit_mean: Option<Fixed>,
+    /// Estimated value of Pi from Monte Carlo method.
+    pub pi_estimate: Fixed,
+    /// Serial correlation coefficient between adjacent values, or -99999
+    /// when undefined.
+    pub serial_correlation: Fixed,
+}
+
+impl FixedStats {
+    /// Compute fixed-point statistics from a byte slice.
+    pub fn from_data(data: &[u8], bit_mode: bool) -> Self {
+        FixedStats::from_iter(data.iter().copied(), bit_mode)
+    }
+
+    /// Compute fixed-point statistics from a byte iterator.
+    pub fn from_iter<I: IntoIterator<Item = u8>>(iter: I, bit_mode: bool) -> Self {
+        let mut acc = FixedAccumulator::new();
+        for b in iter {
+            acc.push(b);
+        }
+        acc.finalize(bit_mode)
+    }
+
+    /// Like [`FixedStats::from_iter`], for iterators of fallible reads.
+    /// Stops at the first error and returns it.
+    pub fn try_from_iter<I, E>(iter: I, bit_mode: bool) -> Result<Self, E>
+    where
+        I: IntoIterator<Item = Result<u8, E>>,
+    {
+        let mut acc = FixedAccumulator::new();
+        for b in iter {
+            acc.push(b?);
+        }
+        Ok(acc.finalize(bit_mode))
+    }
+}
+
+/// Exact integer sums from which every metric is derived.
+#[derive(Debug, Clone)]
+struct FixedAccumulator {
+    counts: [u64; 256],
+    total: u64,
+    sum: u128,
+    pi_pending: [u8; 6],
+    pi_pending_len: usize,
+    pi_hits: u64,
+    pi_total: u64,
+    prev: Option<u8>,
+    sum_x: u128,
+    sum_y: u128,
+    sum_xy: u128,
+    sum_x2: u128,
+    sum_y2: u128,
+}
+
+impl FixedAccumulator {
+    fn new() -> Self {
+        FixedAccumulator {
+            counts: [0; 256],
+            total: 0,
+            sum: 0,
+            pi_pending: [0; 6],
+            pi_pending_len: 0,
+            pi_hits: 0,
+            pi_total: 0,
+            prev: None,
+            sum_x: 0,
+            sum_y: 0,
+            sum_xy: 0,
+            sum_x2: 0,
+            sum_y2: 0,
+        }
+    }
+
+    fn push(&mut self, b: u8) {
+        self.counts[b as usize] += 1;
+        self.total += 1;
+        self.sum += b as u128;
+
+        self.pi_pending[self.pi_pending_len] = b;
+        self.pi_pending_len += 1;
+        if self.pi_pending_len == 6 {
+            if monte_carlo_hit(&self.pi_pending) {
+                self.pi_hits += 1;
+            }
+            self.pi_total += 1;
+            self.pi_pending_len = 0;
+        }
+
+        if let Some(prev) = self.prev {
+            let (x, y) = (prev as u128, b as u128);
+            self.sum_x += x;
+            self.sum_y += y;
+            self.sum_xy += x * y;
+            self.sum_x2 += x * x;
+            self.sum_y2 += y * y;
+        }
+        self.prev = Some(b);
+    }
+
+    fn finalize(&self, bit_mode: bool) -> FixedStats {
+        let (entropy, chisquare, bit_mean) = if bit_mode {
+            let ones: u64 = self
+                .counts
+                .iter()
+                .enumerate()
+                .map(|(v, &c)| c * (v as u8).count_ones() as u64)
+                .sum();
+            let bits = [8 * self.total - ones, ones];
+            let bit_mean = Fixed(ratio(ones as u128, 8 * self.total as u128));
+            (entropy(&bits), chisquare(&bits), Some(bit_mean))
+        } else {
+            (entropy(&self.counts), chisquare(&self.counts), None)
+        };
+        let compression_percent = if bit_mode {
+            100 * ONE - 100 * entropy
+        } else {
+            100 * ONE - 100 * entropy / 8
+        };
+        FixedStats {
+            entropy: Fixed(entropy),
+            compression_percent: Fixed(compression_percent),
+            chisquare: Fixed(chisquare),
+            mean: Fixed(ratio(self.sum, self.total as u128)),
+            bit_mean,
+            pi_estimate: Fixed(ratio(4 * self.pi_hits as u128, self.pi_total as u128)),
+            serial_correlation: self.serial_correlation(),
+        }
+    }
+
+    fn serial_correlation(&self) -> Fixed {
+        let undefined = Fixed::from_int(-99999);
+        if self.total < 2 {
+            return undefined;
+        }
+        let n = (self.total - 1) as u128;
+        let num = (n * self.sum_xy) as i128 - (self.sum_x * self.sum_y) as i128;
+        let var_x = n * self.sum_x2 - self.sum_x * self.sum_x;
+        let var_y = n * self.sum_y2 - self.sum_y * self.sum_y;
+        if var_x == 0 || var_y == 0 {
+            return undefined;
+        }
+        // Shift both variances below 2^63 so their product fits, keeping the
+        // total shift even so it can be taken out of the square root.
+        let mut shift_x = (128 - var_x.leading_zeros()).saturating_sub(63);
+        let shift_y = (128 - var_y.leading_zeros()).saturating_sub(63);
+        if (shift_x + shift_y) % 2 == 1 {
+            shift_x += 1;
+        }
+        let root = ((var_x >> shift_x) * (var_y >> shift_y)).isqrt() as i128;
+        let half = (shift_x + shift_y) / 2;
+        // |num| <= sqrt(var_x * var_y), so neither shift overflows.
+        let scaled = if half <= FRAC_BITS {
+            num << (FRAC_BITS - half)
+        } else {
+            num >> (half - FRAC_BITS)
+        };
+        Fixed(scaled / root)
+    }
+}
+
+/// Entropy in bits per symbol, `log2(N) - sum(c * log2(c)) / N`, summed in
+/// symbol order.
+fn entropy(counts: &[u64]) -> i128 {
+    let total: u64 = counts.iter().sum();
+    if total == 0 {
+        return 0;
+    }
+    let weighted: i128 = counts
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| c as i128 * log2(c))
+        .sum();
+    log2(total) - weighted / total as i128
+}
+
+/// Chi-square against uniform, `(k * sum(c^2) - N^2) / N`, from exact sums.
+fn chisquare(counts: &[u64]) -> i128 {
+    let total: u128 = counts.iter().map(|&c| c as u128).sum();
+    let squares: u128 = counts.iter().map(|&c| c as u128 * c as u128).sum();
+    ratio(counts.len() as u128 * squares - total * total, total)
+}
+
+/// `num / den` in fixed point, truncated; zero when `den` is zero.
+fn ratio(num: u128, den: u128) -> i128 {
+    if den == 0 {
+        return 0;
+    }
+    let whole = num / den;
+    let frac = ((num % den) << FRAC_BITS) / den;
+    ((whole << FRAC_BITS) + frac) as i128
+}
+
+/// `log2(x)` in fixed point for `x >= 1`, by repeated squaring of the
+/// mantissa; each squaring yields one fractional bit.
+fn log2(x: u64) -> i128 {
+    let int = 63 - x.leading_zeros();
+    // Mantissa in [1, 2) with 62 fractional bits.
+    let mut m = ((x as u128) << 62) >> int;
+    let mut frac = 0i128;
+    for bit in (0..FRAC_BITS).rev() {
+        m = (m * m) >> 62;
+        if m >= 2 << 62 {
+            m >>= 1;
+            frac |= 1 << bit;
+        }
+    }
+    ((int as i128) << FRAC_BITS) | frac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EntStats;
+
+    #[test]
+    fn test_log2_exact_on_powers_and_close_elsewhere() {
+        assert_eq!(log2(1), 0);
+        assert_eq!(log2(1 << 40), 40 * ONE);
+        for x in [3u64, 10, 255, 1_000_003, u64::MAX] {
+            let err = Fixed(log2(x)).to_f64() - (x as f64).log2();
+            assert!(err.abs() < 1e-9, "log2({x}) off by {err}");
+        }
+    }
+
+    #[test]
+    fn test_matches_floating_point_statistics() {
+        let data: Vec<u8> = (0u32..50_000)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8 ^ (i as u8 & 3))
+            .collect();
+        for bit_mode in [false, true] {
+            let fixed = FixedStats::from_data(&data, bit_mode);
+            let float = EntStats::from_data(&data, bit_mode);
+            let close = |a: Fixed, b: f64| (a.to_f64() - b).abs() < 1e-7;
+            assert!(close(fixed.entropy, float.entropy));
+            assert!(close(fixed.compression_percent, float.compression_percent));
+            assert!(close(fixed.chisquare, float.chisquare));
+            assert!(close(fixed.mean, float.mean));
+            let ones = float.bit_frequencies.map(|[_, (_, ones)]| ones);
+            assert_eq!(fixed.bit_mean.is_some(), bit_mode);
+            assert!(fixed.bit_mean.zip(ones).is_none_or(|(a, b)| close(a, b)));
+            assert!(close(fixed.pi_estimate, float.pi_estimate));
+            assert!(close(fixed.serial_correlation, float.serial_correlation));
+        }
+    }
+
+    #[test]
+    fn test_display_and_edge_cases() {
+        assert_eq!(Fixed::from_raw(-ONE / 4).to_string(), "-0.250000");
+        assert_eq!(format!("{:.0}", Fixed::from_int(7)), "7");
+        let empty = FixedStats::from_data(&[], false);
+        assert_eq!(empty.entropy, Fixed::default());
+        let constant = FixedStats::from_data(&[0x33; 100], false);
+        assert_eq!(constant.serial_correlation, Fixed::from_int(-99999));
+        // Perfectly alternating bytes are perfectly anti-correlated.
+        let alternating: Vec<u8> = (0..1000)
+            .map(|i| if i % 2 == 0 { 0 } else { 255 })
+            .collect();
+        let stats = FixedStats::from_data(&alternating, false);
+        assert_eq!(stats.serial_correlation, Fixed::from_int(-1));
+        assert_eq!(stats.entropy, Fixed::from_int(1));
+    }
+}
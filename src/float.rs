@@ -0,0 +1,71 @@
+//! Floating-point functions missing from `core`, from `libm`.
+//!
+//! With `std` the inherent `f64` methods of the same names are used; this
+//! trait only fills them in for `no_std` builds.
+
+pub(crate) trait Float {
+    fn ceil(self) -> Self;
+    fn exp(self) -> Self;
+    fn exp2(self) -> Self;
+    fn ln(self) -> Self;
+    fn log2(self) -> Self;
+    fn powi(self, n: i32) -> Self;
+    fn sin(self) -> Self;
+    fn sqrt(self) -> Self;
+}
+
+impl Float for f64 {
+    fn ceil(self) -> f64 {
+        libm::ceil(self)
+    }
+
+    fn exp(self) -> f64 {
+        libm::exp(self)
+    }
+
+    fn exp2(self) -> f64 {
+        libm::exp2(self)
+    }
+
+    fn ln(self) -> f64 {
+        libm::log(self)
+    }
+
+    fn log2(self) -> f64 {
+        libm::log2(self)
+    }
+
+    fn powi(self, n: i32) -> f64 {
+        libm::pow(self, n as f64)
+    }
+
+    fn sin(self) -> f64 {
+        libm::sin(self)
+    }
+
+    fn sqrt(self) -> f64 {
+        libm::sqrt(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Float;
+
+    #[test]
+    fn test_matches_std() {
+        for x in [0.1, 0.5, 1.0, 2.5, 20.0, 1e-300, 1e300] {
+            let close = |a: f64, b: f64| a == b || ((a - b) / b).abs() < 1e-15;
+            assert!(close(Float::ceil(x), x.ceil()));
+            assert!(close(Float::exp(-x), (-x).exp()));
+            assert!(close(Float::exp2(-x), (-x).exp2()));
+            assert!(close(Float::ln(x), x.ln()));
+            assert!(close(Float::log2(x), x.log2()));
+            assert!(close(Float::powi(x, 3), x.powi(3)));
+            assert!(close(Float::sin(x), x.sin()));
+            assert!(close(Float::sqrt(x), x.sqrt()));
+        }
+        assert!(Float::ln(-1.0).is_nan());
+        assert_eq!(Float::log2(0.0), f64::NEG_INFINITY);
+    }
+}
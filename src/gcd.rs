@@ -0,0 +1,213 @@
+//! Marsaglia and Tsang's GCD test.
+//!
+//! Euclid's algorithm on pairs of random 32-bit integers exercises their
+//! arithmetic structure in a way few other tests do: both the number of
+//! division steps and the greatest common divisor itself have known
+//! distributions, and generators with subtle lattice or low-bit structure
+//! drift away from them. PractRand and TestU01 include variants of the test.
+//!
+//! ```rust
+//! use ent_rs::gcd::gcd_test;
+//! // Even integers: every gcd is at least 2.
+//! let data: Vec<u8> = (0u32..400_000)
+//!     .flat_map(|i| (i.wrapping_mul(2_654_435_761) & !1).to_be_bytes())
+//!     .collect();
+//! let result = gcd_test(&data);
+//! assert_eq!(result.gcd_counts[0], 0);
+//! assert!(result.gcd_p_value < 1e-10);
+//! ```
+
+use std::f64::consts::PI;
+
+use crate::chisquare_p_value;
+
+/// Classes of step counts: at most 6 steps, 7 to 32, and 33 or more.
+const STEP_CLASSES: usize = 28;
+
+/// Fewest steps with a class of their own.
+const MIN_STEPS: usize = 6;
+
+/// Probability of each step count class for two uniform nonzero 32-bit
+/// integers. The distribution has no closed form, and the binomial
+/// approximation Marsaglia and Tsang give fails good generators within a
+/// million pairs, so like their own table these were estimated, from 10^9
+/// pairs of SplitMix64 output.
+const STEP_PROBABILITIES: [f64; STEP_CLASSES] = [
+    0.000077115,
+    0.000206734,
+    0.000626766,
+    0.001678911,
+    0.003997794,
+    0.008513519,
+    0.016347869,
+    0.028428601,
+    0.044931408,
+    0.064767981,
+    0.085350452,
+    0.103002198,
+    0.114074376,
+    0.116048981,
+    0.108532811,
+    0.093362522,
+    0.073896738,
+    0.053800204,
+    0.036013492,
+    0.022156487,
+    0.012510707,
+    0.006481230,
+    0.003068029,
+    0.001328614,
+    0.000523995,
+    0.000187850,
+    0.000060674,
+    0.000023942,
+];
+
+/// Classes of gcd values: 1 to 99, and 100 or more.
+pub const GCD_CLASSES: usize = 100;
+
+/// Result of the GCD test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GcdResult {
+    /// Pairs of nonzero integers tested.
+    pub pairs: usize,
+    /// Pairs whose gcd took at most 6, 7, 8, ..., 32, and at least 33
+    /// division steps.
+    pub step_counts: [usize; STEP_CLASSES],
+    /// Chi-square statistic of `step_counts`, with 27 degrees of freedom.
+    pub steps_chi_square: f64,
+    /// Upper-tail p-value of the steps chi-square.
+    pub steps_p_value: f64,
+    /// Pairs with gcd 1, 2, ..., 99, and at least 100.
+    pub gcd_counts: [usize; GCD_CLASSES],
+    /// Chi-square statistic of `gcd_counts`, with 99 degrees of freedom.
+    pub gcd_chi_square: f64,
+    /// Upper-tail p-value of the gcd chi-square.
+    pub gcd_p_value: f64,
+}
+
+/// GCD test over consecutive pairs of big-endian 32-bit integers of `data`.
+///
+/// Each pair runs Euclid's algorithm, `(u, v) ← (v, u mod v)` until `v` is
+/// 0, counting the steps. The gcd is `j` with probability `6 / (π² j²)`.
+/// Pairs with a zero, which have no meaningful gcd, are skipped, as is a
+/// trailing partial pair.
+///
+/// The rarest classes of both statistics are expected 5 times in about
+/// 210,000 pairs (1.7 MB); Marsaglia and Tsang use 10^7 pairs. The p-values
+/// are NaN without a pair.
+pub fn gcd_test(data: &[u8]) -> GcdResult {
+    let mut step_counts = [0; STEP_CLASSES];
+    let mut gcd_counts = [0; GCD_CLASSES];
+    for pair in data.chunks_exact(8) {
+        let mut u = u32::from_be_bytes(pair[..4].try_into().unwrap());
+        let mut v = u32::from_be_bytes(pair[4..].try_into().unwrap());
+        if u == 0 || v == 0 {
+            continue;
+        }
+        let mut steps = 0;
+        while v != 0 {
+            (u, v) = (v, u % v);
+            steps += 1;
+        }
+        step_counts[steps.clamp(MIN_STEPS, MIN_STEPS + STEP_CLASSES - 1) - MIN_STEPS] += 1;
+        gcd_counts[(u as usize).min(GCD_CLASSES) - 1] += 1;
+    }
+
+    let pairs = step_counts.iter().sum::<usize>();
+    let (steps_chi_square, steps_p_value) = chi_square(&step_counts, &STEP_PROBABILITIES);
+    let (gcd_chi_square, gcd_p_value) = chi_square(&gcd_counts, &gcd_probabilities());
+    GcdResult {
+        pairs,
+        step_counts,
+        steps_chi_square,
+        steps_p_value,
+        gcd_counts,
+        gcd_chi_square,
+        gcd_p_value,
+    }
+}
+
+/// `6 / (π² j²)` for `j` from 1 to 99, and the rest for 100 or more.
+fn gcd_probabilities() -> [f64; GCD_CLASSES] {
+    let mut probabilities = [0.0; GCD_CLASSES];
+    for (j, p) in probabilities[..GCD_CLASSES - 1].iter_mut().enumerate() {
+        *p = 6.0 / (PI * (j + 1) as f64).powi(2);
+    }
+    probabilities[GCD_CLASSES - 1] = 1.0 - probabilities.iter().sum::<f64>();
+    probabilities
+}
+
+/// Pearson statistic of `counts` against `probabilities`, and its p-value.
+fn chi_square(counts: &[usize], probabilities: &[f64]) -> (f64, f64) {
+    let n = counts.iter().sum::<usize>() as f64;
+    if n == 0.0 {
+        return (f64::NAN, f64::NAN);
+    }
+    let statistic = counts
+        .iter()
+        .zip(probabilities)
+        .map(|(&observed, &p)| (observed as f64 - n * p).powi(2) / (n * p))
+        .sum::<f64>();
+    let dof = (counts.len() - 1) as f64;
+    (statistic, chisquare_p_value(statistic, dof))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_bytes(len: usize, seed: u64) -> Vec<u8> {
+        let mut x = seed;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_probabilities_sum_to_one() {
+        assert!((STEP_PROBABILITIES.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        let gcd = gcd_probabilities();
+        assert!((gcd.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        // Σ_(j ≥ 100) 6 / (π² j²) ≈ 6 / (99.5 π²).
+        assert!((gcd[GCD_CLASSES - 1] - 6.0 / (99.5 * PI * PI)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_random_pairs_pass() {
+        let result = gcd_test(&random_bytes(8 * 300_000 + 5, 1));
+        assert_eq!(result.pairs, 300_000);
+        assert!(result.steps_p_value > 0.001, "{result:?}");
+        assert!(result.gcd_p_value > 0.001, "{result:?}");
+    }
+
+    #[test]
+    fn test_counts_small_pairs_and_skips_zeros() {
+        // gcd(12, 18): (12, 18) → (18, 12) → (12, 6) → (6, 0), 3 steps.
+        let data = [0, 0, 0, 12, 0, 0, 0, 18, 0, 0, 0, 0, 0, 0, 0, 5];
+        let result = gcd_test(&data);
+        assert_eq!(result.pairs, 1);
+        assert_eq!(result.gcd_counts[5], 1);
+        assert_eq!(result.step_counts[0], 1);
+        assert!(gcd_test(&[]).steps_p_value.is_nan());
+    }
+
+    #[test]
+    fn test_flags_structured_pairs() {
+        // The second integer of each pair is the first with its low bit
+        // flipped: consecutive integers, coprime after two or three steps.
+        let data: Vec<u8> = random_bytes(4 * 300_000, 2)
+            .chunks(4)
+            .flat_map(|b| [b[0], b[1], b[2], b[3], b[0], b[1], b[2], b[3] ^ 1])
+            .collect();
+        let result = gcd_test(&data);
+        assert_eq!(result.gcd_counts[0], result.pairs);
+        assert!(result.steps_p_value < 1e-10, "{result:?}");
+        assert!(result.gcd_p_value < 1e-10, "{result:?}");
+    }
+}
@@ -0,0 +1,433 @@
+//! SP 800-90B §4.4 health tests: Repetition Count Test and Adaptive
+//! Proportion Test.
+//!
+//! Cutoffs are derived from the claimed min-entropy per sample `h` (bits per
+//! byte) and a false-positive probability of `2^-20` per test, as recommended
+//! by the standard.
+//!
+//! ```rust
+//! use ent_rs::health::check;
+//! let stuck = [0x42u8; 1000];
+//! let status = check(&stuck, 4.0);
+//! assert!(status.rct_failures > 0 && !status.passed());
+//! ```
+//!
+//! At runtime, with `std`, [`HealthCheckedReader`] wraps a live source and
+//! withholds its output from the first failure on, as §4.4 requires:
+//!
+//! ```rust
+//! # #[cfg(feature = "std")]
+//! # fn main() {
+//! use std::io::Read;
+//! use ent_rs::health::{HealthCheckedReader, HealthFailure, HealthTests};
+//! let source: &[u8] = &[7; 64];
+//! let mut reader = HealthCheckedReader::new(source, HealthTests::new(4.0));
+//! let error = reader.read(&mut [0; 64]).unwrap_err();
+//! let failure = error.into_inner().unwrap().downcast::<HealthFailure>().unwrap();
+//! assert!(matches!(*failure, HealthFailure::RepetitionCount { sample: 5, value: 7 }));
+//! # }
+//! # #[cfg(not(feature = "std"))]
+//! # fn main() {}
+//! ```
+
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io::{self, Read};
+
+#[cfg(not(any(feature = "std", test)))]
+use crate::float::Float;
+
+/// `-log2` of the false-positive probability per health test.
+pub const ALPHA_EXPONENT: f64 = 20.0;
+
+/// APT window size for non-binary (byte) samples.
+pub const APT_WINDOW: usize = 512;
+
+/// Outcome of running both health tests over a capture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthStatus {
+    /// Repetition Count Test cutoff in consecutive identical samples.
+    pub rct_cutoff: usize,
+    /// Number of times the RCT cutoff was reached.
+    pub rct_failures: usize,
+    /// Adaptive Proportion Test cutoff in occurrences per window.
+    pub apt_cutoff: usize,
+    /// Adaptive Proportion Test window in samples.
+    pub apt_window: usize,
+    /// Number of APT windows that reached the cutoff.
+    pub apt_failures: usize,
+}
+
+impl HealthStatus {
+    /// Whether neither test failed.
+    pub fn passed(&self) -> bool {
+        self.rct_failures == 0 && self.apt_failures == 0
+    }
+}
+
+/// RCT cutoff `C = 1 + ceil(20 / h)`, at least 2.
+///
+/// An `h` that is not positive, including NaN, gives `usize::MAX`, which
+/// never fails.
+pub fn rct_cutoff(min_entropy: f64) -> usize {
+    if min_entropy.is_nan() || min_entropy <= 0.0 {
+        return usize::MAX;
+    }
+    // The cast saturates, so a tiny `h` reaches `usize::MAX` rather than
+    // overflowing.
+    ((ALPHA_EXPONENT / min_entropy).ceil() as usize)
+        .saturating_add(1)
+        .max(2)
+}
+
+/// APT cutoff `C = 1 + CRITBINOM(W, 2^-h, 1 - 2^-20)`, capped at `window`.
+pub fn apt_cutoff(min_entropy: f64, window: usize) -> usize {
+    let p = (-min_entropy).exp2();
+    if min_entropy.is_nan() || min_entropy <= 0.0 || p >= 1.0 {
+        return window;
+    }
+    let target = 1.0 - (-ALPHA_EXPONENT).exp2();
+    let w = window as f64;
+    let mut log_pmf = w * (1.0 - p).ln();
+    let log_odds = (p / (1.0 - p)).ln();
+    let mut cdf = 0.0;
+    for k in 0..=window {
+        cdf += log_pmf.exp();
+        if cdf >= target {
+            return (k + 1).min(window);
+        }
+        log_pmf += ((w - k as f64) / (k as f64 + 1.0)).ln() + log_odds;
+    }
+    window
+}
+
+/// A health test failure, raised by the sample that reached a cutoff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthFailure {
+    /// The Repetition Count Test saw `value` repeated as often as its cutoff.
+    RepetitionCount {
+        /// Index of the failing sample since the tests started.
+        sample: u64,
+        /// The repeated sample value.
+        value: u8,
+    },
+    /// The Adaptive Proportion Test saw its reference `value` as often as its
+    /// cutoff within one window.
+    AdaptiveProportion {
+        /// Index of the failing sample since the tests started.
+        sample: u64,
+        /// The window's reference sample value.
+        value: u8,
+    },
+}
+
+impl fmt::Display for HealthFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HealthFailure::RepetitionCount { sample, value } => write!(
+                f,
+                "repetition count test failed at sample {sample} (value {value:#04x})"
+            ),
+            HealthFailure::AdaptiveProportion { sample, value } => write!(
+                f,
+                "adaptive proportion test failed at sample {sample} (value {value:#04x})"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for HealthFailure {}
+
+/// Streaming RCT and APT over a live sequence of byte samples.
+#[derive(Debug, Clone)]
+pub struct HealthTests {
+    rct_cutoff: usize,
+    apt_cutoff: usize,
+    apt_window: usize,
+    samples: u64,
+    prev: Option<u8>,
+    run: usize,
+    apt_reference: u8,
+    apt_seen: usize,
+    apt_count: usize,
+    rct_failures: usize,
+    apt_failures: usize,
+}
+
+impl HealthTests {
+    /// Create tests with cutoffs for the claimed `min_entropy` in bits per byte.
+    pub fn new(min_entropy: f64) -> Self {
+        HealthTests::with_cutoffs(
+            rct_cutoff(min_entropy),
+            apt_cutoff(min_entropy, APT_WINDOW),
+            APT_WINDOW,
+        )
+    }
+
+    /// Create tests with explicit cutoffs, such as those of a validated
+    /// entropy source, and an APT window of `apt_window` samples.
+    ///
+    /// # Panics
+    ///
+    /// If `rct_cutoff` is below 2 or `apt_window` is 0.
+    pub fn with_cutoffs(rct_cutoff: usize, apt_cutoff: usize, apt_window: usize) -> Self {
+        assert!(rct_cutoff >= 2, "RCT cutoff must be at least 2");
+        assert!(apt_window > 0, "APT window must not be empty");
+        HealthTests {
+            rct_cutoff,
+            apt_cutoff,
+            apt_window,
+            samples: 0,
+            prev: None,
+            run: 0,
+            apt_reference: 0,
+            apt_seen: 0,
+            apt_count: 0,
+            rct_failures: 0,
+            apt_failures: 0,
+        }
+    }
+
+    /// Feed one sample, returning the failure it raised, if any. Should both
+    /// tests fail on the same sample, both are counted and the Repetition
+    /// Count Test's failure is returned.
+    pub fn push(&mut self, b: u8) -> Option<HealthFailure> {
+        let sample = self.samples;
+        self.samples += 1;
+        let mut failure = None;
+        if self.prev == Some(b) {
+            self.run += 1;
+        } else {
+            self.run = 1;
+            self.prev = Some(b);
+        }
+        if self.run == self.rct_cutoff {
+            self.rct_failures += 1;
+            failure = Some(HealthFailure::RepetitionCount { sample, value: b });
+        }
+
+        if self.apt_seen == 0 {
+            self.apt_reference = b;
+            self.apt_count = 0;
+        }
+        if b == self.apt_reference {
+            self.apt_count += 1;
+            if self.apt_count == self.apt_cutoff {
+                self.apt_failures += 1;
+                failure.get_or_insert(HealthFailure::AdaptiveProportion { sample, value: b });
+            }
+        }
+        self.apt_seen = (self.apt_seen + 1) % self.apt_window;
+        failure
+    }
+
+    /// Feed every sample of `data`.
+    pub fn extend(&mut self, data: &[u8]) {
+        for &b in data {
+            self.push(b);
+        }
+    }
+
+    /// Cutoffs and failure counts so far.
+    pub fn status(&self) -> HealthStatus {
+        HealthStatus {
+            rct_cutoff: self.rct_cutoff,
+            rct_failures: self.rct_failures,
+            apt_cutoff: self.apt_cutoff,
+            apt_window: self.apt_window,
+            apt_failures: self.apt_failures,
+        }
+    }
+}
+
+/// A reader that runs the health tests over everything read from a live
+/// entropy source.
+///
+/// A read containing a failing sample returns an error wrapping the
+/// [`HealthFailure`] instead of its data, and so does every later read
+/// until [`clear_failure`](Self::clear_failure) acknowledges it.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct HealthCheckedReader<R> {
+    inner: R,
+    tests: HealthTests,
+    failure: Option<HealthFailure>,
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> HealthCheckedReader<R> {
+    /// Check the output of `inner` with `tests`.
+    pub fn new(inner: R, tests: HealthTests) -> Self {
+        HealthCheckedReader {
+            inner,
+            tests,
+            failure: None,
+        }
+    }
+
+    /// The health tests, with failure counts so far.
+    pub fn tests(&self) -> &HealthTests {
+        &self.tests
+    }
+
+    /// The failure withholding output, if any.
+    pub fn failure(&self) -> Option<HealthFailure> {
+        self.failure
+    }
+
+    /// Resume output after a failure, for example once the source has been
+    /// restarted. The tests keep their counts.
+    pub fn clear_failure(&mut self) {
+        self.failure = None;
+    }
+
+    /// The wrapped source.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> Read for HealthCheckedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(failure) = self.failure {
+            return Err(io::Error::other(failure));
+        }
+        let n = self.inner.read(buf)?;
+        for &b in &buf[..n] {
+            if let Some(failure) = self.tests.push(b) {
+                self.failure.get_or_insert(failure);
+            }
+        }
+        match self.failure {
+            Some(failure) => {
+                buf[..n].fill(0);
+                Err(io::Error::other(failure))
+            }
+            None => Ok(n),
+        }
+    }
+}
+
+/// Run the RCT and APT over `data` with cutoffs for the claimed
+/// `min_entropy` in bits per byte.
+pub fn check(data: &[u8], min_entropy: f64) -> HealthStatus {
+    let mut tests = HealthTests::new(min_entropy);
+    tests.extend(data);
+    tests.status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cutoffs_match_standard_examples() {
+        // 90B §4.4.1: H = 2 gives C = 11.
+        assert_eq!(rct_cutoff(2.0), 11);
+        assert_eq!(rct_cutoff(8.0), 4);
+        assert_eq!(rct_cutoff(1e-300), usize::MAX);
+        assert_eq!(rct_cutoff(f64::NAN), usize::MAX);
+        assert_eq!(rct_cutoff(f64::INFINITY), 2);
+        assert_eq!(apt_cutoff(f64::NAN, 512), 512);
+        // Neither panics building the tests.
+        HealthTests::new(1e-300);
+        HealthTests::new(f64::NAN);
+        // 90B Table 2, W = 512.
+        assert_eq!(apt_cutoff(0.5, 512), 410);
+        assert_eq!(apt_cutoff(1.0, 512), 311);
+        assert_eq!(apt_cutoff(2.0, 512), 177);
+        assert_eq!(apt_cutoff(4.0, 512), 62);
+        assert_eq!(apt_cutoff(8.0, 512), 13);
+    }
+
+    #[test]
+    fn test_healthy_source_passes() {
+        let data: Vec<u8> = (0..1u32 << 16)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+            .collect();
+        assert!(check(&data, 7.0).passed());
+    }
+
+    #[test]
+    fn test_streaming_matches_batch() {
+        let data: Vec<u8> = (0..4096u32).map(|i| (i / 7 % 5) as u8).collect();
+        let mut tests = HealthTests::new(2.0);
+        for chunk in data.chunks(100) {
+            tests.extend(chunk);
+        }
+        assert_eq!(tests.status(), check(&data, 2.0));
+    }
+
+    #[test]
+    fn test_custom_cutoffs_and_failures() {
+        let mut tests = HealthTests::with_cutoffs(3, 4, 8);
+        let failures: Vec<Option<HealthFailure>> = [1, 2, 2, 2, 3, 1, 4, 1, 5, 1]
+            .iter()
+            .map(|&b| tests.push(b))
+            .collect();
+        assert_eq!(
+            failures[3],
+            Some(HealthFailure::RepetitionCount {
+                sample: 3,
+                value: 2
+            })
+        );
+        // Window of 8 from sample 0: the fourth 1 is in the next window.
+        assert!(failures.iter().filter(|f| f.is_some()).count() == 1);
+        let status = tests.status();
+        assert_eq!(
+            (status.rct_cutoff, status.apt_cutoff, status.apt_window),
+            (3, 4, 8)
+        );
+
+        let mut tests = HealthTests::with_cutoffs(10, 4, 8);
+        let failures: Vec<_> = [1, 2, 1, 3, 1, 4, 1]
+            .iter()
+            .map(|&b| tests.push(b))
+            .collect();
+        assert_eq!(
+            failures[6],
+            Some(HealthFailure::AdaptiveProportion {
+                sample: 6,
+                value: 1
+            })
+        );
+        assert_eq!(tests.status().apt_failures, 1);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_reader_withholds_output_after_failure() {
+        let mut source = vec![0u8; 32];
+        source.extend((0..=255).cycle().take(1000));
+        source.extend([9; 64]);
+        let tests = HealthTests::with_cutoffs(40, 512, 512);
+        let mut reader = HealthCheckedReader::new(&source[..], tests);
+        let mut buf = [0; 100];
+        assert_eq!(reader.read(&mut buf).unwrap(), 100);
+        let mut rest = Vec::new();
+        let error = reader.read_to_end(&mut rest).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Other);
+        assert_eq!(
+            reader.failure(),
+            Some(HealthFailure::RepetitionCount {
+                sample: 32 + 1000 + 39,
+                value: 9
+            })
+        );
+        assert!(reader.read(&mut buf).is_err());
+        reader.clear_failure();
+        assert!(reader.read(&mut buf).is_ok());
+        assert_eq!(reader.tests().status().rct_failures, 1);
+    }
+
+    #[test]
+    fn test_apt_catches_bias() {
+        let data: Vec<u8> = (0..4096u32)
+            .map(|i| if i % 3 == 0 { (i >> 2) as u8 } else { 0 })
+            .collect();
+        let status = check(&data, 6.0);
+        assert!(status.apt_failures > 0);
+    }
+}
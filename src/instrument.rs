@@ -0,0 +1,85 @@
+//! Optional `tracing` instrumentation helpers.
+//!
+//! Without the `tracing` feature these compile to nothing.
+
+/// Measures an operation and reports its throughput when finished.
+pub(crate) struct Timer {
+    #[cfg(feature = "tracing")]
+    started: std::time::Instant,
+}
+
+impl Timer {
+    #[inline]
+    pub(crate) fn start() -> Self {
+        Timer {
+            #[cfg(feature = "tracing")]
+            started: std::time::Instant::now(),
+        }
+    }
+
+    /// Emit a debug event with the number of bytes processed and bytes/sec.
+    #[inline]
+    pub(crate) fn finish(self, operation: &'static str, bytes: usize) {
+        #[cfg(feature = "tracing")]
+        {
+            let elapsed = self.started.elapsed();
+            let secs = elapsed.as_secs_f64();
+            let bytes_per_sec = if secs > 0.0 { bytes as f64 / secs } else { 0.0 };
+            tracing::debug!(
+                operation,
+                bytes,
+                elapsed_us = elapsed.as_micros() as u64,
+                bytes_per_sec,
+                "analysis finished"
+            );
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = (operation, bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timer_reports_throughput() {
+        #[cfg(feature = "tracing")]
+        {
+            use std::io;
+            use std::sync::{Arc, Mutex};
+
+            #[derive(Clone, Default)]
+            struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+            impl io::Write for Buffer {
+                fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                    self.0.lock().unwrap().write(buf)
+                }
+
+                fn flush(&mut self) -> io::Result<()> {
+                    Ok(())
+                }
+            }
+
+            let buffer = Buffer::default();
+            let writer = buffer.clone();
+            let subscriber = tracing_subscriber::fmt()
+                .with_max_level(tracing::Level::DEBUG)
+                .with_ansi(false)
+                .with_writer(move || writer.clone())
+                .finish();
+            tracing::subscriber::with_default(subscriber, || {
+                Timer::start().finish("test", 1000);
+            });
+            let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+            assert!(output.contains("analysis finished"), "{output}");
+            assert!(
+                output.contains(r#"operation="test" bytes=1000"#),
+                "{output}"
+            );
+        }
+        #[cfg(not(feature = "tracing"))]
+        Timer::start().finish("test", 1000);
+    }
+}
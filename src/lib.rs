@@ -9,12 +9,149 @@
 //! let stats = EntStats::from_data(data, false);
 //! println!("Entropy: {}", stats.entropy);
 //! ```
+//!
+//! Without the default `std` feature the crate is `no_std` and needs only
+//! `alloc`, with the `libm` feature supplying floating-point functions. It
+//! then provides [`EntStats`], [`EntStatsBuilder`], [`EntAccumulator`] and the
+//! [`health`] and [`fips140`] tests, enough for a device to check its
+//! hardware RNG at boot.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("ent-rs needs the `std` feature, or `libm` for floating-point math without it");
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod ais31;
+#[cfg(feature = "std")]
+pub mod anomaly;
+#[cfg(feature = "std")]
+pub mod baseline;
+#[cfg(feature = "std")]
+pub mod binning;
+#[cfg(feature = "binscan")]
+pub mod binscan;
+#[cfg(feature = "cache")]
+pub mod cache;
+#[cfg(feature = "std")]
+pub mod carving;
+#[cfg(feature = "std")]
+pub mod channels;
+#[cfg(feature = "std")]
+pub mod classify;
+#[cfg(feature = "std")]
+pub mod collision;
+#[cfg(feature = "std")]
+pub mod conditioning;
+#[cfg(feature = "std")]
+pub mod correction;
+#[cfg(feature = "device")]
+pub mod device;
+#[cfg(feature = "std")]
+pub mod diehard;
+#[cfg(feature = "std")]
+pub mod diff;
+#[cfg(feature = "std")]
+pub mod digest;
+#[cfg(feature = "std")]
+pub mod extract;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+pub mod file;
+pub mod fips140;
+#[cfg(feature = "std")]
+pub mod fixed;
+#[cfg(feature = "std")]
+pub mod gcd;
+pub mod health;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "std")]
+pub mod model;
+#[cfg(feature = "std")]
+pub mod monitor;
+#[cfg(feature = "std")]
+pub mod nist;
+#[cfg(feature = "std")]
+pub mod pair;
+#[cfg(feature = "std")]
+pub mod percentile;
+#[cfg(feature = "std")]
+pub mod profile;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "std")]
+pub mod randomness;
+#[cfg(feature = "std")]
+pub mod regions;
+#[cfg(feature = "std")]
+pub mod renyi;
+#[cfg(feature = "json")]
+pub mod report;
+#[cfg(feature = "std")]
+pub mod second_level;
+#[cfg(feature = "magic")]
+pub mod signature;
+#[cfg(feature = "std")]
+pub mod sp800_90b;
+#[cfg(feature = "std")]
+pub mod suite;
+#[cfg(feature = "std")]
+pub mod surprisal;
+#[cfg(feature = "std")]
+pub mod symbol;
+#[cfg(feature = "std")]
+pub mod text;
+#[cfg(feature = "std")]
+pub mod timeseries;
+#[cfg(feature = "std")]
+pub mod transfer;
+#[cfg(feature = "std")]
+pub mod trng;
+#[cfg(feature = "std")]
+pub mod twopass;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "std")]
+pub mod window;
+
+#[cfg(feature = "tokio")]
+mod async_io;
+#[cfg(feature = "std")]
+mod fft;
+// Compiled in tests too when `libm` is enabled, to check it against `std`.
+#[cfg(any(not(any(feature = "std", test)), all(test, feature = "libm")))]
+mod float;
+mod instrument;
+#[cfg(feature = "parallel")]
+mod parallel;
+#[cfg(feature = "rand")]
+mod rng;
+#[cfg(feature = "serde")]
+mod serde_float;
+mod simd;
+mod special;
 
-use statrs::function::erf::erfc;
-use std::f64::consts::SQRT_2;
+#[cfg(not(any(feature = "std", test)))]
+use alloc::{string::String, vec, vec::Vec};
+use core::f64::consts::SQRT_2;
+use core::fmt;
+#[cfg(not(any(feature = "std", test)))]
+use float::Float;
+use special::{erfc, gamma_ur};
+#[cfg(feature = "std")]
+use std::io;
 
 /// Result of statistical analysis on binary data.
+///
+/// Every statistic is defined from one byte on, except the Monte Carlo Pi
+/// estimate (6 bytes) and the serial correlation (2 bytes, not all equal).
+/// [`EntStats::try_from_data`] rejects inputs too short for all of them.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EntStats {
     /// Shannon entropy in bits per byte (or bit).
     pub entropy: f64,
@@ -23,12 +160,17 @@ pub struct EntStats {
     /// Chi-square test value.
     pub chisquare: f64,
     /// p-value of chi-square test.
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "serde_float::nan_from_null")
+    )]
     pub p_value: f64,
-    /// Arithmetic mean of all data bytes.
+    /// Arithmetic mean of all data bytes; NaN for empty input.
     pub mean: f64,
-    /// Estimated value of Pi from Monte Carlo method.
+    /// Estimated value of Pi from Monte Carlo method; 0 below 6 bytes.
     pub pi_estimate: f64,
-    /// Serial correlation coefficient between adjacent values.
+    /// Serial correlation coefficient between adjacent values; -99999.0 when
+    /// undefined (fewer than 2 bytes, or constant data).
     pub serial_correlation: f64,
     /// Byte frequency table: (value, count, fraction).
     pub byte_frequencies: Option<Vec<(u8, usize, f64)>>,
@@ -37,23 +179,1019 @@ pub struct EntStats {
 }
 
 impl EntStats {
+    /// Fewest bytes for which every statistic is defined.
+    pub const MIN_LEN: usize = 6;
+
     /// Compute entropy statistics from byte slice, using bit mode or byte mode.
+    ///
+    /// All statistics come from one pass over `data`.
     pub fn from_data(data: &[u8], bit_mode: bool) -> Self {
-        let entropy = calculate_entropy(data, bit_mode);
-        let compression_percent = if bit_mode {
+        Sums::from_data(data, bit_mode).finalize()
+    }
+
+    /// Statistics of every `stride`-th byte of `data` from `offset`, such as
+    /// one channel of interleaved RGB pixels or stereo PCM, without copying
+    /// the channel out.
+    ///
+    /// Equal to [`EntStats::from_data`] of the de-interleaved channel; see
+    /// the `channels` module to analyze every channel and their correlation.
+    ///
+    /// ```rust
+    /// use ent_rs::EntStats;
+    /// let rgb = b"\x10\x80\xFF\x20\x80\xFE\x30\x80\xFD";
+    /// let green = EntStats::from_strided(rgb, 1, 3, false);
+    /// assert_eq!((green.entropy, green.mean), (0.0, 128.0));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `stride` is 0.
+    pub fn from_strided(data: &[u8], offset: usize, stride: usize, bit_mode: bool) -> Self {
+        assert!(stride > 0, "stride must be at least 1");
+        let mut acc = EntAccumulator::new(bit_mode);
+        for &b in data.iter().skip(offset).step_by(stride) {
+            acc.push(b);
+        }
+        acc.stats()
+    }
+
+    /// Like [`EntStats::from_data`], but fails instead of returning
+    /// placeholder values when `data` is too short for every statistic.
+    ///
+    /// ```rust
+    /// use ent_rs::{EntError, EntStats};
+    /// assert_eq!(EntStats::try_from_data(&[], false).unwrap_err(), EntError::Empty);
+    /// assert!(matches!(
+    ///     EntStats::try_from_data(b"abc", false),
+    ///     Err(EntError::TooShort { needed: 6, .. })
+    /// ));
+    /// assert!(EntStats::try_from_data(b"abcdef", false).is_ok());
+    /// ```
+    pub fn try_from_data(data: &[u8], bit_mode: bool) -> Result<Self, EntError> {
+        if data.is_empty() {
+            return Err(EntError::Empty);
+        }
+        if data.len() < EntStats::MIN_LEN {
+            return Err(EntError::TooShort {
+                statistic: "Monte Carlo Pi estimate",
+                needed: EntStats::MIN_LEN,
+                len: data.len(),
+            });
+        }
+        Ok(EntStats::from_data(data, bit_mode))
+    }
+
+    /// A builder that computes only selected statistics.
+    pub fn builder() -> EntStatsBuilder {
+        EntStatsBuilder::new()
+    }
+
+    /// Number of symbols analyzed (bytes, or bits in bit mode), from the
+    /// frequency table; `None` if the table was not computed.
+    pub fn sample_count(&self) -> Option<usize> {
+        match (&self.byte_frequencies, &self.bit_frequencies) {
+            (_, Some(bits)) => Some(bits[0].0 + bits[1].0),
+            (Some(bytes), None) => Some(bytes.iter().map(|&(_, count, _)| count).sum()),
+            (None, None) => None,
+        }
+    }
+
+    /// The terse CSV of `ent -t -c`, for pipelines built around that tool.
+    ///
+    /// Frequency rows (`2,Value,Occurrences,Fraction` and one `3,...` row for
+    /// each of the 256 byte values, or both bit values, including those that
+    /// never occur) come first, as in `ent`, when the frequency table was
+    /// computed; then the `0,File-bytes,...` header and the `1,...` row.
+    /// In bit mode the header reads `File-bits` and the mean is that of the
+    /// bits. Every line ends with a newline.
+    ///
+    /// ```rust
+    /// use ent_rs::EntStats;
+    /// let csv = EntStats::from_data(b"aaaaaabbbbbb", false).to_terse_csv();
+    /// let mut lines = csv.lines();
+    /// assert_eq!(lines.next(), Some("2,Value,Occurrences,Fraction"));
+    /// assert_eq!(lines.next(), Some("3,0,0,0.000000"));
+    /// assert_eq!(lines.nth(96), Some("3,97,6,0.500000"));
+    /// assert_eq!(lines.nth(158), Some("0,File-bytes,Entropy,Chi-square,Mean,Monte-Carlo-Pi,Serial-Correlation"));
+    /// ```
+    pub fn to_terse_csv(&self) -> String {
+        use core::fmt::Write;
+
+        let samples = self.sample_count().unwrap_or(0);
+        let rows: Vec<(usize, usize)> = match (&self.byte_frequencies, &self.bit_frequencies) {
+            (_, Some(bits)) => vec![(0, bits[0].0), (1, bits[1].0)],
+            (Some(bytes), None) => bytes
+                .iter()
+                .map(|&(v, count, _)| (v as usize, count))
+                .collect(),
+            (None, None) => Vec::new(),
+        };
+
+        let mut out = String::new();
+        if self.byte_frequencies.is_some() || self.bit_frequencies.is_some() {
+            out.push_str("2,Value,Occurrences,Fraction\n");
+            for (value, count) in rows {
+                let fraction = count as f64 / samples as f64;
+                let _ = writeln!(out, "3,{value},{count},{fraction:.6}");
+            }
+        }
+        let (unit, mean) = match self.bit_frequencies {
+            Some([_, (_, ones)]) => ("bit", ones),
+            None => ("byte", self.mean),
+        };
+        let _ = writeln!(
+            out,
+            "0,File-{unit}s,Entropy,Chi-square,Mean,Monte-Carlo-Pi,Serial-Correlation"
+        );
+        let _ = writeln!(
+            out,
+            "1,{samples},{:.6},{:.6},{mean:.6},{:.6},{:.6}",
+            self.entropy, self.chisquare, self.pi_estimate, self.serial_correlation
+        );
+        out
+    }
+
+    /// Compute entropy statistics from a byte iterator without collecting it.
+    ///
+    /// Produces the same result as [`EntStats::from_data`] on the collected bytes.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(iter)))]
+    pub fn from_iter<I: IntoIterator<Item = u8>>(iter: I, bit_mode: bool) -> Self {
+        let timer = instrument::Timer::start();
+        let mut acc = EntAccumulator::new(bit_mode);
+        for b in iter {
+            acc.push(b);
+        }
+        timer.finish("from_iter", acc.total);
+        acc.finalize()
+    }
+
+    /// Like [`EntStats::from_iter`], for iterators of fallible reads such as
+    /// [`std::io::Bytes`]. Stops at the first error and returns it.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(iter)))]
+    pub fn try_from_iter<I, E>(iter: I, bit_mode: bool) -> Result<Self, E>
+    where
+        I: IntoIterator<Item = Result<u8, E>>,
+    {
+        let timer = instrument::Timer::start();
+        let mut acc = EntAccumulator::new(bit_mode);
+        for b in iter {
+            acc.push(b?);
+        }
+        timer.finish("try_from_iter", acc.total);
+        Ok(acc.finalize())
+    }
+}
+
+/// The report of John Walker's `ent`, line for line, so scripts that parse
+/// its output keep working.
+///
+/// The chi-square percentage is the exact upper-tail probability, and in
+/// bit mode the mean is that of the bits, as in `ent`. Without a frequency
+/// table the sample count reads 0.
+///
+/// ```rust
+/// use ent_rs::EntStats;
+/// let report = EntStats::from_data(&[0u8; 1000], false).to_string();
+/// assert!(report.starts_with("Entropy = 0.000000 bits per byte.\n"));
+/// assert!(report.ends_with("Serial correlation coefficient is undefined (all values equal!)."));
+/// ```
+impl fmt::Display for EntStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bits = self.bit_frequencies;
+        let (unit, dof) = if bits.is_some() {
+            ("bit", 1.0)
+        } else {
+            ("byte", 255.0)
+        };
+        let samples = self.sample_count().unwrap_or(0);
+        writeln!(f, "Entropy = {:.6} bits per {unit}.", self.entropy)?;
+        writeln!(f, "\nOptimum compression would reduce the size")?;
+        writeln!(
+            f,
+            "of this {samples} {unit} file by {} percent.\n",
+            self.compression_percent as i64
+        )?;
+
+        writeln!(
+            f,
+            "Chi square distribution for {samples} samples is {:.2}, and randomly",
+            self.chisquare
+        )?;
+        let p = chisquare_p_value(self.chisquare, dof);
+        // "more than than" is `ent`'s own wording.
+        if p < 0.0001 {
+            f.write_str("would exceed this value less than 0.01")?;
+        } else if p > 0.9999 {
+            f.write_str("would exceed this value more than than 99.99")?;
+        } else {
+            write!(f, "would exceed this value {:.2}", p * 100.0)?;
+        }
+        writeln!(f, " percent of the times.\n")?;
+
+        let (mean, random) = match bits {
+            Some([_, (_, ones)]) => (ones, 0.5),
+            None => (self.mean, 127.5),
+        };
+        writeln!(
+            f,
+            "Arithmetic mean value of data {unit}s is {mean:.4} ({random:.1} = random)."
+        )?;
+        let pi_error =
+            100.0 * (core::f64::consts::PI - self.pi_estimate).abs() / core::f64::consts::PI;
+        writeln!(
+            f,
+            "Monte Carlo value for Pi is {:.9} (error {pi_error:.2} percent).",
+            self.pi_estimate
+        )?;
+        if self.serial_correlation == -99999.0 {
+            write!(
+                f,
+                "Serial correlation coefficient is undefined (all values equal!)."
+            )
+        } else {
+            write!(
+                f,
+                "Serial correlation coefficient is {:.6} (totally uncorrelated = 0.0).",
+                self.serial_correlation
+            )
+        }
+    }
+}
+
+/// Input rejected by [`EntStats::try_from_data`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntError {
+    /// No data at all.
+    Empty,
+    /// Fewer bytes than a statistic needs.
+    TooShort {
+        /// The statistic that cannot be computed.
+        statistic: &'static str,
+        /// Bytes it needs.
+        needed: usize,
+        /// Bytes given.
+        len: usize,
+    },
+}
+
+impl fmt::Display for EntError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntError::Empty => f.write_str("empty input"),
+            EntError::TooShort {
+                statistic,
+                needed,
+                len,
+            } => write!(f, "{statistic} needs at least {needed} bytes, got {len}"),
+        }
+    }
+}
+
+impl core::error::Error for EntError {}
+
+/// Bytes to drop and a mask to apply before analysis, so that padding or
+/// formatting does not drown out the rest of the data.
+///
+/// Exclusion looks at the original byte; the mask is applied to the bytes
+/// that remain. Statistics are then those of the shorter stream, so the
+/// serial correlation and Monte Carlo pairs span the dropped bytes.
+///
+/// ```rust
+/// use ent_rs::{ByteFilter, EntStats};
+/// let filter = ByteFilter::new().exclude(0x00).exclude_whitespace();
+/// assert_eq!(filter.filter(b"a b\0\0\nc"), b"abc");
+/// let stats = EntStats::builder().filter(filter).analyze(b"a b\0\0\nc");
+/// assert_eq!(stats.entropy, EntStats::from_data(b"abc", false).entropy);
+///
+/// let low_bits = ByteFilter::new().mask(0x0F);
+/// assert_eq!(low_bits.filter(&[0x12, 0xF3]), [0x02, 0x03]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteFilter {
+    excluded: [bool; 256],
+    mask: u8,
+}
+
+impl Default for ByteFilter {
+    fn default() -> Self {
+        ByteFilter::new()
+    }
+}
+
+impl ByteFilter {
+    /// Keep every byte unchanged.
+    pub fn new() -> Self {
+        ByteFilter {
+            excluded: [false; 256],
+            mask: 0xFF,
+        }
+    }
+
+    /// Drop every occurrence of `byte`.
+    pub fn exclude(mut self, byte: u8) -> Self {
+        self.excluded[byte as usize] = true;
+        self
+    }
+
+    /// Drop every occurrence of each of `bytes`.
+    pub fn exclude_all<I: IntoIterator<Item = u8>>(mut self, bytes: I) -> Self {
+        for byte in bytes {
+            self.excluded[byte as usize] = true;
+        }
+        self
+    }
+
+    /// Drop ASCII whitespace: space, tab, line feed, form feed and carriage
+    /// return.
+    pub fn exclude_whitespace(self) -> Self {
+        self.exclude_all((0..=255).filter(u8::is_ascii_whitespace))
+    }
+
+    /// AND each kept byte with `mask`, for example `0x7F` to ignore the top
+    /// bit of 7-bit data.
+    pub fn mask(mut self, mask: u8) -> Self {
+        self.mask = mask;
+        self
+    }
+
+    /// Whether `byte` is dropped.
+    pub fn excludes(&self, byte: u8) -> bool {
+        self.excluded[byte as usize]
+    }
+
+    /// Whether the filter keeps every byte unchanged.
+    pub fn is_identity(&self) -> bool {
+        self.mask == 0xFF && !self.excluded.contains(&true)
+    }
+
+    /// `byte` masked, or `None` if it is dropped.
+    pub fn apply(&self, byte: u8) -> Option<u8> {
+        (!self.excludes(byte)).then_some(byte & self.mask)
+    }
+
+    /// The bytes of `data` that are kept, masked.
+    pub fn filter(&self, data: &[u8]) -> Vec<u8> {
+        data.iter().filter_map(|&b| self.apply(b)).collect()
+    }
+}
+
+/// Computes a chosen subset of the statistics, skipping the work for the
+/// rest.
+///
+/// Skipped statistics are NaN in the result, and skipped frequency tables
+/// are `None`. Entropy includes the compression percentage and chi-square
+/// its p-value.
+///
+/// ```rust
+/// use ent_rs::EntStats;
+/// let data: Vec<u8> = (0u32..4096).map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8).collect();
+/// let stats = EntStats::builder().without_pi().without_serial_correlation().analyze(&data);
+/// assert_eq!(stats.entropy, EntStats::from_data(&data, false).entropy);
+/// assert!(stats.pi_estimate.is_nan());
+///
+/// let entropy_only = ent_rs::EntStatsBuilder::none().with_entropy().analyze(&data);
+/// assert!(entropy_only.byte_frequencies.is_none());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntStatsBuilder {
+    bit_mode: bool,
+    entropy: bool,
+    chisquare: bool,
+    mean: bool,
+    pi: bool,
+    serial_correlation: bool,
+    frequencies: bool,
+    fold_case: bool,
+    filter: ByteFilter,
+}
+
+impl Default for EntStatsBuilder {
+    fn default() -> Self {
+        EntStatsBuilder::new()
+    }
+}
+
+impl EntStatsBuilder {
+    /// Every statistic, in byte mode.
+    pub fn new() -> Self {
+        EntStatsBuilder {
+            bit_mode: false,
+            entropy: true,
+            chisquare: true,
+            mean: true,
+            pi: true,
+            serial_correlation: true,
+            frequencies: true,
+            fold_case: false,
+            filter: ByteFilter::new(),
+        }
+    }
+
+    /// No statistics; enable them with the `with_*` methods.
+    pub fn none() -> Self {
+        EntStatsBuilder {
+            bit_mode: false,
+            entropy: false,
+            chisquare: false,
+            mean: false,
+            pi: false,
+            serial_correlation: false,
+            frequencies: false,
+            fold_case: false,
+            filter: ByteFilter::new(),
+        }
+    }
+
+    /// Analyze bits instead of bytes.
+    pub fn bit_mode(mut self, bit_mode: bool) -> Self {
+        self.bit_mode = bit_mode;
+        self
+    }
+
+    /// Fold upper-case ISO 8859-1 letters to lower case before analyzing,
+    /// like `ent -f`, so that text analyses count `A` and `a` as one value.
+    pub fn fold_case(mut self, fold_case: bool) -> Self {
+        self.fold_case = fold_case;
+        self
+    }
+
+    /// Drop and mask bytes with `filter` before analyzing, after any case
+    /// folding.
+    pub fn filter(mut self, filter: ByteFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Compute the entropy and compression percentage.
+    pub fn with_entropy(mut self) -> Self {
+        self.entropy = true;
+        self
+    }
+
+    /// Skip the entropy and compression percentage.
+    pub fn without_entropy(mut self) -> Self {
+        self.entropy = false;
+        self
+    }
+
+    /// Compute the chi-square statistic and p-value.
+    pub fn with_chisquare(mut self) -> Self {
+        self.chisquare = true;
+        self
+    }
+
+    /// Skip the chi-square statistic and p-value.
+    pub fn without_chisquare(mut self) -> Self {
+        self.chisquare = false;
+        self
+    }
+
+    /// Compute the arithmetic mean.
+    pub fn with_mean(mut self) -> Self {
+        self.mean = true;
+        self
+    }
+
+    /// Skip the arithmetic mean.
+    pub fn without_mean(mut self) -> Self {
+        self.mean = false;
+        self
+    }
+
+    /// Compute the Monte Carlo Pi estimate.
+    pub fn with_pi(mut self) -> Self {
+        self.pi = true;
+        self
+    }
+
+    /// Skip the Monte Carlo Pi estimate.
+    pub fn without_pi(mut self) -> Self {
+        self.pi = false;
+        self
+    }
+
+    /// Compute the serial correlation coefficient.
+    pub fn with_serial_correlation(mut self) -> Self {
+        self.serial_correlation = true;
+        self
+    }
+
+    /// Skip the serial correlation coefficient.
+    pub fn without_serial_correlation(mut self) -> Self {
+        self.serial_correlation = false;
+        self
+    }
+
+    /// Fill in the byte or bit frequency table.
+    pub fn with_frequencies(mut self) -> Self {
+        self.frequencies = true;
+        self
+    }
+
+    /// Leave the frequency tables empty.
+    pub fn without_frequencies(mut self) -> Self {
+        self.frequencies = false;
+        self
+    }
+
+    /// Compute the selected statistics of `data`.
+    pub fn analyze(&self, data: &[u8]) -> EntStats {
+        if self.fold_case || !self.filter.is_identity() {
+            let folded = data
+                .iter()
+                .map(|&b| if self.fold_case { fold_case(b) } else { b });
+            let filtered: Vec<u8> = folded.filter_map(|b| self.filter.apply(b)).collect();
+            let builder = EntStatsBuilder {
+                fold_case: false,
+                filter: ByteFilter::new(),
+                ..*self
+            };
+            return builder.analyze(&filtered);
+        }
+        // With both pair-wise statistics the fused pass is fastest.
+        let mut stats = if self.pi && self.serial_correlation {
+            EntStats::from_data(data, self.bit_mode)
+        } else {
+            let counts = if self.entropy
+                || self.chisquare
+                || self.mean
+                || self.serial_correlation
+                || self.frequencies
+            {
+                byte_counts(data)
+            } else {
+                [0; 256]
+            };
+            let pi_hits = if self.pi {
+                data.chunks_exact(6).filter(|c| monte_carlo_hit(c)).count()
+            } else {
+                0
+            };
+            let sxy = if self.serial_correlation {
+                data.windows(2).map(|w| w[0] as u64 * w[1] as u64).sum()
+            } else {
+                0
+            };
+            Sums::from_histogram(self.bit_mode, counts, data, pi_hits, sxy).finalize()
+        };
+
+        if !self.entropy {
+            stats.entropy = f64::NAN;
+            stats.compression_percent = f64::NAN;
+        }
+        if !self.chisquare {
+            stats.chisquare = f64::NAN;
+            stats.p_value = f64::NAN;
+        }
+        if !self.mean {
+            stats.mean = f64::NAN;
+        }
+        if !self.pi {
+            stats.pi_estimate = f64::NAN;
+        }
+        if !self.serial_correlation {
+            stats.serial_correlation = f64::NAN;
+        }
+        if !self.frequencies {
+            stats.byte_frequencies = None;
+            stats.bit_frequencies = None;
+        }
+        stats
+    }
+}
+
+/// Streaming statistics over data that arrives in pieces.
+///
+/// Only the histogram and a few running sums are kept, so memory use does not
+/// grow with the input. Pieces may be fed with [`update`](Self::update), by
+/// writing to the accumulator (it implements [`io::Write`], so
+/// [`io::copy`] from a socket or pipe works), or analyzed separately and
+/// combined with [`merge`](Self::merge); either way the result equals
+/// [`EntStats::from_data`] on the concatenated bytes. Monte Carlo coordinates
+/// and serial-correlation pairs that span a boundary are carried across it.
+///
+/// Monte Carlo coordinates are the 6-byte groups of the whole stream, so a
+/// piece that does not start at a multiple of 6 bytes must be analyzed
+/// with an accumulator from [`at_offset`](Self::at_offset).
+///
+/// ```rust
+/// use ent_rs::{EntAccumulator, EntStats};
+/// let data: Vec<u8> = (0u32..1000).map(|i| (i * 7 % 251) as u8).collect();
+/// let mut left = EntAccumulator::new(false);
+/// left.update(&data[..333]);
+/// let mut right = EntAccumulator::at_offset(false, 333);
+/// right.update(&data[333..]);
+/// left.merge(&right);
+/// assert_eq!(left.finalize().pi_estimate, EntStats::from_data(&data, false).pi_estimate);
+/// ```
+///
+/// With the `serde` feature the accumulator itself is serializable, so
+/// shards analyzed on different machines can be shipped to one place and
+/// merged there.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "AccumulatorState", try_from = "AccumulatorState")
+)]
+pub struct EntAccumulator {
+    bit_mode: bool,
+    counts: [usize; 256],
+    total: usize,
+    sum: u64,
+    /// Offset modulo 6 of the first byte in the whole stream.
+    phase: usize,
+    /// Monte Carlo hits of the 6-byte groups of the stream that lie within
+    /// the bytes seen.
+    pi_hits: usize,
+    /// First and last `min(total, 5)` bytes, for windows and pairs that
+    /// span a merge.
+    head: [u8; 5],
+    tail: [u8; 5],
+    /// Exact serial-correlation sums: x, y, xy, x², y² over adjacent pairs.
+    scc: [u64; 5],
+}
+
+impl EntAccumulator {
+    /// An accumulator that has seen no data.
+    pub fn new(bit_mode: bool) -> Self {
+        EntAccumulator::at_offset(bit_mode, 0)
+    }
+
+    /// An accumulator for the bytes from `offset` on of a longer stream, to
+    /// be merged after the accumulator of the bytes before them.
+    pub fn at_offset(bit_mode: bool, offset: u64) -> Self {
+        EntAccumulator {
+            bit_mode,
+            counts: [0; 256],
+            total: 0,
+            sum: 0,
+            phase: (offset % 6) as usize,
+            pi_hits: 0,
+            head: [0; 5],
+            tail: [0; 5],
+            scc: [0; 5],
+        }
+    }
+
+    /// Add the next byte.
+    pub fn push(&mut self, b: u8) {
+        let n = self.total;
+        if n < 5 {
+            self.head[n] = b;
+            if n > 0 {
+                add_pair(&mut self.scc, self.tail[n - 1], b);
+            }
+            self.tail[n] = b;
+        } else {
+            if (self.phase + n) % 6 == 5 {
+                let mut window = [0u8; 6];
+                window[..5].copy_from_slice(&self.tail);
+                window[5] = b;
+                if monte_carlo_hit(&window) {
+                    self.pi_hits += 1;
+                }
+            }
+            add_pair(&mut self.scc, self.tail[4], b);
+            self.tail.copy_within(1.., 0);
+            self.tail[4] = b;
+        }
+        self.counts[b as usize] += 1;
+        self.total += 1;
+        self.sum += b as u64;
+    }
+
+    /// Add the next chunk of data.
+    pub fn update(&mut self, chunk: &[u8]) {
+        // Byte by byte up to the start of a 6-byte group with the head filled,
+        // then whole groups directly.
+        let mut rest = chunk;
+        while let Some((&b, tail)) = rest.split_first() {
+            if self.total >= 5 && (self.phase + self.total).is_multiple_of(6) {
+                break;
+            }
+            self.push(b);
+            rest = tail;
+        }
+        let mut groups = rest.chunks_exact(6);
+        let mut prev = self.tail[4];
+        for group in &mut groups {
+            if monte_carlo_hit(group) {
+                self.pi_hits += 1;
+            }
+            for &b in group {
+                self.counts[b as usize] += 1;
+                self.sum += b as u64;
+                add_pair(&mut self.scc, prev, b);
+                prev = b;
+            }
+        }
+        let whole = rest.len() - groups.remainder().len();
+        if whole > 0 {
+            self.tail.copy_from_slice(&rest[whole - 5..whole]);
+            self.total += whole;
+        }
+        for &b in groups.remainder() {
+            self.push(b);
+        }
+    }
+
+    /// Append the data seen by `other`, as if it had been fed to `self`
+    /// after everything seen so far.
+    ///
+    /// # Panics
+    ///
+    /// If the accumulators use different modes, or if neither is empty and
+    /// `other` was not created [`at_offset`](Self::at_offset) an offset
+    /// equal to the end of `self` modulo 6.
+    pub fn merge(&mut self, other: &EntAccumulator) {
+        assert_eq!(
+            self.bit_mode, other.bit_mode,
+            "cannot merge bit-mode and byte-mode accumulators"
+        );
+        let (left, right) = (self.total, other.total);
+        if right == 0 {
+            return;
+        }
+        if left == 0 {
+            *self = other.clone();
+            return;
+        }
+        assert_eq!(
+            (self.phase + left) % 6,
+            other.phase,
+            "merged accumulator does not start where this one ends; create it with `at_offset`"
+        );
+        let tail_len = left.min(5);
+        let head_len = right.min(5);
+        let mut joined = [0u8; 10];
+        joined[..tail_len].copy_from_slice(&self.tail[..tail_len]);
+        joined[tail_len..tail_len + head_len].copy_from_slice(&other.head[..head_len]);
+
+        // Windows starting in our tail and ending in `other`'s head.
+        for k in 0..tail_len {
+            if (self.phase + left - tail_len + k).is_multiple_of(6)
+                && k + 6 <= tail_len + head_len
+                && monte_carlo_hit(&joined[k..k + 6])
+            {
+                self.pi_hits += 1;
+            }
+        }
+        self.pi_hits += other.pi_hits;
+
+        add_pair(&mut self.scc, self.tail[tail_len - 1], other.head[0]);
+        for (sum, &term) in self.scc.iter_mut().zip(&other.scc) {
+            *sum += term;
+        }
+
+        for i in left..(left + right).min(5) {
+            self.head[i] = other.head[i - left];
+        }
+        let other_tail = &other.tail[..head_len];
+        let mut combined = [0u8; 10];
+        combined[..tail_len].copy_from_slice(&self.tail[..tail_len]);
+        combined[tail_len..tail_len + head_len].copy_from_slice(other_tail);
+        let len = (tail_len + head_len).min(5);
+        self.tail[..len].copy_from_slice(&combined[tail_len + head_len - len..tail_len + head_len]);
+
+        for (count, &c) in self.counts.iter_mut().zip(&other.counts) {
+            *count += c;
+        }
+        self.total += right;
+        self.sum += other.sum;
+    }
+
+    /// Whether the accumulator analyzes bits rather than bytes.
+    pub fn bit_mode(&self) -> bool {
+        self.bit_mode
+    }
+
+    /// Number of bytes seen.
+    pub fn len(&self) -> usize {
+        self.total
+    }
+
+    /// Whether no bytes have been seen.
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    /// Number of 6-byte groups of the stream within the bytes seen.
+    fn pi_windows(&self) -> usize {
+        let first = (6 - self.phase) % 6;
+        self.total.saturating_sub(first) / 6
+    }
+
+    /// Statistics of the data seen so far, leaving the accumulator usable.
+    pub fn stats(&self) -> EntStats {
+        self.clone().finalize()
+    }
+
+    /// Statistics of all data seen.
+    pub fn finalize(self) -> EntStats {
+        Sums {
+            bit_mode: self.bit_mode,
+            counts: self.counts,
+            total: self.total,
+            sum: self.sum,
+            pi_hits: self.pi_hits,
+            pi_total: self.pi_windows(),
+            scc: self.scc,
+        }
+        .finalize()
+    }
+}
+
+/// Serialized form of [`EntAccumulator`]: serde has no impls for arrays
+/// longer than 32, so the histogram travels as a sequence.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename = "EntAccumulator")]
+struct AccumulatorState {
+    bit_mode: bool,
+    counts: Vec<usize>,
+    total: usize,
+    sum: u64,
+    phase: usize,
+    pi_hits: usize,
+    head: [u8; 5],
+    tail: [u8; 5],
+    scc: [u64; 5],
+}
+
+#[cfg(feature = "serde")]
+impl From<EntAccumulator> for AccumulatorState {
+    fn from(acc: EntAccumulator) -> Self {
+        AccumulatorState {
+            bit_mode: acc.bit_mode,
+            counts: acc.counts.to_vec(),
+            total: acc.total,
+            sum: acc.sum,
+            phase: acc.phase,
+            pi_hits: acc.pi_hits,
+            head: acc.head,
+            tail: acc.tail,
+            scc: acc.scc,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<AccumulatorState> for EntAccumulator {
+    type Error = String;
+
+    fn try_from(state: AccumulatorState) -> Result<Self, Self::Error> {
+        let counts: [usize; 256] = state
+            .counts
+            .try_into()
+            .map_err(|c: Vec<usize>| format!("expected 256 counts, found {}", c.len()))?;
+        if counts.iter().sum::<usize>() != state.total {
+            return Err("counts do not add up to the total".into());
+        }
+        if state.phase >= 6 {
+            return Err(format!("phase must be below 6, found {}", state.phase));
+        }
+        Ok(EntAccumulator {
+            bit_mode: state.bit_mode,
+            counts,
+            total: state.total,
+            sum: state.sum,
+            phase: state.phase,
+            pi_hits: state.pi_hits,
+            head: state.head,
+            tail: state.tail,
+            scc: state.scc,
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl io::Write for EntAccumulator {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Order-independent sums from which all statistics can be derived.
+#[derive(Debug, Clone)]
+struct Sums {
+    bit_mode: bool,
+    counts: [usize; 256],
+    total: usize,
+    sum: u64,
+    pi_hits: usize,
+    pi_total: usize,
+    scc: [u64; 5],
+}
+
+impl Sums {
+    /// All sums in a single pass over `data`.
+    ///
+    /// Only the histogram, the Monte Carlo hits and Σxy are accumulated per
+    /// byte; the remaining serial-correlation sums follow from the histogram
+    /// and the first and last bytes.
+    fn from_data(data: &[u8], bit_mode: bool) -> Self {
+        let mut counts = [0usize; 256];
+        let mut pi_hits = 0;
+        let mut sxy = 0u64;
+        // The first byte pairs with 0, adding nothing to Σxy.
+        let mut prev = 0u64;
+        let mut chunks = data.chunks_exact(6);
+        for chunk in &mut chunks {
+            if monte_carlo_hit(chunk) {
+                pi_hits += 1;
+            }
+            for &b in chunk {
+                counts[b as usize] += 1;
+                sxy += prev * b as u64;
+                prev = b as u64;
+            }
+        }
+        for &b in chunks.remainder() {
+            counts[b as usize] += 1;
+            sxy += prev * b as u64;
+            prev = b as u64;
+        }
+
+        Sums::from_histogram(bit_mode, counts, data, pi_hits, sxy)
+    }
+
+    /// Sums from the histogram of `data`, its Monte Carlo hits and the Σxy
+    /// of its adjacent pairs.
+    fn from_histogram(
+        bit_mode: bool,
+        counts: [usize; 256],
+        data: &[u8],
+        pi_hits: usize,
+        sxy: u64,
+    ) -> Self {
+        let (mut sum, mut sum_sq) = (0u64, 0u64);
+        for (v, &c) in counts.iter().enumerate() {
+            sum += (v * c) as u64;
+            sum_sq += (v * v * c) as u64;
+        }
+        let first = data.first().map_or(0, |&b| b as u64);
+        let last = data.last().map_or(0, |&b| b as u64);
+        Sums {
+            bit_mode,
+            counts,
+            total: data.len(),
+            sum,
+            pi_hits,
+            pi_total: data.len() / 6,
+            // Saturating: `EntStatsBuilder` leaves the histogram empty when no
+            // statistic needs it.
+            scc: [
+                sum.saturating_sub(last),
+                sum.saturating_sub(first),
+                sxy,
+                sum_sq.saturating_sub(last * last),
+                sum_sq.saturating_sub(first * first),
+            ],
+        }
+    }
+
+    fn bit_counts(&self) -> [usize; 2] {
+        let ones = self
+            .counts
+            .iter()
+            .enumerate()
+            .map(|(v, &c)| c * (v as u8).count_ones() as usize)
+            .sum::<usize>();
+        [8 * self.total - ones, ones]
+    }
+
+    fn finalize(self) -> EntStats {
+        let (entropy, (chisquare, p_value)) = if self.bit_mode {
+            let bits = self.bit_counts();
+            (entropy_from_counts(&bits), chisquare_from_counts(&bits))
+        } else {
+            (
+                entropy_from_counts(&self.counts),
+                chisquare_from_counts(&self.counts),
+            )
+        };
+        let compression_percent = if self.bit_mode {
             100.0 * (1.0 - entropy)
         } else {
             100.0 * (1.0 - entropy / 8.0)
         };
-        let (chisquare, p_value) = calculate_chisquare(data, bit_mode);
-        let mean = calculate_mean(data);
-        let pi_estimate = estimate_pi(data);
-        let serial_correlation = serial_correlation(data);
-
-        let (byte_frequencies, bit_frequencies) = if bit_mode {
-            (None, Some(bit_occurrences(data)))
+        let pi_estimate = if self.pi_total > 0 {
+            4.0 * self.pi_hits as f64 / self.pi_total as f64
+        } else {
+            0.0
+        };
+        let serial_correlation =
+            serial_correlation_from_sums(self.total.saturating_sub(1), &self.scc);
+        let (byte_frequencies, bit_frequencies) = if self.bit_mode {
+            (None, Some(bit_frequencies_from_counts(&self.bit_counts())))
         } else {
-            (Some(byte_occurrences(data)), None)
+            (Some(byte_frequencies_from_counts(&self.counts)), None)
         };
 
         EntStats {
@@ -61,7 +1199,7 @@ impl EntStats {
             compression_percent,
             chisquare,
             p_value,
-            mean,
+            mean: self.sum as f64 / self.total as f64,
             pi_estimate,
             serial_correlation,
             byte_frequencies,
@@ -72,154 +1210,133 @@ impl EntStats {
 
 // Internal computation functions
 
+#[cfg(feature = "std")]
 fn calculate_entropy(data: &[u8], bit_mode: bool) -> f64 {
-    let mut freq = if bit_mode {
-        vec![0f64; 2]
-    } else {
-        vec![0f64; 256]
-    };
-
     if bit_mode {
-        for &b in data {
-            for i in 0..8 {
-                freq[(b >> i) as usize & 1] += 1.0;
-            }
-        }
-        let total = 8.0 * data.len() as f64;
-        for f in freq.iter_mut() {
-            *f /= total;
-        }
+        entropy_from_counts(&bit_counts(data))
     } else {
-        for &b in data {
-            freq[b as usize] += 1.0;
-        }
-        let total = data.len() as f64;
-        for f in freq.iter_mut() {
-            *f /= total;
-        }
+        entropy_from_counts(&byte_counts(data))
     }
+}
 
-    freq.iter()
-        .filter(|&&p| p > 0.0)
-        .map(|&p| -p * p.log2())
-        .sum()
+fn byte_counts(data: &[u8]) -> [usize; 256] {
+    simd::byte_histogram(data)
 }
 
-fn calculate_chisquare(data: &[u8], bit_mode: bool) -> (f64, f64) {
-    if bit_mode {
-        let mut count = [0usize; 2];
-        for &b in data {
-            for i in 0..8 {
-                count[(b >> i) as usize & 1] += 1;
-            }
-        }
-        let total = data.len() * 8;
-        let expected = total as f64 / 2.0;
-        let chisq = count
-            .iter()
-            .map(|&obs| {
-                let diff = obs as f64 - expected;
-                diff * diff / expected
-            })
-            .sum::<f64>();
-        let z = (chisq - 1.0).sqrt();
-        (chisq, 1.0 - 0.5 * erfc(-z / SQRT_2))
-    } else {
-        let mut count = [0usize; 256];
-        for &b in data {
-            count[b as usize] += 1;
-        }
-        let total = data.len();
-        let expected = total as f64 / 256.0;
-        let chisq = count
-            .iter()
-            .map(|&obs| {
-                let diff = obs as f64 - expected;
-                diff * diff / expected
-            })
-            .sum::<f64>();
-        let z = (chisq - 255.0).sqrt();
-        (chisq, 1.0 - 0.5 * erfc(-z / SQRT_2))
-    }
+#[cfg(any(feature = "std", test))]
+fn bit_counts(data: &[u8]) -> [usize; 2] {
+    let ones = simd::count_ones(data) as usize;
+    [8 * data.len() - ones, ones]
 }
 
-fn calculate_mean(data: &[u8]) -> f64 {
-    data.iter().map(|&b| b as f64).sum::<f64>() / data.len() as f64
+/// Shannon entropy in bits per symbol of a frequency table.
+fn entropy_from_counts(counts: &[usize]) -> f64 {
+    let total = counts.iter().sum::<usize>() as f64;
+    compensated_sum(
+        counts
+            .iter()
+            .map(|&c| c as f64 / total)
+            .filter(|&p| p > 0.0)
+            .map(|p| -p * p.log2()),
+    )
 }
 
-fn estimate_pi(data: &[u8]) -> f64 {
-    let mut hits = 0;
-    let mut total = 0;
-    let r_sq = 1u64 << 48;
+/// Chi-square statistic of a frequency table against a uniform distribution.
+fn chisquare_from_counts(counts: &[usize]) -> (f64, f64) {
+    let total = counts.iter().sum::<usize>();
+    let expected = total as f64 / counts.len() as f64;
+    let chisq = compensated_sum(counts.iter().map(|&obs| {
+        let diff = obs as f64 - expected;
+        diff * diff / expected
+    }));
+    let z = (chisq - (counts.len() - 1) as f64).sqrt();
+    (chisq, 1.0 - 0.5 * erfc(-z / SQRT_2))
+}
 
-    for chunk in data.chunks_exact(6) {
-        let x = ((chunk[0] as u64) << 16) | ((chunk[1] as u64) << 8) | chunk[2] as u64;
-        let y = ((chunk[3] as u64) << 16) | ((chunk[4] as u64) << 8) | chunk[5] as u64;
-        let dist_sq = x * x + y * y;
-        if dist_sq < r_sq {
-            hits += 1;
-        }
-        total += 1;
+/// Upper-tail probability of a chi-square statistic with `dof` degrees of freedom.
+fn chisquare_p_value(chisq: f64, dof: f64) -> f64 {
+    if dof <= 0.0 || chisq.is_nan() {
+        return f64::NAN;
     }
-
-    if total > 0 {
-        4.0 * hits as f64 / total as f64
-    } else {
-        0.0
+    if chisq <= 0.0 {
+        return 1.0;
     }
+    gamma_ur(dof / 2.0, chisq / 2.0)
 }
 
-fn serial_correlation(data: &[u8]) -> f64 {
-    if data.len() < 2 {
-        return -99999.0;
+/// `b` in lower case if it is an upper-case ISO 8859-1 letter: `A`–`Z`
+/// and `À`–`Þ` except `×`.
+fn fold_case(b: u8) -> u8 {
+    match b {
+        b'A'..=b'Z' | 0xC0..=0xD6 | 0xD8..=0xDE => b + 0x20,
+        _ => b,
     }
+}
 
-    let mut sum_x = 0f64;
-    let mut sum_y = 0f64;
-    let mut sum_xy = 0f64;
-    let mut sum_x2 = 0f64;
-    let mut sum_y2 = 0f64;
+/// Whether a 6-byte (x, y) coordinate pair falls inside the unit circle.
+fn monte_carlo_hit(chunk: &[u8]) -> bool {
+    let r_sq = 1u64 << 48;
+    let x = ((chunk[0] as u64) << 16) | ((chunk[1] as u64) << 8) | chunk[2] as u64;
+    let y = ((chunk[3] as u64) << 16) | ((chunk[4] as u64) << 8) | chunk[5] as u64;
+    x * x + y * y < r_sq
+}
 
-    for i in 1..data.len() {
-        let x = data[i - 1] as f64;
-        let y = data[i] as f64;
-        sum_x += x;
-        sum_y += y;
-        sum_xy += x * y;
-        sum_x2 += x * x;
-        sum_y2 += y * y;
+/// Add an adjacent pair to serial-correlation sums of x, y, xy, x², y².
+fn add_pair(sums: &mut [u64; 5], x: u8, y: u8) {
+    let (x, y) = (x as u64, y as u64);
+    for (sum, term) in sums.iter_mut().zip([x, y, x * y, x * x, y * y]) {
+        *sum += term;
     }
+}
 
-    let n = (data.len() - 1) as f64;
-    let num = n * sum_xy - sum_x * sum_y;
-    let denom = ((n * sum_x2 - sum_x.powi(2)) * (n * sum_y2 - sum_y.powi(2))).sqrt();
-
-    if denom == 0.0 {
+/// Serial correlation of `pairs` adjacent pairs from their exact sums, or
+/// the -99999 sentinel when undefined.
+///
+/// The numerator and variances are formed in integer arithmetic, so the
+/// cancellation between `n·Σxy` and `Σx·Σy` loses nothing however long the
+/// stream.
+fn serial_correlation_from_sums(pairs: usize, sums: &[u64; 5]) -> f64 {
+    if pairs == 0 {
+        return -99999.0;
+    }
+    let n = pairs as i128;
+    let [sx, sy, sxy, sx2, sy2] = sums.map(|v| v as i128);
+    let num = n * sxy - sx * sy;
+    let var_x = n * sx2 - sx * sx;
+    let var_y = n * sy2 - sy * sy;
+    if var_x == 0 || var_y == 0 {
         -99999.0
     } else {
-        num / denom
+        num as f64 / (var_x as f64 * var_y as f64).sqrt()
     }
 }
 
-fn byte_occurrences(data: &[u8]) -> Vec<(u8, usize, f64)> {
-    let mut counts = [0usize; 256];
-    for &b in data {
-        counts[b as usize] += 1;
+/// Neumaier compensated sum: the error stays at about one rounding
+/// regardless of the number of terms or their magnitudes.
+fn compensated_sum<I: IntoIterator<Item = f64>>(terms: I) -> f64 {
+    let mut sum = 0.0f64;
+    let mut compensation = 0.0f64;
+    for x in terms {
+        let t = sum + x;
+        if sum.abs() >= x.abs() {
+            compensation += (sum - t) + x;
+        } else {
+            compensation += (x - t) + sum;
+        }
+        sum = t;
     }
-    let total = data.len() as f64;
+    sum + compensation
+}
+
+fn byte_frequencies_from_counts(counts: &[usize; 256]) -> Vec<(u8, usize, f64)> {
+    let total = counts.iter().sum::<usize>() as f64;
     (0..=255)
         .map(|i| (i as u8, counts[i], counts[i] as f64 / total))
         .collect()
 }
 
-fn bit_occurrences(data: &[u8]) -> [(usize, f64); 2] {
-    let mut count = [0usize; 2];
-    for &b in data {
-        for i in 0..8 {
-            count[(b >> i) as usize & 1] += 1;
-        }
-    }
-    let total = (data.len() * 8) as f64;
+fn bit_frequencies_from_counts(count: &[usize; 2]) -> [(usize, f64); 2] {
+    let total = (count[0] + count[1]) as f64;
     [
         (count[0], count[0] as f64 / total),
         (count[1], count[1] as f64 / total),
@@ -309,6 +1426,48 @@ mod tests {
         assert!((freqs[1].1 - 0.5).abs() < 0.01);
     }
 
+    #[test]
+    fn test_bit_counts_match_per_bit_loop() {
+        let data: Vec<u8> = (0u32..1027)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+            .collect();
+        let mut expected = [0usize; 2];
+        for &b in &data {
+            for i in 0..8 {
+                expected[(b >> i) as usize & 1] += 1;
+            }
+        }
+        assert_eq!(bit_counts(&data), expected);
+        let stats = EntStats::from_data(&data, true);
+        assert_eq!(stats.bit_frequencies.unwrap()[1].0, expected[1]);
+    }
+
+    #[test]
+    fn test_from_iter_matches_from_data() {
+        let data: Vec<u8> = (0u32..10_000).map(|i| (i * 7919 % 251) as u8).collect();
+        for bit_mode in [false, true] {
+            let a = EntStats::from_data(&data, bit_mode);
+            let b = EntStats::from_iter(data.iter().copied(), bit_mode);
+            assert_eq!(a.entropy, b.entropy);
+            assert_eq!(a.chisquare, b.chisquare);
+            assert_eq!(a.mean, b.mean);
+            assert_eq!(a.pi_estimate, b.pi_estimate);
+            assert_eq!(a.serial_correlation, b.serial_correlation);
+            assert_eq!(a.byte_frequencies, b.byte_frequencies);
+            assert_eq!(a.bit_frequencies, b.bit_frequencies);
+        }
+    }
+
+    #[test]
+    fn test_try_from_iter_propagates_errors() {
+        use std::io::Read;
+        let stats = EntStats::try_from_iter(std::io::Cursor::new(vec![1u8, 2, 3]).bytes(), false);
+        assert!((stats.unwrap().mean - 2.0).abs() < 1e-12);
+
+        let items = vec![Ok(1u8), Err("broken"), Ok(2u8)];
+        assert_eq!(EntStats::try_from_iter(items, false).unwrap_err(), "broken");
+    }
+
     #[test]
     fn test_byte_frequency_distribution_length() {
         let data: Vec<u8> = (0..=255).cycle().take(4096).collect();
@@ -316,4 +1475,310 @@ mod tests {
         let freqs = stats.byte_frequencies.as_ref().unwrap();
         assert_eq!(freqs.len(), 256);
     }
+
+    #[test]
+    fn test_compensated_sum_on_cancelling_terms() {
+        assert_eq!(compensated_sum([1.0, 1e100, 1.0, -1e100]), 2.0);
+        let tenths = compensated_sum(std::iter::repeat_n(0.1, 1_000_000));
+        assert_eq!(tenths, 100_000.0);
+    }
+
+    #[test]
+    fn test_serial_correlation_of_long_stream() {
+        // 2^40 pairs of 255s with 1000 isolated 254s: summed in f64, the
+        // sums are inexact and the numerator cancels to exactly zero.
+        let pairs: u64 = 1 << 40;
+        let k = 1000;
+        let sx = 255 * pairs - k;
+        let sxy = 255 * 255 * (pairs - 2 * k) + 2 * k * 254 * 255;
+        let sx2 = 255 * 255 * (pairs - k) + 254 * 254 * k;
+        let r = serial_correlation_from_sums(pairs as usize, &[sx, sx, sxy, sx2, sx2]);
+        let expected = -9.094947026001089e-10;
+        assert!(((r - expected) / expected).abs() < 1e-9, "r = {r}");
+    }
+
+    #[test]
+    fn test_entropy_of_dominated_histogram() {
+        // One value seen 2^50 times, 255 others once each.
+        let mut counts = [1usize; 256];
+        counts[0] = 1 << 50;
+        let n = (1u64 << 50) as f64 + 255.0;
+        let expected =
+            (255.0 / (1u64 << 50) as f64).ln_1p() / std::f64::consts::LN_2 + 50.0 * 255.0 / n;
+        let entropy = entropy_from_counts(&counts);
+        assert!(((entropy - expected) / expected).abs() < 1e-12);
+    }
+
+    fn assert_stats_eq(a: &EntStats, b: &EntStats) {
+        assert_eq!(a.entropy, b.entropy);
+        assert_eq!(a.chisquare, b.chisquare);
+        assert_eq!(a.mean, b.mean);
+        assert_eq!(a.pi_estimate, b.pi_estimate);
+        assert_eq!(a.serial_correlation, b.serial_correlation);
+        assert_eq!(a.byte_frequencies, b.byte_frequencies);
+        assert_eq!(a.bit_frequencies, b.bit_frequencies);
+    }
+
+    #[test]
+    fn test_chunked_updates_match_whole_buffer() {
+        let data: Vec<u8> = (0u32..5000)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+            .collect();
+        for chunk_len in [1, 2, 5, 6, 7, 13, 4999] {
+            let mut acc = EntAccumulator::new(false);
+            for chunk in data.chunks(chunk_len) {
+                acc.update(chunk);
+            }
+            assert_stats_eq(&acc.finalize(), &EntStats::from_data(&data, false));
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_accumulator_as_writer() {
+        let data: Vec<u8> = (0u32..10_000)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+            .collect();
+        let mut acc = EntAccumulator::new(true);
+        io::copy(&mut &data[..4321], &mut acc).unwrap();
+        assert_stats_eq(&acc.stats(), &EntStats::from_data(&data[..4321], true));
+        io::copy(&mut &data[4321..], &mut acc).unwrap();
+        assert!(acc.bit_mode());
+        assert_stats_eq(&acc.finalize(), &EntStats::from_data(&data, true));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_accumulator_serde_round_trip() {
+        let data: Vec<u8> = (0u32..2000)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+            .collect();
+        // Shards analyzed "remotely" and shipped as JSON.
+        let mut combined = EntAccumulator::new(false);
+        for (i, shard) in data.chunks(777).enumerate() {
+            let mut acc = EntAccumulator::at_offset(false, i as u64 * 777);
+            acc.update(shard);
+            let json = serde_json::to_string(&acc).unwrap();
+            combined.merge(&serde_json::from_str(&json).unwrap());
+        }
+        assert_stats_eq(&combined.finalize(), &EntStats::from_data(&data, false));
+
+        let truncated = r#"{"bit_mode":false,"counts":[1],"total":1,"sum":0,
+            "phase":0,"pi_hits":0,"head":[0,0,0,0,0],"tail":[0,0,0,0,0],"scc":[0,0,0,0,0]}"#;
+        let err = serde_json::from_str::<EntAccumulator>(truncated).unwrap_err();
+        assert!(err.to_string().contains("expected 256 counts"));
+    }
+
+    #[test]
+    fn test_single_pass_matches_streaming_on_short_inputs() {
+        let data = [200u8, 3, 77, 255, 0, 9, 18, 140, 33, 250, 1, 64, 128];
+        for len in 1..=data.len() {
+            let mut acc = EntAccumulator::new(false);
+            acc.update(&data[..len]);
+            assert_stats_eq(&EntStats::from_data(&data[..len], false), &acc.finalize());
+        }
+    }
+
+    #[test]
+    fn test_builder_subsets_match_full_analysis() {
+        let data: Vec<u8> = (0u32..5000)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+            .collect();
+        for bit_mode in [false, true] {
+            let full = EntStats::from_data(&data, bit_mode);
+            let builder = EntStats::builder().bit_mode(bit_mode);
+            assert_stats_eq(&builder.analyze(&data), &full);
+
+            let no_pi = builder.without_pi().analyze(&data);
+            assert!(no_pi.pi_estimate.is_nan());
+            assert_eq!(no_pi.serial_correlation, full.serial_correlation);
+            assert_eq!(no_pi.chisquare, full.chisquare);
+            assert_eq!(no_pi.bit_frequencies, full.bit_frequencies);
+
+            let pi_only = EntStatsBuilder::none()
+                .bit_mode(bit_mode)
+                .with_pi()
+                .analyze(&data);
+            assert_eq!(pi_only.pi_estimate, full.pi_estimate);
+            assert!(pi_only.entropy.is_nan() && pi_only.mean.is_nan());
+            assert!(pi_only.byte_frequencies.is_none() && pi_only.bit_frequencies.is_none());
+        }
+    }
+
+    #[test]
+    fn test_from_strided_matches_deinterleaved() {
+        let data: Vec<u8> = (0u32..10_001)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+            .collect();
+        for (offset, stride) in [(0, 1), (0, 3), (2, 3), (1, 4), (5, 7)] {
+            let channel: Vec<u8> = data.iter().skip(offset).step_by(stride).copied().collect();
+            for bit_mode in [false, true] {
+                assert_stats_eq(
+                    &EntStats::from_strided(&data, offset, stride, bit_mode),
+                    &EntStats::from_data(&channel, bit_mode),
+                );
+            }
+        }
+        let past_end = EntStats::from_strided(&data, data.len(), 2, false);
+        assert_eq!(past_end.sample_count(), Some(0));
+    }
+
+    #[test]
+    fn test_builder_filters_bytes() {
+        let data: Vec<u8> = (0u32..4096)
+            .flat_map(|i| [(i.wrapping_mul(2_654_435_761) >> 24) as u8 | 0x80, 0, 0])
+            .collect();
+        let filter = ByteFilter::new().exclude(0).mask(0x7F);
+        let kept = filter.filter(&data);
+        assert_eq!(kept.len(), 4096);
+        assert!(kept.iter().all(|&b| b < 0x80));
+        let stats = EntStats::builder().filter(filter).analyze(&data);
+        assert_stats_eq(&stats, &EntStats::from_data(&kept, false));
+
+        // Exclusion sees the byte before masking, and folding comes first.
+        let filter = ByteFilter::new().exclude(b'a').mask(0x0F);
+        assert_eq!(filter.apply(b'a'), None);
+        assert_eq!(filter.apply(b'q'), Some(1));
+        let stats = EntStats::builder()
+            .fold_case(true)
+            .filter(filter)
+            .analyze(b"AaBb");
+        assert_stats_eq(&stats, &EntStats::from_data(&[2, 2], false));
+
+        assert!(ByteFilter::new().is_identity());
+        assert!(!ByteFilter::new().mask(0xFE).is_identity());
+        let whitespace = ByteFilter::new().exclude_whitespace();
+        assert!(whitespace.excludes(b'\t') && !whitespace.excludes(0x0B));
+    }
+
+    #[test]
+    fn test_builder_folds_case() {
+        let stats = EntStats::builder()
+            .fold_case(true)
+            .analyze(b"Hello, WORLD! \xC0\xC9\xD7\xDE\xDF");
+        let expected = EntStats::from_data(b"hello, world! \xE0\xE9\xD7\xFE\xDF", false);
+        assert_stats_eq(&stats, &expected);
+        let latin1 = [b'A', 0xC0, 0xD7, 0xDE, 0xDF, b'z'];
+        assert_eq!(latin1.map(fold_case), [b'a', 0xE0, 0xD7, 0xFE, 0xDF, b'z']);
+    }
+
+    #[test]
+    fn test_try_from_data_rejects_short_input() {
+        assert_eq!(
+            EntStats::try_from_data(&[], true).unwrap_err(),
+            EntError::Empty
+        );
+        let err = EntStats::try_from_data(&[1, 2, 3, 4, 5], false).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Monte Carlo Pi estimate needs at least 6 bytes, got 5"
+        );
+        let stats = EntStats::try_from_data(&[1, 2, 3, 4, 5, 6], false).unwrap();
+        assert_ne!(stats.serial_correlation, -99999.0);
+    }
+
+    #[test]
+    fn test_display_matches_ent_layout() {
+        let data: Vec<u8> = (0..=255u8).cycle().take(1024).collect();
+        let expected = "\
+Entropy = 8.000000 bits per byte.
+
+Optimum compression would reduce the size
+of this 1024 byte file by 0 percent.
+
+Chi square distribution for 1024 samples is 0.00, and randomly
+would exceed this value more than than 99.99 percent of the times.
+
+Arithmetic mean value of data bytes is 127.5000 (127.5 = random).
+Monte Carlo value for Pi is 2.847058824 (error 9.38 percent).
+Serial correlation coefficient is 0.982422 (totally uncorrelated = 0.0).";
+        assert_eq!(EntStats::from_data(&data, false).to_string(), expected);
+
+        let bits = EntStats::from_data(&[0x0F; 100], true).to_string();
+        assert!(bits.contains("of this 800 bit file by 0 percent."));
+        assert!(bits.contains("Arithmetic mean value of data bits is 0.5000 (0.5 = random)."));
+    }
+
+    #[test]
+    fn test_terse_csv_matches_ent() {
+        let stats = EntStats::from_data(b"aaaaaabbbbbb", false);
+        let csv = stats.to_terse_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.iter().filter(|l| l.starts_with("3,")).count(), 256);
+        assert_eq!(lines[0], "2,Value,Occurrences,Fraction");
+        assert_eq!(lines[1], "3,0,0,0.000000");
+        assert_eq!(lines[98..100], ["3,97,6,0.500000", "3,98,6,0.500000"]);
+        assert_eq!(lines[256], "3,255,0,0.000000");
+        assert_eq!(
+            lines[257],
+            "0,File-bytes,Entropy,Chi-square,Mean,Monte-Carlo-Pi,Serial-Correlation"
+        );
+        assert!(lines[258].starts_with("1,12,1.000000,"));
+        assert!(csv.ends_with('\n'));
+
+        let bits = EntStats::from_data(&[0xFF; 4], true).to_terse_csv();
+        assert!(bits.starts_with(
+            "2,Value,Occurrences,Fraction\n3,0,0,0.000000\n3,1,32,1.000000\n0,File-bits,"
+        ));
+        assert!(bits.contains("\n1,32,0.000000,32.000000,1.000000,"));
+
+        let bare = EntStats::builder()
+            .without_frequencies()
+            .analyze(b"abcdefgh");
+        assert!(bare.to_terse_csv().starts_with("0,File-bytes,"));
+    }
+
+    #[test]
+    fn test_merges_match_whole_buffer() {
+        let data: Vec<u8> = (0u32..3000)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+            .collect();
+        for bit_mode in [false, true] {
+            let expected = EntStats::from_data(&data, bit_mode);
+            // Shards of every length modulo 6, including empty and tiny ones.
+            let mut cuts = vec![0, 0, 1, 3, 4, 11, 17, 18, 100, 1001, 1003, 2000, 2999];
+            cuts.push(data.len());
+            let shards: Vec<EntAccumulator> = cuts
+                .windows(2)
+                .map(|w| {
+                    let mut acc = EntAccumulator::at_offset(bit_mode, w[0] as u64);
+                    acc.update(&data[w[0]..w[1]]);
+                    acc
+                })
+                .collect();
+
+            let mut left_to_right = EntAccumulator::new(bit_mode);
+            for shard in &shards {
+                left_to_right.merge(shard);
+            }
+            assert_eq!(left_to_right.len(), data.len());
+            assert_stats_eq(&left_to_right.finalize(), &expected);
+
+            // Merging is associative: combine pairs, then pairs of pairs.
+            let mut level = shards;
+            while level.len() > 1 {
+                level = level
+                    .chunks(2)
+                    .map(|pair| {
+                        let mut acc = pair[0].clone();
+                        if let Some(right) = pair.get(1) {
+                            acc.merge(right);
+                        }
+                        acc
+                    })
+                    .collect();
+            }
+            assert_stats_eq(&level.pop().unwrap().finalize(), &expected);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "does not start where this one ends")]
+    fn test_merge_rejects_misaligned_shard() {
+        let mut left = EntAccumulator::new(false);
+        left.update(&[1; 7]);
+        let mut right = EntAccumulator::new(false);
+        right.update(&[2; 7]);
+        left.merge(&right);
+    }
 }
@@ -0,0 +1,932 @@
+//! Command-line front end for `ent-rs`.
+//!
+//! Reads a file, block device, character device, or standard input
+//! sequentially and prints its statistics, watches a source indefinitely
+//! with the `monitor` subcommand, or tabulates every file under a directory
+//! with the `scan` subcommand.
+
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use ent_rs::digest::{DigestReader, InputDigest, InputHasher};
+use ent_rs::file::FileOptions;
+use ent_rs::fixed::FixedStats;
+use ent_rs::monitor::{Monitor, Preset, Thresholds, Violation};
+use ent_rs::text::{fold_case, CharTable};
+use ent_rs::{EntAccumulator, EntStats};
+
+const USAGE: &str = "\
+Usage: ent-rs [OPTIONS] [FILE]
+       ent-rs monitor [MONITOR OPTIONS] SOURCE
+       ent-rs scan [SCAN OPTIONS] DIR
+
+Analyze the entropy and randomness of FILE (a regular file, block device, or
+character device). With no FILE, or when FILE is -, read standard input.
+
+Options:
+  -b, --bits           Analyze as a stream of bits instead of bytes
+  -c, --chars          Print occurrences of each printable character first,
+                       like `ent -c` (terse output always includes them)
+  -f, --fold           Fold upper-case letters to lower case first, like
+                       `ent -f`
+      --deterministic  Use integer arithmetic for bit-identical results on every
+                       platform (values truncated, no p-value)
+      --offset SIZE    Skip SIZE bytes before analyzing
+      --length SIZE    Analyze at most SIZE bytes
+  -t, --terse          Print comma-separated values like `ent -t -c`
+      --follow         Keep reading as FILE grows, like `tail -f`, and print
+                       the statistics again whenever new data has arrived
+      --interval SECS  Seconds between reports; implies --follow [default: 5]
+  -h, --help           Print this help
+
+Monitor options:
+      --window SIZE            Bytes per statistics window [default: 64K]
+      --preset NAME            Thresholds for a kind of content: ciphertext,
+                               compressed-archive, executable-code, plain-text,
+                               sensor-noise, or auto to pick from the file
+                               signature (needs the magic feature)
+                               [default: ciphertext]
+      --interval SECS          Seconds between summaries [default: 60]
+      --min-entropy BITS       Minimum entropy per byte [default: 7.9]
+      --max-correlation R      Maximum absolute serial correlation [default: 0.05]
+      --claimed-entropy BITS   Min-entropy per byte for health-test cutoffs [default: 7]
+      --hook CMD               Run CMD via `sh -c` on a violation instead of exiting
+
+The monitor reads SOURCE (a device, FIFO, growing file, or - for standard input)
+until it ends, printing a summary every interval. When a threshold is crossed
+or a health test fails it exits with status 3, or runs the hook with the
+violations in $ENT_RS_VIOLATIONS and the stream offset in $ENT_RS_OFFSET.
+
+Scan options:
+  -b, --bits                   Analyze as a stream of bits instead of bytes
+      --include GLOB           Only analyze files matching GLOB (repeatable)
+      --exclude GLOB           Skip files and directories matching GLOB (repeatable)
+      --min-entropy BITS       Only list files with at least this entropy
+
+The scan walks DIR recursively, without following symbolic links, and prints
+one line per file. A GLOB containing / matches the path relative to DIR,
+otherwise the file or directory name; * and ? do not match /, and ** matches
+any number of directories.
+
+SIZE accepts the suffixes K, M, G and T (powers of 1024), e.g. --length 1G.";
+
+/// Read buffer size used for sequential device reads.
+const READ_BUFFER: usize = 1 << 20;
+
+/// Bytes read from a monitored source per iteration.
+const MONITOR_CHUNK: usize = 1 << 16;
+
+/// Default time between reports with `--follow`.
+const FOLLOW_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Exit status of the monitor when a threshold is violated.
+const VIOLATION_EXIT: u8 = 3;
+
+#[derive(Debug, Default, PartialEq)]
+struct Options {
+    bit_mode: bool,
+    chars: bool,
+    fold: bool,
+    deterministic: bool,
+    terse: bool,
+    follow: Option<Duration>,
+    offset: u64,
+    length: Option<u64>,
+    path: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+struct MonitorOptions {
+    window: usize,
+    interval: Duration,
+    thresholds: Thresholds,
+    detect_preset: bool,
+    overrides: Overrides,
+    claimed_entropy: f64,
+    hook: Option<String>,
+    source: String,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct ScanOptions {
+    bit_mode: bool,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    min_entropy: Option<f64>,
+    root: PathBuf,
+}
+
+/// Thresholds given individually, which take precedence over `--preset`.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Overrides {
+    min_entropy: Option<f64>,
+    max_serial_correlation: Option<f64>,
+}
+
+impl Overrides {
+    fn apply(&self, mut thresholds: Thresholds) -> Thresholds {
+        if let Some(v) = self.min_entropy {
+            thresholds.min_entropy = v;
+        }
+        if let Some(v) = self.max_serial_correlation {
+            thresholds.max_serial_correlation = v;
+        }
+        thresholds
+    }
+}
+
+fn main() -> ExitCode {
+    // Spans and events of the library as `RUST_LOG` selects, errors only
+    // without it.
+    #[cfg(feature = "tracing")]
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+    let mut args = std::env::args().skip(1).peekable();
+    if args.peek().map(String::as_str) == Some("monitor") {
+        args.next();
+        return match parse_monitor_args(args) {
+            Ok(Some(opts)) => monitor(&opts),
+            Ok(None) => {
+                println!("{USAGE}");
+                ExitCode::SUCCESS
+            }
+            Err(msg) => {
+                eprintln!("ent-rs monitor: {msg}\n\n{USAGE}");
+                ExitCode::from(2)
+            }
+        };
+    }
+    if args.peek().map(String::as_str) == Some("scan") {
+        args.next();
+        return match parse_scan_args(args) {
+            Ok(Some(opts)) => scan(&opts),
+            Ok(None) => {
+                println!("{USAGE}");
+                ExitCode::SUCCESS
+            }
+            Err(msg) => {
+                eprintln!("ent-rs scan: {msg}\n\n{USAGE}");
+                ExitCode::from(2)
+            }
+        };
+    }
+
+    let opts = match parse_args(args) {
+        Ok(Some(opts)) => opts,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(msg) => {
+            eprintln!("ent-rs: {msg}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    if let Some(interval) = opts.follow {
+        return follow(&opts, interval);
+    }
+    match analyze(&opts) {
+        Ok((Analysis::Float(stats), _)) if opts.terse => {
+            print!("{}", stats.to_terse_csv());
+            ExitCode::SUCCESS
+        }
+        Ok((Analysis::Float(stats), input)) => {
+            if opts.chars {
+                if let Some(table) = CharTable::from_stats(&stats) {
+                    println!("{table}\n");
+                }
+            }
+            print_stats(&stats, &input);
+            ExitCode::SUCCESS
+        }
+        Ok((Analysis::Fixed(stats), input)) => {
+            print_fixed_stats(&stats, &input);
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            let name = opts.path.as_deref().unwrap_or("-");
+            eprintln!("ent-rs: {name}: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Parse command-line arguments; `Ok(None)` means help was requested.
+fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Option<Options>, String> {
+    let mut opts = Options::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-b" | "--bits" => opts.bit_mode = true,
+            "-c" | "--chars" => opts.chars = true,
+            "-f" | "--fold" => opts.fold = true,
+            "--deterministic" => opts.deterministic = true,
+            "-t" | "--terse" => opts.terse = true,
+            "--follow" => {
+                opts.follow.get_or_insert(FOLLOW_INTERVAL);
+            }
+            "--interval" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("{arg} requires a value"))?;
+                let secs = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|v| v.is_finite() && *v > 0.0)
+                    .ok_or_else(|| format!("invalid value '{value}' for {arg}"))?;
+                opts.follow = Some(Duration::from_secs_f64(secs));
+            }
+            "--offset" | "--length" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("{arg} requires a value"))?;
+                let size = parse_size(&value)?;
+                if arg == "--offset" {
+                    opts.offset = size;
+                } else {
+                    opts.length = Some(size);
+                }
+            }
+            _ if arg.starts_with('-') && arg != "-" => {
+                return Err(format!("unknown option '{arg}'"));
+            }
+            _ if opts.path.is_some() => return Err(format!("unexpected argument '{arg}'")),
+            _ => opts.path = Some(arg),
+        }
+    }
+    if opts.terse && opts.deterministic {
+        return Err("--terse cannot be combined with --deterministic".into());
+    }
+    if opts.follow.is_some() && opts.deterministic {
+        return Err("--follow cannot be combined with --deterministic".into());
+    }
+    for (set, name) in [
+        (opts.bit_mode, "--bits"),
+        (opts.deterministic, "--deterministic"),
+        (opts.follow.is_some(), "--follow"),
+    ] {
+        if opts.chars && set {
+            return Err(format!("--chars cannot be combined with {name}"));
+        }
+    }
+    Ok(Some(opts))
+}
+
+/// Parse `monitor` arguments; `Ok(None)` means help was requested.
+fn parse_monitor_args<I: IntoIterator<Item = String>>(
+    args: I,
+) -> Result<Option<MonitorOptions>, String> {
+    let mut window = 1 << 16;
+    let mut interval = Duration::from_secs(60);
+    let mut preset = Preset::Ciphertext;
+    let mut detect_preset = false;
+    let mut overrides = Overrides::default();
+    let mut claimed_entropy = 7.0;
+    let mut hook = None;
+    let mut source = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            return Ok(None);
+        }
+        if arg == "-" || !arg.starts_with('-') {
+            if source.is_some() {
+                return Err(format!("unexpected argument '{arg}'"));
+            }
+            source = Some(arg);
+            continue;
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("{arg} requires a value"))?;
+        let number = || {
+            value
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite() && *v >= 0.0)
+                .ok_or_else(|| format!("invalid value '{value}' for {arg}"))
+        };
+        match arg.as_str() {
+            "--window" => {
+                window = usize::try_from(parse_size(&value)?)
+                    .ok()
+                    .filter(|&w| w > 1)
+                    .ok_or_else(|| format!("invalid window '{value}'"))?;
+            }
+            "--interval" => {
+                let secs = number()?;
+                if secs == 0.0 {
+                    return Err(format!("invalid value '{value}' for {arg}"));
+                }
+                interval = Duration::from_secs_f64(secs);
+            }
+            "--preset" if value == "auto" => {
+                if !cfg!(feature = "magic") {
+                    return Err("--preset auto requires the magic feature".into());
+                }
+                detect_preset = true;
+            }
+            "--preset" => preset = value.parse()?,
+            "--min-entropy" => overrides.min_entropy = Some(number()?),
+            "--max-correlation" => overrides.max_serial_correlation = Some(number()?),
+            "--claimed-entropy" => claimed_entropy = number()?,
+            "--hook" => hook = Some(value),
+            _ => return Err(format!("unknown option '{arg}'")),
+        }
+    }
+    let source = source.ok_or("missing SOURCE")?;
+    Ok(Some(MonitorOptions {
+        window,
+        interval,
+        thresholds: overrides.apply(preset.thresholds()),
+        detect_preset,
+        overrides,
+        claimed_entropy,
+        hook,
+        source,
+    }))
+}
+
+/// Parse `scan` arguments; `Ok(None)` means help was requested.
+fn parse_scan_args<I: IntoIterator<Item = String>>(args: I) -> Result<Option<ScanOptions>, String> {
+    let mut opts = ScanOptions::default();
+    let mut root = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-b" | "--bits" => opts.bit_mode = true,
+            "--include" | "--exclude" | "--min-entropy" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("{arg} requires a value"))?;
+                match arg.as_str() {
+                    "--include" => opts.include.push(value),
+                    "--exclude" => opts.exclude.push(value),
+                    _ => {
+                        let bits = value
+                            .parse::<f64>()
+                            .ok()
+                            .filter(|v| v.is_finite() && *v >= 0.0)
+                            .ok_or_else(|| format!("invalid value '{value}' for {arg}"))?;
+                        opts.min_entropy = Some(bits);
+                    }
+                }
+            }
+            _ if arg.starts_with('-') => return Err(format!("unknown option '{arg}'")),
+            _ if root.is_some() => return Err(format!("unexpected argument '{arg}'")),
+            _ => root = Some(PathBuf::from(arg)),
+        }
+    }
+    opts.root = root.ok_or("missing DIR")?;
+    Ok(Some(opts))
+}
+
+/// Parse a byte count with an optional K/M/G/T suffix.
+fn parse_size(s: &str) -> Result<u64, String> {
+    let upper = s.trim().to_ascii_uppercase();
+    let digits = upper.trim_end_matches(['B', 'I']);
+    let (digits, shift) = match digits.chars().last() {
+        Some('K') => (&digits[..digits.len() - 1], 10),
+        Some('M') => (&digits[..digits.len() - 1], 20),
+        Some('G') => (&digits[..digits.len() - 1], 30),
+        Some('T') => (&digits[..digits.len() - 1], 40),
+        _ => (digits, 0),
+    };
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(1u64 << shift))
+        .ok_or_else(|| format!("invalid size '{s}'"))
+}
+
+/// Statistics in the representation selected by `--deterministic`.
+enum Analysis {
+    Float(EntStats),
+    Fixed(FixedStats),
+}
+
+/// The input positioned at `--offset`, and whether it is a regular file.
+fn open_input(opts: &Options) -> io::Result<(Box<dyn Read>, bool)> {
+    Ok(match opts.path.as_deref() {
+        None | Some("-") => (
+            Box::new(skip_bytes(io::stdin().lock(), opts.offset)?),
+            false,
+        ),
+        Some(path) => {
+            let mut file = File::open(path)?;
+            let is_file = file.metadata()?.is_file();
+            // Character devices and pipes cannot seek; fall back to reading past the offset.
+            if opts.offset > 0 && file.seek(SeekFrom::Start(opts.offset)).is_err() {
+                (Box::new(skip_bytes(file, opts.offset)?), is_file)
+            } else {
+                (Box::new(file), is_file)
+            }
+        }
+    })
+}
+
+fn analyze(opts: &Options) -> io::Result<(Analysis, InputDigest)> {
+    let (input, _) = open_input(opts)?;
+    let limit = opts.length.unwrap_or(u64::MAX);
+    let mut reader = DigestReader::new(input.take(limit));
+    let analysis = if opts.deterministic {
+        // The integer sums take one byte at a time in any case.
+        let fold = opts.fold;
+        let bytes = BufReader::with_capacity(READ_BUFFER, &mut reader)
+            .bytes()
+            .map(|b| b.map(|b| if fold { fold_case(b) } else { b }));
+        Analysis::Fixed(FixedStats::try_from_iter(bytes, opts.bit_mode)?)
+    } else {
+        let mut acc = EntAccumulator::new(opts.bit_mode);
+        let mut buf = vec![0u8; READ_BUFFER];
+        loop {
+            let n = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            if opts.fold {
+                buf[..n].iter_mut().for_each(|b| *b = fold_case(*b));
+            }
+            acc.update(&buf[..n]);
+        }
+        Analysis::Float(acc.finalize())
+    };
+    Ok((analysis, reader.finish(opts.bit_mode)))
+}
+
+fn skip_bytes<R: Read>(mut reader: R, count: u64) -> io::Result<R> {
+    io::copy(&mut (&mut reader).take(count), &mut io::sink())?;
+    Ok(reader)
+}
+
+/// Analyze the input as it grows, reporting every `interval` if new data
+/// has arrived, until `--length` bytes have been read or a non-file input
+/// ends; regular files are followed until the process is killed.
+fn follow(opts: &Options, interval: Duration) -> ExitCode {
+    let name = opts.path.as_deref().unwrap_or("-");
+    let (mut input, grows) = match open_input(opts) {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("ent-rs: {name}: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut acc = EntAccumulator::new(opts.bit_mode);
+    let mut hasher = InputHasher::new();
+    let mut remaining = opts.length.unwrap_or(u64::MAX);
+    let mut buf = vec![0u8; READ_BUFFER];
+    let mut next_report = Instant::now() + interval;
+    let mut reported = None;
+    let report = |acc: &EntAccumulator, hasher: &InputHasher, reported: &mut Option<usize>| {
+        if *reported == Some(acc.len()) {
+            return;
+        }
+        if reported.is_some() && !opts.terse {
+            println!();
+        }
+        let stats = acc.stats();
+        if opts.terse {
+            print!("{}", stats.to_terse_csv());
+        } else {
+            print_stats(&stats, &hasher.clone().finish(opts.bit_mode));
+        }
+        *reported = Some(acc.len());
+    };
+    while remaining > 0 {
+        let want = buf
+            .len()
+            .min(usize::try_from(remaining).unwrap_or(usize::MAX));
+        match input.read(&mut buf[..want]) {
+            Ok(0) if grows => thread::sleep(interval.min(Duration::from_secs(1))),
+            Ok(0) => break,
+            Ok(n) => {
+                hasher.update(&buf[..n]);
+                if opts.fold {
+                    buf[..n].iter_mut().for_each(|b| *b = fold_case(*b));
+                }
+                acc.update(&buf[..n]);
+                remaining -= n as u64;
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => {
+                eprintln!("ent-rs: {name}: {err}");
+                return ExitCode::FAILURE;
+            }
+        }
+        if Instant::now() >= next_report {
+            report(&acc, &hasher, &mut reported);
+            next_report = Instant::now() + interval;
+        }
+    }
+    report(&acc, &hasher, &mut reported);
+    ExitCode::SUCCESS
+}
+
+/// Watch a source until it ends, a violation occurs without a hook, or the
+/// process is killed.
+///
+/// Regular files are followed like `tail -f`; standard input, FIFOs and
+/// devices end the monitor at end of file.
+fn monitor(opts: &MonitorOptions) -> ExitCode {
+    let (mut input, follow): (Box<dyn Read>, bool) = match opts.source.as_str() {
+        "-" => (Box::new(io::stdin().lock()), false),
+        path => match File::open(path).and_then(|f| Ok((f.metadata()?.is_file(), f))) {
+            Ok((is_file, file)) => (Box::new(file), is_file),
+            Err(err) => {
+                eprintln!("ent-rs: {path}: {err}");
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+
+    let mut monitor = Monitor::new(opts.window, opts.thresholds, opts.claimed_entropy);
+    let mut buf = vec![0u8; MONITOR_CHUNK];
+    let start = Instant::now();
+    let mut next_summary = start + opts.interval;
+    let mut alarmed = false;
+    loop {
+        let n = match input.read(&mut buf) {
+            Ok(0) if follow => {
+                thread::sleep(Duration::from_secs(1));
+                0
+            }
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => {
+                eprintln!("ent-rs: {}: {err}", opts.source);
+                return ExitCode::FAILURE;
+            }
+        };
+        #[cfg(feature = "magic")]
+        if opts.detect_preset && monitor.bytes_seen() == 0 && n > 0 {
+            if let Some(preset) = Preset::detect(&buf[..n]) {
+                eprintln!("ent-rs: {}: using {preset} thresholds", opts.source);
+                monitor.set_thresholds(opts.overrides.apply(preset.thresholds()));
+            }
+        }
+        monitor.push(&buf[..n]);
+
+        let violations = monitor.check();
+        if !violations.is_empty() && !alarmed {
+            let offset = monitor.bytes_seen();
+            for v in &violations {
+                eprintln!("ent-rs: {}: offset {offset}: {v}", opts.source);
+            }
+            let Some(hook) = &opts.hook else {
+                return ExitCode::from(VIOLATION_EXIT);
+            };
+            run_hook(hook, offset, &violations);
+        } else if violations.is_empty() && alarmed {
+            eprintln!(
+                "ent-rs: {}: offset {}: recovered",
+                opts.source,
+                monitor.bytes_seen()
+            );
+        }
+        alarmed = !violations.is_empty();
+
+        if Instant::now() >= next_summary {
+            print_summary(&monitor, start.elapsed());
+            next_summary += opts.interval;
+        }
+    }
+    print_summary(&monitor, start.elapsed());
+    ExitCode::SUCCESS
+}
+
+/// Analyze every file under the scan root and print one line per file.
+///
+/// Unreadable files and directories are reported and skipped; the exit
+/// status is then a failure.
+fn scan(opts: &ScanOptions) -> ExitCode {
+    let mut files = Vec::new();
+    let mut ok = walk(&opts.root, "", opts, &mut files);
+    let file_options = FileOptions {
+        bit_mode: opts.bit_mode,
+        ..FileOptions::default()
+    };
+    println!(
+        "{:>9}  {:>12}  {:>7}  {:>12}  Path",
+        "Entropy", "Chi-square", "p-value", "Size"
+    );
+    for (path, relative) in files {
+        let stats = match EntStats::from_file(&path, &file_options) {
+            Ok(stats) => stats,
+            Err(err) => {
+                eprintln!("ent-rs: {}: {err}", path.display());
+                ok = false;
+                continue;
+            }
+        };
+        if opts.min_entropy.is_some_and(|min| stats.entropy < min) {
+            continue;
+        }
+        let size = stats.sample_count().unwrap_or(0) / if opts.bit_mode { 8 } else { 1 };
+        println!(
+            "{:>9.6}  {:>12.2}  {:>7.4}  {size:>12}  {relative}",
+            stats.entropy, stats.chisquare, stats.p_value
+        );
+    }
+    if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Collect the files under `dir` that pass the scan filters, in name order,
+/// with their paths relative to the root; `false` if anything was unreadable.
+fn walk(dir: &Path, prefix: &str, opts: &ScanOptions, files: &mut Vec<(PathBuf, String)>) -> bool {
+    let entries = match std::fs::read_dir(dir).and_then(|it| it.collect::<io::Result<Vec<_>>>()) {
+        Ok(entries) => entries,
+        Err(err) => {
+            eprintln!("ent-rs: {}: {err}", dir.display());
+            return false;
+        }
+    };
+    let mut entries: Vec<_> = entries
+        .into_iter()
+        .map(|e| (e.file_name().to_string_lossy().into_owned(), e))
+        .collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut ok = true;
+    for (name, entry) in entries {
+        let relative = format!("{prefix}{name}");
+        if opts.exclude.iter().any(|g| glob_matches(g, &relative)) {
+            continue;
+        }
+        // DirEntry::file_type does not follow symbolic links.
+        match entry.file_type() {
+            Ok(t) if t.is_dir() => ok &= walk(&entry.path(), &format!("{relative}/"), opts, files),
+            Ok(t) if t.is_file() => {
+                if opts.include.is_empty()
+                    || opts.include.iter().any(|g| glob_matches(g, &relative))
+                {
+                    files.push((entry.path(), relative));
+                }
+            }
+            Ok(_) => {}
+            Err(err) => {
+                eprintln!("ent-rs: {}: {err}", entry.path().display());
+                ok = false;
+            }
+        }
+    }
+    ok
+}
+
+/// Whether `glob` matches the `/`-separated `relative` path, or only its
+/// last component if `glob` has no `/`.
+fn glob_matches(glob: &str, relative: &str) -> bool {
+    let text = if glob.contains('/') {
+        relative
+    } else {
+        relative.rsplit('/').next().unwrap_or(relative)
+    };
+    glob_match(glob.as_bytes(), text.as_bytes())
+}
+
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', b'/', rest @ ..] => {
+            // Zero or more whole directories.
+            glob_match(rest, text)
+                || (0..text.len()).any(|i| text[i] == b'/' && glob_match(rest, &text[i + 1..]))
+        }
+        [b'*', b'*', rest @ ..] => (0..=text.len()).any(|i| glob_match(rest, &text[i..])),
+        [b'*', rest @ ..] => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != b'/')
+            .any(|i| glob_match(rest, &text[i..])),
+        [b'?', rest @ ..] => {
+            matches!(text, [c, tail @ ..] if *c != b'/' && glob_match(rest, tail))
+        }
+        [p, rest @ ..] => matches!(text, [c, tail @ ..] if c == p && glob_match(rest, tail)),
+    }
+}
+
+fn run_hook(hook: &str, offset: u64, violations: &[Violation]) {
+    let list = violations
+        .iter()
+        .map(Violation::to_string)
+        .collect::<Vec<_>>()
+        .join("; ");
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(hook)
+        .env("ENT_RS_VIOLATIONS", list)
+        .env("ENT_RS_OFFSET", offset.to_string())
+        .status();
+    match status {
+        Ok(status) if !status.success() => eprintln!("ent-rs: hook exited with {status}"),
+        Ok(_) => {}
+        Err(err) => eprintln!("ent-rs: cannot run hook: {err}"),
+    }
+}
+
+fn print_summary(monitor: &Monitor, elapsed: Duration) {
+    let stats = monitor.stats();
+    let health = monitor.health();
+    println!(
+        "[{:>8.0}s] {} bytes: entropy {:.6}, mean {:.4}, serial correlation {:.6}, \
+         health {} (RCT {}, APT {} failures)",
+        elapsed.as_secs_f64(),
+        monitor.bytes_seen(),
+        stats.entropy,
+        stats.mean,
+        stats.serial_correlation,
+        if health.passed() { "ok" } else { "FAILED" },
+        health.rct_failures,
+        health.apt_failures,
+    );
+}
+
+fn print_stats(stats: &EntStats, input: &InputDigest) {
+    let unit = if input.bit_mode { "bit" } else { "byte" };
+    println!("Input: {input}.");
+    println!("Entropy = {:.6} bits per {unit}.", stats.entropy);
+    println!(
+        "Optimum compression would reduce the size by {:.0} percent.",
+        stats.compression_percent
+    );
+    println!(
+        "Chi square = {:.2}, p-value = {:.4}.",
+        stats.chisquare, stats.p_value
+    );
+    let mean = match stats.bit_frequencies {
+        Some([_, (_, ones)]) => ones,
+        None => stats.mean,
+    };
+    println!("Arithmetic mean value of data {unit}s is {mean:.4}.");
+    println!("Monte Carlo value for Pi is {:.9}.", stats.pi_estimate);
+    println!(
+        "Serial correlation coefficient is {:.6}.",
+        stats.serial_correlation
+    );
+}
+
+fn print_fixed_stats(stats: &FixedStats, input: &InputDigest) {
+    let unit = if input.bit_mode { "bit" } else { "byte" };
+    println!("Input: {input}.");
+    println!("Entropy = {:.6} bits per {unit}.", stats.entropy);
+    println!(
+        "Optimum compression would reduce the size by {:.0} percent.",
+        stats.compression_percent
+    );
+    println!("Chi square = {:.2}.", stats.chisquare);
+    let mean = stats.bit_mean.unwrap_or(stats.mean);
+    println!("Arithmetic mean value of data {unit}s is {mean:.4}.");
+    println!("Monte Carlo value for Pi is {:.9}.", stats.pi_estimate);
+    println!(
+        "Serial correlation coefficient is {:.6}.",
+        stats.serial_correlation
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_size_suffixes() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("4K"), Ok(4096));
+        assert_eq!(parse_size("1G"), Ok(1 << 30));
+        assert_eq!(parse_size("2MiB"), Ok(2 << 20));
+        assert!(parse_size("lots").is_err());
+    }
+
+    #[test]
+    fn test_parse_device_arguments() {
+        let opts = parse_args(args(&["/dev/sdb", "--length", "1G", "--offset", "1M"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(opts.path.as_deref(), Some("/dev/sdb"));
+        assert_eq!(opts.length, Some(1 << 30));
+        assert_eq!(opts.offset, 1 << 20);
+        assert!(parse_args(args(&["--length"])).is_err());
+        assert!(parse_args(args(&["a", "b"])).is_err());
+        assert!(parse_args(args(&["-t", "a"])).unwrap().unwrap().terse);
+        assert!(parse_args(args(&["-t", "--deterministic"])).is_err());
+        assert!(parse_args(args(&["-c", "a"])).unwrap().unwrap().chars);
+        assert!(parse_args(args(&["-c", "-b", "a"])).is_err());
+        assert!(parse_args(args(&["--follow", "--chars", "a"])).is_err());
+        // `-f` folds case as in `ent`; following has no short flag.
+        let fold = parse_args(args(&["-f", "-c", "a"])).unwrap().unwrap();
+        assert!(fold.fold && fold.follow.is_none());
+        assert!(parse_args(args(&["--fold", "a"])).unwrap().unwrap().fold);
+        let follow = parse_args(args(&["--follow", "log"])).unwrap().unwrap();
+        assert_eq!(follow.follow, Some(FOLLOW_INTERVAL));
+        let opts = parse_args(args(&["--interval", "0.5", "log"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(opts.follow, Some(Duration::from_millis(500)));
+        assert!(parse_args(args(&["--interval", "0", "log"])).is_err());
+    }
+
+    #[test]
+    fn test_glob_matching() {
+        assert!(glob_matches("*.pem", "keys/server.pem"));
+        assert!(!glob_matches("*.pem", "keys/server.pem.bak"));
+        assert!(glob_matches("keys/*.pem", "keys/server.pem"));
+        assert!(!glob_matches("keys/*.pem", "keys/old/server.pem"));
+        assert!(glob_matches("keys/**/*.pem", "keys/server.pem"));
+        assert!(glob_matches("keys/**/*.pem", "keys/a/b/server.pem"));
+        assert!(glob_matches("**/target", "crates/x/target"));
+        assert!(glob_matches("id_?sa", "id_rsa"));
+        assert!(!glob_matches("*/x", "a/b/x"));
+        assert!(glob_matches("**", "a/b/x"));
+    }
+
+    #[test]
+    fn test_scan_walks_filtered_tree() {
+        let root = std::env::temp_dir().join(format!("ent-rs-scan-{}", std::process::id()));
+        for dir in ["src", "target/debug", "keys"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in [
+            "src/main.rs",
+            "target/debug/app",
+            "keys/b.key",
+            "keys/a.key",
+            "README",
+        ] {
+            std::fs::write(root.join(file), file).unwrap();
+        }
+        let opts = parse_scan_args(args(&[
+            root.to_str().unwrap(),
+            "--exclude",
+            "target",
+            "--include",
+            "*.key",
+            "--include",
+            "src/*",
+        ]))
+        .unwrap()
+        .unwrap();
+        let mut files = Vec::new();
+        assert!(walk(&opts.root, "", &opts, &mut files));
+        let relative: Vec<&str> = files.iter().map(|(_, r)| r.as_str()).collect();
+        assert_eq!(relative, ["keys/a.key", "keys/b.key", "src/main.rs"]);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert!(parse_scan_args(args(&["--include"])).is_err());
+        assert!(parse_scan_args(args(&["--bits"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_monitor_arguments() {
+        let opts = parse_monitor_args(args(&[
+            "--window",
+            "1M",
+            "--min-entropy",
+            "7.5",
+            "--hook",
+            "logger entropy",
+            "/dev/hwrng",
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(opts.source, "/dev/hwrng");
+        assert_eq!(opts.window, 1 << 20);
+        assert_eq!(opts.thresholds.min_entropy, 7.5);
+        assert_eq!(opts.hook.as_deref(), Some("logger entropy"));
+        assert_eq!(opts.interval, Duration::from_secs(60));
+        assert!(parse_monitor_args(args(&["--window", "1M"])).is_err());
+
+        let opts = parse_monitor_args(args(&[
+            "--max-correlation",
+            "0.5",
+            "--preset",
+            "plain-text",
+            "-",
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(opts.thresholds.max_entropy, 6.0);
+        assert_eq!(opts.thresholds.max_serial_correlation, 0.5);
+        assert!(parse_monitor_args(args(&["--preset", "zip", "-"])).is_err());
+        assert!(parse_monitor_args(args(&["--interval", "soon", "-"])).is_err());
+        assert!(parse_monitor_args(args(&["--interval", "0", "-"])).is_err());
+        let opts = parse_monitor_args(args(&["--interval", "0.5", "-"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(opts.interval, Duration::from_millis(500));
+    }
+}
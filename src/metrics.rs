@@ -0,0 +1,350 @@
+//! Prometheus metrics for continuous monitoring.
+//!
+//! [`PrometheusMetrics`] renders statistics and health-test counts in the
+//! Prometheus text exposition format, and [`serve`] answers scrapes with
+//! them over HTTP, so operators can alert when an entropy source degrades.
+//!
+//! ```rust
+//! use ent_rs::metrics::PrometheusMetrics;
+//! use ent_rs::monitor::{Monitor, Thresholds};
+//! let mut monitor = Monitor::new(4096, Thresholds::default(), 7.0);
+//! monitor.push(&[0u8; 8192]);
+//! let text = PrometheusMetrics::new()
+//!     .label("source", "/dev/hwrng")
+//!     .render_monitor(&monitor);
+//! assert!(text.contains("ent_entropy_bits{source=\"/dev/hwrng\"} 0\n"));
+//! assert!(text.contains("ent_rct_failures_total{source=\"/dev/hwrng\"} 1\n"));
+//! ```
+
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::time::Duration;
+
+use crate::health::HealthStatus;
+use crate::monitor::Monitor;
+use crate::{chisquare_p_value, EntAccumulator, EntStats};
+
+/// Longest a scrape connection may wait to read its request or write the
+/// response.
+pub const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// `Content-Type` of the text exposition format.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Renders metrics under a namespace, with labels common to every series.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrometheusMetrics {
+    namespace: String,
+    labels: Vec<(String, String)>,
+}
+
+impl Default for PrometheusMetrics {
+    fn default() -> Self {
+        PrometheusMetrics::new()
+    }
+}
+
+impl PrometheusMetrics {
+    /// Metrics named `ent_*`, without labels.
+    pub fn new() -> Self {
+        PrometheusMetrics {
+            namespace: "ent".to_string(),
+            labels: Vec::new(),
+        }
+    }
+
+    /// Name the metrics `{namespace}_*`.
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = namespace.into();
+        self
+    }
+
+    /// Add the label `name="value"` to every series, for example to tell
+    /// several sources apart.
+    pub fn label(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.push((name.into(), value.into()));
+        self
+    }
+
+    /// Gauges of `stats`, a counter of the `bytes` they cover, and the
+    /// health-test cutoffs and failure counters if `health` is given.
+    /// `bit_mode` is the mode `stats` were computed in, which sets the
+    /// degrees of freedom of the chi-square p-value.
+    ///
+    /// Undefined values, such as the serial correlation of constant data,
+    /// are exported as `NaN`.
+    pub fn render(
+        &self,
+        bytes: u64,
+        stats: &EntStats,
+        bit_mode: bool,
+        health: Option<&HealthStatus>,
+    ) -> String {
+        let dof = if bit_mode { 1.0 } else { 255.0 };
+        let serial_correlation = if stats.serial_correlation == -99999.0 {
+            f64::NAN
+        } else {
+            stats.serial_correlation
+        };
+        let mut out = String::new();
+        self.write(
+            &mut out,
+            "bytes_total",
+            "counter",
+            "Bytes analyzed.",
+            bytes as f64,
+        );
+        self.write(
+            &mut out,
+            "entropy_bits",
+            "gauge",
+            "Shannon entropy in bits per byte, or per bit in bit mode.",
+            stats.entropy,
+        );
+        self.write(
+            &mut out,
+            "chi_square",
+            "gauge",
+            "Chi-square statistic of the value distribution.",
+            stats.chisquare,
+        );
+        self.write(
+            &mut out,
+            "chi_square_p_value",
+            "gauge",
+            "Upper-tail p-value of the chi-square statistic.",
+            chisquare_p_value(stats.chisquare, dof),
+        );
+        self.write(
+            &mut out,
+            "mean",
+            "gauge",
+            "Arithmetic mean of the values.",
+            stats.mean,
+        );
+        self.write(
+            &mut out,
+            "monte_carlo_pi",
+            "gauge",
+            "Monte Carlo estimate of pi.",
+            stats.pi_estimate,
+        );
+        self.write(
+            &mut out,
+            "serial_correlation",
+            "gauge",
+            "Serial correlation coefficient of adjacent values.",
+            serial_correlation,
+        );
+        if let Some(health) = health {
+            self.write(
+                &mut out,
+                "rct_failures_total",
+                "counter",
+                "SP 800-90B repetition count test failures.",
+                health.rct_failures as f64,
+            );
+            self.write(
+                &mut out,
+                "apt_failures_total",
+                "counter",
+                "SP 800-90B adaptive proportion test failures.",
+                health.apt_failures as f64,
+            );
+            self.write(
+                &mut out,
+                "rct_cutoff",
+                "gauge",
+                "Repetition count test cutoff in consecutive identical samples.",
+                health.rct_cutoff as f64,
+            );
+            self.write(
+                &mut out,
+                "apt_cutoff",
+                "gauge",
+                "Adaptive proportion test cutoff in occurrences per window.",
+                health.apt_cutoff as f64,
+            );
+        }
+        out
+    }
+
+    /// Metrics of everything fed to `acc`.
+    pub fn render_accumulator(&self, acc: &EntAccumulator) -> String {
+        self.render(acc.len() as u64, &acc.stats(), acc.bit_mode(), None)
+    }
+
+    /// Metrics of the current window of `monitor`, with every byte it has
+    /// seen counted and its cumulative health-test status.
+    pub fn render_monitor(&self, monitor: &Monitor) -> String {
+        self.render(
+            monitor.bytes_seen(),
+            &monitor.stats(),
+            false,
+            Some(&monitor.health()),
+        )
+    }
+
+    fn write(&self, out: &mut String, name: &str, kind: &str, help: &str, value: f64) {
+        let name = format!("{}_{name}", self.namespace);
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} {kind}");
+        out.push_str(&name);
+        if !self.labels.is_empty() {
+            let labels: Vec<String> = self
+                .labels
+                .iter()
+                .map(|(label, value)| format!("{label}=\"{}\"", escape(value)))
+                .collect();
+            let _ = write!(out, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(out, " {}", format_value(value));
+    }
+}
+
+/// Answer every HTTP request on `listener` with the output of `render`:
+/// `GET /metrics` gets the metrics, anything else a 404. Runs until
+/// accepting a connection fails; errors on a single connection only drop
+/// that connection.
+///
+/// Connections are served one at a time; a client that stalls for
+/// [`IO_TIMEOUT`] is dropped, so it cannot hold up later scrapes.
+pub fn serve<F: FnMut() -> String>(listener: &TcpListener, render: F) -> io::Result<()> {
+    serve_with_timeout(listener, IO_TIMEOUT, render)
+}
+
+fn serve_with_timeout<F: FnMut() -> String>(
+    listener: &TcpListener,
+    timeout: Duration,
+    mut render: F,
+) -> io::Result<()> {
+    for stream in listener.incoming() {
+        let mut stream = stream?;
+        if stream.set_read_timeout(Some(timeout)).is_err()
+            || stream.set_write_timeout(Some(timeout)).is_err()
+        {
+            continue;
+        }
+        let mut request_line = String::new();
+        let mut reader = BufReader::new(&stream);
+        if reader.read_line(&mut request_line).is_err() {
+            continue;
+        }
+        // Skip the headers up to the blank line.
+        let mut line = String::new();
+        while matches!(reader.read_line(&mut line), Ok(n) if n > 2) {
+            line.clear();
+        }
+        let mut parts = request_line.split_whitespace();
+        let response = match (parts.next(), parts.next()) {
+            (Some("GET"), Some("/metrics")) => {
+                let body = render();
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: {CONTENT_TYPE}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+            }
+            _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                .to_string(),
+        };
+        let _ = stream.write_all(response.as_bytes());
+    }
+    Ok(())
+}
+
+/// Escape a label value: backslash, double quote and newline.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// A sample value as Prometheus parses it.
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::net::TcpStream;
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn test_render_names_labels_and_values() {
+        let stats = EntStats::from_data(&[7u8; 100], false);
+        let text = PrometheusMetrics::new()
+            .namespace("rng")
+            .label("source", "a \"b\"\\c")
+            .label("host", "x")
+            .render(100, &stats, false, None);
+        assert!(text.starts_with(
+            "# HELP rng_bytes_total Bytes analyzed.\n# TYPE rng_bytes_total counter\n"
+        ));
+        assert!(text.contains("rng_bytes_total{source=\"a \\\"b\\\"\\\\c\",host=\"x\"} 100\n"));
+        assert!(
+            text.contains("rng_serial_correlation{source=\"a \\\"b\\\"\\\\c\",host=\"x\"} NaN\n")
+        );
+        assert!(!text.contains("rct"));
+        assert_eq!(format_value(f64::NEG_INFINITY), "-Inf");
+        assert_eq!(format_value(0.5), "0.5");
+
+        let mut acc = EntAccumulator::new(false);
+        acc.update(&[1, 2, 3]);
+        let text = PrometheusMetrics::new().render_accumulator(&acc);
+        assert!(text.contains("ent_bytes_total 3\n"));
+        assert!(text.contains("ent_mean 2\n"));
+
+        // A chi-square statistic below 255, where the normal approximation
+        // of `EntStats::p_value` is undefined.
+        let data: Vec<u8> = (0..=255).cycle().take(4096).collect();
+        let stats = EntStats::from_data(&data, false);
+        let text = PrometheusMetrics::new().render(4096, &stats, false, None);
+        assert!(text.contains("ent_chi_square_p_value 1\n"), "{text}");
+
+        // Bit mode without a frequency table still has one degree of freedom.
+        // 420 ones in 800 bits: chi-square 2, p = 0.157 at one degree.
+        let data: Vec<u8> = [0x1F; 20].into_iter().chain([0x0F; 80]).collect();
+        let bits = EntStats::builder()
+            .bit_mode(true)
+            .without_frequencies()
+            .analyze(&data);
+        let text = PrometheusMetrics::new().render(100, &bits, true, None);
+        assert!(text.contains("ent_chi_square_p_value 0.157"), "{text}");
+    }
+
+    #[test]
+    fn test_serve_answers_scrapes() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            serve_with_timeout(&listener, Duration::from_millis(200), || {
+                "ent_bytes_total 1\n".to_string()
+            })
+        });
+        // A client that connects and sends nothing only delays the next.
+        let _silent = TcpStream::connect(addr).unwrap();
+        let get = |path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        let response = get("/metrics");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{response}");
+        assert!(response.contains(CONTENT_TYPE));
+        assert!(response.ends_with("\r\n\r\nent_bytes_total 1\n"));
+        assert!(get("/").starts_with("HTTP/1.1 404"));
+    }
+}
@@ -0,0 +1,124 @@
+//! Cross-entropy of data under a trained reference model.
+//!
+//! Cross-entropy is the average number of bits per byte the data would cost
+//! if coded with the reference model. It equals the data's own entropy only
+//! when the model matches, so it answers "does this look like our usual
+//! traffic?" directly.
+//!
+//! ```rust
+//! use ent_rs::model::{cross_entropy, train_byte_model};
+//! let usual = b"GET /index.html HTTP/1.1\r\nHost: example.com\r\n\r\n".repeat(50);
+//! let model = train_byte_model(&usual, 0.01);
+//! let same = cross_entropy(b"GET /about.html HTTP/1.1\r\n", &model);
+//! let odd = cross_entropy(&[0xde, 0xad, 0xbe, 0xef, 0x00, 0x11], &model);
+//! assert!(odd > same + 5.0);
+//! ```
+
+use crate::byte_counts;
+
+/// Train an order-0 byte model with additive `smoothing` per symbol, so bytes
+/// absent from the training data keep a small non-zero probability.
+pub fn train_byte_model(data: &[u8], smoothing: f64) -> [f64; 256] {
+    let counts = byte_counts(data);
+    let total = data.len() as f64 + 256.0 * smoothing;
+    counts.map(|c| (c as f64 + smoothing) / total)
+}
+
+/// Cross-entropy in bits per byte of `data` under the order-0 model
+/// `model_probs`. Infinite if the data contains a byte with probability zero.
+pub fn cross_entropy(data: &[u8], model_probs: &[f64; 256]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let counts = byte_counts(data);
+    let bits = counts
+        .iter()
+        .zip(model_probs)
+        .filter(|(&c, _)| c > 0)
+        .map(|(&c, &p)| -(c as f64) * p.log2())
+        .sum::<f64>();
+    bits / data.len() as f64
+}
+
+/// Order-1 Markov model: the probability of each byte given the previous one.
+#[derive(Debug, Clone)]
+pub struct MarkovModel {
+    initial: [f64; 256],
+    transitions: Vec<[f64; 256]>,
+}
+
+impl MarkovModel {
+    /// Train from `data` with additive `smoothing` per transition.
+    pub fn train(data: &[u8], smoothing: f64) -> Self {
+        let mut counts = vec![[0usize; 256]; 256];
+        for pair in data.windows(2) {
+            counts[pair[0] as usize][pair[1] as usize] += 1;
+        }
+        let transitions = counts
+            .iter()
+            .map(|row| {
+                let total = row.iter().sum::<usize>() as f64 + 256.0 * smoothing;
+                row.map(|c| (c as f64 + smoothing) / total)
+            })
+            .collect();
+
+        MarkovModel {
+            initial: train_byte_model(data, smoothing),
+            transitions,
+        }
+    }
+
+    /// Probability of `next` following `prev`.
+    pub fn probability(&self, prev: u8, next: u8) -> f64 {
+        self.transitions[prev as usize][next as usize]
+    }
+
+    /// Cross-entropy in bits per byte of `data` under this model. The first
+    /// byte is scored with the model's order-0 distribution.
+    pub fn cross_entropy(&self, data: &[u8]) -> f64 {
+        let Some(&first) = data.first() else {
+            return 0.0;
+        };
+        let bits = -self.initial[first as usize].log2()
+            - data
+                .windows(2)
+                .map(|pair| self.probability(pair[0], pair[1]).log2())
+                .sum::<f64>();
+        bits / data.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculate_entropy;
+
+    #[test]
+    fn test_cross_entropy_of_own_model_is_entropy() {
+        let data: Vec<u8> = (0..4096u32).map(|i| (i * i % 37) as u8).collect();
+        let model = train_byte_model(&data, 0.0);
+        let h = cross_entropy(&data, &model);
+        assert!((h - calculate_entropy(&data, false)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_zero_probability_is_infinite() {
+        let model = train_byte_model(b"aaaa", 0.0);
+        assert!(cross_entropy(b"ab", &model).is_infinite());
+    }
+
+    #[test]
+    fn test_markov_model_captures_order() {
+        let training = b"abcd".repeat(1000);
+        let markov = MarkovModel::train(&training, 0.01);
+        let in_order = markov.cross_entropy(&b"abcd".repeat(10));
+        let shuffled = markov.cross_entropy(&b"dcba".repeat(10));
+        assert!(in_order < 0.5);
+        assert!(shuffled > 5.0);
+        // An order-0 model cannot tell the two apart.
+        let flat = train_byte_model(&training, 0.01);
+        let a = cross_entropy(&b"abcd".repeat(10), &flat);
+        let b = cross_entropy(&b"dcba".repeat(10), &flat);
+        assert!((a - b).abs() < 1e-12);
+    }
+}
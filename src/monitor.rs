@@ -0,0 +1,434 @@
+//! Continuous monitoring of an entropy stream against thresholds.
+//!
+//! ```rust
+//! use ent_rs::monitor::{Metric, Monitor, Thresholds};
+//! let mut monitor = Monitor::new(4096, Thresholds::default(), 7.0);
+//! monitor.push(&[0u8; 8192]);
+//! let violations = monitor.check();
+//! assert!(violations.iter().any(|v| v.metric == Metric::Entropy));
+//! assert!(violations.iter().any(|v| v.metric == Metric::RepetitionCount));
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::classify::ContentClass;
+use crate::health::{HealthStatus, HealthTests};
+use crate::window::WindowAnalyzer;
+use crate::{chisquare_p_value, EntStats};
+
+/// Limits a healthy stream is expected to stay within.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Thresholds {
+    /// Minimum entropy in bits per byte.
+    pub min_entropy: f64,
+    /// Maximum entropy in bits per byte; below 8 flags data that is more
+    /// random than its content type should be.
+    pub max_entropy: f64,
+    /// Minimum chi-square p-value (upper-tail probability, not [`EntStats::p_value`]).
+    pub min_p_value: f64,
+    /// Maximum distance of the mean byte value from 127.5.
+    pub max_mean_deviation: f64,
+    /// Maximum absolute serial correlation coefficient.
+    pub max_serial_correlation: f64,
+}
+
+impl Default for Thresholds {
+    /// Limits for full-entropy output analyzed in windows of tens of kilobytes.
+    fn default() -> Self {
+        Thresholds {
+            min_entropy: 7.9,
+            max_entropy: 8.0,
+            min_p_value: 0.0001,
+            max_mean_deviation: 2.0,
+            max_serial_correlation: 0.05,
+        }
+    }
+}
+
+impl Thresholds {
+    /// Limits suited to a kind of content.
+    pub fn preset(preset: Preset) -> Self {
+        let lenient = Thresholds {
+            min_entropy: 0.0,
+            max_entropy: 8.0,
+            min_p_value: 0.0,
+            max_mean_deviation: 127.5,
+            max_serial_correlation: 1.0,
+        };
+        match preset {
+            Preset::Ciphertext => Thresholds::default(),
+            // Compressors leave a measurable bias that fails chi-square.
+            Preset::CompressedArchive => Thresholds {
+                min_entropy: 7.0,
+                max_mean_deviation: 10.0,
+                max_serial_correlation: 0.2,
+                ..lenient
+            },
+            Preset::ExecutableCode => Thresholds {
+                min_entropy: 3.0,
+                max_entropy: 7.5,
+                ..lenient
+            },
+            Preset::PlainText => Thresholds {
+                min_entropy: 2.0,
+                max_entropy: 6.0,
+                ..lenient
+            },
+            // Raw noise is biased and correlated, but must not be constant.
+            Preset::SensorNoise => Thresholds {
+                min_entropy: 1.0,
+                max_serial_correlation: 0.95,
+                ..lenient
+            },
+        }
+    }
+
+    /// Compare statistics against the limits.
+    pub fn check(&self, stats: &EntStats) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let mut check = |metric, value: f64, limit: f64, bad: bool| {
+            if bad {
+                violations.push(Violation {
+                    metric,
+                    value,
+                    limit,
+                });
+            }
+        };
+        check(
+            Metric::Entropy,
+            stats.entropy,
+            self.min_entropy,
+            stats.entropy < self.min_entropy,
+        );
+        check(
+            Metric::Entropy,
+            stats.entropy,
+            self.max_entropy,
+            stats.entropy > self.max_entropy,
+        );
+        let dof = if stats.bit_frequencies.is_some() {
+            1.0
+        } else {
+            255.0
+        };
+        let p_value = chisquare_p_value(stats.chisquare, dof);
+        check(
+            Metric::PValue,
+            p_value,
+            self.min_p_value,
+            p_value < self.min_p_value,
+        );
+        let deviation = (stats.mean - 127.5).abs();
+        check(
+            Metric::Mean,
+            stats.mean,
+            self.max_mean_deviation,
+            deviation > self.max_mean_deviation,
+        );
+        // The -99999.0 sentinel (constant data) always violates the limit.
+        let scc = stats.serial_correlation;
+        check(
+            Metric::SerialCorrelation,
+            scc,
+            self.max_serial_correlation,
+            scc == -99999.0 || scc.abs() > self.max_serial_correlation,
+        );
+        violations
+    }
+}
+
+/// Named threshold set for a kind of content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Preset {
+    /// Full-entropy output: ciphertext, keys, RNG output. The default limits.
+    Ciphertext,
+    /// Compressed archives and media: high entropy, but not uniform.
+    CompressedArchive,
+    /// Machine code and structured binary data.
+    ExecutableCode,
+    /// Human-readable text; high entropy is suspicious.
+    PlainText,
+    /// Raw, unconditioned noise-source samples.
+    SensorNoise,
+}
+
+impl Preset {
+    /// Every preset, in declaration order.
+    pub const ALL: [Preset; 5] = [
+        Preset::Ciphertext,
+        Preset::CompressedArchive,
+        Preset::ExecutableCode,
+        Preset::PlainText,
+        Preset::SensorNoise,
+    ];
+
+    /// Name accepted by [`FromStr`], e.g. `compressed-archive`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Preset::Ciphertext => "ciphertext",
+            Preset::CompressedArchive => "compressed-archive",
+            Preset::ExecutableCode => "executable-code",
+            Preset::PlainText => "plain-text",
+            Preset::SensorNoise => "sensor-noise",
+        }
+    }
+
+    /// Threshold limits of the preset.
+    pub fn thresholds(self) -> Thresholds {
+        Thresholds::preset(self)
+    }
+
+    /// Preset for content of the given class; padding has none.
+    pub fn for_class(class: ContentClass) -> Option<Preset> {
+        match class {
+            ContentClass::Padding => None,
+            ContentClass::Text => Some(Preset::PlainText),
+            ContentClass::Code => Some(Preset::ExecutableCode),
+            ContentClass::Compressed => Some(Preset::CompressedArchive),
+            ContentClass::Encrypted => Some(Preset::Ciphertext),
+        }
+    }
+
+    /// Preset for the file format identified from the leading bytes of
+    /// `data`, if any.
+    #[cfg(feature = "magic")]
+    pub fn detect(data: &[u8]) -> Option<Preset> {
+        crate::signature::identify(data).and_then(|s| Preset::for_class(s.class))
+    }
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Preset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Preset::ALL
+            .into_iter()
+            .find(|p| p.name() == s)
+            .ok_or_else(|| format!("unknown preset '{s}'"))
+    }
+}
+
+/// Quantity that left its allowed range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Metric {
+    /// Shannon entropy.
+    Entropy,
+    /// Chi-square p-value.
+    PValue,
+    /// Arithmetic mean.
+    Mean,
+    /// Serial correlation coefficient.
+    SerialCorrelation,
+    /// SP 800-90B Repetition Count Test.
+    RepetitionCount,
+    /// SP 800-90B Adaptive Proportion Test.
+    AdaptiveProportion,
+}
+
+/// A threshold violation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Violation {
+    /// Which quantity was out of range.
+    pub metric: Metric,
+    /// Observed value (failure count for health tests).
+    pub value: f64,
+    /// The limit that was crossed.
+    pub limit: f64,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.metric {
+            Metric::Entropy if self.value > self.limit => {
+                write!(f, "entropy {:.6} above {}", self.value, self.limit)
+            }
+            Metric::Entropy => write!(f, "entropy {:.6} below {}", self.value, self.limit),
+            Metric::PValue => write!(
+                f,
+                "chi-square p-value {:.6} below {}",
+                self.value, self.limit
+            ),
+            Metric::Mean => write!(
+                f,
+                "mean {:.4} more than {} from 127.5",
+                self.value, self.limit
+            ),
+            Metric::SerialCorrelation => write!(
+                f,
+                "serial correlation {:.6} beyond ±{}",
+                self.value, self.limit
+            ),
+            Metric::RepetitionCount => write!(
+                f,
+                "{} repetition count test failure(s), cutoff {}",
+                self.value, self.limit
+            ),
+            Metric::AdaptiveProportion => write!(
+                f,
+                "{} adaptive proportion test failure(s), cutoff {}",
+                self.value, self.limit
+            ),
+        }
+    }
+}
+
+/// Windowed statistics plus health tests over a never-ending stream.
+#[derive(Debug, Clone)]
+pub struct Monitor {
+    window: WindowAnalyzer,
+    health: HealthTests,
+    thresholds: Thresholds,
+    reported: HealthStatus,
+    bytes_seen: u64,
+}
+
+impl Monitor {
+    /// Monitor the most recent `window` bytes against `thresholds`, running
+    /// health tests with cutoffs for `claimed_min_entropy` bits per byte.
+    pub fn new(window: usize, thresholds: Thresholds, claimed_min_entropy: f64) -> Self {
+        let health = HealthTests::new(claimed_min_entropy);
+        Monitor {
+            window: WindowAnalyzer::new(window),
+            reported: health.status(),
+            health,
+            thresholds,
+            bytes_seen: 0,
+        }
+    }
+
+    /// Feed newly arrived data.
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.window.extend(chunk);
+        self.health.extend(chunk);
+        self.bytes_seen += chunk.len() as u64;
+    }
+
+    /// Total bytes fed so far.
+    pub fn bytes_seen(&self) -> u64 {
+        self.bytes_seen
+    }
+
+    /// Replace the limits checked from now on.
+    pub fn set_thresholds(&mut self, thresholds: Thresholds) {
+        self.thresholds = thresholds;
+    }
+
+    /// Statistics of the current window.
+    pub fn stats(&self) -> EntStats {
+        self.window.current_stats()
+    }
+
+    /// Cumulative health-test status.
+    pub fn health(&self) -> HealthStatus {
+        self.health.status()
+    }
+
+    /// Report health-test failures since the previous call and, once the
+    /// window has filled, threshold violations of the current window.
+    pub fn check(&mut self) -> Vec<Violation> {
+        let mut violations = if self.window.len() == self.window.capacity() {
+            self.thresholds.check(&self.stats())
+        } else {
+            Vec::new()
+        };
+
+        let status = self.health.status();
+        let new_rct = status.rct_failures - self.reported.rct_failures;
+        let new_apt = status.apt_failures - self.reported.apt_failures;
+        if new_rct > 0 {
+            violations.push(Violation {
+                metric: Metric::RepetitionCount,
+                value: new_rct as f64,
+                limit: status.rct_cutoff as f64,
+            });
+        }
+        if new_apt > 0 {
+            violations.push(Violation {
+                metric: Metric::AdaptiveProportion,
+                value: new_apt as f64,
+                limit: status.apt_cutoff as f64,
+            });
+        }
+        self.reported = status;
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn good_bytes(len: usize) -> Vec<u8> {
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_healthy_stream_has_no_violations() {
+        let mut monitor = Monitor::new(65536, Thresholds::default(), 7.0);
+        for chunk in good_bytes(200_000).chunks(4096) {
+            monitor.push(chunk);
+            assert!(monitor.check().is_empty());
+        }
+        assert_eq!(monitor.bytes_seen(), 200_000);
+    }
+
+    #[test]
+    fn test_health_failures_reported_once() {
+        let mut monitor = Monitor::new(1 << 20, Thresholds::default(), 7.0);
+        monitor.push(&good_bytes(1000));
+        monitor.push(&[9u8; 16]);
+        let first = monitor.check();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].metric, Metric::RepetitionCount);
+        assert!(monitor.check().is_empty());
+    }
+
+    #[test]
+    fn test_presets() {
+        for preset in Preset::ALL {
+            assert_eq!(preset.name().parse(), Ok(preset));
+        }
+        assert!("zip".parse::<Preset>().is_err());
+
+        let random = EntStats::from_data(&good_bytes(65536), false);
+        assert!(Preset::CompressedArchive
+            .thresholds()
+            .check(&random)
+            .is_empty());
+        let violations = Preset::PlainText.thresholds().check(&random);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].metric, Metric::Entropy);
+        assert!(violations[0].to_string().contains("above 6"));
+
+        let text = EntStats::from_data(&b"the quick brown fox ".repeat(1000), false);
+        assert!(Preset::PlainText.thresholds().check(&text).is_empty());
+        assert!(!Thresholds::default().check(&text).is_empty());
+    }
+
+    #[cfg(feature = "magic")]
+    #[test]
+    fn test_detect_preset() {
+        assert_eq!(
+            Preset::detect(b"PK\x03\x04rest"),
+            Some(Preset::CompressedArchive)
+        );
+        assert_eq!(Preset::detect(b"\x7fELF\x02"), Some(Preset::ExecutableCode));
+        assert_eq!(Preset::detect(b"no signature"), None);
+    }
+}
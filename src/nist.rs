@@ -0,0 +1,1224 @@
+//! NIST SP 800-22 statistical tests over bit sequences.
+//!
+//! Bytes are read most significant bit first, matching the reference
+//! implementation's handling of binary input files.
+//!
+//! ```rust
+//! use ent_rs::nist::{non_overlapping_template, BitStream, Template};
+//! // SP 800-22 §2.7.8 example.
+//! let bits: BitStream = "10100100101110010110".parse().unwrap();
+//! let template: Template = "001".parse().unwrap();
+//! let result = non_overlapping_template(&bits, &template, 2);
+//! assert_eq!(result.counts, [2, 1]);
+//! assert!((result.p_value - 0.344154).abs() < 1e-6);
+//! ```
+
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::fft::{dft, Complex};
+use crate::special::erfc;
+use crate::suite::SuiteConfig;
+use crate::{chisquare_p_value, SQRT_2};
+
+/// A sequence of bits backed by bytes, most significant bit first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitStream<'a> {
+    bytes: Cow<'a, [u8]>,
+    len: usize,
+}
+
+impl<'a> BitStream<'a> {
+    /// View every bit of `bytes`.
+    pub fn from_bytes(bytes: &'a [u8]) -> Self {
+        BitStream {
+            len: bytes.len() * 8,
+            bytes: Cow::Borrowed(bytes),
+        }
+    }
+
+    /// Number of bits.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// The bytes holding complete groups of 8 bits; a trailing partial byte
+    /// is left out.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len / 8]
+    }
+
+    /// Whether the stream has no bits.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Bit `i` as 0 or 1.
+    pub fn bit(&self, i: usize) -> u8 {
+        assert!(
+            i < self.len,
+            "bit index {i} out of range for {} bits",
+            self.len
+        );
+        (self.bytes[i / 8] >> (7 - i % 8)) & 1
+    }
+
+    /// Iterate over the bits of `start..end`.
+    pub fn bits(&self, start: usize, end: usize) -> impl Iterator<Item = u8> + '_ {
+        (start..end.min(self.len)).map(move |i| self.bit(i))
+    }
+
+    /// Iterate over all bits.
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        self.bits(0, self.len)
+    }
+
+    /// Number of one bits.
+    pub fn count_ones(&self) -> usize {
+        let full = self.len / 8;
+        let ones = crate::simd::count_ones(&self.bytes[..full]) as usize;
+        ones + self.bits(full * 8, self.len).filter(|&b| b == 1).count()
+    }
+}
+
+impl FromStr for BitStream<'static> {
+    type Err = String;
+
+    /// Parse a string of `0` and `1` characters; whitespace is ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bytes = Vec::new();
+        let mut len = 0;
+        for c in s.chars().filter(|c| !c.is_whitespace()) {
+            let bit = match c {
+                '0' => 0,
+                '1' => 1,
+                _ => return Err(format!("invalid bit '{c}'")),
+            };
+            if len % 8 == 0 {
+                bytes.push(0);
+            }
+            bytes[len / 8] |= bit << (7 - len % 8);
+            len += 1;
+        }
+        Ok(BitStream {
+            bytes: Cow::Owned(bytes),
+            len,
+        })
+    }
+}
+
+/// Longest template supported by the template-matching tests.
+pub const MAX_TEMPLATE_LEN: usize = 32;
+
+/// A bit pattern searched for by the template-matching tests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Template {
+    len: u8,
+    bits: u32,
+}
+
+impl Template {
+    /// The `len` low bits of `bits`, first bit most significant.
+    ///
+    /// # Panics
+    ///
+    /// If `len` is 0 or exceeds [`MAX_TEMPLATE_LEN`].
+    pub fn new(bits: u32, len: usize) -> Self {
+        assert!(
+            (1..=MAX_TEMPLATE_LEN).contains(&len),
+            "template length must be in 1..={MAX_TEMPLATE_LEN}"
+        );
+        Template {
+            len: len as u8,
+            bits: bits & mask(len),
+        }
+    }
+
+    /// Number of bits.
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// Always false; templates have at least one bit.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// The pattern as an integer, first bit most significant.
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// Whether no proper shift of the template overlaps itself, i.e. no
+    /// prefix equals a suffix of the same length.
+    ///
+    /// The non-overlapping test's reference distribution assumes aperiodic
+    /// templates; periodic custom templates give miscalibrated p-values.
+    pub fn is_aperiodic(&self) -> bool {
+        let m = self.len();
+        (1..m).all(|k| self.bits >> k != self.bits & mask(m - k))
+    }
+}
+
+impl FromStr for Template {
+    type Err = String;
+
+    /// Parse a string of `0` and `1` characters such as `"000000001"`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || s.len() > MAX_TEMPLATE_LEN {
+            return Err(format!(
+                "template must have 1 to {MAX_TEMPLATE_LEN} bits, got {}",
+                s.len()
+            ));
+        }
+        let bits = u32::from_str_radix(s, 2).map_err(|_| format!("invalid template '{s}'"))?;
+        Ok(Template::new(bits, s.len()))
+    }
+}
+
+impl fmt::Display for Template {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:0width$b}", self.bits, width = self.len())
+    }
+}
+
+/// The aperiodic templates of length `m` in ascending order, as used by the
+/// NIST reference implementation (148 templates for `m = 9`).
+///
+/// Enumerates all `2^m` patterns, so lengths beyond the standard's 2..=21
+/// get slow quickly.
+pub fn aperiodic_templates(m: usize) -> Vec<Template> {
+    assert!(
+        (1..=MAX_TEMPLATE_LEN).contains(&m),
+        "template length must be in 1..={MAX_TEMPLATE_LEN}"
+    );
+    (0..=mask(m))
+        .map(|bits| Template::new(bits, m))
+        .filter(Template::is_aperiodic)
+        .collect()
+}
+
+/// Result of the frequency (monobit) test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrequencyResult {
+    /// Ones minus zeros, `S_n`.
+    pub sum: i64,
+    /// `|S_n| / sqrt(n)`, half-normal for random sequences.
+    pub statistic: f64,
+    /// Two-sided p-value.
+    pub p_value: f64,
+}
+
+/// SP 800-22 §2.1 frequency (monobit) test: whether ones and zeros are
+/// equally common over the whole sequence.
+///
+/// The standard recommends at least 100 bits. The statistic and p-value are
+/// NaN for an empty sequence.
+pub fn frequency(bits: &BitStream) -> FrequencyResult {
+    let n = bits.len();
+    let sum = 2 * bits.count_ones() as i64 - n as i64;
+    let statistic = sum.unsigned_abs() as f64 / (n as f64).sqrt();
+    FrequencyResult {
+        sum,
+        statistic,
+        p_value: erfc(statistic / SQRT_2),
+    }
+}
+
+/// Suite adapter for [`frequency`].
+pub(crate) fn frequency_test(data: &[u8], _: &SuiteConfig) -> Vec<f64> {
+    vec![frequency(&BitStream::from_bytes(data)).p_value]
+}
+
+/// Result of the frequency within a block test.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockFrequencyResult {
+    /// Block length `M`.
+    pub block_len: usize,
+    /// Ones in each block; trailing bits that fill no block are ignored.
+    pub counts: Vec<usize>,
+    /// Chi-square statistic with `counts.len()` degrees of freedom.
+    pub chi_square: f64,
+    /// Upper-tail p-value.
+    pub p_value: f64,
+}
+
+/// SP 800-22 §2.2 frequency within a block test: whether each block of
+/// `block_len` bits has about as many ones as zeros.
+///
+/// The standard recommends `M ≥ 20`, `M > n / 100` and fewer than 100
+/// blocks; see [`NistParams::block_frequency_len_for`](crate::suite::NistParams::block_frequency_len_for).
+/// The p-value is NaN if the sequence is shorter than one block.
+pub fn block_frequency(bits: &BitStream, block_len: usize) -> BlockFrequencyResult {
+    let block_len = block_len.max(1);
+    let counts: Vec<usize> = (0..bits.len() / block_len)
+        .map(|j| {
+            bits.bits(j * block_len, (j + 1) * block_len)
+                .filter(|&b| b == 1)
+                .count()
+        })
+        .collect();
+    let m = block_len as f64;
+    let chi_square = 4.0
+        * m
+        * counts
+            .iter()
+            .map(|&ones| (ones as f64 / m - 0.5).powi(2))
+            .sum::<f64>();
+    BlockFrequencyResult {
+        block_len,
+        p_value: chisquare_p_value(chi_square, counts.len() as f64),
+        counts,
+        chi_square,
+    }
+}
+
+/// Suite adapter for [`block_frequency`], with `M` from
+/// [`NistParams::block_frequency_len_for`](crate::suite::NistParams::block_frequency_len_for).
+pub(crate) fn block_frequency_test(data: &[u8], config: &SuiteConfig) -> Vec<f64> {
+    let bits = BitStream::from_bytes(data);
+    let block_len = config.nist.block_frequency_len_for(bits.len());
+    vec![block_frequency(&bits, block_len).p_value]
+}
+
+/// Result of the runs test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RunsResult {
+    /// Proportion of ones, `π`.
+    pub ones_fraction: f64,
+    /// Whether `|π - 1/2| < 2 / sqrt(n)`, the frequency prerequisite.
+    pub prerequisite_met: bool,
+    /// Total number of runs of identical bits, `V_n`.
+    pub runs: usize,
+    /// Two-sided p-value; 0 if the prerequisite is not met.
+    pub p_value: f64,
+}
+
+/// SP 800-22 §2.3 runs test: whether the sequence switches between ones
+/// and zeros too often or too rarely.
+///
+/// The test is only meaningful for sequences that pass the frequency test,
+/// so the p-value is 0 when the proportion of ones is far from 1/2. The
+/// standard recommends at least 100 bits; the p-value is NaN for an empty
+/// sequence.
+pub fn runs(bits: &BitStream) -> RunsResult {
+    let n = bits.len();
+    let ones_fraction = bits.count_ones() as f64 / n as f64;
+    let prerequisite_met = (ones_fraction - 0.5).abs() < 2.0 / (n as f64).sqrt();
+    let mut iter = bits.iter();
+    let runs = match iter.next() {
+        None => 0,
+        Some(first) => {
+            1 + iter
+                .scan(first, |prev, b| Some(std::mem::replace(prev, b) != b))
+                .filter(|&changed| changed)
+                .count()
+        }
+    };
+    let p_value = if n == 0 {
+        f64::NAN
+    } else if !prerequisite_met {
+        0.0
+    } else {
+        let pq = ones_fraction * (1.0 - ones_fraction);
+        let n = n as f64;
+        erfc((runs as f64 - 2.0 * n * pq).abs() / (2.0 * (2.0 * n).sqrt() * pq))
+    };
+    RunsResult {
+        ones_fraction,
+        prerequisite_met,
+        runs,
+        p_value,
+    }
+}
+
+/// Suite adapter for [`runs`].
+pub(crate) fn runs_test(data: &[u8], _: &SuiteConfig) -> Vec<f64> {
+    vec![runs(&BitStream::from_bytes(data)).p_value]
+}
+
+/// Block length, shortest class, and class probabilities of the longest run
+/// test for each sequence length the standard covers.
+struct LongestRunTable {
+    min_bits: usize,
+    block_len: usize,
+    min_run: usize,
+    probabilities: &'static [f64],
+}
+
+/// SP 800-22 Table 2.4, longest sequence length first; the probabilities are
+/// those of the reference implementation.
+const LONGEST_RUN_TABLES: [LongestRunTable; 3] = [
+    LongestRunTable {
+        min_bits: 750_000,
+        block_len: 10_000,
+        min_run: 10,
+        probabilities: &[0.0882, 0.2092, 0.2483, 0.1933, 0.1208, 0.0675, 0.0727],
+    },
+    LongestRunTable {
+        min_bits: 6272,
+        block_len: 128,
+        min_run: 4,
+        probabilities: &[
+            0.1174035788,
+            0.242955959,
+            0.249363483,
+            0.17517706,
+            0.102701071,
+            0.112398847,
+        ],
+    },
+    LongestRunTable {
+        min_bits: 128,
+        block_len: 8,
+        min_run: 1,
+        probabilities: &[0.21484375, 0.3671875, 0.23046875, 0.1875],
+    },
+];
+
+/// Result of the longest run of ones in a block test.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LongestRunResult {
+    /// Block length `M`: 8, 128 or 10 000 bits depending on `n`, or 0 for
+    /// sequences shorter than 128 bits.
+    pub block_len: usize,
+    /// Blocks per class of longest run; the first class also counts shorter
+    /// runs and the last also longer ones.
+    pub counts: Vec<usize>,
+    /// Chi-square statistic with `K = counts.len() - 1` degrees of freedom.
+    pub chi_square: f64,
+    /// Upper-tail p-value.
+    pub p_value: f64,
+}
+
+/// SP 800-22 §2.4 longest run of ones in a block test: whether the longest
+/// runs of ones within blocks are as long as for a random sequence.
+///
+/// The block length and classes follow the standard's table for `n` of at
+/// least 128, 6272 or 750 000 bits. Shorter sequences are not tested: the
+/// counts are empty and the statistic and p-value NaN.
+pub fn longest_run_of_ones(bits: &BitStream) -> LongestRunResult {
+    let Some(table) = LONGEST_RUN_TABLES.iter().find(|t| bits.len() >= t.min_bits) else {
+        return LongestRunResult {
+            block_len: 0,
+            counts: Vec::new(),
+            chi_square: f64::NAN,
+            p_value: f64::NAN,
+        };
+    };
+    let classes = table.probabilities.len();
+    let mut counts = vec![0; classes];
+    for j in 0..bits.len() / table.block_len {
+        let (_, longest) = bits
+            .bits(j * table.block_len, (j + 1) * table.block_len)
+            .fold((0, 0), |(run, longest), b| {
+                let run = if b == 1 { run + 1 } else { 0 };
+                (run, longest.max(run))
+            });
+        counts[longest.clamp(table.min_run, table.min_run + classes - 1) - table.min_run] += 1;
+    }
+    let blocks = (bits.len() / table.block_len) as f64;
+    let chi_square = counts
+        .iter()
+        .zip(table.probabilities)
+        .map(|(&v, &p)| (v as f64 - blocks * p).powi(2) / (blocks * p))
+        .sum::<f64>();
+    LongestRunResult {
+        block_len: table.block_len,
+        counts,
+        chi_square,
+        p_value: chisquare_p_value(chi_square, (classes - 1) as f64),
+    }
+}
+
+/// Suite adapter for [`longest_run_of_ones`].
+pub(crate) fn longest_run_test(data: &[u8], _: &SuiteConfig) -> Vec<f64> {
+    vec![longest_run_of_ones(&BitStream::from_bytes(data)).p_value]
+}
+
+/// Result of the discrete Fourier transform (spectral) test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpectralResult {
+    /// Peak height `T = sqrt(n ln 20)` that 95% of the first `n / 2`
+    /// Fourier coefficients of a random sequence stay below.
+    pub threshold: f64,
+    /// Coefficients below the threshold, `N_1`.
+    pub peaks_below: usize,
+    /// Coefficients expected below the threshold, `N_0 = 0.95 n / 2`.
+    pub expected_below: f64,
+    /// Normalized difference `d = (N_1 - N_0) / sqrt(n · 0.95 · 0.05 / 4)`.
+    pub statistic: f64,
+    /// Two-sided p-value.
+    pub p_value: f64,
+}
+
+/// SP 800-22 §2.6 discrete Fourier transform (spectral) test: whether the
+/// sequence has periodic features, which show up as too many tall peaks in
+/// its spectrum.
+///
+/// The transform of `n` bits takes O(n log n) time and up to about 150 bytes
+/// per bit of memory when `n` is not a power of two. The standard recommends
+/// at least 1000 bits; the p-value is NaN for an empty sequence.
+pub fn spectral(bits: &BitStream) -> SpectralResult {
+    let n = bits.len();
+    let signal: Vec<Complex> = bits
+        .iter()
+        .map(|b| Complex::new(2.0 * b as f64 - 1.0, 0.0))
+        .collect();
+    let threshold = ((1.0f64 / 0.05).ln() * n as f64).sqrt();
+    let peaks_below = dft(&signal)[..n / 2]
+        .iter()
+        .filter(|x| x.abs() < threshold)
+        .count();
+    let expected_below = 0.95 * n as f64 / 2.0;
+    let statistic = (peaks_below as f64 - expected_below) / (n as f64 * 0.95 * 0.05 / 4.0).sqrt();
+    SpectralResult {
+        threshold,
+        peaks_below,
+        expected_below,
+        statistic,
+        p_value: erfc(statistic.abs() / SQRT_2),
+    }
+}
+
+/// Suite adapter for [`spectral`].
+pub(crate) fn spectral_test(data: &[u8], _: &SuiteConfig) -> Vec<f64> {
+    vec![spectral(&BitStream::from_bytes(data)).p_value]
+}
+
+/// Result of the non-overlapping template matching test for one template.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateResult {
+    /// The template searched for.
+    pub template: Template,
+    /// Matches found in each block.
+    pub counts: Vec<usize>,
+    /// Chi-square statistic with `counts.len()` degrees of freedom.
+    pub chi_square: f64,
+    /// Upper-tail p-value.
+    pub p_value: f64,
+}
+
+/// SP 800-22 §2.7 non-overlapping template matching test.
+///
+/// The sequence is divided into `blocks` blocks of `M = n / blocks` bits; in
+/// each block, matches of `template` are counted, skipping past each match.
+/// The p-value is NaN if the blocks are not longer than the template.
+pub fn non_overlapping_template(
+    bits: &BitStream,
+    template: &Template,
+    blocks: usize,
+) -> TemplateResult {
+    let blocks = blocks.max(1);
+    let m = template.len();
+    let block_len = bits.len() / blocks;
+    let counts: Vec<usize> = (0..blocks)
+        .map(|j| count_non_overlapping(bits, j * block_len, block_len, template))
+        .collect();
+
+    let (chi_square, p_value) = if block_len <= m {
+        (f64::NAN, f64::NAN)
+    } else {
+        let p = (-(m as f64)).exp2();
+        let mean = (block_len - m + 1) as f64 * p;
+        let variance = block_len as f64 * (p - (2 * m - 1) as f64 * p * p);
+        let chi_square = counts
+            .iter()
+            .map(|&w| (w as f64 - mean).powi(2) / variance)
+            .sum::<f64>();
+        (chi_square, chisquare_p_value(chi_square, blocks as f64))
+    };
+
+    TemplateResult {
+        template: *template,
+        counts,
+        chi_square,
+        p_value,
+    }
+}
+
+/// Run the non-overlapping template test for each of `templates`.
+pub fn non_overlapping_templates(
+    bits: &BitStream,
+    templates: &[Template],
+    blocks: usize,
+) -> Vec<TemplateResult> {
+    templates
+        .iter()
+        .map(|t| non_overlapping_template(bits, t, blocks))
+        .collect()
+}
+
+/// Occurrence classes of the overlapping template test, `K + 1`.
+const OVERLAPPING_CLASSES: usize = 6;
+
+/// Class probabilities for `m = 9, M = 1032` as corrected in SP 800-22
+/// §3.8; the approximation of [`overlapping_probabilities`] is less accurate.
+const OVERLAPPING_PROBABILITIES_9_1032: [f64; OVERLAPPING_CLASSES] =
+    [0.364091, 0.185659, 0.139381, 0.100571, 0.0704323, 0.139865];
+
+/// Result of the overlapping template matching test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OverlappingTemplateResult {
+    /// Template length `m`; the template is `m` ones.
+    pub template_len: usize,
+    /// Block length `M`.
+    pub block_len: usize,
+    /// Blocks with 0, 1, 2, 3, 4 and at least 5 occurrences.
+    pub counts: [usize; OVERLAPPING_CLASSES],
+    /// Chi-square statistic with 5 degrees of freedom.
+    pub chi_square: f64,
+    /// Upper-tail p-value.
+    pub p_value: f64,
+}
+
+/// SP 800-22 §2.8 overlapping template matching test: the number of
+/// occurrences, overlaps included, of a run of `template_len` ones in each
+/// block of `block_len` bits.
+///
+/// The standard parameters are `m = 9` and `M = 1032`, for which the
+/// corrected class probabilities are used. The p-value is NaN if no block
+/// is longer than the template.
+pub fn overlapping_template(
+    bits: &BitStream,
+    template_len: usize,
+    block_len: usize,
+) -> OverlappingTemplateResult {
+    let template_len = template_len.max(1);
+    let blocks = bits.len() / block_len.max(1);
+    let mut counts = [0; OVERLAPPING_CLASSES];
+    for j in 0..blocks {
+        let mut run = 0;
+        let mut occurrences = 0;
+        for b in bits.bits(j * block_len, (j + 1) * block_len) {
+            run = if b == 1 { run + 1 } else { 0 };
+            occurrences += (run >= template_len) as usize;
+        }
+        counts[occurrences.min(OVERLAPPING_CLASSES - 1)] += 1;
+    }
+
+    let (chi_square, p_value) = if blocks == 0 || block_len <= template_len {
+        (f64::NAN, f64::NAN)
+    } else {
+        let probabilities = if (template_len, block_len) == (9, 1032) {
+            OVERLAPPING_PROBABILITIES_9_1032
+        } else {
+            overlapping_probabilities(template_len, block_len)
+        };
+        let n = blocks as f64;
+        let chi_square = counts
+            .iter()
+            .zip(probabilities)
+            .map(|(&v, p)| (v as f64 - n * p).powi(2) / (n * p))
+            .sum::<f64>();
+        let dof = (OVERLAPPING_CLASSES - 1) as f64;
+        (chi_square, chisquare_p_value(chi_square, dof))
+    };
+    OverlappingTemplateResult {
+        template_len,
+        block_len,
+        counts,
+        chi_square,
+        p_value,
+    }
+}
+
+/// The reference implementation's approximate class probabilities for a
+/// compound Poisson number of occurrences with `η = (M - m + 1) / 2^(m+1)`.
+fn overlapping_probabilities(m: usize, block_len: usize) -> [f64; OVERLAPPING_CLASSES] {
+    let eta = (block_len - m + 1) as f64 / (m as f64 + 1.0).exp2();
+    let mut probabilities = [0.0; OVERLAPPING_CLASSES];
+    probabilities[0] = (-eta).exp();
+    for (u, p) in probabilities
+        .iter_mut()
+        .enumerate()
+        .take(OVERLAPPING_CLASSES - 1)
+        .skip(1)
+    {
+        // Σ_l C(u-1, l-1) η^l / l! · e^(-η) / 2^u
+        let mut binomial = 1.0;
+        let mut power_over_factorial = 1.0;
+        let mut sum = 0.0;
+        for l in 1..=u {
+            power_over_factorial *= eta / l as f64;
+            sum += binomial * power_over_factorial;
+            binomial *= (u - l) as f64 / l as f64;
+        }
+        *p = sum * (-eta).exp() / (u as f64).exp2();
+    }
+    probabilities[OVERLAPPING_CLASSES - 1] =
+        1.0 - probabilities[..OVERLAPPING_CLASSES - 1].iter().sum::<f64>();
+    probabilities
+}
+
+/// Suite adapter for [`overlapping_template`] with the template and block
+/// lengths of [`NistParams`](crate::suite::NistParams).
+pub(crate) fn overlapping_template_test(data: &[u8], config: &SuiteConfig) -> Vec<f64> {
+    let params = &config.nist;
+    let bits = BitStream::from_bytes(data);
+    vec![
+        overlapping_template(
+            &bits,
+            params.overlapping_template_len,
+            params.overlapping_block_len,
+        )
+        .p_value,
+    ]
+}
+
+/// Longest pattern length of the serial test; its counts take 8 · 2^m bytes.
+pub const MAX_SERIAL_LEN: usize = 24;
+
+/// Result of the serial test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SerialResult {
+    /// Pattern length `m`.
+    pub pattern_len: usize,
+    /// `ψ²` for patterns of length `m`, `m - 1` and `m - 2`.
+    pub psi_squared: [f64; 3],
+    /// `∇ψ²_m = ψ²_m - ψ²_(m-1)`.
+    pub first_difference: f64,
+    /// `∇²ψ²_m = ψ²_m - 2ψ²_(m-1) + ψ²_(m-2)`.
+    pub second_difference: f64,
+    /// Upper-tail p-values of the first and second differences.
+    pub p_values: [f64; 2],
+}
+
+/// SP 800-22 §2.11 serial test: whether every overlapping `m`-bit pattern
+/// is about equally common, and likewise for `m - 1` and `m - 2` bits.
+///
+/// Patterns wrap around the end of the sequence. The standard recommends
+/// `m < log2(n) - 2`. The statistics are NaN if `m` is outside
+/// `2..=`[`MAX_SERIAL_LEN`] or the sequence is shorter than `m` bits.
+pub fn serial(bits: &BitStream, pattern_len: usize) -> SerialResult {
+    let m = pattern_len;
+    let n = bits.len();
+    if !(2..=MAX_SERIAL_LEN).contains(&m) || n < m {
+        return SerialResult {
+            pattern_len: m,
+            psi_squared: [f64::NAN; 3],
+            first_difference: f64::NAN,
+            second_difference: f64::NAN,
+            p_values: [f64::NAN; 2],
+        };
+    }
+
+    let mut counts = vec![0usize; 1 << m];
+    let window_mask = (1usize << m) - 1;
+    let mut window = 0;
+    for (i, b) in bits.iter().chain(bits.bits(0, m - 1)).enumerate() {
+        window = ((window << 1) | b as usize) & window_mask;
+        if i + 1 >= m {
+            counts[window] += 1;
+        }
+    }
+    let mut psi_squared = [0.0; 3];
+    for psi in &mut psi_squared {
+        let sum_sq = counts.iter().map(|&c| (c as f64).powi(2)).sum::<f64>();
+        *psi = counts.len() as f64 / n as f64 * sum_sq - n as f64;
+        // Counts of the (k - 1)-bit prefixes; wrapping makes them exact.
+        counts = counts.chunks(2).map(|pair| pair.iter().sum()).collect();
+    }
+    let [psi_m, psi_m1, psi_m2] = psi_squared;
+    let first_difference = psi_m - psi_m1;
+    let second_difference = psi_m - 2.0 * psi_m1 + psi_m2;
+    SerialResult {
+        pattern_len: m,
+        psi_squared,
+        first_difference,
+        second_difference,
+        p_values: [
+            chisquare_p_value(first_difference, (1 << (m - 1)) as f64),
+            chisquare_p_value(second_difference, (1 << (m - 2)) as f64),
+        ],
+    }
+}
+
+/// Suite adapter for [`serial`] with the pattern length of
+/// [`NistParams`](crate::suite::NistParams); both p-values.
+pub(crate) fn serial_test(data: &[u8], config: &SuiteConfig) -> Vec<f64> {
+    serial(&BitStream::from_bytes(data), config.nist.serial_len)
+        .p_values
+        .to_vec()
+}
+
+/// Result of the cumulative sums test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CumulativeSumsResult {
+    /// Largest `|S_k|` of the ±1 partial sums, forward and backward.
+    pub max_excursions: [usize; 2],
+    /// Two-sided p-values, forward and backward.
+    pub p_values: [f64; 2],
+}
+
+/// SP 800-22 §2.13 cumulative sums test: whether the random walk of the
+/// sequence mapped to ±1 strays too far from zero, summing from the start
+/// (forward) and from the end (backward).
+///
+/// The standard recommends at least 100 bits; the p-values are NaN for an
+/// empty sequence.
+pub fn cumulative_sums(bits: &BitStream) -> CumulativeSumsResult {
+    let n = bits.len();
+    // The backward walk's partial sums are S_n - S_k, so both maxima come
+    // from the forward sums.
+    let (mut sum, mut min, mut max) = (0i64, 0i64, 0i64);
+    for b in bits.iter() {
+        sum += 2 * b as i64 - 1;
+        min = min.min(sum);
+        max = max.max(sum);
+    }
+    let forward = min.unsigned_abs().max(max.unsigned_abs()) as usize;
+    let backward = (sum - min).unsigned_abs().max((sum - max).unsigned_abs()) as usize;
+    CumulativeSumsResult {
+        max_excursions: [forward, backward],
+        p_values: [
+            cumulative_sums_p_value(n, forward),
+            cumulative_sums_p_value(n, backward),
+        ],
+    }
+}
+
+/// The reference implementation's p-value for a maximum excursion `z` of an
+/// `n`-step walk, including its integer division for the summation bounds.
+fn cumulative_sums_p_value(n: usize, z: usize) -> f64 {
+    if n == 0 || z == 0 {
+        return f64::NAN;
+    }
+    let normal_cdf = |x: f64| 0.5 * erfc(-x / SQRT_2);
+    let (n, z) = (n as i64, z as i64);
+    let sqrt_n = (n as f64).sqrt();
+    let term =
+        |a: i64, b: i64| normal_cdf((a * z) as f64 / sqrt_n) - normal_cdf((b * z) as f64 / sqrt_n);
+    let first = ((-n / z + 1) / 4..=(n / z - 1) / 4)
+        .map(|k| term(4 * k + 1, 4 * k - 1))
+        .sum::<f64>();
+    let second = ((-n / z - 3) / 4..=(n / z - 1) / 4)
+        .map(|k| term(4 * k + 3, 4 * k + 1))
+        .sum::<f64>();
+    1.0 - first + second
+}
+
+/// Suite adapter for [`cumulative_sums`]; both p-values.
+pub(crate) fn cumulative_sums_test(data: &[u8], _: &SuiteConfig) -> Vec<f64> {
+    cumulative_sums(&BitStream::from_bytes(data))
+        .p_values
+        .to_vec()
+}
+
+/// States of the random excursions variant test, in result order.
+pub const VARIANT_STATES: [i32; 18] = [
+    -9, -8, -7, -6, -5, -4, -3, -2, -1, 1, 2, 3, 4, 5, 6, 7, 8, 9,
+];
+
+/// Result of the random excursions variant test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RandomExcursionsVariantResult {
+    /// Number of cycles `J`: excursions of the walk from zero back to zero,
+    /// counting an unfinished last one.
+    pub cycles: usize,
+    /// Whether `J ≥ max(500, 0.005 sqrt(n))`, below which the test does not
+    /// apply.
+    pub applicable: bool,
+    /// Visits `ξ(x)` to each of [`VARIANT_STATES`].
+    pub visits: [usize; 18],
+    /// Two-sided p-value for each of [`VARIANT_STATES`]; NaN if the test
+    /// does not apply.
+    pub p_values: [f64; 18],
+}
+
+/// SP 800-22 §2.15 random excursions variant test: whether the random walk
+/// of the sequence mapped to ±1 visits each state from -9 to 9 as often as
+/// expected, about `J` times.
+///
+/// Needs at least 500 cycles, which random sequences reach at around
+/// 10^6 bits.
+pub fn random_excursions_variant(bits: &BitStream) -> RandomExcursionsVariantResult {
+    let mut sum = 0i64;
+    let mut zeros = 0;
+    let mut visits = [0; 18];
+    for b in bits.iter() {
+        sum += 2 * b as i64 - 1;
+        match sum {
+            0 => zeros += 1,
+            -9..=-1 => visits[(sum + 9) as usize] += 1,
+            1..=9 => visits[(sum + 8) as usize] += 1,
+            _ => {}
+        }
+    }
+    let cycles = zeros + (sum != 0) as usize;
+    let applicable = cycles as f64 >= (0.005 * (bits.len() as f64).sqrt()).max(500.0);
+    let mut p_values = [f64::NAN; 18];
+    if applicable {
+        for ((p, &x), &visits) in p_values.iter_mut().zip(&VARIANT_STATES).zip(&visits) {
+            *p = variant_p_value(x, visits, cycles);
+        }
+    }
+    RandomExcursionsVariantResult {
+        cycles,
+        applicable,
+        visits,
+        p_values,
+    }
+}
+
+fn variant_p_value(state: i32, visits: usize, cycles: usize) -> f64 {
+    let deviation = (visits as f64 - cycles as f64).abs();
+    erfc(deviation / (2.0 * cycles as f64 * (4.0 * state.abs() as f64 - 2.0)).sqrt())
+}
+
+/// Suite adapter for [`random_excursions_variant`]; one p-value per state,
+/// all NaN if the test does not apply.
+pub(crate) fn random_excursions_variant_test(data: &[u8], _: &SuiteConfig) -> Vec<f64> {
+    random_excursions_variant(&BitStream::from_bytes(data))
+        .p_values
+        .to_vec()
+}
+
+/// Suite adapter: one p-value per template, using the custom templates of
+/// [`NistParams`](crate::suite::NistParams) if set and otherwise all
+/// aperiodic templates of the configured length.
+pub(crate) fn non_overlapping_template_test(data: &[u8], config: &SuiteConfig) -> Vec<f64> {
+    let params = &config.nist;
+    let bits = BitStream::from_bytes(data);
+    let templates = match &params.templates {
+        Some(templates) => Cow::Borrowed(templates.as_slice()),
+        None => Cow::Owned(aperiodic_templates(params.template_len)),
+    };
+    non_overlapping_templates(&bits, &templates, params.template_blocks)
+        .into_iter()
+        .map(|r| r.p_value)
+        .collect()
+}
+
+fn count_non_overlapping(bits: &BitStream, start: usize, len: usize, template: &Template) -> usize {
+    let m = template.len();
+    let window_mask = mask(m);
+    let mut window = 0u32;
+    let mut filled = 0;
+    let mut count = 0;
+    for b in bits.bits(start, start + len) {
+        window = ((window << 1) | b as u32) & window_mask;
+        filled += 1;
+        if filled >= m && window == template.bits() {
+            count += 1;
+            filled = 0;
+        }
+    }
+    count
+}
+
+fn mask(len: usize) -> u32 {
+    if len >= 32 {
+        u32::MAX
+    } else {
+        (1 << len) - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frequency_matches_reference_examples() {
+        // SP 800-22 §2.1.4 and §2.1.8.
+        let result = frequency(&"1011010101".parse().unwrap());
+        assert_eq!(result.sum, 2);
+        assert!((result.statistic - 0.632456).abs() < 1e-6);
+        assert!((result.p_value - 0.527089).abs() < 1e-6);
+        let bits: BitStream = "11001001000011111101101010100010001000010110100011\
+                               00001000110100110001001100011001100010100010111000"
+            .parse()
+            .unwrap();
+        let result = frequency(&bits);
+        assert_eq!(result.sum, -16);
+        assert!((result.p_value - 0.109599).abs() < 1e-6);
+        assert!(frequency(&BitStream::from_bytes(&[])).p_value.is_nan());
+    }
+
+    #[test]
+    fn test_block_frequency_matches_reference_examples() {
+        // SP 800-22 §2.2.4 and §2.2.8.
+        let result = block_frequency(&"0110011010".parse().unwrap(), 3);
+        assert_eq!(result.counts, [2, 1, 2]);
+        assert!((result.chi_square - 1.0).abs() < 1e-12);
+        assert!((result.p_value - 0.801252).abs() < 1e-6);
+        let bits: BitStream = "11001001000011111101101010100010001000010110100011\
+                               00001000110100110001001100011001100010100010111000"
+            .parse()
+            .unwrap();
+        let result = block_frequency(&bits, 10);
+        assert!((result.chi_square - 7.2).abs() < 1e-9);
+        assert!((result.p_value - 0.706438).abs() < 1e-6);
+        assert!(block_frequency(&bits, 101).p_value.is_nan());
+    }
+
+    #[test]
+    fn test_runs_matches_reference_examples() {
+        // SP 800-22 §2.3.4 and §2.3.8.
+        let result = runs(&"1001101011".parse().unwrap());
+        assert_eq!(result.runs, 7);
+        assert!(result.prerequisite_met);
+        assert!((result.p_value - 0.147232).abs() < 1e-6);
+        let bits: BitStream = "11001001000011111101101010100010001000010110100011\
+                               00001000110100110001001100011001100010100010111000"
+            .parse()
+            .unwrap();
+        let result = runs(&bits);
+        assert!((result.ones_fraction - 0.42).abs() < 1e-12);
+        assert_eq!(result.runs, 52);
+        assert!((result.p_value - 0.500798).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_runs_flags_oscillation_and_bias() {
+        // Alternating bits are perfectly balanced but switch every bit.
+        let result = runs(&BitStream::from_bytes(&[0x55; 1000]));
+        assert!(result.prerequisite_met);
+        assert_eq!(result.runs, 8000);
+        assert!(result.p_value < 1e-10);
+        let result = runs(&BitStream::from_bytes(&[0xF7; 1000]));
+        assert!(!result.prerequisite_met);
+        assert_eq!(result.p_value, 0.0);
+    }
+
+    #[test]
+    fn test_longest_run_matches_reference_example() {
+        // SP 800-22 §2.4.8.
+        let bits: BitStream = "11001100000101010110110001001100111000000000001001\
+                               00110101010001000100111101011010000000110101111100\
+                               1100111001101101100010110010"
+            .parse()
+            .unwrap();
+        let result = longest_run_of_ones(&bits);
+        assert_eq!(result.block_len, 8);
+        assert_eq!(result.counts, [4, 9, 3, 0]);
+        // The example rounds the class probabilities to four places.
+        assert!((result.chi_square - 4.882605).abs() < 1e-3);
+        assert!((result.p_value - 0.180609).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_longest_run_block_lengths() {
+        let block_len = |bytes: usize| {
+            let data: Vec<u8> = (0..bytes as u32)
+                .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+                .collect();
+            longest_run_of_ones(&BitStream::from_bytes(&data)).block_len
+        };
+        assert_eq!(block_len(15), 0);
+        assert_eq!(block_len(16), 8);
+        assert_eq!(block_len(784), 128);
+        assert_eq!(block_len(93_750), 10_000);
+        // Long runs of ones in every block.
+        let result = longest_run_of_ones(&BitStream::from_bytes(&[0xFF, 0xFF, 0, 0].repeat(256)));
+        assert_eq!(result.counts, [0, 0, 0, 0, 0, 64]);
+        assert!(result.p_value < 1e-10);
+    }
+
+    #[test]
+    fn test_spectral_counts_peaks() {
+        // The sequences of SP 800-22 §2.6.4 and §2.6.8. The standard's worked
+        // results (N_1 = 4 and 46) do not follow from its own algorithm; these
+        // values were checked against a direct evaluation of the DFT.
+        let result = spectral(&"1001010011".parse().unwrap());
+        assert_eq!(result.peaks_below, 5);
+        assert!((result.expected_below - 4.75).abs() < 1e-12);
+        assert!((result.p_value - 0.468160).abs() < 1e-6);
+        let bits: BitStream = "11001001000011111101101010100010001000010110100011\
+                               00001000110100110001001100011001100010100010111000"
+            .parse()
+            .unwrap();
+        let result = spectral(&bits);
+        assert_eq!(result.peaks_below, 48);
+        assert!((result.statistic - 0.458831).abs() < 1e-6);
+        assert!((result.p_value - 0.646355).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_spectral_detects_periodic_pattern() {
+        let noise: Vec<u8> = (0..10_000u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+            .collect();
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        let random: Vec<u8> = (0..10_000)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 56) as u8
+            })
+            .collect();
+        assert!(spectral(&BitStream::from_bytes(&random)).p_value > 0.001);
+        // A Weyl sequence repeats its structure with a fixed stride.
+        assert!(spectral(&BitStream::from_bytes(&noise)).p_value < 1e-6);
+    }
+
+    #[test]
+    fn test_overlapping_template_counts() {
+        // The sequence of SP 800-22 §2.8.4. The worked example miscounts its
+        // second and third blocks, which hold 1 and 3 occurrences of "11".
+        let bits: BitStream = "10111011110010110100011100101110111110000101101001"
+            .parse()
+            .unwrap();
+        let result = overlapping_template(&bits, 2, 10);
+        assert_eq!(result.counts, [0, 2, 0, 1, 1, 1]);
+        assert!((overlapping_probabilities(2, 10)[1] - 0.182617).abs() < 1e-6);
+        assert!((result.chi_square - 5.051441).abs() < 1e-6);
+        assert!((result.p_value - 0.409635).abs() < 1e-6);
+        assert!(overlapping_template(&bits, 10, 10).p_value.is_nan());
+    }
+
+    #[test]
+    fn test_overlapping_template_flags_runs_of_ones() {
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        let mut data: Vec<u8> = (0..200_000)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 56) as u8
+            })
+            .collect();
+        let result = overlapping_template(&BitStream::from_bytes(&data), 9, 1032);
+        assert_eq!(result.counts.iter().sum::<usize>(), 1550);
+        assert!(result.p_value > 0.001);
+        // A stuck-high burst of 16 bits in every 1 KiB.
+        for chunk in data.chunks_mut(1024) {
+            chunk[..2].copy_from_slice(&[0xFF, 0xFF]);
+        }
+        let result = overlapping_template(&BitStream::from_bytes(&data), 9, 1032);
+        assert!(result.p_value < 1e-10);
+    }
+
+    #[test]
+    fn test_serial_matches_reference_example() {
+        // SP 800-22 §2.11.4.
+        let result = serial(&"0011011101".parse().unwrap(), 3);
+        let [psi_3, psi_2, psi_1] = result.psi_squared;
+        assert!((psi_3 - 2.8).abs() < 1e-12);
+        assert!((psi_2 - 1.2).abs() < 1e-12);
+        assert!((psi_1 - 0.4).abs() < 1e-12);
+        assert!((result.first_difference - 1.6).abs() < 1e-12);
+        assert!((result.second_difference - 0.8).abs() < 1e-12);
+        assert!((result.p_values[0] - 0.808792).abs() < 1e-6);
+        assert!((result.p_values[1] - 0.670320).abs() < 1e-6);
+        assert!(serial(&"0011".parse().unwrap(), 5).p_values[0].is_nan());
+        assert!(serial(&"0011".parse().unwrap(), 1).p_values[0].is_nan());
+    }
+
+    #[test]
+    fn test_serial_detects_pattern_bias() {
+        // Each byte's low nibble repeats its high nibble: bytes and bits are
+        // balanced, but half of all 8-bit patterns never occur.
+        let data: Vec<u8> = (0..20_000u32)
+            .map(|i| {
+                let nibble = (i.wrapping_mul(2_654_435_761) >> 28) as u8;
+                nibble << 4 | nibble
+            })
+            .collect();
+        let bits = BitStream::from_bytes(&data);
+        assert!(frequency(&bits).p_value > 0.01);
+        assert!(serial(&bits, 8).p_values[0] < 1e-10);
+    }
+
+    #[test]
+    fn test_cumulative_sums_matches_reference_examples() {
+        // SP 800-22 §2.13.4 and §2.13.8.
+        let result = cumulative_sums(&"1011010111".parse().unwrap());
+        assert_eq!(result.max_excursions[0], 4);
+        assert!((result.p_values[0] - 0.411659).abs() < 1e-6);
+        let bits: BitStream = "11001001000011111101101010100010001000010110100011\
+                               00001000110100110001001100011001100010100010111000"
+            .parse()
+            .unwrap();
+        let result = cumulative_sums(&bits);
+        assert_eq!(result.max_excursions, [16, 19]);
+        assert!((result.p_values[0] - 0.219194).abs() < 1e-6);
+        assert!((result.p_values[1] - 0.114866).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_cumulative_sums_flags_drift() {
+        // Balanced overall, but all the ones come first.
+        let mut data = vec![0xFFu8; 500];
+        data.extend([0u8; 500]);
+        let result = cumulative_sums(&BitStream::from_bytes(&data));
+        assert_eq!(result.max_excursions, [4000, 4000]);
+        assert!(result.p_values.iter().all(|&p| p < 1e-10));
+        assert!(frequency(&BitStream::from_bytes(&data)).p_value > 0.99);
+    }
+
+    #[test]
+    fn test_random_excursions_variant_counts_and_example() {
+        // SP 800-22 §2.15.4; far too short for the test to apply.
+        let result = random_excursions_variant(&"0110110101".parse().unwrap());
+        assert_eq!(result.cycles, 3);
+        assert_eq!(result.visits[9..12], [4, 3, 0]);
+        assert!(!result.applicable);
+        assert!(result.p_values.iter().all(|p| p.is_nan()));
+        assert!((variant_p_value(1, 4, 3) - 0.683091).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_random_excursions_variant_on_random_and_biased_walks() {
+        // A seed whose walk completes enough cycles for the test to apply.
+        let mut x = 7u64;
+        let data: Vec<u8> = (0..125_000)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 56) as u8
+            })
+            .collect();
+        let result = random_excursions_variant(&BitStream::from_bytes(&data));
+        assert_eq!(result.cycles, 1017);
+        assert!(result.applicable);
+        assert!(result.p_values.iter().all(|&p| p > 1e-4));
+        // Alternating bits: the walk shuttles between -1 and 0, returning to
+        // zero every other step and never reaching the other states.
+        let result = random_excursions_variant(&BitStream::from_bytes(&[0x55; 1000]));
+        assert_eq!(result.cycles, 4000);
+        assert_eq!(result.visits[8], 4000);
+        assert_eq!(result.p_values[8], 1.0);
+        assert!(result.p_values[9] < 1e-10);
+        assert!(result.p_values[17] < 1e-10);
+    }
+
+    #[test]
+    fn test_aperiodic_template_counts_match_reference() {
+        let counts: Vec<usize> = (2..=10).map(|m| aperiodic_templates(m).len()).collect();
+        assert_eq!(counts, [2, 4, 6, 12, 20, 40, 74, 148, 284]);
+        let m9 = aperiodic_templates(9);
+        assert_eq!(m9[0].to_string(), "000000001");
+        assert_eq!(m9[147].to_string(), "111111110");
+        assert!(!"101".parse::<Template>().unwrap().is_aperiodic());
+    }
+
+    #[test]
+    fn test_custom_template_finds_stuck_pattern() {
+        // A source that periodically emits 0xDEAD.
+        let mut data: Vec<u8> = (0..1u32 << 14)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+            .collect();
+        for chunk in data.chunks_mut(64) {
+            chunk[..2].copy_from_slice(&[0xDE, 0xAD]);
+        }
+        let bits = BitStream::from_bytes(&data);
+        let signature: Template = "1101111010101101".parse().unwrap();
+        let result = non_overlapping_template(&bits, &signature, 8);
+        assert!(result.counts.iter().all(|&c| c >= 32));
+        assert!(result.p_value < 1e-6);
+    }
+
+    #[test]
+    fn test_bitstream_parse_and_count() {
+        let bits: BitStream = "1011 0000 1".parse().unwrap();
+        assert_eq!(bits.len(), 9);
+        assert_eq!(bits.count_ones(), 4);
+        assert_eq!(bits.iter().collect::<Vec<_>>(), [1, 0, 1, 1, 0, 0, 0, 0, 1]);
+        assert!("10x".parse::<BitStream>().is_err());
+    }
+}
@@ -0,0 +1,184 @@
+//! Analysis of two byte streams side by side.
+//!
+//! ```rust
+//! use ent_rs::pair::cross_correlation;
+//! let a: Vec<u8> = (0..2000u32).map(|i| (i * 7919 % 251) as u8).collect();
+//! let b = [&[0u8; 5][..], &a].concat(); // b is a delayed by 5 bytes
+//! let xc = cross_correlation(&a, &b, 10);
+//! assert_eq!(xc.peak_lag, 5);
+//! assert!(xc.peak > 0.99);
+//! ```
+
+use crate::EntStats;
+
+/// Correlation coefficients of two streams over a range of lags.
+#[derive(Debug, Clone)]
+pub struct CrossCorrelation {
+    /// `(lag, coefficient)` for every lag from `-max_lag` to `max_lag`. A
+    /// positive lag pairs `a[i]` with `b[i + lag]`. The coefficient is NaN
+    /// when either side of the overlap is constant.
+    pub lags: Vec<(isize, f64)>,
+    /// Lag with the largest absolute correlation.
+    pub peak_lag: isize,
+    /// Correlation at `peak_lag` (NaN if no lag was defined).
+    pub peak: f64,
+}
+
+/// Pearson correlation between `a` and `b` shifted by every lag in
+/// `-max_lag..=max_lag`.
+///
+/// Independent sources should show no peak; a shifted copy shows a peak near 1
+/// at the shift.
+pub fn cross_correlation(a: &[u8], b: &[u8], max_lag: usize) -> CrossCorrelation {
+    let max_lag = max_lag as isize;
+    let lags: Vec<(isize, f64)> = (-max_lag..=max_lag)
+        .map(|lag| {
+            let (xs, ys) = if lag >= 0 {
+                let lag = lag as usize;
+                (a, b.get(lag..).unwrap_or(&[]))
+            } else {
+                (a.get(lag.unsigned_abs()..).unwrap_or(&[]), b)
+            };
+            (lag, pearson(xs, ys))
+        })
+        .collect();
+
+    let (peak_lag, peak) = lags
+        .iter()
+        .copied()
+        .filter(|(_, r)| !r.is_nan())
+        .max_by(|x, y| x.1.abs().total_cmp(&y.1.abs()))
+        .unwrap_or((0, f64::NAN));
+
+    CrossCorrelation {
+        lags,
+        peak_lag,
+        peak,
+    }
+}
+
+/// Statistics of two streams and of their byte-wise XOR.
+#[derive(Debug, Clone)]
+pub struct XorAnalysis {
+    /// Statistics of the first stream.
+    pub a: EntStats,
+    /// Statistics of the second stream.
+    pub b: EntStats,
+    /// Statistics of `a[i] ^ b[i]`.
+    pub combined: EntStats,
+    /// Entropy of the combination minus the larger input entropy.
+    ///
+    /// XOR of independent sources is never less random than either input, so
+    /// a clearly negative gain means the sources share structure.
+    pub entropy_gain: f64,
+}
+
+/// Byte-wise XOR of two streams, truncated to the shorter one.
+pub fn xor_streams(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b).map(|(&x, &y)| x ^ y).collect()
+}
+
+/// Analyze `a[i] ^ b[i]` and compare it against each input.
+///
+/// Both inputs are truncated to the shorter length so all three statistics
+/// describe the same positions.
+pub fn xor_analysis(a: &[u8], b: &[u8], bit_mode: bool) -> XorAnalysis {
+    let len = a.len().min(b.len());
+    let (a, b) = (&a[..len], &b[..len]);
+    let a_stats = EntStats::from_data(a, bit_mode);
+    let b_stats = EntStats::from_data(b, bit_mode);
+    let combined = EntStats::from_data(&xor_streams(a, b), bit_mode);
+    let entropy_gain = combined.entropy - a_stats.entropy.max(b_stats.entropy);
+
+    XorAnalysis {
+        a: a_stats,
+        b: b_stats,
+        combined,
+        entropy_gain,
+    }
+}
+
+/// Pearson correlation of two byte sequences over their common length.
+pub(crate) fn pearson(xs: &[u8], ys: &[u8]) -> f64 {
+    let n = xs.len().min(ys.len());
+    if n < 2 {
+        return f64::NAN;
+    }
+    let (mut sx, mut sy, mut sxy, mut sx2, mut sy2) = (0u64, 0u64, 0u64, 0u64, 0u64);
+    for (&x, &y) in xs.iter().zip(ys) {
+        let (x, y) = (x as u64, y as u64);
+        sx += x;
+        sy += y;
+        sxy += x * y;
+        sx2 += x * x;
+        sy2 += y * y;
+    }
+    let n = n as f64;
+    let (sx, sy) = (sx as f64, sy as f64);
+    let num = n * sxy as f64 - sx * sy;
+    let denom = ((n * sx2 as f64 - sx * sx) * (n * sy2 as f64 - sy * sy)).sqrt();
+    if denom == 0.0 {
+        f64::NAN
+    } else {
+        num / denom
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noise(len: usize, seed: u64) -> Vec<u8> {
+        let mut x = seed;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_independent_sources_have_no_peak() {
+        let xc = cross_correlation(&noise(50_000, 1), &noise(50_000, 2), 16);
+        assert_eq!(xc.lags.len(), 33);
+        assert!(xc.peak.abs() < 0.05);
+    }
+
+    #[test]
+    fn test_negative_lag_and_inversion() {
+        let a = noise(4000, 7);
+        let b: Vec<u8> = a[3..].iter().map(|&v| 255 - v).collect();
+        let xc = cross_correlation(&a, &b, 8);
+        assert_eq!(xc.peak_lag, -3);
+        assert!(xc.peak < -0.999);
+    }
+
+    #[test]
+    fn test_xor_whitens_biased_independent_sources() {
+        let biased =
+            |seed| -> Vec<u8> { noise(20_000, seed).iter().map(|&v| v & v >> 1).collect() };
+        let report = xor_analysis(&biased(11), &biased(12), true);
+        assert!(report.entropy_gain > 0.0);
+        assert!(report.combined.entropy > report.a.entropy);
+    }
+
+    #[test]
+    fn test_xor_reveals_shared_structure() {
+        let a = noise(20_000, 5);
+        let b: Vec<u8> = a.iter().map(|&v| v ^ 0x0F).collect();
+        let report = xor_analysis(&a, &b, false);
+        assert_eq!(report.combined.entropy, 0.0);
+        assert!(report.entropy_gain < -7.0);
+        assert_eq!(xor_streams(&[1, 2, 3], &[1, 2]), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_constant_stream_is_undefined() {
+        let xc = cross_correlation(&[5; 100], &noise(100, 3), 2);
+        assert!(xc.peak.is_nan());
+        assert!(xc.lags.iter().all(|(_, r)| r.is_nan()));
+    }
+}
@@ -0,0 +1,87 @@
+//! Multi-threaded analysis on the rayon thread pool.
+//!
+//! The input is split into chunks, each chunk is fed to its own
+//! [`EntAccumulator`], and the partial states are merged in order, so the
+//! result is identical to a single-threaded pass.
+
+use rayon::prelude::*;
+
+use crate::{instrument, EntAccumulator, EntStats};
+
+/// Bytes per parallel work item: large enough to amortize scheduling, small
+/// enough to balance load across many cores.
+const CHUNK_SIZE: usize = 4 << 20;
+
+impl EntStats {
+    /// Like [`EntStats::from_data`], using all threads of the current rayon
+    /// pool.
+    ///
+    /// ```rust
+    /// use ent_rs::EntStats;
+    /// let data: Vec<u8> = (0u32..1 << 20).map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8).collect();
+    /// let stats = EntStats::par_from_data(&data, false);
+    /// assert_eq!(stats.entropy, EntStats::from_data(&data, false).entropy);
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(data)))]
+    pub fn par_from_data(data: &[u8], bit_mode: bool) -> Self {
+        let mut acc = EntAccumulator::new(bit_mode);
+        acc.par_update(data);
+        acc.finalize()
+    }
+}
+
+impl EntAccumulator {
+    /// Like [`EntAccumulator::update`], splitting `chunk` across the threads
+    /// of the current rayon pool.
+    pub fn par_update(&mut self, chunk: &[u8]) {
+        let timer = instrument::Timer::start();
+        self.par_update_in(chunk, CHUNK_SIZE);
+        timer.finish("par_update", chunk.len());
+    }
+
+    fn par_update_in(&mut self, chunk: &[u8], piece_size: usize) {
+        let bit_mode = self.bit_mode();
+        let start = self.len();
+        let combined = chunk
+            .par_chunks(piece_size)
+            .enumerate()
+            .map(|(i, piece)| {
+                let mut acc = EntAccumulator::at_offset(bit_mode, (start + i * piece_size) as u64);
+                acc.update(piece);
+                acc
+            })
+            .reduce(
+                || EntAccumulator::new(bit_mode),
+                |mut left, right| {
+                    left.merge(&right);
+                    left
+                },
+            );
+        self.merge(&combined);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        // Many pieces, none a multiple of 6 long.
+        let data: Vec<u8> = (0u32..100_000)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+            .collect();
+        for bit_mode in [false, true] {
+            let mut acc = EntAccumulator::new(bit_mode);
+            acc.update(&data[..5]);
+            acc.par_update_in(&data[5..], 1001);
+            let parallel = acc.finalize();
+            let sequential = EntStats::from_data(&data, bit_mode);
+            assert_eq!(parallel.entropy, sequential.entropy);
+            assert_eq!(parallel.chisquare, sequential.chisquare);
+            assert_eq!(parallel.pi_estimate, sequential.pi_estimate);
+            assert_eq!(parallel.serial_correlation, sequential.serial_correlation);
+            assert_eq!(parallel.bit_frequencies, sequential.bit_frequencies);
+        }
+    }
+}
@@ -0,0 +1,294 @@
+//! Percentile ranks of statistics among those of ideal random data.
+//!
+//! A raw serial correlation of 0.012 means little without knowing how large
+//! it typically is for the sample size. Ranking each metric within its
+//! distribution for truly uniform data of the same length answers that
+//! directly: "the serial correlation is at the 99.7th percentile".
+//!
+//! Two reference distributions are available. [`Percentiles::asymptotic`]
+//! uses the closed-form large-sample distributions (chi-square with 255 or 1
+//! degrees of freedom, normal for the mean, Pi estimate and correlation) and
+//! is instant. [`Simulation`] draws the statistics of many pseudo-random
+//! inputs of the exact length, which is slower but also correct for short
+//! inputs where the asymptotic forms are poor.
+//!
+//! ```rust
+//! use ent_rs::percentile::Percentiles;
+//! use ent_rs::EntStats;
+//! let ramp: Vec<u8> = (0..=255).cycle().take(1 << 16).collect();
+//! let ranks = Percentiles::asymptotic(&EntStats::from_data(&ramp, false));
+//! // A perfectly flat histogram is more uniform than almost any random input.
+//! assert!(ranks.chisquare < 0.1);
+//! // ...and its adjacent bytes are perfectly correlated.
+//! assert!(ranks.serial_correlation.unwrap() > 99.9);
+//! ```
+
+use std::f64::consts::{FRAC_PI_4, LN_2, SQRT_2};
+use std::fmt;
+
+use crate::special::erfc;
+use crate::{chisquare_p_value, EntStats};
+
+/// Percentile (0 to 100) of each metric within its distribution for ideal
+/// random data of the same length and mode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Percentiles {
+    /// Entropy; low percentiles mean less entropy than random data.
+    pub entropy: f64,
+    /// Chi-square statistic; high percentiles mean a less uniform histogram.
+    pub chisquare: f64,
+    /// Arithmetic mean.
+    pub mean: f64,
+    /// Monte Carlo Pi estimate; NaN with fewer than six bytes.
+    pub pi_estimate: f64,
+    /// Serial correlation; `None` when undefined for the sample.
+    pub serial_correlation: Option<f64>,
+}
+
+impl Percentiles {
+    /// Rank `stats` using the large-sample reference distributions.
+    ///
+    /// The input length and mode are taken from the frequency table of
+    /// `stats`. Reasonable from a few thousand bytes on; use a
+    /// [`Simulation`] for shorter inputs.
+    pub fn asymptotic(stats: &EntStats) -> Self {
+        let (bytes, symbols, dof) = shape(stats);
+        let n = bytes as f64;
+
+        // Entropy and chi-square are linked by H ≈ log2(k) - X² / (2 N ln 2),
+        // so the entropy deficit maps to an equivalent chi-square statistic.
+        let max_entropy = (dof + 1.0).log2();
+        let equivalent = (max_entropy - stats.entropy) * 2.0 * symbols as f64 * LN_2;
+        let entropy = 100.0 * chisquare_p_value(equivalent.max(0.0), dof);
+        let chisquare = 100.0 * (1.0 - chisquare_p_value(stats.chisquare, dof));
+
+        // Uniform bytes have mean 127.5 and variance (256² - 1) / 12.
+        let mean = normal_percentile(stats.mean, 127.5, (65535.0 / 12.0 / n).sqrt());
+        let tuples = (bytes / 6) as f64;
+        let p = FRAC_PI_4;
+        let pi_estimate = if tuples > 0.0 {
+            normal_percentile(
+                stats.pi_estimate,
+                4.0 * p,
+                4.0 * (p * (1.0 - p) / tuples).sqrt(),
+            )
+        } else {
+            f64::NAN
+        };
+        let serial_correlation = defined(stats.serial_correlation)
+            .then(|| normal_percentile(stats.serial_correlation, -1.0 / n, 1.0 / n.sqrt()));
+
+        Percentiles {
+            entropy,
+            chisquare,
+            mean,
+            pi_estimate,
+            serial_correlation,
+        }
+    }
+
+    /// Rank `stats` within simulated reference distributions.
+    ///
+    /// # Panics
+    ///
+    /// If `stats` does not have the simulation's length and mode.
+    pub fn simulated(stats: &EntStats, simulation: &Simulation) -> Self {
+        let (bytes, _, _) = shape(stats);
+        assert_eq!(
+            (bytes, stats.bit_frequencies.is_some()),
+            (simulation.len, simulation.bit_mode),
+            "statistics and simulation differ in length or mode"
+        );
+        Percentiles {
+            entropy: rank(&simulation.entropy, stats.entropy),
+            chisquare: rank(&simulation.chisquare, stats.chisquare),
+            mean: rank(&simulation.mean, stats.mean),
+            pi_estimate: rank(&simulation.pi_estimate, stats.pi_estimate),
+            serial_correlation: defined(stats.serial_correlation)
+                .then(|| rank(&simulation.serial_correlation, stats.serial_correlation)),
+        }
+    }
+
+    /// Largest distance of any percentile from the median, in percentage
+    /// points (0 to 50); the serial correlation counts only if defined.
+    pub fn max_deviation(&self) -> f64 {
+        [self.entropy, self.chisquare, self.mean, self.pi_estimate]
+            .into_iter()
+            .chain(self.serial_correlation)
+            .filter(|p| !p.is_nan())
+            .map(|p| (p - 50.0).abs())
+            .fold(0.0, f64::max)
+    }
+}
+
+impl fmt::Display for Percentiles {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "entropy {:.1}th, chi-square {:.1}th, mean {:.1}th, pi {:.1}th",
+            self.entropy, self.chisquare, self.mean, self.pi_estimate
+        )?;
+        if let Some(p) = self.serial_correlation {
+            write!(f, ", serial correlation {p:.1}th")?;
+        }
+        f.write_str(" percentile")
+    }
+}
+
+/// Sorted statistics of simulated uniform random inputs of one length.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Simulation {
+    /// Bytes per simulated input.
+    pub len: usize,
+    /// Whether the inputs were analyzed in bit mode.
+    pub bit_mode: bool,
+    entropy: Vec<f64>,
+    chisquare: Vec<f64>,
+    mean: Vec<f64>,
+    pi_estimate: Vec<f64>,
+    serial_correlation: Vec<f64>,
+}
+
+impl Simulation {
+    /// Analyze `trials` pseudo-random inputs of `len` bytes generated from
+    /// `seed`.
+    ///
+    /// Percentiles resolve to about `100 / trials`; 1000 trials give a
+    /// tenth of a percentile. The generator is SplitMix64, whose output is
+    /// indistinguishable from uniform for these statistics.
+    pub fn run(len: usize, trials: usize, bit_mode: bool, seed: u64) -> Self {
+        let mut state = seed;
+        let mut buf = vec![0u8; len];
+        let mut simulation = Simulation {
+            len,
+            bit_mode,
+            entropy: Vec::with_capacity(trials),
+            chisquare: Vec::with_capacity(trials),
+            mean: Vec::with_capacity(trials),
+            pi_estimate: Vec::with_capacity(trials),
+            serial_correlation: Vec::with_capacity(trials),
+        };
+        for _ in 0..trials {
+            for chunk in buf.chunks_mut(8) {
+                let word = splitmix64(&mut state).to_le_bytes();
+                chunk.copy_from_slice(&word[..chunk.len()]);
+            }
+            let stats = EntStats::from_data(&buf, bit_mode);
+            simulation.entropy.push(stats.entropy);
+            simulation.chisquare.push(stats.chisquare);
+            simulation.mean.push(stats.mean);
+            simulation.pi_estimate.push(stats.pi_estimate);
+            if defined(stats.serial_correlation) {
+                simulation.serial_correlation.push(stats.serial_correlation);
+            }
+        }
+        for values in [
+            &mut simulation.entropy,
+            &mut simulation.chisquare,
+            &mut simulation.mean,
+            &mut simulation.pi_estimate,
+            &mut simulation.serial_correlation,
+        ] {
+            values.sort_by(f64::total_cmp);
+        }
+        simulation
+    }
+
+    /// Number of simulated inputs.
+    pub fn trials(&self) -> usize {
+        self.entropy.len()
+    }
+}
+
+/// Input length in bytes, number of symbols counted, and chi-square degrees
+/// of freedom, from the frequency table of `stats`.
+fn shape(stats: &EntStats) -> (usize, usize, f64) {
+    match (&stats.bit_frequencies, &stats.byte_frequencies) {
+        (Some(bits), _) => {
+            let symbols = bits[0].0 + bits[1].0;
+            (symbols / 8, symbols, 1.0)
+        }
+        (None, Some(bytes)) => {
+            let symbols = bytes.iter().map(|&(_, c, _)| c).sum();
+            (symbols, symbols, 255.0)
+        }
+        (None, None) => (0, 0, 255.0),
+    }
+}
+
+fn defined(serial_correlation: f64) -> bool {
+    serial_correlation != -99999.0
+}
+
+fn normal_percentile(x: f64, mean: f64, sd: f64) -> f64 {
+    50.0 * erfc(-(x - mean) / (sd * SQRT_2))
+}
+
+/// Mid-rank percentile of `x` in the sorted `values`.
+fn rank(values: &[f64], x: f64) -> f64 {
+    if values.is_empty() || x.is_nan() {
+        return f64::NAN;
+    }
+    let below = values.partition_point(|&v| v < x);
+    let not_above = values.partition_point(|&v| v <= x);
+    100.0 * (below + not_above) as f64 / (2 * values.len()) as f64
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random(len: usize, seed: u64) -> Vec<u8> {
+        let mut state = seed;
+        (0..len).map(|_| splitmix64(&mut state) as u8).collect()
+    }
+
+    #[test]
+    fn test_asymptotic_ranks_of_random_data_are_unremarkable() {
+        let stats = EntStats::from_data(&random(1 << 16, 42), false);
+        let ranks = Percentiles::asymptotic(&stats);
+        assert!(ranks.max_deviation() < 49.5, "{ranks}");
+        // Entropy and chi-square are mirror images.
+        assert!(
+            (ranks.entropy + ranks.chisquare - 100.0).abs() < 1.0,
+            "{ranks}"
+        );
+    }
+
+    #[test]
+    fn test_simulated_ranks_agree_with_asymptotic() {
+        let simulation = Simulation::run(4096, 400, false, 7);
+        assert_eq!(simulation.trials(), 400);
+        let sample = random(4096, 12345);
+        let stats = EntStats::from_data(&sample, false);
+        let simulated = Percentiles::simulated(&stats, &simulation);
+        let asymptotic = Percentiles::asymptotic(&stats);
+        assert!((simulated.chisquare - asymptotic.chisquare).abs() < 10.0);
+        assert!((simulated.mean - asymptotic.mean).abs() < 10.0);
+
+        // Correlated data lands in the extreme tail.
+        let smoothed: Vec<u8> = sample
+            .windows(2)
+            .map(|w| ((w[0] as u16 + w[1] as u16) / 2) as u8)
+            .chain([0])
+            .collect();
+        let ranks = Percentiles::simulated(&EntStats::from_data(&smoothed, false), &simulation);
+        assert_eq!(ranks.serial_correlation, Some(100.0));
+    }
+
+    #[test]
+    fn test_bit_mode_shape() {
+        let stats = EntStats::from_data(&random(1000, 1), true);
+        assert_eq!(shape(&stats), (1000, 8000, 1.0));
+        let ranks = Percentiles::asymptotic(&stats);
+        assert!((0.0..=100.0).contains(&ranks.entropy));
+    }
+}
@@ -0,0 +1,327 @@
+//! Entropy-vs-offset profiles for plotting.
+//!
+//! ```rust
+//! use ent_rs::profile::entropy_profile;
+//! let data: Vec<u8> = (0..=255).cycle().take(1 << 16).collect();
+//! let profile = entropy_profile(&data, 2000);
+//! assert!(profile.points.len() <= 2000);
+//! ```
+
+use crate::window::Windowing;
+use crate::{calculate_entropy, entropy_from_counts};
+
+/// Smallest window used when choosing a window size automatically.
+pub const MIN_WINDOW: usize = 256;
+
+/// Block sizes used by [`entropy_pyramid`] callers that have no preference.
+pub const DEFAULT_SCALES: [usize; 4] = [256, 4096, 65536, 1 << 20];
+
+/// One sample of an entropy profile.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProfilePoint {
+    /// Offset of the first byte of the window.
+    pub offset: usize,
+    /// Shannon entropy of the window in bits per byte.
+    pub entropy: f64,
+}
+
+/// Entropy sampled along the input at a fixed window and step.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EntropyProfile {
+    /// Number of bytes in each window.
+    pub window: usize,
+    /// Distance in bytes between consecutive windows.
+    pub step: usize,
+    /// Samples in offset order.
+    pub points: Vec<ProfilePoint>,
+}
+
+/// Compute an entropy profile with at most `target_points` samples.
+///
+/// The step is chosen so roughly `target_points` windows span the input, and
+/// the window is never smaller than [`MIN_WINDOW`] bytes (or the input length,
+/// if shorter). Windows start every `step` bytes; like
+/// [`EntStats::per_block`](crate::EntStats::per_block), bytes past the last
+/// whole window are covered by a final, shorter window.
+pub fn entropy_profile(data: &[u8], target_points: usize) -> EntropyProfile {
+    let target_points = target_points.max(1);
+    let step = data.len().div_ceil(target_points).max(1);
+    let window = step.max(MIN_WINDOW).min(data.len());
+    let mut profile = entropy_profile_with(data, window, step);
+    let offset = profile.points.len() * step;
+    if profile
+        .points
+        .last()
+        .is_some_and(|p| p.offset + window < data.len())
+    {
+        profile.points.push(ProfilePoint {
+            offset,
+            entropy: calculate_entropy(&data[offset..], false),
+        });
+    }
+    profile
+}
+
+/// Compute an entropy profile with an explicit window and step.
+///
+/// A step smaller than the window gives overlapping windows, whose entropies
+/// are correlated; see [`Windowing`] for when that matters.
+pub fn entropy_profile_with(data: &[u8], window: usize, step: usize) -> EntropyProfile {
+    entropy_profile_windowed(data, Windowing::overlapping(window, step))
+}
+
+/// Compute an entropy profile with windows placed by `windowing`.
+///
+/// Overlapping windows update one set of byte counts as they slide, so each
+/// byte is counted at most twice however small the step is; each point then
+/// costs a pass over the 256 counts.
+pub fn entropy_profile_windowed(data: &[u8], windowing: Windowing) -> EntropyProfile {
+    let window = windowing.window();
+    let step = windowing.step();
+    let mut counts = [0usize; 256];
+    let mut end = 0;
+    let points = windowing
+        .offsets(data.len())
+        .map(|offset| {
+            if windowing.is_overlapping() && offset > 0 {
+                for &b in &data[offset - step..offset] {
+                    counts[b as usize] -= 1;
+                }
+            } else {
+                counts = [0; 256];
+                end = offset;
+            }
+            for &b in &data[end..offset + window] {
+                counts[b as usize] += 1;
+            }
+            end = offset + window;
+            ProfilePoint {
+                offset,
+                entropy: entropy_from_counts(&counts),
+            }
+        })
+        .collect();
+
+    EntropyProfile {
+        window,
+        step: windowing.step(),
+        points,
+    }
+}
+
+/// Block entropies at one block size.
+#[derive(Debug, Clone)]
+pub struct PyramidLevel {
+    /// Block size in bytes.
+    pub block_size: usize,
+    /// Entropy of each block in bits per byte; block `i` starts at
+    /// `i * block_size`. A trailing partial block is included.
+    pub entropy: Vec<f64>,
+}
+
+/// Block entropies of the same input at several block sizes.
+#[derive(Debug, Clone)]
+pub struct EntropyPyramid {
+    /// Input length in bytes.
+    pub len: usize,
+    /// One level per requested block size, in the order given.
+    pub levels: Vec<PyramidLevel>,
+}
+
+impl EntropyPyramid {
+    /// Level with the given block size, if it was requested.
+    pub fn level(&self, block_size: usize) -> Option<&PyramidLevel> {
+        self.levels.iter().find(|l| l.block_size == block_size)
+    }
+}
+
+/// Compute block entropy at every size in `scales` in a single pass.
+///
+/// Fine levels expose small anomalies while coarse levels show section
+/// boundaries; [`DEFAULT_SCALES`] covers 256 B to 1 MiB.
+pub fn entropy_pyramid(data: &[u8], scales: &[usize]) -> EntropyPyramid {
+    let mut levels: Vec<PyramidLevel> = scales
+        .iter()
+        .map(|&block_size| PyramidLevel {
+            block_size: block_size.max(1),
+            entropy: Vec::with_capacity(data.len() / block_size.max(1) + 1),
+        })
+        .collect();
+    let mut counts = vec![[0usize; 256]; levels.len()];
+    let mut filled = vec![0usize; levels.len()];
+
+    for &b in data {
+        for (i, level) in levels.iter_mut().enumerate() {
+            counts[i][b as usize] += 1;
+            filled[i] += 1;
+            if filled[i] == level.block_size {
+                level.entropy.push(entropy_from_counts(&counts[i]));
+                counts[i] = [0; 256];
+                filled[i] = 0;
+            }
+        }
+    }
+    for (i, level) in levels.iter_mut().enumerate() {
+        if filled[i] > 0 {
+            level.entropy.push(entropy_from_counts(&counts[i]));
+        }
+    }
+
+    EntropyPyramid {
+        len: data.len(),
+        levels,
+    }
+}
+
+/// A boundary where entropy changes sharply between adjacent windows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChangePoint {
+    /// Offset of the boundary (start of the window after it).
+    pub offset: usize,
+    /// Entropy of the window before the boundary.
+    pub before: f64,
+    /// Entropy of the window after the boundary.
+    pub after: f64,
+}
+
+impl ChangePoint {
+    /// Signed entropy change across the boundary.
+    pub fn delta(&self) -> f64 {
+        self.after - self.before
+    }
+}
+
+/// Find offsets where entropy changes by at least `min_delta` bits per byte
+/// between adjacent non-overlapping windows.
+///
+/// Boundaries are located to a resolution of `window` bytes. A gradual
+/// transition spanning several windows is reported once, at its steepest step.
+pub fn change_points(data: &[u8], window: usize, min_delta: f64) -> Vec<ChangePoint> {
+    let window = window.max(1);
+    let entropies: Vec<f64> = data
+        .chunks_exact(window)
+        .map(|chunk| calculate_entropy(chunk, false))
+        .collect();
+
+    let mut points: Vec<ChangePoint> = Vec::new();
+    let mut last_index = None;
+    for i in 1..entropies.len() {
+        let point = ChangePoint {
+            offset: i * window,
+            before: entropies[i - 1],
+            after: entropies[i],
+        };
+        if point.delta().abs() < min_delta {
+            continue;
+        }
+        match points.last_mut() {
+            Some(prev)
+                if last_index == Some(i - 1) && prev.delta().signum() == point.delta().signum() =>
+            {
+                if point.delta().abs() > prev.delta().abs() {
+                    *prev = point;
+                }
+            }
+            _ => points.push(point),
+        }
+        last_index = Some(i);
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_respects_target_points() {
+        let data: Vec<u8> = (0..=255).cycle().take(1 << 20).collect();
+        let profile = entropy_profile(&data, 2000);
+        assert!(profile.points.len() <= 2000);
+        assert!(profile.points.len() > 1900);
+        assert!(profile.window >= MIN_WINDOW);
+    }
+
+    #[test]
+    fn test_profile_tracks_entropy_changes() {
+        let mut data = vec![0u8; 8192];
+        data.extend((0..=255).cycle().take(8192));
+        let profile = entropy_profile(&data, 16);
+        let first = profile.points.first().unwrap();
+        let last = profile.points.last().unwrap();
+        assert!(first.entropy < 0.1);
+        assert!(last.entropy > 7.9);
+    }
+
+    #[test]
+    fn test_pyramid_levels_match_block_entropy() {
+        let mut data = vec![0u8; 4096];
+        data.extend((0..=255).cycle().take(4096 + 100));
+        let pyramid = entropy_pyramid(&data, &[256, 4096]);
+        let fine = pyramid.level(256).unwrap();
+        let coarse = pyramid.level(4096).unwrap();
+        assert_eq!(fine.entropy.len(), 33);
+        assert_eq!(coarse.entropy.len(), 3);
+        assert_eq!(fine.entropy[0], 0.0);
+        assert_eq!(fine.entropy[16], 8.0);
+        assert_eq!(
+            coarse.entropy[1],
+            calculate_entropy(&data[4096..8192], false)
+        );
+        assert!(pyramid.level(65536).is_none());
+    }
+
+    #[test]
+    fn test_change_points_find_boundaries() {
+        let mut data = vec![0u8; 4096];
+        data.extend((0..=255).cycle().take(4096));
+        data.extend(vec![7u8; 4096]);
+        let points = change_points(&data, 512, 2.0);
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].offset, 4096);
+        assert!(points[0].delta() > 7.9);
+        assert_eq!(points[1].offset, 8192);
+        assert!(points[1].delta() < -7.9);
+    }
+
+    #[test]
+    fn test_profile_covers_the_tail() {
+        // Two whole windows of 367 bytes, and 366 bytes after them.
+        let mut data = vec![0u8; 1000];
+        data.extend((0..=255).cycle().take(100));
+        let profile = entropy_profile(&data, 3);
+        assert_eq!((profile.window, profile.step), (367, 367));
+        let offsets: Vec<usize> = profile.points.iter().map(|p| p.offset).collect();
+        assert_eq!(offsets, [0, 367, 734]);
+        assert_eq!(
+            profile.points[2].entropy,
+            calculate_entropy(&data[734..], false)
+        );
+        assert!(profile.points[2].entropy > 0.0);
+        assert_eq!(entropy_profile(&data, 4).points.len(), 4);
+    }
+
+    #[test]
+    fn test_sliding_profile_matches_each_window() {
+        let data: Vec<u8> = (0u32..3000).map(|i| (i * i % 251) as u8).collect();
+        for (window, step) in [(300, 7), (300, 300), (100, 250)] {
+            let profile = entropy_profile_with(&data, window, step);
+            assert_eq!(profile.points.len(), (data.len() - window) / step + 1);
+            for point in &profile.points {
+                let expected = calculate_entropy(&data[point.offset..point.offset + window], false);
+                assert!((point.entropy - expected).abs() < 1e-12, "{window} {step}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_profile_short_input() {
+        let data = b"short";
+        let profile = entropy_profile(data, 2000);
+        assert_eq!(profile.window, data.len());
+        assert_eq!(profile.points.len(), 1);
+        assert!(entropy_profile(&[], 10).points.is_empty());
+    }
+}
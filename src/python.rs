@@ -0,0 +1,328 @@
+//! Python bindings for notebooks and scripts.
+//!
+//! Built with `maturin develop --features python,pyo3/extension-module`,
+//! the module imports as `ent_rs` and takes any `bytes` object, such as
+//! `os.urandom(n)` or a NumPy array's `tobytes()`:
+//!
+//! ```python
+//! import ent_rs
+//! stats = ent_rs.analyze(data)
+//! print(stats.entropy, stats.chisquare, stats.exceed_probability)
+//! report = ent_rs.randomness_report(data, alpha=0.01, correction="bonferroni")
+//! print(report)
+//! failed = [e.name for e in report.entries if e.status == "failed"]
+//! ```
+//!
+//! The analysis runs without holding the GIL.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::correction::Correction;
+use crate::randomness::{RandomnessReportBuilder, ReportEntry};
+use crate::suite::TestStatus;
+
+/// Statistics of the classic `ent` tool, as in [`crate::EntStats`].
+#[pyclass(
+    name = "EntStats",
+    module = "ent_rs",
+    frozen,
+    get_all,
+    skip_from_py_object
+)]
+#[derive(Debug, Clone)]
+pub struct EntStats {
+    /// Shannon entropy in bits per byte (or bit).
+    pub entropy: f64,
+    /// Ideal compression percentage based on entropy.
+    pub compression_percent: f64,
+    /// Chi-square test value.
+    pub chisquare: f64,
+    /// p-value of the chi-square test, as in [`crate::EntStats::p_value`]:
+    /// a normal approximation, NaN when it is undefined.
+    pub p_value: f64,
+    /// Probability that random data exceeds the chi-square value, exactly;
+    /// the percentage `ent` reports.
+    pub exceed_probability: f64,
+    /// Arithmetic mean; NaN for empty input.
+    pub mean: f64,
+    /// Monte Carlo estimate of Pi; 0 below 6 bytes.
+    pub pi_estimate: f64,
+    /// Serial correlation coefficient; -99999.0 when undefined.
+    pub serial_correlation: f64,
+    /// `(value, count, fraction)` for each of the 256 byte values, or the
+    /// two bit values in bit mode.
+    pub frequencies: Vec<(u8, usize, f64)>,
+    /// Whether the input was analyzed as a bit stream.
+    pub bit_mode: bool,
+}
+
+impl From<&crate::EntStats> for EntStats {
+    fn from(stats: &crate::EntStats) -> Self {
+        let frequencies = match (&stats.byte_frequencies, &stats.bit_frequencies) {
+            (_, Some(bits)) => vec![(0, bits[0].0, bits[0].1), (1, bits[1].0, bits[1].1)],
+            (Some(bytes), None) => bytes.clone(),
+            (None, None) => Vec::new(),
+        };
+        EntStats {
+            entropy: stats.entropy,
+            compression_percent: stats.compression_percent,
+            chisquare: stats.chisquare,
+            p_value: stats.p_value,
+            exceed_probability: crate::chisquare_p_value(
+                stats.chisquare,
+                if stats.bit_frequencies.is_some() {
+                    1.0
+                } else {
+                    255.0
+                },
+            ),
+            mean: stats.mean,
+            pi_estimate: stats.pi_estimate,
+            serial_correlation: stats.serial_correlation,
+            frequencies,
+            bit_mode: stats.bit_frequencies.is_some(),
+        }
+    }
+}
+
+#[pymethods]
+impl EntStats {
+    fn __repr__(&self) -> String {
+        format!(
+            "EntStats(entropy={}, chisquare={}, p_value={}, mean={}, pi_estimate={}, serial_correlation={})",
+            self.entropy,
+            self.chisquare,
+            self.p_value,
+            self.mean,
+            self.pi_estimate,
+            self.serial_correlation
+        )
+    }
+}
+
+/// One test of a [`RandomnessReport`] and its verdict, as in
+/// [`ReportEntry`].
+#[pyclass(module = "ent_rs", frozen, get_all, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct TestEntry {
+    /// `"ent"`, `"nist"`, `"fips140"`, `"diehard"`, `"gcd"`, `"ais31"` or
+    /// `"custom"`.
+    pub battery: String,
+    /// Test name.
+    pub name: String,
+    /// Test statistic; over several blocks, the proportion passing.
+    pub statistic: f64,
+    /// Raw p-value; over several blocks, the uniformity p-value.
+    pub p_value: f64,
+    /// p-value adjusted for the number of tests.
+    pub adjusted_p_value: f64,
+    /// `"passed"`, `"failed"` or `"skipped"`.
+    pub status: String,
+    /// p-value of each block; empty for a single block.
+    pub block_p_values: Vec<f64>,
+}
+
+impl From<&ReportEntry> for TestEntry {
+    fn from(entry: &ReportEntry) -> Self {
+        TestEntry {
+            battery: entry.battery.to_string(),
+            name: entry.result.name.clone(),
+            statistic: entry.result.statistic,
+            p_value: entry.result.p_value,
+            adjusted_p_value: entry.adjusted_p_value,
+            status: match entry.status {
+                TestStatus::Passed => "passed",
+                TestStatus::Failed => "failed",
+                _ => "skipped",
+            }
+            .to_string(),
+            block_p_values: entry.blocks.iter().map(|r| r.p_value).collect(),
+        }
+    }
+}
+
+#[pymethods]
+impl TestEntry {
+    fn __repr__(&self) -> String {
+        format!(
+            "TestEntry(battery={:?}, name={:?}, p_value={}, status={:?})",
+            self.battery, self.name, self.p_value, self.status
+        )
+    }
+}
+
+/// Results of the selected batteries, as in
+/// [`RandomnessReport`](crate::randomness::RandomnessReport).
+#[pyclass(module = "ent_rs", frozen, skip_from_py_object)]
+#[derive(Debug, Clone)]
+pub struct RandomnessReport {
+    report: crate::randomness::RandomnessReport,
+}
+
+#[pymethods]
+impl RandomnessReport {
+    /// Significance level the tests were judged at.
+    #[getter]
+    fn alpha(&self) -> f64 {
+        self.report.alpha
+    }
+
+    /// Number of blocks each test ran on.
+    #[getter]
+    fn blocks(&self) -> usize {
+        self.report.blocks
+    }
+
+    /// Every test, in report order.
+    #[getter]
+    fn entries(&self) -> Vec<TestEntry> {
+        self.report.entries.iter().map(TestEntry::from).collect()
+    }
+
+    /// Whether no test failed.
+    fn passed(&self) -> bool {
+        self.report.passed()
+    }
+
+    /// The report table, as printed by the Rust `Display` implementation.
+    fn __str__(&self) -> String {
+        self.report.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "RandomnessReport(tests={}, passed={})",
+            self.report.entries.len(),
+            self.report.passed()
+        )
+    }
+}
+
+/// Byte-mode statistics of `data`, or bit-mode with `bit_mode=True`.
+#[pyfunction]
+#[pyo3(signature = (data, bit_mode = false))]
+fn analyze(py: Python<'_>, data: &[u8], bit_mode: bool) -> EntStats {
+    py.detach(|| EntStats::from(&crate::EntStats::from_data(data, bit_mode)))
+}
+
+/// Run the chosen batteries over `data`; see [`RandomnessReportBuilder`].
+///
+/// `correction` is `"none"`, `"bonferroni"` or `"benjamini-hochberg"`.
+#[pyfunction]
+#[pyo3(signature = (
+    data,
+    alpha = 0.01,
+    correction = "none",
+    blocks = 1,
+    ent = true,
+    nist = true,
+    fips140 = true,
+    diehard = true,
+))]
+#[allow(clippy::too_many_arguments)]
+fn randomness_report(
+    py: Python<'_>,
+    data: &[u8],
+    alpha: f64,
+    correction: &str,
+    blocks: usize,
+    ent: bool,
+    nist: bool,
+    fips140: bool,
+    diehard: bool,
+) -> PyResult<RandomnessReport> {
+    let builder = report_builder(alpha, correction, blocks, [ent, nist, fips140, diehard])?;
+    let report = py.detach(|| builder.run(data));
+    Ok(RandomnessReport { report })
+}
+
+/// The builder for [`randomness_report`], rejecting what it would panic on.
+fn report_builder(
+    alpha: f64,
+    correction: &str,
+    blocks: usize,
+    [ent, nist, fips140, diehard]: [bool; 4],
+) -> PyResult<RandomnessReportBuilder> {
+    if !(alpha > 0.0 && alpha < 1.0) {
+        return Err(PyValueError::new_err(format!(
+            "alpha must be in (0, 1), got {alpha}"
+        )));
+    }
+    if blocks == 0 {
+        return Err(PyValueError::new_err("blocks must be at least 1"));
+    }
+    let correction = match correction {
+        "none" => Correction::None,
+        "bonferroni" => Correction::Bonferroni,
+        "benjamini-hochberg" => Correction::BenjaminiHochberg,
+        other => {
+            return Err(PyValueError::new_err(format!(
+                "unknown correction {other:?}"
+            )))
+        }
+    };
+    let mut builder = RandomnessReportBuilder::none()
+        .alpha(alpha)
+        .correction(correction)
+        .blocks(blocks);
+    if ent {
+        builder = builder.with_ent();
+    }
+    if nist {
+        builder = builder.with_nist();
+    }
+    if fips140 {
+        builder = builder.with_fips140();
+    }
+    if diehard {
+        builder = builder.with_diehard();
+    }
+    Ok(builder)
+}
+
+#[pymodule]
+fn ent_rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<EntStats>()?;
+    m.add_class::<TestEntry>()?;
+    m.add_class::<RandomnessReport>()?;
+    m.add_function(wrap_pyfunction!(analyze, m)?)?;
+    m.add_function(wrap_pyfunction!(randomness_report, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_conversion() {
+        let data: Vec<u8> = (0..=255).cycle().take(4096).collect();
+        let stats = EntStats::from(&crate::EntStats::from_data(&data, false));
+        assert_eq!(stats.entropy, 8.0);
+        assert_eq!(stats.frequencies.len(), 256);
+        // The normal approximation is undefined here; the exact tail is not.
+        assert!(stats.p_value.is_nan());
+        assert_eq!(stats.exceed_probability, 1.0);
+        assert!(!stats.bit_mode);
+
+        let stats = EntStats::from(&crate::EntStats::from_data(&[0x0F; 10], true));
+        assert_eq!(stats.frequencies, vec![(0, 40, 0.5), (1, 40, 0.5)]);
+        assert!(stats.bit_mode);
+    }
+
+    #[test]
+    fn test_report_entries_and_builder_arguments() {
+        let builder = report_builder(0.01, "bonferroni", 1, [true, false, false, false]).unwrap();
+        let report = builder.run(&[0x5A; 10_000]);
+        let entries: Vec<TestEntry> = report.entries.iter().map(TestEntry::from).collect();
+        assert_eq!(entries.len(), 3);
+        assert!(entries.iter().all(|e| e.battery == "ent"));
+        assert!(entries.iter().any(|e| e.status == "failed"));
+        assert!(entries[0].block_p_values.is_empty());
+
+        assert!(report_builder(1.0, "none", 1, [true; 4]).is_err());
+        assert!(report_builder(0.01, "none", 0, [true; 4]).is_err());
+        assert!(report_builder(0.01, "holm", 1, [true; 4]).is_err());
+    }
+}
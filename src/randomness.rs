@@ -0,0 +1,1580 @@
+//! One pass/fail report over the crate's randomness test batteries.
+//!
+//! [`RandomnessReport`] runs the classic `ent` statistics, the SP 800-22
+//! tests, the FIPS 140-2 tests and the Diehard tests, and optionally the
+//! GCD test and the AIS 31 procedures, over one input and
+//! judges every statistic at one significance level, so a single call
+//! answers "does this look random?" with the evidence attached. With more
+//! than 200 statistics, some fail at `α = 0.01` on any input; a
+//! [`Correction`] adjusts the p-values for their number.
+//!
+//! Over many blocks of one input, each test is instead judged as SP 800-22
+//! judges a generator, by [second-level analysis](crate::second_level) of
+//! its per-block p-values.
+//!
+//! Every test implements [`RandomnessTest`]; downstream tests implementing
+//! it join a battery with [`RandomnessReportBuilder::with_test`].
+//!
+//! ```rust
+//! use ent_rs::correction::Correction;
+//! use ent_rs::randomness::RandomnessReport;
+//! let data: Vec<u8> = (0..1u32 << 16).map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8).collect();
+//! let report = RandomnessReport::builder()
+//!     .without_diehard()
+//!     .correction(Correction::BenjaminiHochberg)
+//!     .run(&data);
+//! assert!(!report.passed());
+//! for entry in report.failures() {
+//!     println!("{} {}: adjusted p = {}", entry.battery, entry.result.name, entry.adjusted_p_value);
+//! }
+//! ```
+
+use std::cell::OnceCell;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::correction::Correction;
+use crate::diehard::{self, CrapsResult};
+use crate::digest::InputDigest;
+use crate::fips140::{self, Fips140Status};
+use crate::gcd::{self, GcdResult};
+use crate::nist::{
+    self, BitStream, CumulativeSumsResult, RandomExcursionsVariantResult, SerialResult, Template,
+    VARIANT_STATES,
+};
+use crate::second_level::{second_level, SecondLevel, UNIFORMITY_THRESHOLD};
+use crate::suite::{self, NistParams, TestStatus};
+use crate::{ais31, chisquare_p_value, EntStats};
+
+/// Bytes the SP 800-22 tests use by default: one sequence of 10^6 bits, the
+/// length the standard's recommended parameters are chosen for.
+pub const NIST_SEQUENCE_LEN: usize = 125_000;
+
+/// A group of related tests, in the order reports list them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Battery {
+    /// The statistics of the classic `ent` tool: byte chi-square, Monte
+    /// Carlo π and serial correlation.
+    Ent,
+    /// The SP 800-22 tests of [`nist`].
+    Nist,
+    /// The FIPS 140-2 tests of [`fips140`].
+    Fips140,
+    /// The Diehard tests of [`diehard`].
+    Diehard,
+    /// Marsaglia and Tsang's GCD test of [`gcd`].
+    Gcd,
+    /// The AIS 31 procedures of [`ais31`].
+    Ais31,
+    /// Tests added with [`RandomnessReportBuilder::with_test`] that belong
+    /// to none of the above.
+    Custom,
+}
+
+impl fmt::Display for Battery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Battery::Ent => "ent",
+            Battery::Nist => "nist",
+            Battery::Fips140 => "fips140",
+            Battery::Diehard => "diehard",
+            Battery::Gcd => "gcd",
+            Battery::Ais31 => "ais31",
+            Battery::Custom => "custom",
+        })
+    }
+}
+
+/// Statistic and p-value of one test.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestResult {
+    /// Test name; tests reporting several statistics name each one, e.g.
+    /// `cumulative-sums (forward)`.
+    pub name: String,
+    /// The test statistic, in the test's own units.
+    pub statistic: f64,
+    /// Its p-value; NaN if the input is too short for the test.
+    pub p_value: f64,
+}
+
+impl TestResult {
+    /// A result named `name`.
+    pub fn new(name: impl Into<String>, statistic: f64, p_value: f64) -> Self {
+        TestResult {
+            name: name.into(),
+            statistic,
+            p_value,
+        }
+    }
+}
+
+/// A statistical test over a bit sequence.
+///
+/// The built-in tests are [`EntTest`], [`NistTest`], [`Fips140Test`] and
+/// [`DiehardTest`]; other tests implementing the trait run alongside them
+/// in a [`RandomnessReport`] or a [`Suite`](crate::suite::Suite).
+///
+/// ```rust
+/// use ent_rs::nist::BitStream;
+/// use ent_rs::randomness::{Battery, RandomnessReportBuilder, RandomnessTest, TestResult};
+/// use ent_rs::suite::TestStatus;
+///
+/// struct LeadingZeros;
+///
+/// impl RandomnessTest for LeadingZeros {
+///     fn name(&self) -> String {
+///         "leading-zeros".to_string()
+///     }
+///
+///     fn min_bits(&self) -> usize {
+///         1
+///     }
+///
+///     fn run(&self, bits: &BitStream) -> TestResult {
+///         // At least `run` leading zeros has probability 2^-run.
+///         let run = bits.iter().take_while(|&b| b == 0).count();
+///         TestResult::new(self.name(), run as f64, 0.5f64.powi(run as i32))
+///     }
+/// }
+///
+/// let report = RandomnessReportBuilder::none()
+///     .with_test(Battery::Custom, LeadingZeros)
+///     .run(&[0, 0, 0, 1]);
+/// assert_eq!(report.get("leading-zeros").unwrap().status, TestStatus::Failed);
+/// ```
+pub trait RandomnessTest: Send + Sync {
+    /// Name of the test's entry in a report.
+    fn name(&self) -> String;
+
+    /// Fewest bits the test runs on; shorter sequences are skipped without
+    /// running it, with a NaN statistic and p-value.
+    fn min_bits(&self) -> usize;
+
+    /// Run the test over `bits`, which has at least
+    /// [`min_bits`](Self::min_bits) bits.
+    fn run(&self, bits: &BitStream) -> TestResult;
+}
+
+/// Run `test` over `bits`, or skip it if they are too short.
+pub(crate) fn run_test(test: &dyn RandomnessTest, bits: &BitStream) -> TestResult {
+    if bits.len() < test.min_bits() {
+        TestResult::new(test.name(), f64::NAN, f64::NAN)
+    } else {
+        test.run(bits)
+    }
+}
+
+/// One test of a report and its verdict.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportEntry {
+    /// Battery the test belongs to.
+    pub battery: Battery,
+    /// What the test measured. Over several blocks, a summary of them: the
+    /// statistic is the proportion of blocks passing at the significance
+    /// level, the p-value the uniformity p-value of their p-values.
+    pub result: TestResult,
+    /// The p-value adjusted for the number of tests in the report; the raw
+    /// p-value without correction.
+    pub adjusted_p_value: f64,
+    /// [`TestStatus::Passed`] if the adjusted p-value reached the
+    /// significance level, [`TestStatus::Failed`] if not,
+    /// [`TestStatus::Skipped`] if it is NaN.
+    ///
+    /// Over several blocks the test fails if the proportion of passing
+    /// blocks is below its interval or the adjusted uniformity p-value is
+    /// below [`UNIFORMITY_THRESHOLD`], as in SP 800-22 §4.2.
+    pub status: TestStatus,
+    /// Result of the test on each block; empty for a single block.
+    pub blocks: Vec<TestResult>,
+    /// Second-level analysis of the block p-values; `None` for a single
+    /// block.
+    pub second_level: Option<SecondLevel>,
+}
+
+/// Results of every selected test over one input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RandomnessReport {
+    /// The input analyzed. Its tests take bytes, so `bit_mode` is false.
+    pub input: InputDigest,
+    /// Significance level the tests were judged at.
+    pub alpha: f64,
+    /// Adjustment of the p-values for their number.
+    pub correction: Correction,
+    /// Blocks the input was divided into; 1 runs the tests once over all of
+    /// it.
+    pub blocks: usize,
+    /// One entry per statistic, battery by battery.
+    pub entries: Vec<ReportEntry>,
+}
+
+impl RandomnessReport {
+    /// A builder running every battery at `α = 0.01`.
+    pub fn builder() -> RandomnessReportBuilder {
+        RandomnessReportBuilder::new()
+    }
+
+    /// Whether no test failed; skipped tests do not count against the input.
+    pub fn passed(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    /// Entries whose test failed.
+    pub fn failures(&self) -> impl Iterator<Item = &ReportEntry> {
+        self.entries
+            .iter()
+            .filter(|e| e.status == TestStatus::Failed)
+    }
+
+    /// Number of entries with `status`.
+    pub fn count(&self, status: TestStatus) -> usize {
+        self.entries.iter().filter(|e| e.status == status).count()
+    }
+
+    /// Look up an entry by test name.
+    pub fn get(&self, name: &str) -> Option<&ReportEntry> {
+        self.entries.iter().find(|e| e.result.name == name)
+    }
+}
+
+/// A table of every entry and a closing verdict line.
+impl fmt::Display for RandomnessReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<9} {:<40} {:>14} {:>10} {:>10}  result",
+            "battery", "test", "statistic", "p-value", "adjusted"
+        )?;
+        for entry in &self.entries {
+            let result = match entry.status {
+                TestStatus::Passed => "passed",
+                TestStatus::Failed => "FAILED",
+                _ => "skipped",
+            };
+            writeln!(
+                f,
+                "{:<9} {:<40} {:>14.4} {:>10.6} {:>10.6}  {result}",
+                entry.battery.to_string(),
+                entry.result.name,
+                entry.result.statistic,
+                entry.result.p_value,
+                entry.adjusted_p_value
+            )?;
+        }
+        let verdict = if self.passed() { "PASSED" } else { "FAILED" };
+        if self.blocks > 1 {
+            writeln!(
+                f,
+                "over {} blocks: statistic = proportion of passing blocks, p-value = uniformity",
+                self.blocks
+            )?;
+        }
+        write!(
+            f,
+            "{} of {} tests passed at alpha = {} ({:?} correction), {} failed, {} skipped: {verdict}",
+            self.count(TestStatus::Passed),
+            self.entries.len(),
+            self.alpha,
+            self.correction,
+            self.count(TestStatus::Failed),
+            self.count(TestStatus::Skipped)
+        )
+    }
+}
+
+/// Chooses the batteries, significance level and correction of a
+/// [`RandomnessReport`].
+///
+/// ```rust
+/// use ent_rs::randomness::{Battery, RandomnessReportBuilder};
+/// let data = vec![0x5Au8; 10_000];
+/// let report = RandomnessReportBuilder::none().with_ent().run(&data);
+/// assert!(report.entries.iter().all(|e| e.battery == Battery::Ent));
+/// assert!(!report.passed());
+/// ```
+#[derive(Clone)]
+pub struct RandomnessReportBuilder {
+    alpha: f64,
+    correction: Correction,
+    blocks: usize,
+    nist: Option<NistParams>,
+    ent: bool,
+    fips140: bool,
+    diehard: bool,
+    gcd: bool,
+    ais31: bool,
+    custom: Vec<(Battery, Arc<dyn RandomnessTest>)>,
+}
+
+impl fmt::Debug for RandomnessReportBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let custom: Vec<(Battery, String)> = self
+            .custom
+            .iter()
+            .map(|(battery, test)| (*battery, test.name()))
+            .collect();
+        f.debug_struct("RandomnessReportBuilder")
+            .field("alpha", &self.alpha)
+            .field("correction", &self.correction)
+            .field("blocks", &self.blocks)
+            .field("nist", &self.nist)
+            .field("ent", &self.ent)
+            .field("fips140", &self.fips140)
+            .field("diehard", &self.diehard)
+            .field("gcd", &self.gcd)
+            .field("ais31", &self.ais31)
+            .field("custom", &custom)
+            .finish()
+    }
+}
+
+impl Default for RandomnessReportBuilder {
+    fn default() -> Self {
+        RandomnessReportBuilder::new()
+    }
+}
+
+impl RandomnessReportBuilder {
+    /// The `ent`, SP 800-22, FIPS 140-2 and Diehard batteries, at
+    /// `α = 0.01` without correction. The GCD test and the AIS 31
+    /// procedures, which need a megabyte or more, are added with
+    /// [`with_gcd`](Self::with_gcd) and [`with_ais31`](Self::with_ais31).
+    pub fn new() -> Self {
+        RandomnessReportBuilder {
+            alpha: 0.01,
+            correction: Correction::None,
+            blocks: 1,
+            nist: Some(default_nist_params()),
+            ent: true,
+            fips140: true,
+            diehard: true,
+            gcd: false,
+            ais31: false,
+            custom: Vec::new(),
+        }
+    }
+
+    /// No batteries; enable them with the `with_*` methods.
+    pub fn none() -> Self {
+        RandomnessReportBuilder {
+            nist: None,
+            ent: false,
+            fips140: false,
+            diehard: false,
+            ..RandomnessReportBuilder::new()
+        }
+    }
+
+    /// Judge the tests at significance level `alpha`.
+    ///
+    /// # Panics
+    ///
+    /// If `alpha` is not in `(0, 1)`.
+    pub fn alpha(mut self, alpha: f64) -> Self {
+        assert!(alpha > 0.0 && alpha < 1.0, "alpha must be in (0, 1)");
+        self.alpha = alpha;
+        self
+    }
+
+    /// Adjust the p-values for the number of statistics in the report
+    /// before judging them.
+    pub fn correction(mut self, correction: Correction) -> Self {
+        self.correction = correction;
+        self
+    }
+
+    /// Divide the input into `blocks` consecutive blocks of equal length,
+    /// run every test on each, and judge it by second-level analysis of its
+    /// block p-values. Trailing bytes that do not fill a block are unused.
+    ///
+    /// SP 800-22 recommends at least [`MIN_SEQUENCES`] blocks; the default,
+    /// 1, runs the tests once over the whole input.
+    ///
+    /// # Panics
+    ///
+    /// If `blocks` is 0.
+    ///
+    /// [`MIN_SEQUENCES`]: crate::second_level::MIN_SEQUENCES
+    pub fn blocks(mut self, blocks: usize) -> Self {
+        assert!(blocks > 0, "blocks must be at least 1");
+        self.blocks = blocks;
+        self
+    }
+
+    /// Run the classic `ent` statistics.
+    pub fn with_ent(mut self) -> Self {
+        self.ent = true;
+        self
+    }
+
+    /// Skip the classic `ent` statistics.
+    pub fn without_ent(mut self) -> Self {
+        self.ent = false;
+        self
+    }
+
+    /// Run the SP 800-22 tests on the first [`NIST_SEQUENCE_LEN`] bytes.
+    pub fn with_nist(mut self) -> Self {
+        self.nist.get_or_insert_with(default_nist_params);
+        self
+    }
+
+    /// Run the SP 800-22 tests with `params`. The tests run on one sequence
+    /// of [`sequence_len`](NistParams::sequence_len) bytes from the start
+    /// of the input, or the whole input if that is `None`; the other
+    /// sequence fields are ignored.
+    pub fn with_nist_params(mut self, params: NistParams) -> Self {
+        self.nist = Some(params);
+        self
+    }
+
+    /// Skip the SP 800-22 tests.
+    pub fn without_nist(mut self) -> Self {
+        self.nist = None;
+        self
+    }
+
+    /// Run the FIPS 140-2 tests.
+    pub fn with_fips140(mut self) -> Self {
+        self.fips140 = true;
+        self
+    }
+
+    /// Skip the FIPS 140-2 tests.
+    pub fn without_fips140(mut self) -> Self {
+        self.fips140 = false;
+        self
+    }
+
+    /// Run the Diehard tests.
+    pub fn with_diehard(mut self) -> Self {
+        self.diehard = true;
+        self
+    }
+
+    /// Skip the Diehard tests.
+    pub fn without_diehard(mut self) -> Self {
+        self.diehard = false;
+        self
+    }
+
+    /// Run the GCD test.
+    pub fn with_gcd(mut self) -> Self {
+        self.gcd = true;
+        self
+    }
+
+    /// Skip the GCD test.
+    pub fn without_gcd(mut self) -> Self {
+        self.gcd = false;
+        self
+    }
+
+    /// Run AIS 31 procedures A and B.
+    pub fn with_ais31(mut self) -> Self {
+        self.ais31 = true;
+        self
+    }
+
+    /// Skip the AIS 31 procedures.
+    pub fn without_ais31(mut self) -> Self {
+        self.ais31 = false;
+        self
+    }
+
+    /// Add `test` to `battery`, after its built-in tests. Tests added to
+    /// [`Battery::Nist`] run on the same leading sequence as the built-in
+    /// SP 800-22 tests.
+    pub fn with_test(mut self, battery: Battery, test: impl RandomnessTest + 'static) -> Self {
+        self.custom.push((battery, Arc::new(test)));
+        self
+    }
+
+    /// Run the selected batteries over `data`.
+    pub fn run(&self, data: &[u8]) -> RandomnessReport {
+        let tests = self.tests();
+        let entries = if self.blocks == 1 {
+            self.single_entries(&tests, data)
+        } else {
+            self.block_entries(&tests, data)
+        };
+        RandomnessReport {
+            input: InputDigest::new(data, false),
+            alpha: self.alpha,
+            correction: self.correction,
+            blocks: self.blocks,
+            entries,
+        }
+    }
+
+    fn single_entries(&self, tests: &[(Battery, Test)], data: &[u8]) -> Vec<ReportEntry> {
+        let results = self.results(tests, data);
+        let p_values: Vec<f64> = results.iter().map(|(_, r)| r.p_value).collect();
+        results
+            .into_iter()
+            .zip(self.correction.adjust(&p_values))
+            .map(|((battery, result), adjusted_p_value)| {
+                let status = if adjusted_p_value.is_nan() {
+                    TestStatus::Skipped
+                } else if adjusted_p_value < self.alpha {
+                    TestStatus::Failed
+                } else {
+                    TestStatus::Passed
+                };
+                ReportEntry {
+                    battery,
+                    result,
+                    adjusted_p_value,
+                    status,
+                    blocks: Vec::new(),
+                    second_level: None,
+                }
+            })
+            .collect()
+    }
+
+    fn block_entries(&self, tests: &[(Battery, Test)], data: &[u8]) -> Vec<ReportEntry> {
+        let block_len = data.len() / self.blocks;
+        let per_block: Vec<Vec<(Battery, TestResult)>> = (0..self.blocks)
+            .map(|i| self.results(tests, &data[i * block_len..(i + 1) * block_len]))
+            .collect();
+        let summaries: Vec<(Battery, Vec<TestResult>, SecondLevel)> = (0..tests.len())
+            .map(|t| {
+                let blocks: Vec<TestResult> = per_block
+                    .iter()
+                    .map(|results| results[t].1.clone())
+                    .collect();
+                let p_values: Vec<f64> = blocks.iter().map(|r| r.p_value).collect();
+                let analysis = second_level(&p_values, self.alpha);
+                (per_block[0][t].0, blocks, analysis)
+            })
+            .collect();
+        let uniformity: Vec<f64> = summaries
+            .iter()
+            .map(|(_, _, analysis)| analysis.uniformity_p_value)
+            .collect();
+        summaries
+            .into_iter()
+            .zip(self.correction.adjust(&uniformity))
+            .map(|((battery, blocks, analysis), adjusted_p_value)| {
+                let status = if analysis.sequences == 0 {
+                    TestStatus::Skipped
+                } else if analysis.proportion < analysis.proportion_interval.0
+                    || adjusted_p_value < UNIFORMITY_THRESHOLD
+                {
+                    TestStatus::Failed
+                } else {
+                    TestStatus::Passed
+                };
+                ReportEntry {
+                    battery,
+                    result: TestResult::new(
+                        blocks[0].name.clone(),
+                        analysis.proportion,
+                        analysis.uniformity_p_value,
+                    ),
+                    adjusted_p_value,
+                    status,
+                    blocks,
+                    second_level: Some(analysis),
+                }
+            })
+            .collect()
+    }
+
+    /// Every selected test, battery by battery.
+    fn tests(&self) -> Vec<(Battery, Test)> {
+        let mut tests: Vec<(Battery, Test)> = Vec::new();
+        if self.ent {
+            tests.extend(EntTest::all().map(|t| (Battery::Ent, Test::Ent(t))));
+        }
+        if let Some(params) = &self.nist {
+            tests.extend(
+                NistTest::all(params)
+                    .into_iter()
+                    .map(|t| (Battery::Nist, Test::Nist(t))),
+            );
+        }
+        if self.fips140 {
+            tests.extend(Fips140Test::all().map(|t| (Battery::Fips140, Test::Fips140(t))));
+        }
+        if self.diehard {
+            tests.extend(
+                DiehardTest::all()
+                    .into_iter()
+                    .map(|t| (Battery::Diehard, Test::Diehard(t))),
+            );
+        }
+        if self.gcd {
+            tests.extend(GcdTest::all().map(|t| (Battery::Gcd, Test::Gcd(t))));
+        }
+        if self.ais31 {
+            tests.extend(Ais31Test::all().map(|t| (Battery::Ais31, Test::Ais31(t))));
+        }
+        tests.extend(
+            self.custom
+                .iter()
+                .map(|(battery, test)| (*battery, Test::Custom(Arc::clone(test)))),
+        );
+        // Stable: custom tests follow the built-in tests of their battery.
+        tests.sort_by_key(|(battery, _)| *battery);
+        tests
+    }
+
+    /// Every test of `tests` over `data`, computing what several statistics
+    /// share once per sequence.
+    fn results(&self, tests: &[(Battery, Test)], data: &[u8]) -> Vec<(Battery, TestResult)> {
+        let nist_len = self
+            .nist
+            .as_ref()
+            .and_then(|params| params.sequence_len)
+            .unwrap_or(data.len())
+            .min(data.len());
+        let all = (BitStream::from_bytes(data), Shared::default());
+        let nist = (BitStream::from_bytes(&data[..nist_len]), Shared::default());
+        tests
+            .iter()
+            .map(|(battery, test)| {
+                let (bits, shared) = if *battery == Battery::Nist {
+                    &nist
+                } else {
+                    &all
+                };
+                (*battery, test.run(bits, shared))
+            })
+            .collect()
+    }
+}
+
+/// A test of a report: a built-in statistic, or one added with
+/// [`RandomnessReportBuilder::with_test`].
+#[derive(Clone)]
+enum Test {
+    Ent(EntTest),
+    Nist(NistTest),
+    Fips140(Fips140Test),
+    Diehard(DiehardTest),
+    Gcd(GcdTest),
+    Ais31(Ais31Test),
+    Custom(Arc<dyn RandomnessTest>),
+}
+
+impl Test {
+    /// Run the test over `bits`, or skip it if they are too short.
+    fn run(&self, bits: &BitStream, shared: &Shared) -> TestResult {
+        let test: &dyn RandomnessTest = match self {
+            Test::Ent(t) => t,
+            Test::Nist(t) => t,
+            Test::Fips140(t) => t,
+            Test::Diehard(t) => t,
+            Test::Gcd(t) => t,
+            Test::Ais31(t) => t,
+            Test::Custom(t) => return run_test(t.as_ref(), bits),
+        };
+        if bits.len() < test.min_bits() {
+            return TestResult::new(test.name(), f64::NAN, f64::NAN);
+        }
+        match self {
+            Test::Ent(t) => t.run_shared(bits, shared),
+            Test::Nist(t) => t.run_shared(bits, shared),
+            Test::Fips140(t) => t.run_shared(bits, shared),
+            Test::Diehard(t) => t.run_shared(bits, shared),
+            Test::Gcd(t) => t.run_shared(bits, shared),
+            Test::Ais31(t) => t.run(bits),
+            Test::Custom(_) => unreachable!(),
+        }
+    }
+}
+
+/// Results several statistics of a report derive from, each computed at
+/// most once per sequence.
+#[derive(Default)]
+struct Shared {
+    stats: OnceCell<EntStats>,
+    serial: OnceCell<(usize, SerialResult)>,
+    cumulative_sums: OnceCell<CumulativeSumsResult>,
+    excursions: OnceCell<RandomExcursionsVariantResult>,
+    fips140: OnceCell<Fips140Status>,
+    craps: OnceCell<CrapsResult>,
+    gcd: OnceCell<GcdResult>,
+}
+
+/// The standard's parameters for one sequence of [`NIST_SEQUENCE_LEN`] bytes.
+fn default_nist_params() -> NistParams {
+    NistParams {
+        sequence_len: Some(NIST_SEQUENCE_LEN),
+        ..NistParams::default()
+    }
+}
+
+/// The statistics of the classic `ent` tool over the complete bytes of a
+/// sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntTest {
+    /// Byte chi-square with 255 degrees of freedom.
+    ChiSquare,
+    /// The Monte Carlo π estimate, tested on its hits inside the circle.
+    MonteCarloPi,
+    /// The lag-1 serial correlation coefficient.
+    SerialCorrelation,
+}
+
+impl EntTest {
+    /// Every statistic, in report order.
+    pub fn all() -> [EntTest; 3] {
+        [
+            EntTest::ChiSquare,
+            EntTest::MonteCarloPi,
+            EntTest::SerialCorrelation,
+        ]
+    }
+}
+
+impl RandomnessTest for EntTest {
+    fn name(&self) -> String {
+        match self {
+            EntTest::ChiSquare => "chi-square",
+            EntTest::MonteCarloPi => "monte-carlo-pi",
+            EntTest::SerialCorrelation => "serial-correlation",
+        }
+        .to_string()
+    }
+
+    fn min_bits(&self) -> usize {
+        match self {
+            EntTest::ChiSquare => 8,
+            // One point of six bytes.
+            EntTest::MonteCarloPi => 48,
+            EntTest::SerialCorrelation => 16,
+        }
+    }
+
+    fn run(&self, bits: &BitStream) -> TestResult {
+        self.run_shared(bits, &Shared::default())
+    }
+}
+
+impl EntTest {
+    fn run_shared(&self, bits: &BitStream, shared: &Shared) -> TestResult {
+        let data = bits.as_bytes();
+        let stats = shared
+            .stats
+            .get_or_init(|| EntStats::from_data(data, false));
+        let (statistic, p_value) = match self {
+            EntTest::ChiSquare => (stats.chisquare, chisquare_p_value(stats.chisquare, 255.0)),
+            EntTest::MonteCarloPi => (
+                stats.pi_estimate,
+                suite::monte_carlo_pi_p_value(stats, data.len()).unwrap_or(f64::NAN),
+            ),
+            EntTest::SerialCorrelation => (
+                stats.serial_correlation,
+                suite::serial_correlation_p_value(stats, data.len()).unwrap_or(f64::NAN),
+            ),
+        };
+        TestResult::new(self.name(), statistic, p_value)
+    }
+}
+
+/// One statistic of the SP 800-22 tests of [`nist`], named as in
+/// [`Suite::nist`](crate::suite::Suite::nist).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NistTest {
+    /// Frequency (monobit) test.
+    Frequency,
+    /// Frequency within a block of `block_len` bits; `None` chooses it from
+    /// the sequence length as [`NistParams::block_frequency_len_for`] does.
+    BlockFrequency { block_len: Option<usize> },
+    /// Runs test.
+    Runs,
+    /// Longest run of ones in a block.
+    LongestRun,
+    /// Discrete Fourier transform (spectral) test.
+    Spectral,
+    /// Non-overlapping matches of `template` in `blocks` blocks.
+    NonOverlappingTemplate { template: Template, blocks: usize },
+    /// Overlapping matches of the all-ones template of `template_len` bits
+    /// in blocks of `block_len` bits.
+    OverlappingTemplate {
+        template_len: usize,
+        block_len: usize,
+    },
+    /// Serial test of `pattern_len`-bit patterns: its first difference
+    /// `∇ψ²`, or the second `∇²ψ²` if `second` is set.
+    Serial { pattern_len: usize, second: bool },
+    /// Cumulative sums from the start, or from the end if `backward` is set.
+    CumulativeSums { backward: bool },
+    /// Random excursions variant visits to `state`, one of
+    /// [`VARIANT_STATES`]; other states give a NaN p-value.
+    RandomExcursionsVariant { state: i32 },
+}
+
+impl NistTest {
+    /// Every statistic with `params`, in report order.
+    pub fn all(params: &NistParams) -> Vec<NistTest> {
+        let mut tests = vec![
+            NistTest::Frequency,
+            NistTest::BlockFrequency {
+                block_len: params.block_frequency_len,
+            },
+            NistTest::Runs,
+            NistTest::LongestRun,
+            NistTest::Spectral,
+        ];
+        let templates = match &params.templates {
+            Some(templates) => templates.clone(),
+            None => nist::aperiodic_templates(params.template_len),
+        };
+        tests.extend(
+            templates
+                .into_iter()
+                .map(|template| NistTest::NonOverlappingTemplate {
+                    template,
+                    blocks: params.template_blocks,
+                }),
+        );
+        tests.push(NistTest::OverlappingTemplate {
+            template_len: params.overlapping_template_len,
+            block_len: params.overlapping_block_len,
+        });
+        for second in [false, true] {
+            tests.push(NistTest::Serial {
+                pattern_len: params.serial_len,
+                second,
+            });
+        }
+        for backward in [false, true] {
+            tests.push(NistTest::CumulativeSums { backward });
+        }
+        tests.extend(
+            VARIANT_STATES
+                .iter()
+                .map(|&state| NistTest::RandomExcursionsVariant { state }),
+        );
+        tests
+    }
+}
+
+impl RandomnessTest for NistTest {
+    fn name(&self) -> String {
+        match self {
+            NistTest::Frequency => "frequency".to_string(),
+            NistTest::BlockFrequency { .. } => "block-frequency".to_string(),
+            NistTest::Runs => "runs".to_string(),
+            NistTest::LongestRun => "longest-run".to_string(),
+            NistTest::Spectral => "spectral".to_string(),
+            NistTest::NonOverlappingTemplate { template, .. } => {
+                format!("non-overlapping-template ({template})")
+            }
+            NistTest::OverlappingTemplate { .. } => "overlapping-template".to_string(),
+            NistTest::Serial { second: false, .. } => "serial (first difference)".to_string(),
+            NistTest::Serial { second: true, .. } => "serial (second difference)".to_string(),
+            NistTest::CumulativeSums { backward: false } => "cumulative-sums (forward)".to_string(),
+            NistTest::CumulativeSums { backward: true } => "cumulative-sums (backward)".to_string(),
+            NistTest::RandomExcursionsVariant { state } => {
+                format!("random-excursions-variant (x = {state})")
+            }
+        }
+    }
+
+    fn min_bits(&self) -> usize {
+        match self {
+            NistTest::Frequency | NistTest::Runs | NistTest::Spectral => 1,
+            NistTest::CumulativeSums { .. } => 1,
+            NistTest::BlockFrequency { block_len } => block_len.unwrap_or(20),
+            NistTest::LongestRun => 128,
+            NistTest::NonOverlappingTemplate { template, blocks } => {
+                (*blocks).max(1) * (template.len() + 1)
+            }
+            NistTest::OverlappingTemplate { block_len, .. } => *block_len,
+            NistTest::Serial { pattern_len, .. } => *pattern_len,
+            // 500 cycles take at least 999 steps.
+            NistTest::RandomExcursionsVariant { .. } => 999,
+        }
+    }
+
+    fn run(&self, bits: &BitStream) -> TestResult {
+        self.run_shared(bits, &Shared::default())
+    }
+}
+
+impl NistTest {
+    fn run_shared(&self, bits: &BitStream, shared: &Shared) -> TestResult {
+        let (statistic, p_value) = match *self {
+            NistTest::Frequency => {
+                let result = nist::frequency(bits);
+                (result.statistic, result.p_value)
+            }
+            NistTest::BlockFrequency { block_len } => {
+                let params = NistParams {
+                    block_frequency_len: block_len,
+                    ..NistParams::default()
+                };
+                let result =
+                    nist::block_frequency(bits, params.block_frequency_len_for(bits.len()));
+                (result.chi_square, result.p_value)
+            }
+            NistTest::Runs => {
+                let result = nist::runs(bits);
+                (result.runs as f64, result.p_value)
+            }
+            NistTest::LongestRun => {
+                let result = nist::longest_run_of_ones(bits);
+                (result.chi_square, result.p_value)
+            }
+            NistTest::Spectral => {
+                let result = nist::spectral(bits);
+                (result.statistic, result.p_value)
+            }
+            NistTest::NonOverlappingTemplate { template, blocks } => {
+                let result = nist::non_overlapping_template(bits, &template, blocks);
+                (result.chi_square, result.p_value)
+            }
+            NistTest::OverlappingTemplate {
+                template_len,
+                block_len,
+            } => {
+                let result = nist::overlapping_template(bits, template_len, block_len);
+                (result.chi_square, result.p_value)
+            }
+            NistTest::Serial {
+                pattern_len,
+                second,
+            } => {
+                let cached = shared
+                    .serial
+                    .get_or_init(|| (pattern_len, nist::serial(bits, pattern_len)));
+                let fresh;
+                let result = if cached.0 == pattern_len {
+                    &cached.1
+                } else {
+                    fresh = nist::serial(bits, pattern_len);
+                    &fresh
+                };
+                if second {
+                    (result.second_difference, result.p_values[1])
+                } else {
+                    (result.first_difference, result.p_values[0])
+                }
+            }
+            NistTest::CumulativeSums { backward } => {
+                let result = shared
+                    .cumulative_sums
+                    .get_or_init(|| nist::cumulative_sums(bits));
+                let i = backward as usize;
+                (result.max_excursions[i] as f64, result.p_values[i])
+            }
+            NistTest::RandomExcursionsVariant { state } => {
+                match VARIANT_STATES.iter().position(|&x| x == state) {
+                    Some(i) => {
+                        let result = shared
+                            .excursions
+                            .get_or_init(|| nist::random_excursions_variant(bits));
+                        (result.visits[i] as f64, result.p_values[i])
+                    }
+                    None => (f64::NAN, f64::NAN),
+                }
+            }
+        };
+        TestResult::new(self.name(), statistic, p_value)
+    }
+}
+
+/// One of the FIPS 140-2 tests of [`fips140`] over the complete bytes of a
+/// sequence.
+///
+/// They pass or fail by fixed bounds rather than by p-value: the statistic
+/// counts failing blocks (repeated words for the continuous test), and the
+/// p-value is 1 if there are none and 0 if there are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Fips140Test {
+    /// Monobit test of each 20,000-bit block.
+    Monobit,
+    /// Poker test of each block.
+    Poker,
+    /// Runs test of each block.
+    Runs,
+    /// Long run test of each block.
+    LongRun,
+    /// Continuous test of consecutive 32-bit words.
+    Continuous,
+}
+
+impl Fips140Test {
+    /// Every test, in report order.
+    pub fn all() -> [Fips140Test; 5] {
+        [
+            Fips140Test::Monobit,
+            Fips140Test::Poker,
+            Fips140Test::Runs,
+            Fips140Test::LongRun,
+            Fips140Test::Continuous,
+        ]
+    }
+}
+
+impl RandomnessTest for Fips140Test {
+    fn name(&self) -> String {
+        match self {
+            Fips140Test::Monobit => "monobit",
+            Fips140Test::Poker => "poker",
+            Fips140Test::Runs => "runs",
+            Fips140Test::LongRun => "long-run",
+            Fips140Test::Continuous => "continuous",
+        }
+        .to_string()
+    }
+
+    fn min_bits(&self) -> usize {
+        match self {
+            Fips140Test::Continuous => 64,
+            _ => 20_000,
+        }
+    }
+
+    fn run(&self, bits: &BitStream) -> TestResult {
+        self.run_shared(bits, &Shared::default())
+    }
+}
+
+impl Fips140Test {
+    fn run_shared(&self, bits: &BitStream, shared: &Shared) -> TestResult {
+        let status = shared
+            .fips140
+            .get_or_init(|| fips140::check(bits.as_bytes()));
+        let failures = match self {
+            Fips140Test::Monobit => status.monobit_failures,
+            Fips140Test::Poker => status.poker_failures,
+            Fips140Test::Runs => status.runs_failures,
+            Fips140Test::LongRun => status.long_run_failures,
+            Fips140Test::Continuous => status.continuous_failures,
+        };
+        let p_value = if failures == 0 { 1.0 } else { 0.0 };
+        TestResult::new(self.name(), failures as f64, p_value)
+    }
+}
+
+/// One statistic of the Diehard tests of [`diehard`], over the complete
+/// bytes of a sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiehardTest {
+    /// Birthday spacings.
+    BirthdaySpacings,
+    /// Count-the-1s on a stream of bytes.
+    CountOnesStream,
+    /// Count-the-1s on byte `(x >> shift) as u8` of each integer.
+    CountOnesBytes { shift: u32 },
+    /// Parking lot.
+    ParkingLot,
+    /// Minimum distance.
+    MinimumDistance,
+    /// 3D spheres.
+    Spheres3d,
+    /// Squeeze.
+    Squeeze,
+    /// Number of craps wins.
+    CrapsWins,
+    /// Throws per craps game.
+    CrapsThrows,
+}
+
+impl DiehardTest {
+    /// Every statistic, with count-the-1s on specific bytes for every shift
+    /// as Diehard runs it, in report order.
+    pub fn all() -> Vec<DiehardTest> {
+        let mut tests = vec![DiehardTest::BirthdaySpacings, DiehardTest::CountOnesStream];
+        tests.extend(
+            (0..=24)
+                .rev()
+                .map(|shift| DiehardTest::CountOnesBytes { shift }),
+        );
+        tests.extend([
+            DiehardTest::ParkingLot,
+            DiehardTest::MinimumDistance,
+            DiehardTest::Spheres3d,
+            DiehardTest::Squeeze,
+            DiehardTest::CrapsWins,
+            DiehardTest::CrapsThrows,
+        ]);
+        tests
+    }
+}
+
+impl RandomnessTest for DiehardTest {
+    fn name(&self) -> String {
+        match self {
+            DiehardTest::BirthdaySpacings => "birthday-spacings".to_string(),
+            DiehardTest::CountOnesStream => "count-ones-stream".to_string(),
+            DiehardTest::CountOnesBytes { shift } => format!("count-ones-bytes (shift {shift})"),
+            DiehardTest::ParkingLot => "parking-lot".to_string(),
+            DiehardTest::MinimumDistance => "minimum-distance".to_string(),
+            DiehardTest::Spheres3d => "3d-spheres".to_string(),
+            DiehardTest::Squeeze => "squeeze".to_string(),
+            DiehardTest::CrapsWins => "craps (wins)".to_string(),
+            DiehardTest::CrapsThrows => "craps (throws)".to_string(),
+        }
+    }
+
+    fn min_bits(&self) -> usize {
+        // One sample, or one game or squeeze.
+        match self {
+            DiehardTest::BirthdaySpacings => diehard::BIRTHDAYS * 32,
+            DiehardTest::CountOnesStream => 8,
+            DiehardTest::CountOnesBytes { .. } | DiehardTest::Squeeze => 32,
+            DiehardTest::ParkingLot => diehard::PARKING_ATTEMPTS * 64,
+            DiehardTest::MinimumDistance => diehard::DISTANCE_POINTS * 64,
+            DiehardTest::Spheres3d => diehard::SPHERE_POINTS * 96,
+            DiehardTest::CrapsWins | DiehardTest::CrapsThrows => 64,
+        }
+    }
+
+    /// # Panics
+    ///
+    /// If a count-the-1s `shift` exceeds 24.
+    fn run(&self, bits: &BitStream) -> TestResult {
+        self.run_shared(bits, &Shared::default())
+    }
+}
+
+impl DiehardTest {
+    fn run_shared(&self, bits: &BitStream, shared: &Shared) -> TestResult {
+        let data = bits.as_bytes();
+        let craps = || shared.craps.get_or_init(|| diehard::craps(data));
+        let (statistic, p_value) = match *self {
+            DiehardTest::BirthdaySpacings => {
+                let result = diehard::birthday_spacings(data);
+                (result.chi_square, result.p_value)
+            }
+            DiehardTest::CountOnesStream => {
+                let result = diehard::count_ones_stream(data);
+                (result.statistic, result.p_value)
+            }
+            DiehardTest::CountOnesBytes { shift } => {
+                let result = diehard::count_ones_bytes(data, shift);
+                (result.statistic, result.p_value)
+            }
+            DiehardTest::ParkingLot => {
+                let result = diehard::parking_lot(data);
+                (result.mean, result.p_value)
+            }
+            DiehardTest::MinimumDistance => {
+                let result = diehard::minimum_distance(data);
+                (result.ks_statistic, result.p_value)
+            }
+            DiehardTest::Spheres3d => {
+                let result = diehard::spheres_3d(data);
+                (result.ks_statistic, result.p_value)
+            }
+            DiehardTest::Squeeze => {
+                let result = diehard::squeeze(data);
+                (result.chi_square, result.p_value)
+            }
+            DiehardTest::CrapsWins => {
+                let result = craps();
+                (result.wins_z_score, result.wins_p_value)
+            }
+            DiehardTest::CrapsThrows => {
+                let result = craps();
+                (result.throws_chi_square, result.throws_p_value)
+            }
+        };
+        TestResult::new(self.name(), statistic, p_value)
+    }
+}
+
+/// One statistic of the GCD test of [`gcd`], over the complete 8-byte
+/// pairs of a sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GcdTest {
+    /// Chi-square of the division step counts.
+    Steps,
+    /// Chi-square of the gcd values.
+    Gcd,
+}
+
+impl GcdTest {
+    /// Every statistic, in report order.
+    pub fn all() -> [GcdTest; 2] {
+        [GcdTest::Steps, GcdTest::Gcd]
+    }
+}
+
+impl RandomnessTest for GcdTest {
+    fn name(&self) -> String {
+        match self {
+            GcdTest::Steps => "gcd (steps)",
+            GcdTest::Gcd => "gcd (values)",
+        }
+        .to_string()
+    }
+
+    fn min_bits(&self) -> usize {
+        // One pair.
+        64
+    }
+
+    fn run(&self, bits: &BitStream) -> TestResult {
+        self.run_shared(bits, &Shared::default())
+    }
+}
+
+impl GcdTest {
+    fn run_shared(&self, bits: &BitStream, shared: &Shared) -> TestResult {
+        let result = shared.gcd.get_or_init(|| gcd::gcd_test(bits.as_bytes()));
+        let (statistic, p_value) = match self {
+            GcdTest::Steps => (result.steps_chi_square, result.steps_p_value),
+            GcdTest::Gcd => (result.gcd_chi_square, result.gcd_p_value),
+        };
+        TestResult::new(self.name(), statistic, p_value)
+    }
+}
+
+/// One of the AIS 31 procedures of [`ais31`] over the complete bytes of a
+/// sequence.
+///
+/// Like the FIPS 140-2 tests they pass or fail by fixed bounds: the
+/// statistic counts failed tests, and the p-value is 1 if there are none
+/// and 0 if there are. A single failure, which AIS 31 allows to be retried
+/// on fresh data, counts as a failure, since a report has no more data to
+/// retry on. Inputs too short for a procedure skip it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Ais31Test {
+    /// Procedure A, tests T0 to T5 on [`ais31::PROCEDURE_A_LEN`] bytes.
+    ProcedureA,
+    /// Procedure B, tests T6 to T8 on about a megabyte.
+    ProcedureB,
+}
+
+impl Ais31Test {
+    /// Both procedures, in report order.
+    pub fn all() -> [Ais31Test; 2] {
+        [Ais31Test::ProcedureA, Ais31Test::ProcedureB]
+    }
+}
+
+impl RandomnessTest for Ais31Test {
+    fn name(&self) -> String {
+        match self {
+            Ais31Test::ProcedureA => "procedure-a",
+            Ais31Test::ProcedureB => "procedure-b",
+        }
+        .to_string()
+    }
+
+    fn min_bits(&self) -> usize {
+        match self {
+            Ais31Test::ProcedureA => ais31::PROCEDURE_A_LEN * 8,
+            // Procedure B reads as much as its data needs; it reports
+            // running out itself.
+            Ais31Test::ProcedureB => 1,
+        }
+    }
+
+    fn run(&self, bits: &BitStream) -> TestResult {
+        let data = bits.as_bytes();
+        let failures = match self {
+            Ais31Test::ProcedureA => ais31::procedure_a(data).map(|r| r.total_failures()),
+            Ais31Test::ProcedureB => ais31::procedure_b(data).map(|r| r.total_failures()),
+        };
+        let (statistic, p_value) = match failures {
+            Ok(0) => (0.0, 1.0),
+            Ok(failures) => (failures as f64, 0.0),
+            Err(_) => (f64::NAN, f64::NAN),
+        };
+        TestResult::new(self.name(), statistic, p_value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_bytes(len: usize, seed: u64) -> Vec<u8> {
+        let mut x = seed;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_random_data_passes_every_battery() {
+        let data = random_bytes(700_000, 3);
+        let report = RandomnessReport::builder().alpha(0.0001).run(&data);
+        for battery in [
+            Battery::Ent,
+            Battery::Nist,
+            Battery::Fips140,
+            Battery::Diehard,
+        ] {
+            assert!(report.entries.iter().any(|e| e.battery == battery));
+        }
+        // 148 templates and 18 excursion states among the NIST entries, 25
+        // byte positions among the Diehard ones.
+        assert_eq!(report.entries.len(), 3 + 176 + 5 + 33);
+        assert!(report.passed(), "{report}");
+        assert_eq!(report.get("frequency").unwrap().battery, Battery::Nist);
+        assert_eq!(report.input, InputDigest::new(&data, false));
+        let text = report.to_string();
+        assert!(text.ends_with(": PASSED"), "{text}");
+    }
+
+    #[test]
+    fn test_verdicts_follow_alpha() {
+        // Bytes 0..=99: the byte chi-square fails, the FIPS tests are
+        // skipped without a complete block.
+        let data: Vec<u8> = (0..1000).map(|i| (i % 100) as u8).collect();
+        let report = RandomnessReportBuilder::none()
+            .with_ent()
+            .with_fips140()
+            .run(&data);
+        let chi_square = report.get("chi-square").unwrap();
+        assert_eq!(chi_square.status, TestStatus::Failed);
+        assert_eq!(report.get("poker").unwrap().status, TestStatus::Skipped);
+        assert_eq!(report.get("continuous").unwrap().status, TestStatus::Passed);
+        assert!(!report.passed());
+        assert!(report.failures().all(|e| e.battery == Battery::Ent));
+        assert_eq!(report.count(TestStatus::Skipped), 4);
+
+        // The same input judged at a level below its p-value passes it.
+        let p = chi_square.result.p_value;
+        let lenient = RandomnessReportBuilder::none()
+            .with_ent()
+            .alpha(p / 2.0)
+            .run(&data);
+        assert_eq!(
+            lenient.get("chi-square").unwrap().status,
+            TestStatus::Passed
+        );
+    }
+
+    #[test]
+    fn test_correction_across_entries() {
+        // Bytes 250 to 255 folded onto 244 to 249 fail the chi-square at
+        // twice its p-value, until Bonferroni triples it for the three ent
+        // statistics.
+        let data: Vec<u8> = random_bytes(3000, 5)
+            .into_iter()
+            .map(|b| if b >= 250 { b - 6 } else { b })
+            .collect();
+        let raw = RandomnessReportBuilder::none().with_ent().run(&data);
+        let p = raw.get("chi-square").unwrap().result.p_value;
+        let alpha = p * 2.0;
+        let raw = RandomnessReportBuilder::none()
+            .with_ent()
+            .alpha(alpha)
+            .run(&data);
+        let entry = raw.get("chi-square").unwrap();
+        assert_eq!(entry.adjusted_p_value, p);
+        assert_eq!(entry.status, TestStatus::Failed);
+
+        let corrected = RandomnessReportBuilder::none()
+            .with_ent()
+            .alpha(alpha)
+            .correction(Correction::Bonferroni)
+            .run(&data);
+        let entry = corrected.get("chi-square").unwrap();
+        assert_eq!(entry.result.p_value, p);
+        assert!((entry.adjusted_p_value - 3.0 * p).abs() < 1e-12);
+        assert_eq!(entry.status, TestStatus::Passed);
+        assert_eq!(corrected.correction, Correction::Bonferroni);
+        assert!(corrected.to_string().contains("(Bonferroni correction)"));
+    }
+
+    #[test]
+    fn test_blocks_are_judged_by_second_level_analysis() {
+        let data = random_bytes(100 * 5000 + 17, 6);
+        let report = RandomnessReportBuilder::none()
+            .with_ent()
+            .blocks(100)
+            .run(&data);
+        assert_eq!(report.blocks, 100);
+        assert!(report.passed(), "{report}");
+        let entry = report.get("chi-square").unwrap();
+        assert_eq!(entry.blocks.len(), 100);
+        let analysis = entry.second_level.as_ref().unwrap();
+        assert_eq!(analysis.sequences, 100);
+        assert_eq!(entry.result.statistic, analysis.proportion);
+        assert_eq!(entry.result.p_value, analysis.uniformity_p_value);
+        assert!(analysis.ks_p_value > 0.001);
+
+        // Every block's chi-square passes, but with p-values crowded near 1:
+        // the blocks are too uniform, which only the second level sees.
+        let flat: Vec<u8> = (0..100 * 5120).map(|i| (i % 256) as u8).collect();
+        let report = RandomnessReportBuilder::none()
+            .with_ent()
+            .blocks(100)
+            .run(&flat);
+        let entry = report.get("chi-square").unwrap();
+        assert!(entry.blocks.iter().all(|r| r.p_value == 1.0));
+        assert_eq!(entry.result.statistic, 1.0);
+        assert_eq!(entry.status, TestStatus::Failed);
+
+        // Too short for any block to give a Monte Carlo estimate.
+        let report = RandomnessReportBuilder::none()
+            .with_ent()
+            .blocks(10)
+            .run(&[1, 2, 3]);
+        assert_eq!(
+            report.get("monte-carlo-pi").unwrap().status,
+            TestStatus::Skipped
+        );
+    }
+
+    #[test]
+    fn test_nist_uses_leading_sequence() {
+        let mut data = random_bytes(20_000, 4);
+        // A constant tail beyond the sequence the NIST tests see.
+        data.extend([0; 20_000]);
+        let mut params = NistParams {
+            sequence_len: Some(20_000),
+            templates: Some(vec!["000000001".parse().unwrap()]),
+            ..NistParams::default()
+        };
+        let report = RandomnessReportBuilder::none()
+            .with_nist_params(params.clone())
+            .run(&data);
+        assert_eq!(report.get("frequency").unwrap().status, TestStatus::Passed);
+        params.sequence_len = None;
+        let report = RandomnessReportBuilder::none()
+            .with_nist_params(params)
+            .run(&data);
+        assert_eq!(report.get("frequency").unwrap().status, TestStatus::Failed);
+    }
+
+    /// Reports the number of bits it was given.
+    struct Length(&'static str);
+
+    impl RandomnessTest for Length {
+        fn name(&self) -> String {
+            self.0.to_string()
+        }
+
+        fn min_bits(&self) -> usize {
+            16
+        }
+
+        fn run(&self, bits: &BitStream) -> TestResult {
+            TestResult::new(self.name(), bits.len() as f64, 0.5)
+        }
+    }
+
+    #[test]
+    fn test_custom_tests_join_their_battery() {
+        let params = NistParams {
+            sequence_len: Some(100),
+            templates: Some(Vec::new()),
+            ..NistParams::default()
+        };
+        let builder = RandomnessReportBuilder::none()
+            .with_test(Battery::Custom, Length("custom"))
+            .with_test(Battery::Nist, Length("nist"))
+            .with_ent()
+            .with_nist_params(params);
+        let report = builder.run(&random_bytes(1000, 7));
+        let names: Vec<&str> = report
+            .entries
+            .iter()
+            .map(|e| e.result.name.as_str())
+            .collect();
+        assert_eq!(
+            names[..4],
+            [
+                "chi-square",
+                "monte-carlo-pi",
+                "serial-correlation",
+                "frequency"
+            ]
+        );
+        assert_eq!(names[names.len() - 2..], ["nist", "custom"]);
+        assert_eq!(report.get("nist").unwrap().result.statistic, 800.0);
+        assert_eq!(report.get("custom").unwrap().result.statistic, 8000.0);
+        assert_eq!(report.get("custom").unwrap().status, TestStatus::Passed);
+        assert!(format!("{builder:?}").contains("(Custom, \"custom\")"));
+
+        let report = builder.run(&[1]);
+        let entry = report.get("custom").unwrap();
+        assert_eq!(entry.status, TestStatus::Skipped);
+        assert!(entry.result.statistic.is_nan());
+    }
+
+    #[test]
+    fn test_built_in_tests_match_their_functions() {
+        let data = random_bytes(10_000, 8);
+        let bits = BitStream::from_bytes(&data);
+        let result = DiehardTest::CountOnesBytes { shift: 8 }.run(&bits);
+        assert_eq!(result.p_value, diehard::count_ones_bytes(&data, 8).p_value);
+        let result = NistTest::CumulativeSums { backward: true }.run(&bits);
+        assert_eq!(result.p_value, nist::cumulative_sums(&bits).p_values[1]);
+        assert_eq!(result.name, "cumulative-sums (backward)");
+        let result = NistTest::RandomExcursionsVariant { state: 0 }.run(&bits);
+        assert!(result.p_value.is_nan());
+        assert_eq!(NistTest::all(&NistParams::default()).len(), 176);
+        assert_eq!(DiehardTest::all().len(), 33);
+    }
+
+    #[test]
+    fn test_shared_results_match_separate_runs() {
+        let data = random_bytes(400_000, 9);
+        let builder = RandomnessReport::builder().with_gcd();
+        let report = builder.run(&data);
+        let bits = BitStream::from_bytes(&data);
+        let nist = BitStream::from_bytes(&data[..NIST_SEQUENCE_LEN]);
+        for (entry, (battery, test)) in report.entries.iter().zip(builder.tests()) {
+            let bits = if battery == Battery::Nist {
+                &nist
+            } else {
+                &bits
+            };
+            let expected = test.run(bits, &Shared::default());
+            assert_eq!(entry.result.name, expected.name);
+            assert!(
+                entry.result.p_value == expected.p_value
+                    || entry.result.p_value.is_nan() && expected.p_value.is_nan(),
+                "{}",
+                entry.result.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_gcd_and_ais31_batteries() {
+        let data = random_bytes(ais31::PROCEDURE_A_LEN, 10);
+        let report = RandomnessReportBuilder::none()
+            .with_gcd()
+            .with_ais31()
+            .run(&data);
+        let names: Vec<_> = report
+            .entries
+            .iter()
+            .map(|e| e.result.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            ["gcd (steps)", "gcd (values)", "procedure-a", "procedure-b"]
+        );
+        let steps = report.get("gcd (steps)").unwrap();
+        assert_eq!(steps.battery, Battery::Gcd);
+        assert_eq!(steps.result.p_value, gcd::gcd_test(&data).steps_p_value);
+        let procedure_a = report.get("procedure-a").unwrap();
+        assert_eq!(procedure_a.battery, Battery::Ais31);
+        let failures = ais31::procedure_a(&data).unwrap().total_failures();
+        assert_eq!(procedure_a.result.statistic, failures as f64);
+        assert_eq!(procedure_a.result.p_value, (failures == 0) as u8 as f64);
+
+        // Too short for procedure A.
+        let report = RandomnessReportBuilder::none()
+            .with_ais31()
+            .run(&data[..1000]);
+        assert_eq!(
+            report.get("procedure-a").unwrap().status,
+            TestStatus::Skipped
+        );
+        assert!(!RandomnessReport::builder()
+            .tests()
+            .iter()
+            .any(|(b, _)| *b == Battery::Gcd));
+    }
+}
@@ -0,0 +1,118 @@
+//! Rényi entropy of any order.
+//!
+//! H_α = log2(Σ p_i^α) / (1 - α) generalizes Shannon entropy. It never
+//! increases with α, and its special cases bracket how hard a source is to
+//! guess:
+//!
+//! - α = 0, Hartley entropy: log2 of the number of values that occur;
+//! - α = 1, Shannon entropy (the limit);
+//! - α = 2, collision entropy: -log2 of the chance two samples are equal;
+//! - α = ∞, min-entropy: -log2 of the most likely value's probability.
+//!
+//! ```rust
+//! use ent_rs::renyi::renyi_entropy;
+//! let data = b"aaaabbcd";
+//! assert_eq!(renyi_entropy(data, 0.0), 2.0);
+//! assert_eq!(renyi_entropy(data, 1.0), 1.75);
+//! assert_eq!(renyi_entropy(data, f64::INFINITY), 1.0);
+//! ```
+
+use crate::{byte_counts, entropy_from_counts};
+
+/// Rényi entropy of order `alpha` of the byte distribution of `data`, in
+/// bits per byte.
+///
+/// Orders 0, 1 and infinity give the Hartley, Shannon and min-entropy limits.
+/// Returns NaN for a negative or NaN `alpha`, and 0 for empty input.
+pub fn renyi_entropy(data: &[u8], alpha: f64) -> f64 {
+    renyi_entropy_from_counts(&byte_counts(data), alpha)
+}
+
+/// Rényi entropy of order `alpha` of a frequency table, in bits per symbol.
+pub fn renyi_entropy_from_counts(counts: &[usize], alpha: f64) -> f64 {
+    if alpha.is_nan() || alpha < 0.0 {
+        return f64::NAN;
+    }
+    let total = counts.iter().sum::<usize>();
+    let max = counts.iter().copied().max().unwrap_or(0);
+    if total == 0 {
+        return 0.0;
+    }
+    if alpha == 0.0 {
+        return (counts.iter().filter(|&&c| c > 0).count() as f64).log2();
+    }
+    if alpha == 1.0 {
+        return entropy_from_counts(counts);
+    }
+    let p_max = max as f64 / total as f64;
+    if alpha.is_infinite() {
+        return -p_max.log2();
+    }
+    // Σ p^α = p_max^α Σ (c / max)^α, which neither underflows nor overflows
+    // for large α.
+    let scaled = counts
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| (c as f64 / max as f64).powf(alpha))
+        .sum::<f64>();
+    (alpha * p_max.log2() + scaled.log2()) / (1.0 - alpha)
+}
+
+/// Rényi entropies of `data` at each order in `alphas`, e.g. to plot the
+/// spectrum of a source.
+pub fn renyi_spectrum(data: &[u8], alphas: &[f64]) -> Vec<f64> {
+    let counts = byte_counts(data);
+    alphas
+        .iter()
+        .map(|&alpha| renyi_entropy_from_counts(&counts, alpha))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{actual} vs {expected}");
+    }
+
+    #[test]
+    fn test_special_orders_and_limits() {
+        // p = (1/2, 1/4, 1/8, 1/8)
+        let data = b"aaaabbcd";
+        assert_close(renyi_entropy(data, 0.0), 2.0);
+        assert_close(renyi_entropy(data, 1.0), 1.75);
+        // Σ p² = 1/4 + 1/16 + 2/64 = 11/32
+        assert_close(renyi_entropy(data, 2.0), -(11.0f64 / 32.0).log2());
+        assert_close(renyi_entropy(data, f64::INFINITY), 1.0);
+        // Orders near the special ones approach their limits.
+        assert!((renyi_entropy(data, 1.0 + 1e-7) - 1.75).abs() < 1e-6);
+        assert!((renyi_entropy(data, 1e-9) - 2.0).abs() < 1e-6);
+        assert!((renyi_entropy(data, 5000.0) - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_spectrum_is_non_increasing() {
+        let data: Vec<u8> = (0u32..20_000)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8 & 0x3f | (i % 3) as u8)
+            .collect();
+        let alphas = [0.0, 0.5, 1.0, 1.5, 2.0, 4.0, 16.0, 256.0, f64::INFINITY];
+        let spectrum = renyi_spectrum(&data, &alphas);
+        for pair in spectrum.windows(2) {
+            assert!(pair[0] >= pair[1] - 1e-12, "{spectrum:?}");
+        }
+        // Uniform data has the same entropy at every order.
+        let uniform: Vec<u8> = (0..=255).cycle().take(4096).collect();
+        for h in renyi_spectrum(&uniform, &alphas) {
+            assert_close(h, 8.0);
+        }
+    }
+
+    #[test]
+    fn test_degenerate_inputs() {
+        assert_eq!(renyi_entropy(&[], 2.0), 0.0);
+        assert_eq!(renyi_entropy(&[9; 100], 3.0), 0.0);
+        assert!(renyi_entropy(b"ab", -1.0).is_nan());
+        assert!(renyi_entropy(b"ab", f64::NAN).is_nan());
+    }
+}
@@ -0,0 +1,282 @@
+//! Machine-readable JSON reports.
+//!
+//! A [`Report`] is an [`EntStats`] together with what was analyzed, in a
+//! stable layout meant for dashboards and other tools. Its JSON form is:
+//!
+//! ```json
+//! {
+//!   "format_version": 1,
+//!   "input": { "length": 1048576, "mode": "bytes", "sha256": "9f86d0…" },
+//!   "entropy": 7.999825,
+//!   "compression_percent": 0.0021,
+//!   "chi_square": {
+//!     "value": 255.47,
+//!     "degrees_of_freedom": 255,
+//!     "p_value": 0.4956,
+//!     "exceed_probability": 0.4885
+//!   },
+//!   "mean": 127.4891,
+//!   "monte_carlo_pi": { "estimate": 3.143067502, "error_percent": 0.05 },
+//!   "serial_correlation": 0.000362,
+//!   "frequencies": [{ "value": 0, "count": 4120, "fraction": 0.003929 }, …]
+//! }
+//! ```
+//!
+//! - `input.length` is in bytes in both modes; `input.sha256` is `null`
+//!   without the `sha2` feature or when the input is not known.
+//! - `input.mode` is `"bytes"` or `"bits"`.
+//! - `chi_square.p_value` is [`EntStats::p_value`]; `exceed_probability` is
+//!   the exact upper-tail probability, the percentage `ent` reports.
+//! - `serial_correlation` is `null` when undefined (short or constant input).
+//! - `frequencies` lists every value (256 bytes, or bits 0 and 1), including
+//!   those that never occur, or is `null` if the table was not computed.
+//! - Any statistic that was not computed, or is undefined for the input
+//!   (NaN), is `null`.
+//!
+//! Fields are only ever added within a `format_version`; removals and
+//! changes of meaning increment it.
+//!
+//! ```rust
+//! use ent_rs::EntStats;
+//! let json = EntStats::from_data(b"hello, world", false).to_json();
+//! let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+//! assert_eq!(value["input"]["length"], 12);
+//! assert_eq!(value["frequencies"].as_array().unwrap().len(), 256);
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::digest::InputDigest;
+use crate::{chisquare_p_value, serde_float, EntStats};
+
+/// Version of the JSON layout written by [`Report`].
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Statistics and input metadata in the documented JSON layout.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+    /// Layout version, [`FORMAT_VERSION`] for reports built by this crate.
+    pub format_version: u32,
+    /// What was analyzed.
+    pub input: ReportInput,
+    /// Shannon entropy in bits per byte (or bit).
+    #[serde(deserialize_with = "serde_float::nan_from_null")]
+    pub entropy: f64,
+    /// Ideal compression percentage based on entropy.
+    #[serde(deserialize_with = "serde_float::nan_from_null")]
+    pub compression_percent: f64,
+    /// Chi-square test of the frequency table.
+    pub chi_square: ChiSquare,
+    /// Arithmetic mean of all data bytes.
+    #[serde(deserialize_with = "serde_float::nan_from_null")]
+    pub mean: f64,
+    /// Monte Carlo estimate of Pi.
+    pub monte_carlo_pi: MonteCarloPi,
+    /// Serial correlation coefficient; `None` when undefined.
+    pub serial_correlation: Option<f64>,
+    /// Occurrences of every value, if the frequency table was computed.
+    pub frequencies: Option<Vec<Frequency>>,
+}
+
+/// The analyzed input.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportInput {
+    /// Number of bytes analyzed.
+    pub length: u64,
+    /// Whether the input was analyzed as bytes or bits.
+    pub mode: Mode,
+    /// SHA-256 of the input as lowercase hex, if known.
+    pub sha256: Option<String>,
+}
+
+/// Analysis mode, serialized as `"bytes"` or `"bits"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    /// Byte values 0 to 255.
+    Bytes,
+    /// Individual bits.
+    Bits,
+}
+
+/// Chi-square statistic and its probabilities.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ChiSquare {
+    /// The statistic.
+    #[serde(deserialize_with = "serde_float::nan_from_null")]
+    pub value: f64,
+    /// 255 in byte mode, 1 in bit mode.
+    pub degrees_of_freedom: u32,
+    /// [`EntStats::p_value`].
+    #[serde(deserialize_with = "serde_float::nan_from_null")]
+    pub p_value: f64,
+    /// Probability that random data exceeds `value`.
+    #[serde(deserialize_with = "serde_float::nan_from_null")]
+    pub exceed_probability: f64,
+}
+
+/// Monte Carlo estimate of Pi and its error.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MonteCarloPi {
+    /// The estimate.
+    #[serde(deserialize_with = "serde_float::nan_from_null")]
+    pub estimate: f64,
+    /// Distance from Pi as a percentage of Pi.
+    #[serde(deserialize_with = "serde_float::nan_from_null")]
+    pub error_percent: f64,
+}
+
+/// Occurrences of one value.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Frequency {
+    /// The byte value, or the bit (0 or 1) in bit mode.
+    pub value: u8,
+    /// Number of occurrences.
+    pub count: usize,
+    /// Share of all values.
+    #[serde(deserialize_with = "serde_float::nan_from_null")]
+    pub fraction: f64,
+}
+
+impl Report {
+    /// Report of `stats`, computed over the input described by `input`.
+    pub fn new(stats: &EntStats, input: &InputDigest) -> Self {
+        let mode = if input.bit_mode {
+            Mode::Bits
+        } else {
+            Mode::Bytes
+        };
+        let frequencies = match (&stats.byte_frequencies, &stats.bit_frequencies) {
+            (_, Some(bits)) => Some(
+                bits.iter()
+                    .zip(0..)
+                    .map(|(&(count, fraction), value)| Frequency {
+                        value,
+                        count,
+                        fraction,
+                    })
+                    .collect(),
+            ),
+            (Some(bytes), None) => Some(
+                bytes
+                    .iter()
+                    .map(|&(value, count, fraction)| Frequency {
+                        value,
+                        count,
+                        fraction,
+                    })
+                    .collect(),
+            ),
+            (None, None) => None,
+        };
+        let degrees_of_freedom = match mode {
+            Mode::Bytes => 255,
+            Mode::Bits => 1,
+        };
+        let pi = std::f64::consts::PI;
+        Report {
+            format_version: FORMAT_VERSION,
+            input: ReportInput {
+                length: input.len,
+                mode,
+                sha256: input.sha256_hex(),
+            },
+            entropy: stats.entropy,
+            compression_percent: stats.compression_percent,
+            chi_square: ChiSquare {
+                value: stats.chisquare,
+                degrees_of_freedom,
+                p_value: stats.p_value,
+                exceed_probability: chisquare_p_value(stats.chisquare, degrees_of_freedom as f64),
+            },
+            mean: stats.mean,
+            monte_carlo_pi: MonteCarloPi {
+                estimate: stats.pi_estimate,
+                error_percent: 100.0 * (stats.pi_estimate - pi).abs() / pi,
+            },
+            serial_correlation: Some(stats.serial_correlation).filter(|&r| r != -99999.0),
+            frequencies,
+        }
+    }
+
+    /// The report as compact JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("reports always serialize")
+    }
+
+    /// The report as indented JSON.
+    pub fn to_json_pretty(&self) -> String {
+        serde_json::to_string_pretty(self).expect("reports always serialize")
+    }
+}
+
+/// Report of `stats` without an input digest: the length and mode are taken
+/// from the frequency table, and are 0 bytes in byte mode if it was not
+/// computed.
+impl From<&EntStats> for Report {
+    fn from(stats: &EntStats) -> Self {
+        let bit_mode = stats.bit_frequencies.is_some();
+        let samples = stats.sample_count().unwrap_or(0) as u64;
+        let input = InputDigest {
+            len: if bit_mode { samples / 8 } else { samples },
+            bit_mode,
+            sha256: None,
+        };
+        Report::new(stats, &input)
+    }
+}
+
+impl EntStats {
+    /// The statistics as a compact JSON [`Report`]; see the
+    /// [module documentation](crate::report) for the layout.
+    pub fn to_json(&self) -> String {
+        Report::from(self).to_json()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_layout_and_round_trip() {
+        let data: Vec<u8> = (0u32..10_000)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+            .collect();
+        let stats = EntStats::from_data(&data, false);
+        let report = Report::new(&stats, &InputDigest::new(&data, false));
+        let value: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(value["format_version"], 1);
+        assert_eq!(value["input"]["length"], 10_000);
+        assert_eq!(value["input"]["mode"], "bytes");
+        assert_eq!(value["chi_square"]["degrees_of_freedom"], 255);
+        assert_eq!(value["frequencies"][7]["value"], 7);
+        assert_eq!(
+            value["frequencies"][7]["count"],
+            stats.byte_frequencies.as_ref().unwrap()[7].1
+        );
+        // The p-value is NaN here, so compare the JSON rather than the reports.
+        let parsed: Report = serde_json::from_str(&report.to_json_pretty()).unwrap();
+        assert_eq!(parsed.to_json(), report.to_json());
+
+        let bits = Report::from(&EntStats::from_data(&[0u8; 4], true));
+        assert_eq!(bits.input.length, 4);
+        assert_eq!(bits.input.mode, Mode::Bits);
+        assert_eq!(bits.serial_correlation, None);
+        assert_eq!(bits.frequencies.unwrap()[0].count, 32);
+    }
+
+    #[test]
+    fn test_skipped_statistics_are_null() {
+        let stats = EntStats::builder()
+            .without_pi()
+            .without_frequencies()
+            .analyze(b"abcdefgh");
+        let json = stats.to_json();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(value["monte_carlo_pi"]["estimate"].is_null());
+        assert!(value["frequencies"].is_null());
+        let report: Report = serde_json::from_str(&json).unwrap();
+        assert!(report.monte_carlo_pi.estimate.is_nan());
+    }
+}
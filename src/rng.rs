@@ -0,0 +1,159 @@
+//! Testing `rand_core` generators directly.
+//!
+//! [`EntStats::from_rng`] streams a generator's output through an
+//! [`EntAccumulator`] without holding it; the batteries, which need the
+//! whole input at once, fill a buffer first.
+
+use std::sync::Arc;
+
+use rand_core::RngCore;
+
+use crate::randomness::{RandomnessReport, RandomnessReportBuilder};
+use crate::suite::{Suite, SuiteConfig, SuiteReport};
+use crate::{instrument, EntAccumulator, EntStats};
+
+/// Bytes drawn from the generator at a time.
+const CHUNK_SIZE: usize = 64 << 10;
+
+impl EntStats {
+    /// Like [`EntStats::from_data`] on the next `len` bytes of `rng`, as
+    /// produced by [`RngCore::fill_bytes`].
+    ///
+    /// ```rust
+    /// use ent_rs::EntStats;
+    /// use rand_core::{impls, RngCore};
+    ///
+    /// struct Counter(u64);
+    ///
+    /// impl RngCore for Counter {
+    ///     fn next_u32(&mut self) -> u32 {
+    ///         self.next_u64() as u32
+    ///     }
+    ///     fn next_u64(&mut self) -> u64 {
+    ///         self.0 += 1;
+    ///         self.0
+    ///     }
+    ///     fn fill_bytes(&mut self, dest: &mut [u8]) {
+    ///         impls::fill_bytes_via_next(self, dest)
+    ///     }
+    ///     fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+    ///         self.fill_bytes(dest);
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let stats = EntStats::from_rng(&mut Counter(0), 1 << 16, false);
+    /// assert!(stats.entropy < 4.0);
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(rng)))]
+    pub fn from_rng<R: RngCore + ?Sized>(rng: &mut R, len: usize, bit_mode: bool) -> Self {
+        let timer = instrument::Timer::start();
+        let mut acc = EntAccumulator::new(bit_mode);
+        let mut buf = vec![0u8; len.min(CHUNK_SIZE)];
+        let mut remaining = len;
+        while remaining > 0 {
+            let chunk = &mut buf[..remaining.min(CHUNK_SIZE)];
+            rng.fill_bytes(chunk);
+            acc.update(chunk);
+            remaining -= chunk.len();
+        }
+        timer.finish("from_rng", len);
+        acc.finalize()
+    }
+}
+
+impl RandomnessReportBuilder {
+    /// Like [`run`](Self::run) on the next `len` bytes of `rng`.
+    pub fn run_rng<R: RngCore + ?Sized>(&self, rng: &mut R, len: usize) -> RandomnessReport {
+        self.run(&fill(rng, len))
+    }
+}
+
+impl Suite {
+    /// Like [`run`](Self::run) on the next `len` bytes of `rng`.
+    pub fn run_rng<R: RngCore + ?Sized>(
+        &self,
+        rng: &mut R,
+        len: usize,
+        config: &SuiteConfig,
+    ) -> SuiteReport {
+        self.run(fill(rng, len).into(), config)
+    }
+
+    /// Like [`run_split`](Self::run_split) on the next `len` bytes of `rng`.
+    pub fn run_split_rng<R: RngCore + ?Sized>(
+        &self,
+        rng: &mut R,
+        len: usize,
+        config: &SuiteConfig,
+    ) -> SuiteReport {
+        let data: Arc<[u8]> = fill(rng, len).into();
+        self.run_split(data, config)
+    }
+}
+
+fn fill<R: RngCore + ?Sized>(rng: &mut R, len: usize) -> Vec<u8> {
+    let mut data = vec![0u8; len];
+    rng.fill_bytes(&mut data);
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::suite::TestStatus;
+
+    /// SplitMix64.
+    struct SplitMix(u64);
+
+    impl RngCore for SplitMix {
+        fn next_u32(&mut self) -> u32 {
+            (self.next_u64() >> 32) as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = self.0;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            rand_core::impls::fill_bytes_via_next(self, dest)
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_from_rng_matches_buffered_output() {
+        // Longer than one chunk, and not a multiple of the generator's word.
+        let len = CHUNK_SIZE * 2 + 13;
+        let streamed = EntStats::from_rng(&mut SplitMix(1), len, false);
+        let buffered = EntStats::from_data(&fill(&mut SplitMix(1), len), false);
+        assert_eq!(streamed.entropy, buffered.entropy);
+        assert_eq!(streamed.chisquare, buffered.chisquare);
+        assert_eq!(streamed.serial_correlation, buffered.serial_correlation);
+        let bits = EntStats::from_rng(&mut SplitMix(1), 1000, true);
+        assert!(bits.entropy > 0.99);
+    }
+
+    #[test]
+    fn test_batteries_run_on_generators() {
+        let rng: &mut dyn RngCore = &mut SplitMix(2);
+        let report = RandomnessReportBuilder::none()
+            .with_ent()
+            .with_fips140()
+            .run_rng(rng, 100_000);
+        assert!(report.passed(), "{report}");
+        let report = Suite::standard().run_rng(rng, 100_000, &SuiteConfig::default());
+        assert!(report
+            .results
+            .iter()
+            .all(|r| r.status == TestStatus::Passed));
+    }
+}
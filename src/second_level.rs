@@ -0,0 +1,176 @@
+//! Second-level analysis of p-values from many sequences.
+//!
+//! A test run over many independent sequences yields one p-value per
+//! sequence. For a good generator they are uniform on `[0, 1]`, and about
+//! `1 - α` of them reach the significance level. SP 800-22 §4.2 checks both:
+//! a chi-square test over ten equal bins (the "P-value of P-values") and the
+//! proportion of passing sequences against a three-sigma confidence interval.
+//! A Kolmogorov-Smirnov test of the p-values, which needs no binning and
+//! stays sensitive with few sequences, is reported alongside.
+//!
+//! ```rust
+//! use ent_rs::second_level::second_level;
+//! let p_values: Vec<f64> = (0..100).map(|i| (i as f64 + 0.5) / 100.0).collect();
+//! let result = second_level(&p_values, 0.01);
+//! assert_eq!(result.bins, [10; 10]);
+//! assert!(result.passed());
+//! ```
+
+use crate::chisquare_p_value;
+
+/// Number of equal-width bins over `[0, 1]`.
+pub const BINS: usize = 10;
+
+/// Uniformity p-value below which the p-values are considered non-uniform.
+pub const UNIFORMITY_THRESHOLD: f64 = 0.0001;
+
+/// Smallest number of sequences SP 800-22 considers sufficient for the
+/// uniformity test.
+pub const MIN_SEQUENCES: usize = 55;
+
+/// Second-level statistics of one test's p-values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecondLevel {
+    /// Number of p-values analyzed (NaN p-values are ignored).
+    pub sequences: usize,
+    /// Counts of p-values in `[0, 0.1)`, `[0.1, 0.2)`, ..., `[0.9, 1.0]`.
+    pub bins: [usize; BINS],
+    /// Chi-square p-value of the bin counts against uniform, 9 degrees of freedom.
+    pub uniformity_p_value: f64,
+    /// Kolmogorov-Smirnov statistic of the p-values against uniform.
+    pub ks_statistic: f64,
+    /// p-value of the Kolmogorov-Smirnov statistic; informational, as
+    /// [`passed`](Self::passed) follows SP 800-22 and uses the chi-square.
+    pub ks_p_value: f64,
+    /// Fraction of p-values at or above `alpha`.
+    pub proportion: f64,
+    /// Acceptable range of the proportion, `p̂ ± 3·sqrt(p̂(1 - p̂)/s)` with `p̂ = 1 - α`.
+    pub proportion_interval: (f64, f64),
+}
+
+impl SecondLevel {
+    /// Whether the proportion is within its interval and the p-values are
+    /// uniform at [`UNIFORMITY_THRESHOLD`].
+    pub fn passed(&self) -> bool {
+        self.proportion >= self.proportion_interval.0
+            && self.uniformity_p_value >= UNIFORMITY_THRESHOLD
+    }
+}
+
+/// Compute the second-level statistics of `p_values` at significance `alpha`.
+///
+/// Fewer than [`MIN_SEQUENCES`] p-values make the uniformity p-value
+/// unreliable; it is still reported.
+pub fn second_level(p_values: &[f64], alpha: f64) -> SecondLevel {
+    let defined: Vec<f64> = p_values.iter().copied().filter(|p| !p.is_nan()).collect();
+    let mut bins = [0usize; BINS];
+    let mut passing = 0;
+    let mut sequences = 0;
+    for &p in &defined {
+        let bin = ((p * BINS as f64) as usize).min(BINS - 1);
+        bins[bin] += 1;
+        if p >= alpha {
+            passing += 1;
+        }
+        sequences += 1;
+    }
+
+    let s = sequences as f64;
+    let expected = s / BINS as f64;
+    let chi_square = bins
+        .iter()
+        .map(|&f| (f as f64 - expected).powi(2) / expected)
+        .sum::<f64>();
+    let p_hat = 1.0 - alpha;
+    let margin = 3.0 * (p_hat * (1.0 - p_hat) / s).sqrt();
+    let (ks_statistic, ks_p_value) = ks_uniform(&defined);
+
+    SecondLevel {
+        sequences,
+        bins,
+        uniformity_p_value: chisquare_p_value(chi_square, (BINS - 1) as f64),
+        ks_statistic,
+        ks_p_value,
+        proportion: passing as f64 / s,
+        proportion_interval: (p_hat - margin, (p_hat + margin).min(1.0)),
+    }
+}
+
+/// Kolmogorov-Smirnov statistic of `p_values` against the uniform
+/// distribution, and its p-value with Stephens' small-sample correction.
+pub(crate) fn ks_uniform(p_values: &[f64]) -> (f64, f64) {
+    let n = p_values.len();
+    if n == 0 {
+        return (f64::NAN, f64::NAN);
+    }
+    let mut sorted = p_values.to_vec();
+    sorted.sort_unstable_by(f64::total_cmp);
+    let statistic = sorted
+        .iter()
+        .enumerate()
+        .map(|(i, &p)| ((i + 1) as f64 / n as f64 - p).max(p - i as f64 / n as f64))
+        .fold(0.0, f64::max);
+    let sqrt_n = (n as f64).sqrt();
+    let lambda = (sqrt_n + 0.12 + 0.11 / sqrt_n) * statistic;
+    // Q(λ) = 2 Σ (-1)^(k-1) e^(-2k²λ²); the series needs no terms to speak
+    // of below λ ≈ 0.2, where Q is 1 to double precision.
+    let p_value = if lambda < 0.2 {
+        1.0
+    } else {
+        let terms = (1..=100).map(|k| {
+            let sign = if k % 2 == 1 { 1.0 } else { -1.0 };
+            sign * (-2.0 * (k * k) as f64 * lambda * lambda).exp()
+        });
+        (2.0 * terms.sum::<f64>()).clamp(0.0, 1.0)
+    };
+    (statistic, p_value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nist_proportion_interval() {
+        // SP 800-22 §4.2.1: 1000 sequences at α = 0.01 give 0.99 ± 0.0094392.
+        let p_values = vec![0.5; 1000];
+        let result = second_level(&p_values, 0.01);
+        assert!((result.proportion_interval.0 - 0.9805608).abs() < 1e-6);
+        assert_eq!(result.proportion, 1.0);
+        // All p-values in one bin are far from uniform.
+        assert_eq!(result.bins[5], 1000);
+        assert!(result.uniformity_p_value < UNIFORMITY_THRESHOLD);
+        assert!((result.ks_statistic - 0.5).abs() < 1e-12);
+        assert!(result.ks_p_value < 1e-10);
+        assert!(!result.passed());
+    }
+
+    #[test]
+    fn test_low_proportion_fails() {
+        let p_values: Vec<f64> = (0..200)
+            .map(|i| {
+                if i % 10 == 0 {
+                    0.001
+                } else {
+                    (i as f64 + 0.5) / 200.0
+                }
+            })
+            .collect();
+        let result = second_level(&p_values, 0.01);
+        assert!(result.proportion < result.proportion_interval.0);
+        assert!(!result.passed());
+    }
+
+    #[test]
+    fn test_ks_uniform() {
+        let even: Vec<f64> = (0..100).map(|i| (i as f64 + 0.5) / 100.0).collect();
+        let (d, p) = ks_uniform(&even);
+        assert!((d - 0.005).abs() < 1e-12);
+        assert_eq!(p, 1.0);
+        // D = 0.75 for 20 values: Q(0.75 · (sqrt(20) + 0.12 + 0.11 / sqrt(20))).
+        let (d, p) = ks_uniform(&[0.25; 20]);
+        assert!((d - 0.75).abs() < 1e-12);
+        assert!(p < 1e-8);
+        assert!(ks_uniform(&[]).1.is_nan());
+    }
+}
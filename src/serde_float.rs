@@ -0,0 +1,40 @@
+//! Serde helpers for floats that may be NaN.
+//!
+//! JSON has no NaN, so `serde_json` writes NaN as `null`; these helpers read
+//! it back as NaN instead of failing. [`EntStats::p_value`](crate::EntStats)
+//! is NaN whenever the chi-square statistic is below its degrees of freedom.
+
+use serde::{Deserialize, Deserializer};
+
+/// Deserialize an `f64`, mapping `null` to NaN.
+pub(crate) fn nan_from_null<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    Ok(Option::<f64>::deserialize(deserializer)?.unwrap_or(f64::NAN))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Value {
+        #[serde(deserialize_with = "nan_from_null")]
+        x: f64,
+    }
+
+    fn round_trip(x: f64) -> f64 {
+        let json = serde_json::to_string(&Value { x }).unwrap();
+        serde_json::from_str::<Value>(&json).unwrap().x
+    }
+
+    #[test]
+    fn test_round_trip() {
+        assert_eq!(round_trip(0.25), 0.25);
+        assert_eq!(round_trip(-1e300), -1e300);
+        assert!(round_trip(f64::NAN).is_nan());
+        // Infinities are written as `null` too, and so come back as NaN.
+        assert!(round_trip(f64::INFINITY).is_nan());
+        assert!(serde_json::from_str::<Value>(r#"{"x":"1"}"#).is_err());
+    }
+}
@@ -0,0 +1,138 @@
+//! Magic-byte detection of well-known file formats.
+//!
+//! ```rust
+//! use ent_rs::signature::{find_signatures, identify};
+//! let mut image = vec![0u8; 100];
+//! image.extend(b"PK\x03\x04archive...");
+//! assert_eq!(find_signatures(&image)[0].offset, 100);
+//! assert_eq!(identify(b"\x7fELF\x02\x01\x01").unwrap().name, "ELF");
+//! ```
+
+use crate::classify::ContentClass;
+
+/// A file format recognizable by a fixed byte sequence at its start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signature {
+    /// Short format name.
+    pub name: &'static str,
+    /// Magic bytes.
+    pub magic: &'static [u8],
+    /// Offset of the magic bytes from the start of the file.
+    pub offset: usize,
+    /// What the bulk of such a file usually looks like.
+    pub class: ContentClass,
+}
+
+/// A signature found at some offset in the data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignatureHit {
+    /// Offset where the file (not the magic) starts.
+    pub offset: usize,
+    /// The matching signature.
+    pub signature: &'static Signature,
+}
+
+/// Magic bytes shorter than this are only used by [`identify`], since
+/// scanning for them anywhere in the data produces too many false hits.
+pub const MIN_SCAN_MAGIC: usize = 3;
+
+const fn sig(
+    name: &'static str,
+    magic: &'static [u8],
+    offset: usize,
+    class: ContentClass,
+) -> Signature {
+    Signature {
+        name,
+        magic,
+        offset,
+        class,
+    }
+}
+
+/// Built-in signature table.
+pub const SIGNATURES: &[Signature] = &[
+    sig("PNG", b"\x89PNG\r\n\x1a\n", 0, ContentClass::Compressed),
+    sig("JPEG", b"\xff\xd8\xff", 0, ContentClass::Compressed),
+    sig("GIF", b"GIF8", 0, ContentClass::Compressed),
+    sig("PDF", b"%PDF-", 0, ContentClass::Text),
+    sig("ZIP", b"PK\x03\x04", 0, ContentClass::Compressed),
+    sig("gzip", b"\x1f\x8b\x08", 0, ContentClass::Compressed),
+    sig("bzip2", b"BZh", 0, ContentClass::Compressed),
+    sig("xz", b"\xfd7zXZ\x00", 0, ContentClass::Compressed),
+    sig("zstd", b"\x28\xb5\x2f\xfd", 0, ContentClass::Compressed),
+    sig("7z", b"7z\xbc\xaf\x27\x1c", 0, ContentClass::Compressed),
+    sig("RAR", b"Rar!\x1a\x07", 0, ContentClass::Compressed),
+    sig("tar", b"ustar", 257, ContentClass::Code),
+    sig("ELF", b"\x7fELF", 0, ContentClass::Code),
+    sig("Mach-O", b"\xcf\xfa\xed\xfe", 0, ContentClass::Code),
+    sig("Mach-O", b"\xce\xfa\xed\xfe", 0, ContentClass::Code),
+    sig("Mach-O", b"\xfe\xed\xfa\xcf", 0, ContentClass::Code),
+    sig("Mach-O", b"\xfe\xed\xfa\xce", 0, ContentClass::Code),
+    sig("PE", b"MZ", 0, ContentClass::Code),
+    sig("SQLite", b"SQLite format 3\x00", 0, ContentClass::Code),
+    sig("RIFF", b"RIFF", 0, ContentClass::Code),
+    sig("Ogg", b"OggS", 0, ContentClass::Compressed),
+    sig("LUKS", b"LUKS\xba\xbe", 0, ContentClass::Encrypted),
+    sig("PEM", b"-----BEGIN ", 0, ContentClass::Text),
+];
+
+/// Identify the format of a file from its leading bytes.
+pub fn identify(data: &[u8]) -> Option<&'static Signature> {
+    SIGNATURES.iter().find(|s| {
+        data.get(s.offset..s.offset + s.magic.len())
+            .is_some_and(|window| window == s.magic)
+    })
+}
+
+/// Find every embedded occurrence of a built-in signature, in offset order.
+pub fn find_signatures(data: &[u8]) -> Vec<SignatureHit> {
+    let mut hits = Vec::new();
+    for signature in SIGNATURES
+        .iter()
+        .filter(|s| s.magic.len() >= MIN_SCAN_MAGIC)
+    {
+        let magic = signature.magic;
+        if data.len() < magic.len() {
+            continue;
+        }
+        for pos in 0..=data.len() - magic.len() {
+            if data[pos] == magic[0]
+                && &data[pos..pos + magic.len()] == magic
+                && pos >= signature.offset
+            {
+                hits.push(SignatureHit {
+                    offset: pos - signature.offset,
+                    signature,
+                });
+            }
+        }
+    }
+    hits.sort_by_key(|h| h.offset);
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identify_by_offset() {
+        let mut tar = vec![0u8; 512];
+        tar[257..262].copy_from_slice(b"ustar");
+        assert_eq!(identify(&tar).unwrap().name, "tar");
+        assert_eq!(identify(b"MZ\x90\x00").unwrap().class, ContentClass::Code);
+        assert!(identify(b"nothing").is_none());
+    }
+
+    #[test]
+    fn test_find_signatures_skips_short_magic() {
+        let mut data = b"MZ".to_vec();
+        data.extend([0u8; 64]);
+        data.extend(b"\x89PNG\r\n\x1a\n");
+        let hits = find_signatures(&data);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].offset, 66);
+        assert_eq!(hits[0].signature.name, "PNG");
+    }
+}
@@ -0,0 +1,151 @@
+//! Counting kernels behind the byte and bit frequency tables.
+//!
+//! Bit populations are counted with AVX2 on x86-64 when the CPU supports it
+//! (detected at run time) and with NEON on aarch64, falling back to 64-bit
+//! `count_ones` elsewhere.
+//!
+//! Byte histograms do not vectorize: every byte increments a data-dependent
+//! counter. What limits the naive loop is that runs of equal bytes increment
+//! the same counter back to back, each waiting for the previous store; four
+//! interleaved sub-histograms break that chain on every architecture.
+
+/// Bytes counted into the 32-bit sub-histograms before they are flushed.
+const BLOCK: usize = 1 << 30;
+
+/// Occurrences of each byte value.
+pub(crate) fn byte_histogram(data: &[u8]) -> [usize; 256] {
+    let mut counts = [0usize; 256];
+    for block in data.chunks(BLOCK) {
+        let mut tables = [[0u32; 256]; 4];
+        let mut quads = block.chunks_exact(4);
+        for quad in &mut quads {
+            tables[0][quad[0] as usize] += 1;
+            tables[1][quad[1] as usize] += 1;
+            tables[2][quad[2] as usize] += 1;
+            tables[3][quad[3] as usize] += 1;
+        }
+        for &b in quads.remainder() {
+            tables[0][b as usize] += 1;
+        }
+        for (v, count) in counts.iter_mut().enumerate() {
+            *count += tables.iter().map(|t| t[v] as usize).sum::<usize>();
+        }
+    }
+    counts
+}
+
+/// Number of set bits in `data`.
+pub(crate) fn count_ones(data: &[u8]) -> u64 {
+    #[cfg(all(target_arch = "x86_64", feature = "std"))]
+    if std::arch::is_x86_feature_detected!("avx2") {
+        // SAFETY: the CPU supports AVX2, checked just above.
+        return unsafe { x86_64::count_ones_avx2(data) };
+    }
+    #[cfg(target_arch = "aarch64")]
+    return aarch64::count_ones_neon(data);
+    #[allow(unreachable_code)]
+    count_ones_scalar(data)
+}
+
+fn count_ones_scalar(data: &[u8]) -> u64 {
+    let mut words = data.chunks_exact(8);
+    let mut ones = 0u64;
+    for word in &mut words {
+        ones += u64::from_le_bytes(word.try_into().unwrap()).count_ones() as u64;
+    }
+    ones + words
+        .remainder()
+        .iter()
+        .map(|b| b.count_ones() as u64)
+        .sum::<u64>()
+}
+
+#[cfg(all(target_arch = "x86_64", feature = "std"))]
+mod x86_64 {
+    use core::arch::x86_64::*;
+
+    /// Nibble-lookup population count (Muła, Kurz and Lemire).
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn count_ones_avx2(data: &[u8]) -> u64 {
+        let lookup = _mm256_setr_epi8(
+            0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3, 3, 4, //
+            0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3, 3, 4,
+        );
+        let low_nibbles = _mm256_set1_epi8(0x0f);
+        let mut totals = _mm256_setzero_si256();
+        let mut chunks = data.chunks_exact(32);
+        for chunk in &mut chunks {
+            // SAFETY: `chunk` is exactly 32 readable bytes; the load is unaligned.
+            let v = unsafe { _mm256_loadu_si256(chunk.as_ptr().cast()) };
+            let low = _mm256_and_si256(v, low_nibbles);
+            let high = _mm256_and_si256(_mm256_srli_epi16(v, 4), low_nibbles);
+            let per_byte = _mm256_add_epi8(
+                _mm256_shuffle_epi8(lookup, low),
+                _mm256_shuffle_epi8(lookup, high),
+            );
+            // Horizontal sums of each 8 bytes into the four 64-bit lanes.
+            totals = _mm256_add_epi64(totals, _mm256_sad_epu8(per_byte, _mm256_setzero_si256()));
+        }
+        let mut lanes = [0u64; 4];
+        // SAFETY: `lanes` is 32 writable bytes; the store is unaligned.
+        unsafe { _mm256_storeu_si256(lanes.as_mut_ptr().cast(), totals) };
+        lanes.iter().sum::<u64>() + super::count_ones_scalar(chunks.remainder())
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod aarch64 {
+    use core::arch::aarch64::*;
+
+    /// Per-byte `cnt`, widened pairwise into 64-bit lanes.
+    pub(super) fn count_ones_neon(data: &[u8]) -> u64 {
+        let mut chunks = data.chunks_exact(16);
+        // SAFETY: NEON is part of the aarch64 baseline, and every load reads
+        // exactly the 16 bytes of its chunk.
+        let ones = unsafe {
+            let mut totals = vdupq_n_u64(0);
+            for chunk in &mut chunks {
+                let per_byte = vcntq_u8(vld1q_u8(chunk.as_ptr()));
+                totals = vpadalq_u32(totals, vpaddlq_u16(vpaddlq_u8(per_byte)));
+            }
+            vaddvq_u64(totals)
+        };
+        ones + super::count_ones_scalar(chunks.remainder())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(len: usize) -> Vec<u8> {
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_kernels_match_naive_counts() {
+        let data = data(10_000);
+        // Every length around the 4-, 8-, 16- and 32-byte strides.
+        for len in (0..100).chain([4095, 4096, 9999]) {
+            let slice = &data[..len];
+            let naive_ones = slice.iter().map(|b| b.count_ones() as u64).sum::<u64>();
+            assert_eq!(count_ones(slice), naive_ones, "len {len}");
+            assert_eq!(count_ones_scalar(slice), naive_ones, "len {len}");
+
+            let mut naive = [0usize; 256];
+            for &b in slice {
+                naive[b as usize] += 1;
+            }
+            assert_eq!(byte_histogram(slice), naive, "len {len}");
+        }
+        assert_eq!(count_ones(&[0xFF; 1000]), 8000);
+    }
+}
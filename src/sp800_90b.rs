@@ -0,0 +1,356 @@
+//! NIST SP 800-90B min-entropy estimators.
+//!
+//! Estimates are in bits per sample, where a sample is one byte.
+//!
+//! The most common value estimate works on the bytes directly. The
+//! collision, Markov and compression estimates are defined for binary data
+//! only, so, as §3.1.3 prescribes for wider samples, they run on the
+//! bitstring of the data (most significant bit first) and their per-bit
+//! result is multiplied by 8. [`non_iid_estimates`] runs all four; the
+//! assessed min-entropy is the smallest. These are preliminary assessments,
+//! not a substitute for the full non-IID track of the standard.
+//!
+//! ```rust
+//! use ent_rs::sp800_90b::most_common_value;
+//! let data: Vec<u8> = (0..=255).cycle().take(1 << 16).collect();
+//! let h = most_common_value(&data);
+//! assert!(h > 7.5 && h <= 8.0);
+//! ```
+
+use crate::byte_counts;
+use crate::nist::BitStream;
+
+/// Upper 99% quantile of the standard normal, used by the 90B confidence bounds.
+pub const Z_ALPHA: f64 = 2.576;
+
+/// Most common value estimate (SP 800-90B §6.3.1).
+///
+/// Takes the frequency of the most common byte, raises it to the upper bound
+/// of its 99% confidence interval, and returns `-log2` of that bound.
+pub fn most_common_value(data: &[u8]) -> f64 {
+    let len = data.len();
+    if len < 2 {
+        return 0.0;
+    }
+    let max = *byte_counts(data).iter().max().unwrap_or(&0) as f64;
+    let p_hat = max / len as f64;
+    let p_u = (p_hat + Z_ALPHA * (p_hat * (1.0 - p_hat) / (len - 1) as f64).sqrt()).min(1.0);
+    -p_u.log2()
+}
+
+/// Bits per sample, by which per-bit estimates are scaled.
+const SAMPLE_BITS: f64 = 8.0;
+
+/// Collision estimate (SP 800-90B §6.3.2), in bits per byte.
+///
+/// Walks the bitstring in runs that end at the first repeated value and
+/// lowers the mean run length to its 99% confidence bound. Returns 0 for
+/// inputs too short to hold two runs.
+pub fn collision(data: &[u8]) -> f64 {
+    let bits: Vec<u8> = BitStream::from_bytes(data).iter().collect();
+    // In a binary sequence a repeat occurs at the second or third bit.
+    let mut times = Vec::with_capacity(bits.len() / 2);
+    let mut index = 0;
+    while index + 1 < bits.len() {
+        let t = if bits[index] == bits[index + 1] {
+            2
+        } else if index + 2 < bits.len() {
+            3
+        } else {
+            break;
+        };
+        times.push(t as f64);
+        index += t;
+    }
+    let v = times.len();
+    if v < 2 {
+        return 0.0;
+    }
+    let mean = times.iter().sum::<f64>() / v as f64;
+    let var = times.iter().map(|t| (t - mean) * (t - mean)).sum::<f64>() / (v - 1) as f64;
+    let bound = mean - Z_ALPHA * var.sqrt() / (v as f64).sqrt();
+    // For binary data the expected run length of §6.3.2 step 7 reduces to
+    // 2 + 2pq, so p solves directly instead of by binary search. A bound
+    // above the 2.5 of a fair coin has no solution and gives 1 bit per bit.
+    let p = if bound >= 2.5 {
+        0.5
+    } else if bound <= 2.0 {
+        1.0
+    } else {
+        (1.0 + (5.0 - 2.0 * bound).sqrt()) / 2.0
+    };
+    -p.log2() * SAMPLE_BITS
+}
+
+/// Markov estimate (SP 800-90B §6.3.3), in bits per byte.
+///
+/// Fits a first-order Markov model to the bitstring and takes the most
+/// likely 128-bit sequence under it. Returns 0 for empty input.
+pub fn markov(data: &[u8]) -> f64 {
+    let bits = BitStream::from_bytes(data);
+    if bits.is_empty() {
+        return 0.0;
+    }
+    let ones = bits.count_ones() as f64;
+    let p1 = ones / bits.len() as f64;
+    let p0 = 1.0 - p1;
+    let mut transitions = [[0usize; 2]; 2];
+    let mut prev = None;
+    for b in bits.iter() {
+        if let Some(a) = prev {
+            transitions[a as usize][b as usize] += 1;
+        }
+        prev = Some(b);
+    }
+    let row = |a: usize, b: usize| {
+        let total = transitions[a][0] + transitions[a][1];
+        if total == 0 {
+            0.0
+        } else {
+            transitions[a][b] as f64 / total as f64
+        }
+    };
+    let (p00, p01, p10, p11) = (row(0, 0), row(0, 1), row(1, 0), row(1, 1));
+    // log2 of the probabilities of the candidate most likely sequences,
+    // kept in the log domain as the products underflow.
+    let log = |p: f64, n: f64| if n == 0.0 { 0.0 } else { n * p.log2() };
+    let candidates = [
+        p0.log2() + log(p00, 127.0),
+        p0.log2() + log(p01, 64.0) + log(p10, 63.0),
+        p0.log2() + p01.log2() + log(p11, 126.0),
+        p1.log2() + p10.log2() + log(p00, 126.0),
+        p1.log2() + log(p10, 64.0) + log(p01, 63.0),
+        p1.log2() + log(p11, 127.0),
+    ];
+    let max = candidates.into_iter().fold(f64::NEG_INFINITY, f64::max);
+    (-max / 128.0).min(1.0) * SAMPLE_BITS
+}
+
+/// Block size in bits of the compression estimate.
+const COMPRESSION_BLOCK: usize = 6;
+/// Blocks used to initialize the compression dictionary.
+const COMPRESSION_DICTIONARY: usize = 1000;
+/// Correction factor of the compression estimate's standard deviation.
+const COMPRESSION_C: f64 = 0.5907;
+
+/// Compression estimate (SP 800-90B §6.3.4), in bits per byte.
+///
+/// Measures, Maurer-style, how far back each 6-bit block of the bitstring
+/// last occurred, and finds the most-likely-value probability whose
+/// expected distances match the 99% lower bound of the observed mean.
+/// Needs more than 1000 blocks (750 bytes); returns 0 for shorter input.
+pub fn compression(data: &[u8]) -> f64 {
+    let bits = BitStream::from_bytes(data);
+    let blocks: Vec<usize> = (0..bits.len() / COMPRESSION_BLOCK)
+        .map(|i| {
+            bits.bits(i * COMPRESSION_BLOCK, (i + 1) * COMPRESSION_BLOCK)
+                .fold(0, |acc, b| acc << 1 | b as usize)
+        })
+        .collect();
+    let d = COMPRESSION_DICTIONARY;
+    if blocks.len() <= d + 1 {
+        return 0.0;
+    }
+    let v = blocks.len() - d;
+
+    // Positions are 1-based as in the standard; 0 means never seen.
+    let mut dict = [0usize; 1 << COMPRESSION_BLOCK];
+    for (i, &block) in blocks[..d].iter().enumerate() {
+        dict[block] = i + 1;
+    }
+    let mut sum = 0.0;
+    let mut sum_sq = 0.0;
+    for (i, &block) in blocks.iter().enumerate().skip(d) {
+        let position = i + 1;
+        let distance = position - dict[block];
+        dict[block] = position;
+        let log = (distance as f64).log2();
+        sum += log;
+        sum_sq += log * log;
+    }
+    let mean = sum / v as f64;
+    let sigma = COMPRESSION_C * ((sum_sq / (v - 1) as f64) - mean * mean).max(0.0).sqrt();
+    let bound = mean - Z_ALPHA * sigma / (v as f64).sqrt();
+
+    let symbols = (1usize << COMPRESSION_BLOCK) as f64;
+    let expected = |p: f64| {
+        let q = (1.0 - p) / (symbols - 1.0);
+        compression_g(p, d, v) + (symbols - 1.0) * compression_g(q, d, v)
+    };
+    // The expectation falls from its maximum at the uniform p = 2^-b to 0 at
+    // p = 1; a bound above the maximum means no solution, i.e. full entropy.
+    let (mut lo, mut hi) = (1.0 / symbols, 1.0);
+    if bound >= expected(lo) {
+        return SAMPLE_BITS;
+    }
+    while hi - lo > 1e-10 {
+        let mid = (lo + hi) / 2.0;
+        if expected(mid) > bound {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    (-hi.log2() / COMPRESSION_BLOCK as f64).min(1.0) * SAMPLE_BITS
+}
+
+/// G(z) of §6.3.4 step 6, in O(d + v) by counting, for each distance u, the
+/// test positions t > u whose inner sums include it.
+fn compression_g(z: f64, d: usize, v: usize) -> f64 {
+    let end = d + v;
+    let mut total = 0.0;
+    // (1 - z)^(u - 1), advanced as u grows.
+    let mut power = 1.0;
+    for u in 1..=end {
+        let log = (u as f64).log2();
+        // Terms with u < t for every t in d+1..=end above u.
+        let later = end - u.max(d);
+        total += log * z * z * power * later as f64;
+        // The u = t term, for t among the test positions.
+        if u > d {
+            total += log * z * power;
+        }
+        power *= 1.0 - z;
+        // Later terms are negligible, and subnormal arithmetic is slow.
+        if power < f64::MIN_POSITIVE {
+            break;
+        }
+    }
+    total / v as f64
+}
+
+/// The four non-IID estimates of SP 800-90B §6.3.1 to §6.3.4, in bits per
+/// byte.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NonIidEstimates {
+    /// [`most_common_value`].
+    pub most_common_value: f64,
+    /// [`collision`].
+    pub collision: f64,
+    /// [`markov`].
+    pub markov: f64,
+    /// [`compression`].
+    pub compression: f64,
+}
+
+impl NonIidEstimates {
+    /// The assessed min-entropy: the smallest estimate.
+    pub fn min(&self) -> f64 {
+        self.most_common_value
+            .min(self.collision)
+            .min(self.markov)
+            .min(self.compression)
+    }
+}
+
+/// Run all four estimators on `data`.
+pub fn non_iid_estimates(data: &[u8]) -> NonIidEstimates {
+    NonIidEstimates {
+        most_common_value: most_common_value(data),
+        collision: collision(data),
+        markov: markov(data),
+        compression: compression(data),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mcv_constant_data_has_no_entropy() {
+        assert_eq!(most_common_value(&[7u8; 1000]), 0.0);
+    }
+
+    #[test]
+    fn test_mcv_reference_value() {
+        // p_hat = 0.5 over 10_000 samples: p_u = 0.5 + 2.576 * sqrt(0.25 / 9999)
+        let data: Vec<u8> = (0..10_000).map(|i| (i % 2) as u8).collect();
+        let expected = -(0.5 + 2.576 * (0.25f64 / 9999.0).sqrt()).log2();
+        assert!((most_common_value(&data) - expected).abs() < 1e-12);
+    }
+
+    fn random_bytes(len: usize) -> Vec<u8> {
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_collision_reference_value() {
+        // 0x55 bits alternate: every run is 3 bits long, with no spread.
+        let h = collision(&[0x55; 1000]);
+        assert!((h - 8.0).abs() < 1e-12);
+        // 0x00 repeats at once: runs of 2, so p = 1.
+        assert_eq!(collision(&[0; 1000]), 0.0);
+        // The confidence bound costs random data about a tenth of a bit per
+        // bit, as in the reference implementation.
+        let random = collision(&random_bytes(100_000));
+        assert!(random > 7.0 && random <= 8.0, "{random}");
+    }
+
+    #[test]
+    fn test_markov_on_structured_data() {
+        assert_eq!(markov(&[0; 100]), 0.0);
+        // Strict alternation is perfectly predictable after the first bit.
+        let alternating = markov(&[0xAA; 100]);
+        assert!(alternating < 0.1, "{alternating}");
+        let random = markov(&random_bytes(100_000));
+        assert!(random > 7.9 && random <= 8.0, "{random}");
+    }
+
+    #[test]
+    fn test_compression_estimate() {
+        assert_eq!(compression(&[0; 100]), 0.0);
+        assert!(compression(&[0; 10_000]) < 0.1);
+        let random = compression(&random_bytes(20_000));
+        assert!(random > 6.0 && random <= 8.0, "{random}");
+        // Bytes from a 16-value alphabet carry at most 4 bits.
+        let nibbles: Vec<u8> = random_bytes(20_000).iter().map(|b| b & 0x0f).collect();
+        assert!(compression(&nibbles) < 5.0);
+    }
+
+    #[test]
+    fn test_compression_g_matches_definition() {
+        // G(z) summed term by term, as written in §6.3.4.
+        let (d, v) = (5, 7);
+        for z in [0.1, 0.5, 0.9] {
+            let mut naive = 0.0;
+            for t in d + 1..=d + v {
+                for u in 1..=t {
+                    let f = if u < t {
+                        z * z * (1.0f64 - z).powi(u as i32 - 1)
+                    } else {
+                        z * (1.0f64 - z).powi(t as i32 - 1)
+                    };
+                    naive += (u as f64).log2() * f;
+                }
+            }
+            assert!((compression_g(z, d, v) - naive / v as f64).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_non_iid_min_is_smallest() {
+        let data: Vec<u8> = random_bytes(20_000).iter().map(|b| b | 0x80).collect();
+        let estimates = non_iid_estimates(&data);
+        assert!(estimates.min() <= 7.0);
+        assert_eq!(
+            estimates.min(),
+            [
+                estimates.most_common_value,
+                estimates.collision,
+                estimates.markov,
+                estimates.compression
+            ]
+            .into_iter()
+            .fold(f64::INFINITY, f64::min)
+        );
+    }
+}
@@ -0,0 +1,1126 @@
+//! A battery of randomness tests run in parallel with an optional deadline.
+//!
+//! Each test maps the input to one or more p-values; it passes when every
+//! p-value is at least the configured significance level. Tests run on a pool
+//! of worker threads, and when a deadline is set the suite returns once it
+//! expires, reporting unfinished tests as [`TestStatus::TimedOut`] instead of
+//! waiting for them.
+//!
+//! Tests receive the [`SuiteConfig`], whose [`NistParams`] carry the
+//! SP 800-22 parameters (block lengths, pattern lengths, number of sequences).
+//! [`SuiteConfig::validate`] checks them against the ranges the standard
+//! recommends for a given input size.
+//!
+//! ```rust
+//! use std::sync::Arc;
+//! use ent_rs::suite::{Suite, SuiteConfig};
+//! let data: Vec<u8> = (0..1u32 << 16).map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8).collect();
+//! let report = Suite::standard().run(Arc::from(data), &SuiteConfig::default());
+//! for result in &report.results {
+//!     println!("{:<20} {:?}", result.name, result.status);
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::f64::consts::{FRAC_PI_4, SQRT_2};
+use std::fmt;
+use std::ops::Range;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::correction::Correction;
+use crate::digest::{InputDigest, InputHasher};
+use crate::nist::{self, BitStream, Template};
+use crate::randomness::{self, RandomnessTest};
+use crate::second_level::{self, SecondLevel};
+use crate::special::erfc;
+use crate::{byte_counts, chisquare_from_counts, chisquare_p_value, instrument, EntStats};
+
+/// A test function: input data and configuration to p-values.
+pub type TestFn = Arc<dyn Fn(&[u8], &SuiteConfig) -> Vec<f64> + Send + Sync>;
+
+/// How a suite is run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuiteConfig {
+    /// Significance level; a p-value below it fails the test.
+    pub alpha: f64,
+    /// Adjustment of the p-values of a single-sequence run for the number
+    /// of p-values across all tests. Runs over several sequences are judged
+    /// by second-level analysis instead and are not adjusted.
+    pub correction: Correction,
+    /// Number of worker threads.
+    pub threads: usize,
+    /// Overall time budget; `None` waits for every test.
+    pub deadline: Option<Duration>,
+    /// SP 800-22 test parameters.
+    pub nist: NistParams,
+}
+
+impl Default for SuiteConfig {
+    fn default() -> Self {
+        SuiteConfig {
+            alpha: 0.01,
+            correction: Correction::None,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            deadline: None,
+            nist: NistParams::default(),
+        }
+    }
+}
+
+impl SuiteConfig {
+    /// Check the configuration for an input of `data_len` bytes.
+    pub fn validate(&self, data_len: usize) -> Result<(), ParamError> {
+        if !(self.alpha > 0.0 && self.alpha < 1.0) {
+            return Err(ParamError::new("alpha", self.alpha, "must be in (0, 1)"));
+        }
+        self.nist.validate(self.nist.sequence_bits(data_len))
+    }
+}
+
+/// Parameters of the SP 800-22 tests.
+///
+/// The defaults follow the NIST reference implementation. Fields set to
+/// `None` are derived from the sequence length as the standard recommends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NistParams {
+    /// Number of sequences the input is divided into by
+    /// [`Suite::run_split`].
+    pub sequences: usize,
+    /// Bytes per sequence; `None` divides the input evenly.
+    pub sequence_len: Option<usize>,
+    /// How bytes are assigned to sequences.
+    pub split_strategy: SplitStrategy,
+    /// Block length `M` of the frequency-within-a-block test.
+    pub block_frequency_len: Option<usize>,
+    /// Template length `m` of the non-overlapping template test.
+    pub template_len: usize,
+    /// Number of blocks `N` of the non-overlapping template test.
+    pub template_blocks: usize,
+    /// Templates for the non-overlapping template test; `None` uses every
+    /// aperiodic template of length [`template_len`](Self::template_len).
+    pub templates: Option<Vec<Template>>,
+    /// Template length `m` of the overlapping template test.
+    pub overlapping_template_len: usize,
+    /// Block length `M` of the overlapping template test.
+    pub overlapping_block_len: usize,
+    /// Pattern length `m` of the serial test.
+    pub serial_len: usize,
+}
+
+impl Default for NistParams {
+    fn default() -> Self {
+        NistParams {
+            sequences: 1,
+            sequence_len: None,
+            split_strategy: SplitStrategy::Blocks,
+            block_frequency_len: None,
+            template_len: 9,
+            template_blocks: 8,
+            templates: None,
+            overlapping_template_len: 9,
+            overlapping_block_len: 1032,
+            serial_len: 16,
+        }
+    }
+}
+
+impl NistParams {
+    /// Bits per sequence when `data_len` bytes are divided into
+    /// [`sequences`](Self::sequences) sequences.
+    pub fn sequence_bits(&self, data_len: usize) -> usize {
+        self.split(data_len).sequence_len * 8
+    }
+
+    /// How `data_len` bytes are divided into sequences.
+    ///
+    /// If the input is too short for the requested number of sequences of
+    /// [`sequence_len`](Self::sequence_len) bytes, fewer sequences are used.
+    pub fn split(&self, data_len: usize) -> Split {
+        let requested = self.sequences.max(1);
+        let sequence_len = self.sequence_len.unwrap_or(data_len / requested);
+        let sequences = match sequence_len {
+            0 => 0,
+            len => requested.min(data_len / len),
+        };
+        Split {
+            strategy: self.split_strategy,
+            sequences,
+            sequence_len,
+            unused: data_len - sequences * sequence_len,
+        }
+    }
+
+    /// Block frequency `M` for `n`-bit sequences: the configured value, or
+    /// the smallest `M ≥ 20` giving fewer than 100 blocks.
+    pub fn block_frequency_len_for(&self, n: usize) -> usize {
+        self.block_frequency_len
+            .unwrap_or_else(|| (n / 100 + 1).max(20))
+    }
+
+    /// Check the parameters against the SP 800-22 recommendations for
+    /// sequences of `n` bits.
+    pub fn validate(&self, n: usize) -> Result<(), ParamError> {
+        let check = |ok: bool, parameter, value: usize, reason: &str| {
+            if ok {
+                Ok(())
+            } else {
+                Err(ParamError::new(parameter, value as f64, reason))
+            }
+        };
+        check(
+            self.sequences >= 1,
+            "sequences",
+            self.sequences,
+            "must be at least 1",
+        )?;
+        check(
+            n >= 100,
+            "sequences",
+            self.sequences,
+            "leave fewer than 100 bits per sequence",
+        )?;
+
+        let m = self.block_frequency_len_for(n);
+        check(m >= 20, "block_frequency_len", m, "must be at least 20")?;
+        check(100 * m > n, "block_frequency_len", m, "must exceed n / 100")?;
+
+        let blocks = self.template_blocks;
+        check(
+            (1..=100).contains(&blocks),
+            "template_blocks",
+            blocks,
+            "must be in 1..=100",
+        )?;
+        let m = match &self.templates {
+            Some(templates) => {
+                check(!templates.is_empty(), "templates", 0, "must not be empty")?;
+                templates.iter().map(Template::len).max().unwrap_or(0)
+            }
+            None => {
+                let m = self.template_len;
+                check(
+                    (2..=21).contains(&m),
+                    "template_len",
+                    m,
+                    "must be in 2..=21",
+                )?;
+                m
+            }
+        };
+        check(
+            n / blocks > m,
+            "template_blocks",
+            blocks,
+            "leave blocks shorter than the template",
+        )?;
+
+        let m = self.overlapping_template_len;
+        check(
+            (2..=21).contains(&m),
+            "overlapping_template_len",
+            m,
+            "must be in 2..=21",
+        )?;
+        let block = self.overlapping_block_len;
+        check(
+            block > m,
+            "overlapping_block_len",
+            block,
+            "must exceed the template length",
+        )?;
+        check(
+            block <= n,
+            "overlapping_block_len",
+            block,
+            "must not exceed n",
+        )?;
+
+        let log2_n = n.ilog2() as usize;
+        let m = self.serial_len;
+        check(
+            m >= 2 && m + 2 < log2_n,
+            "serial_len",
+            m,
+            "must be at least 2 and below log2(n) - 2",
+        )?;
+        Ok(())
+    }
+}
+
+/// How [`Suite::run_split`] assigns input bytes to sequences.
+///
+/// Only the first `sequences × sequence_len` bytes are used by every strategy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SplitStrategy {
+    /// Consecutive blocks: sequence `i` is bytes `i·L .. (i+1)·L`.
+    Blocks,
+    /// Round-robin: sequence `i` is bytes `i, i + N, i + 2N, ...`. Exposes
+    /// per-channel defects in interleaved captures, which block splitting
+    /// averages away.
+    Interleaved,
+    /// A random partition of the byte positions drawn from `seed`; each
+    /// sequence keeps its bytes in stream order.
+    Random {
+        /// Seed of the partition, so runs are reproducible.
+        seed: u64,
+    },
+}
+
+impl SplitStrategy {
+    /// The sequences of `data` under this strategy.
+    ///
+    /// Like [`NistParams::split`], if `data` is too short for `sequences`
+    /// sequences of `sequence_len` bytes, fewer sequences are returned.
+    pub fn apply(&self, data: &[u8], sequences: usize, sequence_len: usize) -> Vec<Vec<u8>> {
+        let sequences = match sequence_len {
+            0 => sequences,
+            len => sequences.min(data.len() / len),
+        };
+        let used = sequences * sequence_len;
+        match *self {
+            SplitStrategy::Blocks => data[..used]
+                .chunks_exact(sequence_len.max(1))
+                .map(<[u8]>::to_vec)
+                .collect(),
+            SplitStrategy::Interleaved => (0..sequences)
+                .map(|i| {
+                    data[..used]
+                        .iter()
+                        .skip(i)
+                        .step_by(sequences)
+                        .copied()
+                        .collect()
+                })
+                .collect(),
+            SplitStrategy::Random { seed } => {
+                let mut positions: Vec<usize> = (0..used).collect();
+                let mut state = seed;
+                for i in (1..used).rev() {
+                    let j = (splitmix64(&mut state) % (i as u64 + 1)) as usize;
+                    positions.swap(i, j);
+                }
+                positions
+                    .chunks_exact_mut(sequence_len.max(1))
+                    .map(|chunk| {
+                        chunk.sort_unstable();
+                        chunk.iter().map(|&p| data[p]).collect()
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Division of one input into test sequences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Split {
+    /// Strategy used to assign bytes to sequences.
+    pub strategy: SplitStrategy,
+    /// Number of sequences.
+    pub sequences: usize,
+    /// Bytes per sequence.
+    pub sequence_len: usize,
+    /// Trailing bytes not covered by any sequence.
+    pub unused: usize,
+}
+
+/// A suite parameter outside its allowed range.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamError {
+    /// Name of the offending parameter.
+    pub parameter: &'static str,
+    /// Its value.
+    pub value: f64,
+    /// Why it was rejected.
+    pub reason: String,
+}
+
+impl ParamError {
+    fn new(parameter: &'static str, value: f64, reason: &str) -> Self {
+        ParamError {
+            parameter,
+            value,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}: {}", self.parameter, self.value, self.reason)
+    }
+}
+
+impl std::error::Error for ParamError {}
+
+/// Outcome of one test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TestStatus {
+    /// Every p-value reached the significance level.
+    Passed,
+    /// At least one p-value was below the significance level.
+    Failed,
+    /// The test produced no p-value, e.g. because the input was too short.
+    Skipped,
+    /// The deadline expired before the test finished.
+    TimedOut,
+    /// The test panicked.
+    Errored,
+}
+
+/// Result of one test.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestResult {
+    /// Test name.
+    pub name: String,
+    /// Outcome.
+    pub status: TestStatus,
+    /// p-values produced by the test, sequence by sequence.
+    pub p_values: Vec<f64>,
+    /// `p_values` adjusted by [`SuiteConfig::correction`], which decide the
+    /// status of a single-sequence run; the same as `p_values` without
+    /// correction or over several sequences.
+    pub adjusted_p_values: Vec<f64>,
+    /// Second-level statistics per p-value position when run over several
+    /// sequences; empty for a single sequence.
+    pub second_level: Vec<SecondLevel>,
+    /// Time spent in the test, summed over sequences; `None` unless some
+    /// sequence finished.
+    pub elapsed: Option<Duration>,
+}
+
+/// Results of a suite run, in suite order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuiteReport {
+    /// The input analyzed; for [`Suite::run_sequences`], the sequences
+    /// concatenated in order. Suite inputs are bytes, so `bit_mode` is false.
+    pub input: InputDigest,
+    /// One result per test.
+    pub results: Vec<TestResult>,
+    /// How the input was divided, for [`Suite::run_split`].
+    pub split: Option<Split>,
+    /// Wall-clock time of the whole run.
+    pub elapsed: Duration,
+}
+
+impl SuiteReport {
+    /// Whether no test failed, timed out, or errored.
+    pub fn passed(&self) -> bool {
+        self.results
+            .iter()
+            .all(|r| matches!(r.status, TestStatus::Passed | TestStatus::Skipped))
+    }
+
+    /// Look up a result by test name.
+    pub fn get(&self, name: &str) -> Option<&TestResult> {
+        self.results.iter().find(|r| r.name == name)
+    }
+}
+
+/// An ordered collection of named tests.
+#[derive(Clone, Default)]
+pub struct Suite {
+    tests: Vec<(String, TestFn)>,
+}
+
+impl Suite {
+    /// An empty suite.
+    pub fn new() -> Self {
+        Suite::default()
+    }
+
+    /// The built-in tests derived from the classic `ent` statistics:
+    /// `chi-square`, `monte-carlo-pi` and `serial-correlation`.
+    pub fn standard() -> Self {
+        Suite::new()
+            .with_test("chi-square", chi_square_test)
+            .with_test("monte-carlo-pi", monte_carlo_pi_test)
+            .with_test("serial-correlation", serial_correlation_test)
+    }
+
+    /// The SP 800-22 tests, parameterized by [`SuiteConfig::nist`]:
+    /// `frequency`, `block-frequency`, `runs`, `longest-run`, `spectral`,
+    /// `non-overlapping-template`, `overlapping-template`, `serial`,
+    /// `cumulative-sums` and `random-excursions-variant`.
+    pub fn nist() -> Self {
+        Suite::new()
+            .with_test("frequency", nist::frequency_test)
+            .with_test("block-frequency", nist::block_frequency_test)
+            .with_test("runs", nist::runs_test)
+            .with_test("longest-run", nist::longest_run_test)
+            .with_test("spectral", nist::spectral_test)
+            .with_test(
+                "non-overlapping-template",
+                nist::non_overlapping_template_test,
+            )
+            .with_test("overlapping-template", nist::overlapping_template_test)
+            .with_test("serial", nist::serial_test)
+            .with_test("cumulative-sums", nist::cumulative_sums_test)
+            .with_test(
+                "random-excursions-variant",
+                nist::random_excursions_variant_test,
+            )
+    }
+
+    /// Add a test under `name`.
+    pub fn with_test<F>(mut self, name: impl Into<String>, test: F) -> Self
+    where
+        F: Fn(&[u8], &SuiteConfig) -> Vec<f64> + Send + Sync + 'static,
+    {
+        self.tests.push((name.into(), Arc::new(test)));
+        self
+    }
+
+    /// Add `test` under its own name. It runs on the bits of each sequence
+    /// and reports no p-value for sequences shorter than its
+    /// [`min_bits`](RandomnessTest::min_bits).
+    pub fn with_randomness_test(self, test: impl RandomnessTest + 'static) -> Self {
+        let name = test.name();
+        self.with_test(name, move |data, _| {
+            let bits = BitStream::from_bytes(data);
+            let p_value = randomness::run_test(&test, &bits).p_value;
+            if p_value.is_nan() {
+                Vec::new()
+            } else {
+                vec![p_value]
+            }
+        })
+    }
+
+    /// Names of the tests, in order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.tests.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Run every test over `data`.
+    ///
+    /// The configuration is not validated; tests given parameters outside the
+    /// recommended ranges may skip or produce unreliable p-values.
+    ///
+    /// Workers are detached threads, so a test still running when the
+    /// deadline expires keeps its thread (and a reference to `data`) until it
+    /// finishes on its own; its result is discarded.
+    pub fn run(&self, data: Arc<[u8]>, config: &SuiteConfig) -> SuiteReport {
+        self.run_sequences(&[data], config)
+    }
+
+    /// Divide `data` into sequences as configured by [`NistParams::split`],
+    /// run every test over each, and aggregate as
+    /// [`run_sequences`](Self::run_sequences) does.
+    ///
+    /// With [`SplitStrategy::Blocks`] the sequences share `data` without
+    /// copying; other strategies copy the bytes into new sequences. The split
+    /// is recorded in [`SuiteReport::split`].
+    pub fn run_split(&self, data: Arc<[u8]>, config: &SuiteConfig) -> SuiteReport {
+        let split = config.nist.split(data.len());
+        let len = split.sequence_len;
+        let sequences = match split.strategy {
+            SplitStrategy::Blocks => (0..split.sequences)
+                .map(|i| (Arc::clone(&data), i * len..(i + 1) * len))
+                .collect(),
+            strategy => strategy
+                .apply(&data, split.sequences, len)
+                .into_iter()
+                .map(|sequence| (Arc::from(sequence), 0..len))
+                .collect(),
+        };
+        SuiteReport {
+            split: Some(split),
+            ..self.run_ranges(InputDigest::new(&data, false), sequences, config)
+        }
+    }
+
+    /// Run every test over each of `sequences` and judge each test by
+    /// [second-level analysis](crate::second_level) of its p-values.
+    ///
+    /// A test returning several p-values per sequence (e.g. one per
+    /// template) gets one [`SecondLevel`] per position. With a single
+    /// sequence this is the same as [`run`](Self::run). A test is reported as
+    /// [`TestStatus::TimedOut`] if any of its sequences did not finish.
+    pub fn run_sequences(&self, sequences: &[Arc<[u8]>], config: &SuiteConfig) -> SuiteReport {
+        let mut hasher = InputHasher::new();
+        for data in sequences {
+            hasher.update(data);
+        }
+        let sequences = sequences
+            .iter()
+            .map(|data| (Arc::clone(data), 0..data.len()))
+            .collect();
+        self.run_ranges(hasher.finish(false), sequences, config)
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(tests = self.tests.len(), sequences = sequences.len())
+        )
+    )]
+    fn run_ranges(
+        &self,
+        input: InputDigest,
+        sequences: Vec<Sequence>,
+        config: &SuiteConfig,
+    ) -> SuiteReport {
+        let start = Instant::now();
+        let single = sequences.len() == 1;
+        let outcomes = self.execute(sequences, config, start);
+        let mut results: Vec<TestResult> = self
+            .tests
+            .iter()
+            .zip(outcomes)
+            .map(|((name, _), outcomes)| summarize(name, outcomes, config.alpha))
+            .collect();
+        if single {
+            correct(&mut results, config);
+        }
+        SuiteReport {
+            input,
+            results,
+            split: None,
+            elapsed: start.elapsed(),
+        }
+    }
+
+    /// Run every (test, sequence) pair on the pool; outcomes are indexed by
+    /// test, then sequence.
+    fn execute(
+        &self,
+        sequences: Vec<Sequence>,
+        config: &SuiteConfig,
+        start: Instant,
+    ) -> Vec<Vec<Outcome>> {
+        let jobs: VecDeque<(usize, usize)> = (0..self.tests.len())
+            .flat_map(|t| (0..sequences.len()).map(move |s| (t, s)))
+            .collect();
+        let workers = config.threads.clamp(1, jobs.len().max(1));
+        let queue = Arc::new(Mutex::new(jobs));
+        let (tx, rx) = mpsc::channel();
+        #[cfg(feature = "tracing")]
+        let parent = tracing::Span::current();
+        for _ in 0..workers {
+            #[cfg(feature = "tracing")]
+            let parent = parent.clone();
+            let queue = Arc::clone(&queue);
+            let tests = self.tests.clone();
+            let sequences = sequences.clone();
+            let config = config.clone();
+            let tx = tx.clone();
+            thread::spawn(move || loop {
+                let Some((t, s)) = queue.lock().unwrap().pop_front() else {
+                    return;
+                };
+                let (data, range) = &sequences[s];
+                #[cfg(feature = "tracing")]
+                let _span = tracing::debug_span!(
+                    parent: &parent,
+                    "suite_test",
+                    test = %tests[t].0,
+                    sequence = s
+                )
+                .entered();
+                let timer = instrument::Timer::start();
+                let started = Instant::now();
+                let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+                    (tests[t].1)(&data[range.clone()], &config)
+                }));
+                timer.finish("suite_test", range.len());
+                let outcome = match outcome {
+                    Ok(p_values) => Outcome::Done(p_values, started.elapsed()),
+                    Err(_) => Outcome::Panicked,
+                };
+                if tx.send((t, s, outcome)).is_err() {
+                    return;
+                }
+            });
+        }
+        drop(tx);
+
+        let mut outcomes: Vec<Vec<Outcome>> = self
+            .tests
+            .iter()
+            .map(|_| sequences.iter().map(|_| Outcome::Pending).collect())
+            .collect();
+        let deadline = config.deadline.map(|d| start + d);
+        loop {
+            let received = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    rx.recv_timeout(remaining).ok()
+                }
+                None => rx.recv().ok(),
+            };
+            let Some((t, s, outcome)) = received else {
+                break;
+            };
+            outcomes[t][s] = outcome;
+        }
+        #[cfg(feature = "tracing")]
+        for (t, test_outcomes) in outcomes.iter().enumerate() {
+            for (s, outcome) in test_outcomes.iter().enumerate() {
+                match outcome {
+                    Outcome::Pending => {
+                        tracing::warn!(test = %self.tests[t].0, sequence = s, "suite test timed out")
+                    }
+                    Outcome::Panicked => {
+                        tracing::warn!(test = %self.tests[t].0, sequence = s, "suite test panicked")
+                    }
+                    Outcome::Done(..) => {}
+                }
+            }
+        }
+        // Stop idle workers from picking up jobs nobody is waiting for.
+        queue.lock().unwrap().clear();
+        outcomes
+    }
+}
+
+/// Shared input and the byte range of one sequence within it.
+type Sequence = (Arc<[u8]>, Range<usize>);
+
+enum Outcome {
+    Pending,
+    Done(Vec<f64>, Duration),
+    Panicked,
+}
+
+fn summarize(name: &str, outcomes: Vec<Outcome>, alpha: f64) -> TestResult {
+    let single = outcomes.len() == 1;
+    let mut status = None;
+    let mut elapsed = None;
+    let mut per_sequence = Vec::new();
+    for outcome in outcomes {
+        match outcome {
+            Outcome::Done(p_values, took) => {
+                *elapsed.get_or_insert(Duration::ZERO) += took;
+                per_sequence.push(p_values);
+            }
+            Outcome::Panicked => status = Some(TestStatus::Errored),
+            Outcome::Pending => {
+                status.get_or_insert(TestStatus::TimedOut);
+            }
+        }
+    }
+
+    let positions = per_sequence.iter().map(Vec::len).max().unwrap_or(0);
+    let second_level: Vec<SecondLevel> = if single {
+        Vec::new()
+    } else {
+        (0..positions)
+            .map(|k| {
+                let p_values: Vec<f64> = per_sequence
+                    .iter()
+                    .filter_map(|p| p.get(k).copied())
+                    .collect();
+                second_level::second_level(&p_values, alpha)
+            })
+            .collect()
+    };
+    let p_values: Vec<f64> = per_sequence.into_iter().flatten().collect();
+    let status = status.unwrap_or_else(|| {
+        if single {
+            verdict(&p_values, alpha)
+        } else if second_level.iter().all(|l| l.sequences == 0) {
+            TestStatus::Skipped
+        } else if second_level.iter().all(SecondLevel::passed) {
+            TestStatus::Passed
+        } else {
+            TestStatus::Failed
+        }
+    });
+
+    TestResult {
+        name: name.to_string(),
+        status,
+        adjusted_p_values: p_values.clone(),
+        p_values,
+        second_level,
+        elapsed,
+    }
+}
+
+impl fmt::Debug for Suite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+/// Adjust the p-values of a single-sequence run across every test, and
+/// judge the tests that ran by the adjusted values.
+fn correct(results: &mut [TestResult], config: &SuiteConfig) {
+    if config.correction == Correction::None {
+        return;
+    }
+    let p_values: Vec<f64> = results
+        .iter()
+        .flat_map(|r| r.p_values.iter().copied())
+        .collect();
+    let mut adjusted = config.correction.adjust(&p_values).into_iter();
+    for result in results {
+        result.adjusted_p_values = adjusted.by_ref().take(result.p_values.len()).collect();
+        if matches!(result.status, TestStatus::Passed | TestStatus::Failed) {
+            result.status = verdict(&result.adjusted_p_values, config.alpha);
+        }
+    }
+}
+
+fn verdict(p_values: &[f64], alpha: f64) -> TestStatus {
+    let mut defined = p_values.iter().filter(|p| !p.is_nan()).peekable();
+    if defined.peek().is_none() {
+        TestStatus::Skipped
+    } else if defined.all(|&p| p >= alpha) {
+        TestStatus::Passed
+    } else {
+        TestStatus::Failed
+    }
+}
+
+/// Chi-square goodness of fit of the byte distribution, 255 degrees of freedom.
+fn chi_square_test(data: &[u8], _: &SuiteConfig) -> Vec<f64> {
+    if data.is_empty() {
+        return Vec::new();
+    }
+    let (chisq, _) = chisquare_from_counts(&byte_counts(data));
+    vec![chisquare_p_value(chisq, 255.0)]
+}
+
+fn monte_carlo_pi_test(data: &[u8], _: &SuiteConfig) -> Vec<f64> {
+    let stats = EntStats::from_data(data, false);
+    monte_carlo_pi_p_value(&stats, data.len())
+        .into_iter()
+        .collect()
+}
+
+fn serial_correlation_test(data: &[u8], _: &SuiteConfig) -> Vec<f64> {
+    let stats = EntStats::from_data(data, false);
+    serial_correlation_p_value(&stats, data.len())
+        .into_iter()
+        .collect()
+}
+
+/// Two-sided normal test of the Monte Carlo hit count of `len` bytes
+/// against `π/4`; `None` below 6 bytes.
+pub(crate) fn monte_carlo_pi_p_value(stats: &EntStats, len: usize) -> Option<f64> {
+    let n = (len / 6) as f64;
+    if n == 0.0 {
+        return None;
+    }
+    let hits = stats.pi_estimate / 4.0 * n;
+    let z = (hits - n * FRAC_PI_4) / (n * FRAC_PI_4 * (1.0 - FRAC_PI_4)).sqrt();
+    Some(erfc(z.abs() / SQRT_2))
+}
+
+/// Two-sided test of the lag-1 serial correlation of `len` bytes,
+/// `r·√n ~ N(0, 1)`; `None` when the correlation is undefined.
+pub(crate) fn serial_correlation_p_value(stats: &EntStats, len: usize) -> Option<f64> {
+    let r = stats.serial_correlation;
+    if r == -99999.0 {
+        return None;
+    }
+    let z = r * ((len - 1) as f64).sqrt();
+    Some(erfc(z.abs() / SQRT_2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noise(len: usize) -> Arc<[u8]> {
+        let mut x = 0x853C_49E6_748F_EA9Bu64;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_standard_suite_on_noise_and_ramp() {
+        let config = SuiteConfig::default();
+        let report = Suite::standard().run(noise(1 << 16), &config);
+        assert!(report.passed(), "{report:?}");
+        assert_eq!(report.results.len(), 3);
+
+        let ramp: Vec<u8> = (0..=255u8).cycle().take(1 << 16).collect();
+        let report = Suite::standard().run(Arc::from(ramp), &config);
+        assert_eq!(
+            report.get("serial-correlation").unwrap().status,
+            TestStatus::Failed
+        );
+    }
+
+    #[test]
+    fn test_randomness_tests_join_suites() {
+        use crate::randomness::NistTest;
+        let suite = Suite::new()
+            .with_randomness_test(NistTest::Frequency)
+            .with_randomness_test(NistTest::LongestRun);
+        assert_eq!(
+            suite.names().collect::<Vec<_>>(),
+            ["frequency", "longest-run"]
+        );
+        let data = noise(1000);
+        let report = suite.run(data.clone(), &SuiteConfig::default());
+        let frequency = nist::frequency(&BitStream::from_bytes(&data)).p_value;
+        assert_eq!(report.results[0].p_values, [frequency]);
+        // Below the 128 bits of the longest-run test.
+        let report = suite.run(noise(10), &SuiteConfig::default());
+        assert_eq!(report.results[1].status, TestStatus::Skipped);
+    }
+
+    #[test]
+    fn test_correction_adjusts_single_sequence_verdicts() {
+        let suite = Suite::new()
+            .with_test("a", |_: &[u8], _: &SuiteConfig| vec![0.001])
+            .with_test("b", |_: &[u8], _: &SuiteConfig| vec![0.004, 0.5])
+            .with_test("c", |_: &[u8], _: &SuiteConfig| vec![0.6])
+            .with_test("empty", |_: &[u8], _: &SuiteConfig| Vec::new());
+        let statuses = |correction| {
+            let config = SuiteConfig {
+                correction,
+                ..SuiteConfig::default()
+            };
+            let report = suite.run(noise(16), &config);
+            report.results.iter().map(|r| r.status).collect::<Vec<_>>()
+        };
+        use TestStatus::*;
+        assert_eq!(
+            statuses(Correction::None),
+            [Failed, Failed, Passed, Skipped]
+        );
+        // Four p-values: 0.004 and 0.016 after Bonferroni, 0.004 and 0.008
+        // after Benjamini-Hochberg.
+        assert_eq!(
+            statuses(Correction::Bonferroni),
+            [Failed, Passed, Passed, Skipped]
+        );
+        assert_eq!(
+            statuses(Correction::BenjaminiHochberg),
+            [Failed, Failed, Passed, Skipped]
+        );
+        let config = SuiteConfig {
+            correction: Correction::Bonferroni,
+            ..SuiteConfig::default()
+        };
+        let report = suite.run(noise(16), &config);
+        let b = report.get("b").unwrap();
+        assert_eq!(b.p_values, [0.004, 0.5]);
+        assert_eq!(b.adjusted_p_values, [0.016, 1.0]);
+    }
+
+    #[test]
+    fn test_deadline_reports_timed_out() {
+        let suite = Suite::new()
+            .with_test("quick", |_: &[u8], _: &SuiteConfig| vec![0.5])
+            .with_test("slow", |_: &[u8], _: &SuiteConfig| {
+                thread::sleep(Duration::from_secs(5));
+                vec![0.5]
+            })
+            .with_test("empty", |_: &[u8], _: &SuiteConfig| Vec::new());
+        let config = SuiteConfig {
+            threads: 2,
+            deadline: Some(Duration::from_millis(200)),
+            ..SuiteConfig::default()
+        };
+        let report = suite.run(noise(16), &config);
+        assert!(report.elapsed < Duration::from_secs(2));
+        let statuses: Vec<TestStatus> = report.results.iter().map(|r| r.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                TestStatus::Passed,
+                TestStatus::TimedOut,
+                TestStatus::Skipped
+            ]
+        );
+        assert!(!report.passed());
+    }
+
+    #[test]
+    fn test_nist_params_validation() {
+        let params = NistParams::default();
+        assert_eq!(params.validate(1_000_000), Ok(()));
+        assert_eq!(params.block_frequency_len_for(1_000_000), 10_001);
+
+        let err = params.validate(100_000).unwrap_err();
+        assert_eq!(err.parameter, "serial_len");
+        let short_patterns = NistParams {
+            serial_len: 8,
+            ..params.clone()
+        };
+        assert_eq!(short_patterns.validate(100_000), Ok(()));
+
+        let short_blocks = NistParams {
+            block_frequency_len: Some(128),
+            ..params.clone()
+        };
+        assert_eq!(
+            short_blocks.validate(1_000_000).unwrap_err().parameter,
+            "block_frequency_len"
+        );
+
+        let config = SuiteConfig {
+            nist: NistParams {
+                sequences: 10,
+                ..params
+            },
+            ..SuiteConfig::default()
+        };
+        assert_eq!(config.nist.sequence_bits(1_250_000), 1_000_000);
+        assert!(config.validate(1_250_000).is_ok());
+        assert_eq!(
+            config.validate(125_000).unwrap_err().parameter,
+            "serial_len"
+        );
+    }
+
+    #[test]
+    fn test_nist_suite_with_custom_templates() {
+        let data = noise(1 << 14);
+        let config = SuiteConfig {
+            nist: NistParams {
+                templates: Some(vec!["000000001".parse().unwrap(), "0011".parse().unwrap()]),
+                ..NistParams::default()
+            },
+            ..SuiteConfig::default()
+        };
+        let report = Suite::nist().run(data.clone(), &config);
+        let result = report.get("non-overlapping-template").unwrap();
+        assert_eq!(result.p_values.len(), 2);
+        assert_eq!(result.status, TestStatus::Passed);
+
+        let report = Suite::nist().run(data, &SuiteConfig::default());
+        let result = report.get("non-overlapping-template").unwrap();
+        assert_eq!(result.p_values.len(), 148);
+    }
+
+    #[test]
+    fn test_second_level_over_sequences() {
+        // SplitMix64, one continuous stream cut into 60 sequences.
+        let mut state = 0u64;
+        let mut next = move || {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            ((z ^ (z >> 31)) >> 56) as u8
+        };
+        let sequences: Vec<Arc<[u8]>> = (0..60)
+            .map(|_| (0..4096).map(|_| next()).collect())
+            .collect();
+        let report = Suite::standard().run_sequences(&sequences, &SuiteConfig::default());
+        assert_eq!(report.input.len, 60 * 4096);
+        for result in &report.results {
+            assert_eq!(result.p_values.len(), 60);
+            assert_eq!(result.second_level.len(), 1);
+            assert_eq!(result.second_level[0].sequences, 60);
+            assert_eq!(result.status, TestStatus::Passed, "{result:?}");
+        }
+
+        let biased: Vec<Arc<[u8]>> = sequences
+            .iter()
+            .map(|s| s.iter().map(|&b| b | 1).collect())
+            .collect();
+        let report = Suite::standard().run_sequences(&biased, &SuiteConfig::default());
+        assert_eq!(report.get("chi-square").unwrap().status, TestStatus::Failed);
+    }
+
+    #[test]
+    fn test_run_split_records_split() {
+        let config = SuiteConfig {
+            nist: NistParams {
+                sequences: 8,
+                sequence_len: Some(1000),
+                ..NistParams::default()
+            },
+            ..SuiteConfig::default()
+        };
+        let data = noise(8500);
+        let report = Suite::standard().run_split(Arc::clone(&data), &config);
+        // The digest covers the whole input, including unused bytes.
+        assert_eq!(report.input, InputDigest::new(&data, false));
+        let split = report.split.unwrap();
+        assert_eq!(split.sequences, 8);
+        assert_eq!(split.unused, 500);
+        assert_eq!(report.results[0].p_values.len(), 8);
+        assert_eq!(report.results[0].second_level[0].sequences, 8);
+
+        // Not enough data for 8 sequences of 2000 bytes.
+        let short = NistParams {
+            sequence_len: Some(2000),
+            ..config.nist
+        };
+        assert_eq!(short.split(8500).sequences, 4);
+        assert_eq!(short.sequence_bits(8500), 16_000);
+    }
+
+    #[test]
+    fn test_split_strategies() {
+        let data: Vec<u8> = (0..12).collect();
+        let blocks = SplitStrategy::Blocks.apply(&data, 3, 4);
+        assert_eq!(blocks, [[0, 1, 2, 3], [4, 5, 6, 7], [8, 9, 10, 11]]);
+        let interleaved = SplitStrategy::Interleaved.apply(&data, 3, 4);
+        assert_eq!(interleaved, [[0, 3, 6, 9], [1, 4, 7, 10], [2, 5, 8, 11]]);
+
+        let random = SplitStrategy::Random { seed: 7 }.apply(&data, 3, 4);
+        assert_eq!(random, SplitStrategy::Random { seed: 7 }.apply(&data, 3, 4));
+        let mut all: Vec<u8> = random.iter().flatten().copied().collect();
+        all.sort_unstable();
+        assert_eq!(all, data);
+        assert!(random.iter().all(|s| s.windows(2).all(|w| w[0] < w[1])));
+
+        // Too short, or too large to multiply out: fewer sequences.
+        for strategy in [
+            SplitStrategy::Blocks,
+            SplitStrategy::Interleaved,
+            SplitStrategy::Random { seed: 7 },
+        ] {
+            assert_eq!(strategy.apply(&data, 5, 4).len(), 3);
+            assert_eq!(strategy.apply(&data, usize::MAX, 5).len(), 2);
+            assert!(strategy.apply(&data, 2, usize::MAX).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_interleaved_split_exposes_channel_bias() {
+        // Two interleaved channels; the second is stuck high in its top bit.
+        let mut data = noise(1 << 15).to_vec();
+        for b in data.iter_mut().skip(1).step_by(2) {
+            *b |= 0x80;
+        }
+        let mut config = SuiteConfig {
+            nist: NistParams {
+                sequences: 2,
+                ..NistParams::default()
+            },
+            ..SuiteConfig::default()
+        };
+        let suite = Suite::new().with_test("chi-square", chi_square_test);
+        let blocks = suite.run_split(Arc::from(data.clone()), &config);
+        assert!(blocks.results[0].p_values.iter().all(|&p| p < 1e-6));
+
+        config.nist.split_strategy = SplitStrategy::Interleaved;
+        let interleaved = suite.run_split(Arc::from(data), &config);
+        assert_eq!(
+            interleaved.split.unwrap().strategy,
+            SplitStrategy::Interleaved
+        );
+        let p = &interleaved.results[0].p_values;
+        assert!(p[0] > 1e-4 && p[1] < 1e-6);
+    }
+}
@@ -0,0 +1,71 @@
+//! Per-position surprisal, `-log2 p(byte)`.
+//!
+//! ```rust
+//! use ent_rs::surprisal::{surprisal, SurprisalModel};
+//! let mut data = vec![b'a'; 1000];
+//! data[500] = b'z';
+//! let bits = surprisal(&data, SurprisalModel::Global);
+//! assert!(bits[500] > 9.0 && bits[0] < 0.01);
+//! ```
+
+use crate::byte_counts;
+
+/// Distribution each byte is scored against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SurprisalModel {
+    /// Empirical byte distribution of the whole input.
+    Global,
+    /// Distribution of the preceding `n` bytes, with add-one smoothing so
+    /// bytes not seen in the window still get a finite score.
+    TrailingWindow(usize),
+}
+
+/// Surprisal in bits of every byte of `data` under `model`.
+pub fn surprisal(data: &[u8], model: SurprisalModel) -> Vec<f64> {
+    match model {
+        SurprisalModel::Global => {
+            let counts = byte_counts(data);
+            let total = data.len() as f64;
+            let bits: Vec<f64> = counts.iter().map(|&c| -(c as f64 / total).log2()).collect();
+            data.iter().map(|&b| bits[b as usize]).collect()
+        }
+        SurprisalModel::TrailingWindow(window) => {
+            let mut counts = [0usize; 256];
+            let mut out = Vec::with_capacity(data.len());
+            for (i, &b) in data.iter().enumerate() {
+                let seen = i.min(window);
+                let p = (counts[b as usize] + 1) as f64 / (seen + 256) as f64;
+                out.push(-p.log2());
+                counts[b as usize] += 1;
+                if i >= window {
+                    counts[data[i - window] as usize] -= 1;
+                }
+            }
+            out
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_global_surprisal_sums_to_entropy() {
+        let data: Vec<u8> = (0..=255).cycle().take(4096).collect();
+        let bits = surprisal(&data, SurprisalModel::Global);
+        let mean = bits.iter().sum::<f64>() / bits.len() as f64;
+        assert!((mean - 8.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_trailing_window_flags_new_region() {
+        let mut data = vec![0u8; 2048];
+        data.extend((0..=255).cycle().take(256));
+        let bits = surprisal(&data, SurprisalModel::TrailingWindow(1024));
+        assert!(bits[2047] < 0.5);
+        assert!(bits[2049] > 10.0);
+        // The first byte has no history and is scored against a uniform prior.
+        assert!((bits[0] - 8.0).abs() < 1e-12);
+    }
+}
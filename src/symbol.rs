@@ -0,0 +1,619 @@
+//! Entropy and chi-square over an arbitrary, compile-time sized alphabet.
+//!
+//! ```rust
+//! use ent_rs::symbol::SymbolStats;
+//! // DNA bases encoded as 0..4
+//! let bases = [0u8, 1, 2, 3, 0, 1, 2, 3];
+//! let stats = SymbolStats::<4>::from_symbols(bases);
+//! assert!((stats.entropy - 2.0).abs() < 1e-12);
+//! ```
+//!
+//! For alphabets sized at run time by a symbol width in bits, such as
+//! nibbles or 12-bit ADC samples, use [`WidthStats`]:
+//!
+//! ```rust
+//! use ent_rs::symbol::WidthStats;
+//! let stats = WidthStats::from_data(&[0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF], 4);
+//! assert_eq!(stats.total, 16);
+//! assert_eq!(stats.entropy, 4.0);
+//! ```
+//!
+//! 16- and 32-bit words in either byte order, whose alphabets are mostly
+//! empty, are counted sparsely by [`WordStats`], and symbols of any
+//! hashable type, such as tokens or IDs, by [`AlphabetStats`]:
+//!
+//! ```rust
+//! use ent_rs::symbol::AlphabetStats;
+//! let tokens = "the cat sat on the mat".split(' ');
+//! let stats = AlphabetStats::from_symbols(tokens);
+//! assert_eq!(stats.alphabet_size, 5);
+//! assert_eq!(stats.counts["the"], 2);
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+use crate::{chisquare_from_counts, chisquare_p_value, entropy_from_counts};
+
+/// Widest symbol [`WidthStats`] accepts, in bits.
+pub const MAX_WIDTH: u32 = 16;
+
+/// Statistics for symbols drawn from an alphabet of `N` values `0..N`.
+#[derive(Debug, Clone)]
+pub struct SymbolStats<const N: usize> {
+    /// Occurrences of each symbol.
+    pub counts: [usize; N],
+    /// Number of symbols counted.
+    pub total: usize,
+    /// Symbols that were `>= N` and therefore ignored.
+    pub out_of_range: usize,
+    /// Shannon entropy in bits per symbol.
+    pub entropy: f64,
+    /// Largest possible entropy, `log2(N)`.
+    pub max_entropy: f64,
+    /// Chi-square statistic against a uniform distribution over the alphabet.
+    pub chisquare: f64,
+    /// p-value of the chi-square test with `N - 1` degrees of freedom.
+    pub p_value: f64,
+}
+
+impl<const N: usize> SymbolStats<N> {
+    /// Count symbols and compute statistics over them.
+    pub fn from_symbols<S, I>(symbols: I) -> Self
+    where
+        S: Into<usize>,
+        I: IntoIterator<Item = S>,
+    {
+        let mut counts = [0usize; N];
+        let mut out_of_range = 0;
+        for s in symbols {
+            match counts.get_mut(s.into()) {
+                Some(c) => *c += 1,
+                None => out_of_range += 1,
+            }
+        }
+        let mut stats = Self::from_counts(counts);
+        stats.out_of_range = out_of_range;
+        stats
+    }
+
+    /// Compute statistics from an existing frequency table.
+    pub fn from_counts(counts: [usize; N]) -> Self {
+        let total = counts.iter().sum::<usize>();
+        let (chisquare, _) = chisquare_from_counts(&counts);
+
+        SymbolStats {
+            counts,
+            total,
+            out_of_range: 0,
+            entropy: entropy_from_counts(&counts),
+            max_entropy: (N as f64).log2(),
+            chisquare,
+            p_value: chisquare_p_value(chisquare, N as f64 - 1.0),
+        }
+    }
+
+    /// Relative frequency of each symbol.
+    pub fn frequencies(&self) -> [f64; N] {
+        let total = self.total as f64;
+        self.counts.map(|c| c as f64 / total)
+    }
+}
+
+/// Statistics for `width`-bit symbols, over the alphabet `0..2^width`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WidthStats {
+    /// Symbol width in bits.
+    pub width: u32,
+    /// Occurrences of each symbol value.
+    pub counts: Vec<usize>,
+    /// Number of symbols counted.
+    pub total: usize,
+    /// Values that did not fit in `width` bits, or bytes that were not hex
+    /// digits in [`from_hex`](Self::from_hex), and were therefore ignored.
+    pub out_of_range: usize,
+    /// Bits at the end of the input too few to form a symbol.
+    pub trailing_bits: usize,
+    /// Shannon entropy in bits per symbol.
+    pub entropy: f64,
+    /// Largest possible entropy, `width`.
+    pub max_entropy: f64,
+    /// Chi-square statistic against a uniform distribution over the alphabet.
+    pub chisquare: f64,
+    /// p-value of the chi-square test with `2^width - 1` degrees of freedom.
+    pub p_value: f64,
+    /// Arithmetic mean of the symbol values; NaN without symbols.
+    pub mean: f64,
+}
+
+impl WidthStats {
+    /// Split `data` into consecutive `width`-bit symbols, most significant
+    /// bit first, as in bit mode, and compute statistics over them.
+    ///
+    /// # Panics
+    ///
+    /// If `width` is not in `1..=16`.
+    pub fn from_data(data: &[u8], width: u32) -> Self {
+        let mut counts = alphabet(width);
+        let mask = (1u32 << width) - 1;
+        let (mut buffer, mut buffered) = (0u32, 0u32);
+        for &b in data {
+            buffer = (buffer << 8) | b as u32;
+            buffered += 8;
+            while buffered >= width {
+                buffered -= width;
+                counts[((buffer >> buffered) & mask) as usize] += 1;
+            }
+            buffer &= (1 << buffered) - 1;
+        }
+        let mut stats = WidthStats::from_counts(width, counts);
+        stats.trailing_bits = buffered as usize;
+        stats
+    }
+
+    /// Nibble mode: the 16-value alphabet of 4-bit symbols, high nibble of
+    /// each byte first, for 4-bit DAC output and packed BCD. The entropy is
+    /// in bits per nibble and the chi-square has 15 degrees of freedom.
+    pub fn nibbles(data: &[u8]) -> Self {
+        WidthStats::from_data(data, 4)
+    }
+
+    /// Nibble statistics of hex-encoded `text`, one nibble per hex digit of
+    /// either case; whitespace, separators and other non-digits are skipped
+    /// and counted in `out_of_range`.
+    ///
+    /// ```rust
+    /// use ent_rs::symbol::WidthStats;
+    /// let stats = WidthStats::from_hex(b"de:ad:be:ef\n");
+    /// assert_eq!(stats.total, 8);
+    /// assert_eq!(stats.out_of_range, 4);
+    /// assert_eq!(stats.counts[0xE], 3);
+    /// ```
+    pub fn from_hex(text: &[u8]) -> Self {
+        let digits = text.iter().map(|&c| match c {
+            b'0'..=b'9' => (c - b'0') as u64,
+            b'a'..=b'f' => (c - b'a' + 10) as u64,
+            b'A'..=b'F' => (c - b'A' + 10) as u64,
+            _ => u64::MAX,
+        });
+        WidthStats::from_values(digits, 4)
+    }
+
+    /// Compute statistics over values already unpacked, such as ADC
+    /// samples stored in 16-bit words.
+    ///
+    /// # Panics
+    ///
+    /// If `width` is not in `1..=16`.
+    pub fn from_values<S, I>(values: I, width: u32) -> Self
+    where
+        S: Into<u64>,
+        I: IntoIterator<Item = S>,
+    {
+        let mut counts = alphabet(width);
+        let mut out_of_range = 0;
+        for v in values {
+            match usize::try_from(v.into())
+                .ok()
+                .and_then(|i| counts.get_mut(i))
+            {
+                Some(c) => *c += 1,
+                None => out_of_range += 1,
+            }
+        }
+        let mut stats = WidthStats::from_counts(width, counts);
+        stats.out_of_range = out_of_range;
+        stats
+    }
+
+    /// Compute statistics from an existing frequency table of `2^width`
+    /// entries.
+    ///
+    /// # Panics
+    ///
+    /// If `width` is not in `1..=16`, or `counts` has the wrong length.
+    pub fn from_counts(width: u32, counts: Vec<usize>) -> Self {
+        assert_eq!(
+            counts.len(),
+            alphabet(width).len(),
+            "expected 2^{width} counts"
+        );
+        let total = counts.iter().sum::<usize>();
+        let sum: f64 = counts
+            .iter()
+            .enumerate()
+            .map(|(v, &c)| v as f64 * c as f64)
+            .sum();
+        let (chisquare, _) = chisquare_from_counts(&counts);
+        WidthStats {
+            width,
+            total,
+            out_of_range: 0,
+            trailing_bits: 0,
+            entropy: entropy_from_counts(&counts),
+            max_entropy: width as f64,
+            chisquare,
+            p_value: chisquare_p_value(chisquare, (counts.len() - 1) as f64),
+            mean: sum / total as f64,
+            counts,
+        }
+    }
+
+    /// Relative frequency of each symbol value.
+    pub fn frequencies(&self) -> Vec<f64> {
+        let total = self.total as f64;
+        self.counts.iter().map(|&c| c as f64 / total).collect()
+    }
+}
+
+/// Byte order of multi-byte words.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Endian {
+    /// Least significant byte first, as on x86 and most ARM systems.
+    #[default]
+    Little,
+    /// Most significant byte first, as in network protocols.
+    Big,
+}
+
+/// Statistics for 16- or 32-bit words, with a sparse frequency table
+/// holding only the values that occur.
+///
+/// The chi-square test compares against a uniform distribution over all
+/// `2^bits` values; it is only meaningful when the input holds several
+/// times that many words, which for 32-bit words is rarely the case.
+///
+/// ```rust
+/// use ent_rs::symbol::{Endian, WordStats};
+/// let stats = WordStats::u16(&[0x12, 0x34, 0x12, 0x34, 0x00, 0x01], Endian::Big);
+/// assert_eq!(stats.counts.get(&0x1234), Some(&2));
+/// assert_eq!(stats.counts.get(&0x0001), Some(&1));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordStats {
+    /// Word width in bits, 16 or 32.
+    pub bits: u32,
+    /// Byte order the words were read in.
+    pub endian: Endian,
+    /// Occurrences of each word value that occurs.
+    pub counts: BTreeMap<u32, usize>,
+    /// Number of words counted.
+    pub total: usize,
+    /// Bytes at the end of the input too few to form a word.
+    pub trailing_bytes: usize,
+    /// Shannon entropy in bits per word.
+    pub entropy: f64,
+    /// Largest possible entropy, `bits`.
+    pub max_entropy: f64,
+    /// Chi-square statistic against a uniform distribution over all
+    /// `2^bits` values.
+    pub chisquare: f64,
+    /// p-value of the chi-square test with `2^bits - 1` degrees of freedom.
+    pub p_value: f64,
+    /// Arithmetic mean of the word values; NaN without words.
+    pub mean: f64,
+}
+
+impl WordStats {
+    /// Statistics of `data` read as 16-bit words in `endian` byte order.
+    pub fn u16(data: &[u8], endian: Endian) -> Self {
+        let words = data.chunks_exact(2).map(|w| {
+            let w = [w[0], w[1]];
+            match endian {
+                Endian::Little => u16::from_le_bytes(w),
+                Endian::Big => u16::from_be_bytes(w),
+            }
+        });
+        let mut stats = WordStats::from_words(words.map(u32::from), 16);
+        stats.endian = endian;
+        stats.trailing_bytes = data.len() % 2;
+        stats
+    }
+
+    /// Statistics of `data` read as 32-bit words in `endian` byte order.
+    pub fn u32(data: &[u8], endian: Endian) -> Self {
+        let words = data.chunks_exact(4).map(|w| {
+            let w = [w[0], w[1], w[2], w[3]];
+            match endian {
+                Endian::Little => u32::from_le_bytes(w),
+                Endian::Big => u32::from_be_bytes(w),
+            }
+        });
+        let mut stats = WordStats::from_words(words, 32);
+        stats.endian = endian;
+        stats.trailing_bytes = data.len() % 4;
+        stats
+    }
+
+    /// The `n` most frequent words with their counts, most frequent first
+    /// and ties by value.
+    pub fn most_common(&self, n: usize) -> Vec<(u32, usize)> {
+        let mut words: Vec<(u32, usize)> = self.counts.iter().map(|(&w, &c)| (w, c)).collect();
+        words.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        words.truncate(n);
+        words
+    }
+
+    fn from_words(words: impl Iterator<Item = u32>, bits: u32) -> Self {
+        let mut counts = BTreeMap::new();
+        let (mut total, mut sum) = (0usize, 0u128);
+        for w in words {
+            *counts.entry(w).or_insert(0) += 1;
+            total += 1;
+            sum += w as u128;
+        }
+        let occurring: Vec<usize> = counts.values().copied().collect();
+        // Σ (o - e)² / e over all 2^bits values, of which the missing ones
+        // contribute e each: Σ o² / e - total.
+        let alphabet = (1u64 << bits) as f64;
+        let expected = total as f64 / alphabet;
+        let sum_sq = occurring.iter().map(|&c| c as f64 * c as f64).sum::<f64>();
+        let chisquare = sum_sq / expected - total as f64;
+        WordStats {
+            bits,
+            endian: Endian::Little,
+            counts,
+            total,
+            trailing_bytes: 0,
+            entropy: entropy_from_counts(&occurring),
+            max_entropy: bits as f64,
+            chisquare,
+            p_value: chisquare_p_value(chisquare, alphabet - 1.0),
+            mean: sum as f64 / total as f64,
+        }
+    }
+}
+
+/// Statistics for symbols of any hashable type, over an alphabet of the
+/// symbols observed or of a given size.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlphabetStats<T: Eq + Hash> {
+    /// Occurrences of each symbol that occurs.
+    pub counts: HashMap<T, usize>,
+    /// Number of symbols counted.
+    pub total: usize,
+    /// Number of possible symbols, at least the number observed.
+    pub alphabet_size: usize,
+    /// Shannon entropy in bits per symbol.
+    pub entropy: f64,
+    /// Largest possible entropy, `log2(alphabet_size)`.
+    pub max_entropy: f64,
+    /// Chi-square statistic against a uniform distribution over the
+    /// alphabet.
+    pub chisquare: f64,
+    /// p-value of the chi-square test with `alphabet_size - 1` degrees of
+    /// freedom; NaN for an alphabet of one symbol.
+    pub p_value: f64,
+}
+
+impl<T: Eq + Hash> AlphabetStats<T> {
+    /// Count `symbols` and compute statistics over the alphabet of the
+    /// distinct symbols among them.
+    pub fn from_symbols<I: IntoIterator<Item = T>>(symbols: I) -> Self {
+        let counts = count(symbols);
+        let alphabet_size = counts.len();
+        AlphabetStats::from_map(counts, alphabet_size)
+    }
+
+    /// Like [`from_symbols`](Self::from_symbols), over an alphabet of
+    /// `alphabet_size` symbols of which some may not occur, such as the six
+    /// faces of a die.
+    ///
+    /// # Panics
+    ///
+    /// If more than `alphabet_size` distinct symbols occur.
+    pub fn from_symbols_in<I: IntoIterator<Item = T>>(symbols: I, alphabet_size: usize) -> Self {
+        let counts = count(symbols);
+        assert!(
+            counts.len() <= alphabet_size,
+            "{} distinct symbols in an alphabet of {alphabet_size}",
+            counts.len()
+        );
+        AlphabetStats::from_map(counts, alphabet_size)
+    }
+
+    /// Relative frequency of `symbol`; 0 if it does not occur.
+    pub fn frequency(&self, symbol: &T) -> f64 {
+        self.counts.get(symbol).map_or(0, |&c| c) as f64 / self.total as f64
+    }
+
+    fn from_map(counts: HashMap<T, usize>, alphabet_size: usize) -> Self {
+        let occurring: Vec<usize> = counts.values().copied().collect();
+        let total = occurring.iter().sum::<usize>();
+        let expected = total as f64 / alphabet_size as f64;
+        // Symbols that do not occur contribute `expected` each.
+        let missing = (alphabet_size - occurring.len()) as f64 * expected;
+        let chisquare = occurring
+            .iter()
+            .map(|&obs| {
+                let diff = obs as f64 - expected;
+                diff * diff / expected
+            })
+            .sum::<f64>()
+            + missing;
+        AlphabetStats {
+            counts,
+            total,
+            alphabet_size,
+            entropy: entropy_from_counts(&occurring),
+            max_entropy: (alphabet_size as f64).log2(),
+            chisquare,
+            p_value: chisquare_p_value(chisquare, alphabet_size as f64 - 1.0),
+        }
+    }
+}
+
+impl AlphabetStats<usize> {
+    /// Statistics of a frequency table of any length, symbol `i` occurring
+    /// `counts[i]` times.
+    pub fn from_counts(counts: &[usize]) -> Self {
+        let map = counts
+            .iter()
+            .enumerate()
+            .filter(|&(_, &c)| c > 0)
+            .map(|(i, &c)| (i, c))
+            .collect();
+        AlphabetStats::from_map(map, counts.len())
+    }
+}
+
+fn count<T: Eq + Hash>(symbols: impl IntoIterator<Item = T>) -> HashMap<T, usize> {
+    let mut counts = HashMap::new();
+    for s in symbols {
+        *counts.entry(s).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// An empty frequency table for `width`-bit symbols.
+fn alphabet(width: u32) -> Vec<usize> {
+    assert!(
+        (1..=MAX_WIDTH).contains(&width),
+        "symbol width must be 1 to {MAX_WIDTH} bits, got {width}"
+    );
+    vec![0; 1 << width]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fair_die() {
+        let rolls = (0..6000usize).map(|i| i % 6);
+        let stats = SymbolStats::<6>::from_symbols(rolls);
+        assert_eq!(stats.total, 6000);
+        assert!((stats.entropy - 6f64.log2()).abs() < 1e-12);
+        assert_eq!(stats.chisquare, 0.0);
+        assert_eq!(stats.p_value, 1.0);
+    }
+
+    #[test]
+    fn test_loaded_die_and_out_of_range() {
+        let mut rolls = vec![5u8; 500];
+        rolls.extend((0..500u16).map(|i| (i % 6) as u8));
+        rolls.push(6);
+        let stats = SymbolStats::<6>::from_symbols(rolls);
+        assert_eq!(stats.out_of_range, 1);
+        assert!(stats.entropy < stats.max_entropy);
+        assert!(stats.p_value < 0.001);
+        assert!(stats.frequencies()[5] > 0.5);
+    }
+
+    #[test]
+    fn test_width_stats_match_byte_and_bit_modes() {
+        let data: Vec<u8> = (0u32..10_000)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+            .collect();
+        let bytes = crate::EntStats::from_data(&data, false);
+        let stats = WidthStats::from_data(&data, 8);
+        assert_eq!(stats.total, data.len());
+        assert_eq!(stats.entropy, bytes.entropy);
+        assert_eq!(stats.chisquare, bytes.chisquare);
+        assert_eq!(stats.mean, bytes.mean);
+        let bits = crate::EntStats::from_data(&data, true);
+        let stats = WidthStats::from_data(&data, 1);
+        assert_eq!(stats.entropy, bits.entropy);
+        assert_eq!(stats.counts[1], bits.bit_frequencies.unwrap()[1].0);
+    }
+
+    #[test]
+    fn test_width_stats_unpack_and_values() {
+        // 0xABC, 0xDEF and 4 trailing bits.
+        let stats = WidthStats::from_data(&[0xAB, 0xCD, 0xEF, 0x12], 12);
+        assert_eq!(stats.total, 2);
+        assert_eq!(stats.counts[0xABC], 1);
+        assert_eq!(stats.counts[0xDEF], 1);
+        assert_eq!(stats.trailing_bits, 8);
+        assert_eq!(stats.max_entropy, 12.0);
+
+        let samples = (0..4096u16 * 4).map(|i| i % 4096).chain([4096, u16::MAX]);
+        let stats = WidthStats::from_values(samples, 12);
+        assert_eq!(stats.out_of_range, 2);
+        assert_eq!(stats.entropy, 12.0);
+        assert_eq!(stats.p_value, 1.0);
+        assert!(stats.frequencies().iter().all(|&f| f == 1.0 / 4096.0));
+
+        let stats = WidthStats::from_values(0..=u16::MAX, 16);
+        assert_eq!(stats.counts.len(), 1 << 16);
+        assert_eq!(stats.entropy, 16.0);
+    }
+
+    #[test]
+    fn test_nibble_mode_and_hex() {
+        let data: Vec<u8> = (0..=255).collect();
+        let stats = WidthStats::nibbles(&data);
+        assert_eq!(stats.counts, vec![32; 16]);
+        assert_eq!(stats.entropy, 4.0);
+        assert_eq!(stats.p_value, 1.0);
+
+        let hex: String = data.iter().map(|b| format!("{b:02X} ")).collect();
+        assert_eq!(WidthStats::from_hex(hex.as_bytes()).counts, stats.counts);
+        // Text like "aaaa" is biased towards one digit.
+        let stats = WidthStats::from_hex(b"0000000000000000aaaaaaaaaaaaaaaa");
+        assert_eq!(stats.entropy, 1.0);
+        assert!(stats.p_value < 1e-6);
+    }
+
+    #[test]
+    fn test_word_stats_byte_order_and_sparse_counts() {
+        let words: Vec<u8> = (0..=u16::MAX).flat_map(|w| w.to_le_bytes()).collect();
+        let stats = WordStats::u16(&words, Endian::Little);
+        assert_eq!(stats.total, 1 << 16);
+        assert_eq!(stats.counts.len(), 1 << 16);
+        assert_eq!(stats.entropy, 16.0);
+        assert!(stats.chisquare.abs() < 1e-6);
+        assert_eq!(stats.mean, 32767.5);
+        let dense = WidthStats::from_values(0..=u16::MAX, 16);
+        assert_eq!(stats.entropy, dense.entropy);
+
+        // The same bytes in the other order are a permutation of the words.
+        let swapped = WordStats::u16(&words, Endian::Big);
+        assert_eq!(swapped.counts.get(&0x0100), Some(&1));
+        assert_eq!(swapped.entropy, stats.entropy);
+
+        let data = [0xDE, 0xAD, 0xBE, 0xEF, 0xDE, 0xAD, 0xBE, 0xEF, 0x01];
+        let stats = WordStats::u32(&data, Endian::Big);
+        assert_eq!(stats.counts.get(&0xDEAD_BEEF), Some(&2));
+        assert_eq!(stats.trailing_bytes, 1);
+        assert_eq!(stats.entropy, 0.0);
+        let stats = WordStats::u32(&data, Endian::Little);
+        assert_eq!(stats.most_common(1), vec![(0xEFBE_ADDE, 2)]);
+        assert_eq!(stats.endian, Endian::Little);
+    }
+
+    #[test]
+    fn test_alphabet_stats_match_fixed_alphabet() {
+        let rolls: Vec<u8> = (0..600u32).map(|i| (i * i % 7 % 6) as u8).collect();
+        let fixed = SymbolStats::<6>::from_symbols(rolls.iter().copied());
+        let hashed = AlphabetStats::from_symbols_in(rolls.iter().copied(), 6);
+        assert!((hashed.entropy - fixed.entropy).abs() < 1e-12);
+        assert!((hashed.chisquare - fixed.chisquare).abs() < 1e-9);
+        assert_eq!(hashed.counts[&0], fixed.counts[0]);
+        let counts = AlphabetStats::from_counts(&fixed.counts);
+        assert!((counts.p_value - fixed.p_value).abs() < 1e-12);
+
+        // A die that never rolls a six: five symbols observed out of six.
+        let rolls = (0..500u64).map(|i| i % 5);
+        let observed = AlphabetStats::from_symbols(rolls.clone());
+        assert_eq!(observed.alphabet_size, 5);
+        assert_eq!(observed.p_value, 1.0);
+        let die = AlphabetStats::from_symbols_in(rolls, 6);
+        assert!((die.chisquare - 100.0).abs() < 1e-9);
+        assert!(die.p_value < 1e-15);
+        assert_eq!(die.frequency(&5), 0.0);
+        assert_eq!(die.frequency(&0), 0.2);
+    }
+
+    #[test]
+    #[should_panic(expected = "3 distinct symbols in an alphabet of 2")]
+    fn test_alphabet_stats_reject_small_alphabet() {
+        AlphabetStats::from_symbols_in(['a', 'b', 'c'], 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "symbol width must be 1 to 16 bits")]
+    fn test_width_stats_reject_wide_symbols() {
+        WidthStats::from_data(&[0; 4], 17);
+    }
+}
@@ -0,0 +1,272 @@
+//! Character-level analysis of text.
+//!
+//! [`CharTable`] counts printable ASCII characters, with every other byte in
+//! one bucket, and prints them like `ent -c`:
+//!
+//! ```rust
+//! use ent_rs::text::CharTable;
+//! let table = CharTable::from_data(b"hello\n");
+//! assert_eq!(table.count('l'), 2);
+//! assert_eq!(table.other, 1);
+//! assert!(table.to_string().starts_with("Value Char Occurrences Fraction\n"));
+//! ```
+//!
+//! [`CharStats`] decodes UTF-8 and counts Unicode characters, so text in
+//! scripts with multibyte encodings is measured per character rather than
+//! per byte:
+//!
+//! ```rust
+//! use ent_rs::text::CharStats;
+//! let stats = CharStats::from_utf8("αβγδ".as_bytes()).unwrap();
+//! assert_eq!(stats.total, 4);
+//! assert_eq!(stats.bytes, 8);
+//! assert_eq!(stats.entropy, 2.0);
+//! assert!(CharStats::from_utf8(b"\xCE").is_err());
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::{self, Utf8Error};
+
+use crate::{byte_counts, entropy_from_counts, EntStats};
+
+/// `byte` in lower case if it is an upper-case ISO 8859-1 letter, as
+/// `ent -f` folds its input; see [`EntStatsBuilder::fold_case`].
+///
+/// [`EntStatsBuilder::fold_case`]: crate::EntStatsBuilder::fold_case
+pub fn fold_case(byte: u8) -> u8 {
+    crate::fold_case(byte)
+}
+
+/// Printable ASCII, space through `~`.
+pub const PRINTABLE: RangeInclusive<u8> = b' '..=b'~';
+
+/// Occurrences of each printable ASCII character and of all other bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharTable {
+    /// Occurrences of each printable character, `' '` first.
+    pub printable: [usize; 95],
+    /// Occurrences of every other byte: control characters, DEL and bytes
+    /// above 0x7F.
+    pub other: usize,
+}
+
+impl CharTable {
+    /// Count the characters of `data`.
+    pub fn from_data(data: &[u8]) -> Self {
+        CharTable::from_counts(&byte_counts(data))
+    }
+
+    /// The table of a byte histogram.
+    pub fn from_counts(counts: &[usize; 256]) -> Self {
+        let mut printable = [0; 95];
+        printable.copy_from_slice(&counts[*PRINTABLE.start() as usize..=*PRINTABLE.end() as usize]);
+        let total = counts.iter().sum::<usize>();
+        CharTable {
+            printable,
+            other: total - printable.iter().sum::<usize>(),
+        }
+    }
+
+    /// The table of the byte frequencies of `stats`; `None` in bit mode or
+    /// without a frequency table.
+    pub fn from_stats(stats: &EntStats) -> Option<Self> {
+        let frequencies = stats.byte_frequencies.as_ref()?;
+        let mut counts = [0; 256];
+        for &(value, count, _) in frequencies {
+            counts[value as usize] = count;
+        }
+        Some(CharTable::from_counts(&counts))
+    }
+
+    /// Number of bytes counted.
+    pub fn total(&self) -> usize {
+        self.printable.iter().sum::<usize>() + self.other
+    }
+
+    /// Occurrences of `c`; 0 if it is not printable ASCII.
+    pub fn count(&self, c: char) -> usize {
+        u8::try_from(c)
+            .ok()
+            .filter(|b| PRINTABLE.contains(b))
+            .map_or(0, |b| self.printable[(b - *PRINTABLE.start()) as usize])
+    }
+
+    /// Shannon entropy in bits per character over the 96 buckets.
+    pub fn entropy(&self) -> f64 {
+        let mut counts = self.printable.to_vec();
+        counts.push(self.other);
+        entropy_from_counts(&counts)
+    }
+}
+
+/// Statistics of the Unicode characters of a text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CharStats {
+    /// Occurrences of each character that occurs, in code point order.
+    pub counts: BTreeMap<char, usize>,
+    /// Number of characters counted.
+    pub total: usize,
+    /// Length of the characters in UTF-8.
+    pub bytes: usize,
+    /// Shannon entropy in bits per character.
+    pub entropy: f64,
+}
+
+impl CharStats {
+    /// Count the characters of `text`.
+    pub fn from_text(text: &str) -> Self {
+        CharStats::from_chars(text.chars())
+    }
+
+    /// Decode `data` as UTF-8 and count its characters.
+    ///
+    /// Fails on the first invalid sequence; the error tells where it is.
+    pub fn from_utf8(data: &[u8]) -> Result<Self, Utf8Error> {
+        str::from_utf8(data).map(CharStats::from_text)
+    }
+
+    /// Count already decoded characters.
+    ///
+    /// To fold case, so that `A` and `a` count as one character, pass
+    /// `text.chars().flat_map(char::to_lowercase)`.
+    pub fn from_chars<I: IntoIterator<Item = char>>(chars: I) -> Self {
+        let mut counts = BTreeMap::new();
+        let (mut total, mut bytes) = (0, 0);
+        for c in chars {
+            *counts.entry(c).or_insert(0) += 1;
+            total += 1;
+            bytes += c.len_utf8();
+        }
+        let occurring: Vec<usize> = counts.values().copied().collect();
+        CharStats {
+            counts,
+            total,
+            bytes,
+            entropy: entropy_from_counts(&occurring),
+        }
+    }
+
+    /// Number of distinct characters.
+    pub fn distinct(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Largest entropy for this many distinct characters, `log2(distinct)`.
+    pub fn max_entropy(&self) -> f64 {
+        (self.distinct() as f64).log2()
+    }
+
+    /// Frequency table: (character, count, fraction), in code point order.
+    pub fn frequencies(&self) -> Vec<(char, usize, f64)> {
+        let total = self.total as f64;
+        self.counts
+            .iter()
+            .map(|(&c, &count)| (c, count, count as f64 / total))
+            .collect()
+    }
+}
+
+/// The occurrence table of `ent -c`: one row per printable character that
+/// occurs, then the other bytes and the total.
+impl fmt::Display for CharTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total() as f64;
+        writeln!(f, "Value Char Occurrences Fraction")?;
+        for (value, &count) in PRINTABLE.zip(&self.printable) {
+            if count > 0 {
+                writeln!(
+                    f,
+                    "{value:3}   {}   {count:10}   {:.6}",
+                    value as char,
+                    count as f64 / total
+                )?;
+            }
+        }
+        if self.other > 0 {
+            writeln!(
+                f,
+                "Other:    {:10}   {:.6}",
+                self.other,
+                self.other as f64 / total
+            )?;
+        }
+        write!(f, "\nTotal:    {:10}   {:.6}", self.total(), 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_and_table() {
+        let table = CharTable::from_data(b"abba\x00\xFF~ ");
+        assert_eq!(table.count('a'), 2);
+        assert_eq!(table.count('~'), 1);
+        assert_eq!(table.count(' '), 1);
+        assert_eq!(table.count('\n'), 0);
+        assert_eq!(table.count('é'), 0);
+        assert_eq!(table.other, 2);
+        assert_eq!(table.total(), 8);
+        assert_eq!(
+            table.to_string(),
+            concat!(
+                "Value Char Occurrences Fraction\n",
+                " 32                1   0.125000\n",
+                " 97   a            2   0.250000\n",
+                " 98   b            2   0.250000\n",
+                "126   ~            1   0.125000\n",
+                "Other:             2   0.250000\n",
+                "\n",
+                "Total:             8   1.000000",
+            )
+        );
+        assert_eq!(table.entropy(), 2.25);
+    }
+
+    #[test]
+    fn test_char_stats_count_characters_not_bytes() {
+        let text = "日本語のテキスト、日本語";
+        let stats = CharStats::from_text(text);
+        assert_eq!(stats.total, text.chars().count());
+        assert_eq!(stats.bytes, text.len());
+        assert_eq!(stats.counts[&'日'], 2);
+        assert_eq!(stats.distinct(), 9);
+        assert!(stats.entropy <= stats.max_entropy());
+
+        let frequencies = stats.frequencies();
+        assert_eq!(frequencies.len(), 9);
+        assert_eq!(frequencies[0].0, '、');
+        assert_eq!(CharStats::from_utf8(text.as_bytes()), Ok(stats));
+
+        let mut invalid = b"ok ".to_vec();
+        invalid.extend_from_slice(&text.as_bytes()[..4]);
+        let err = CharStats::from_utf8(&invalid).unwrap_err();
+        assert_eq!(err.valid_up_to(), 6);
+
+        let folded = CharStats::from_chars("ÄäAa".chars().flat_map(char::to_lowercase));
+        assert_eq!(
+            folded.counts.into_iter().collect::<Vec<_>>(),
+            [('a', 2), ('ä', 2)]
+        );
+
+        let empty = CharStats::from_text("");
+        assert_eq!((empty.total, empty.entropy), (0, 0.0));
+    }
+
+    #[test]
+    fn test_from_stats() {
+        let data = b"The quick brown fox jumps over the lazy dog.\n";
+        let stats = EntStats::from_data(data, false);
+        assert_eq!(
+            CharTable::from_stats(&stats),
+            Some(CharTable::from_data(data))
+        );
+        assert_eq!(
+            CharTable::from_stats(&EntStats::from_data(data, true)),
+            None
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Transfer entropy between two byte streams.
+//!
+//! `TE(X→Y)` is the information the past of `X` adds about the next value of
+//! `Y` beyond what the past of `Y` already provides. It is zero when `X` has no
+//! influence on `Y`, and unlike correlation it is directional.
+//!
+//! ```rust
+//! use ent_rs::transfer::transfer_entropy_bits;
+//! // y copies a random bit stream x with a one-step delay
+//! let mut state = 0x853c_49e6_748f_ea9bu64;
+//! let x: Vec<u8> = (0..4096)
+//!     .map(|_| {
+//!         state = state.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+//!         (state >> 63) as u8
+//!     })
+//!     .collect();
+//! let mut y = vec![0u8];
+//! y.extend_from_slice(&x[..x.len() - 1]);
+//! assert!(transfer_entropy_bits(&x, &y, 1, 1) > 0.9);
+//! assert!(transfer_entropy_bits(&y, &x, 1, 1) < 0.05);
+//! ```
+
+use std::collections::HashMap;
+
+/// Transfer entropy `TE(source→target)` in bits using full bytes as symbols.
+///
+/// See [`transfer_entropy_bits`].
+pub fn transfer_entropy(source: &[u8], target: &[u8], history: usize) -> f64 {
+    transfer_entropy_bits(source, target, history, 8)
+}
+
+/// Transfer entropy `TE(source→target)` in bits, with `history` past symbols
+/// of each stream and symbols reduced to their low `symbol_bits` bits.
+///
+/// Plug-in estimates over large alphabets need a lot of data, so reducing
+/// `symbol_bits` (e.g. to 1 for sampled oscillator outputs) keeps the estimate
+/// meaningful on short captures. Streams of different length are truncated to
+/// the shorter one.
+///
+/// # Panics
+///
+/// Panics if `symbol_bits` is not in `1..=8` or `(2 * history + 1) *
+/// symbol_bits` exceeds 128.
+pub fn transfer_entropy_bits(
+    source: &[u8],
+    target: &[u8],
+    history: usize,
+    symbol_bits: u32,
+) -> f64 {
+    assert!(
+        (1..=8).contains(&symbol_bits),
+        "symbol_bits must be in 1..=8"
+    );
+    assert!(
+        (2 * history + 1) * symbol_bits as usize <= 128,
+        "history too long for symbol width"
+    );
+    let history = history.max(1);
+    let len = source.len().min(target.len());
+    if len <= history {
+        return 0.0;
+    }
+
+    let mask = if symbol_bits == 8 {
+        0xFF
+    } else {
+        (1u8 << symbol_bits) - 1
+    };
+    let pack = |stream: &[u8], end: usize| -> u128 {
+        stream[end - history..end]
+            .iter()
+            .fold(0u128, |acc, &b| (acc << symbol_bits) | (b & mask) as u128)
+    };
+
+    // Joint counts for H(Y+,Yk), H(Yk), H(Y+,Yk,Xk) and H(Yk,Xk).
+    let mut next_past: HashMap<u128, usize> = HashMap::new();
+    let mut past: HashMap<u128, usize> = HashMap::new();
+    let mut next_both: HashMap<u128, usize> = HashMap::new();
+    let mut both: HashMap<u128, usize> = HashMap::new();
+    let hist_bits = history as u32 * symbol_bits;
+
+    for t in history..len {
+        let y_past = pack(target, t);
+        let x_past = pack(source, t);
+        let y_next = (target[t] & mask) as u128;
+        let joint_past = (y_past << hist_bits) | x_past;
+        *next_past.entry((y_next << hist_bits) | y_past).or_default() += 1;
+        *past.entry(y_past).or_default() += 1;
+        *next_both
+            .entry((y_next << (2 * hist_bits)) | joint_past)
+            .or_default() += 1;
+        *both.entry(joint_past).or_default() += 1;
+    }
+
+    let samples = (len - history) as f64;
+    let h = |counts: &HashMap<u128, usize>| {
+        counts
+            .values()
+            .map(|&c| {
+                let p = c as f64 / samples;
+                -p * p.log2()
+            })
+            .sum::<f64>()
+    };
+    (h(&next_past) - h(&past) - h(&next_both) + h(&both)).max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noise(len: usize, seed: u64) -> Vec<u8> {
+        let mut x = seed;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_independent_streams_have_low_transfer_entropy() {
+        let x = noise(20_000, 1);
+        let y = noise(20_000, 2);
+        assert!(transfer_entropy_bits(&x, &y, 2, 1) < 0.01);
+    }
+
+    #[test]
+    fn test_coupled_streams_are_directional() {
+        let x = noise(20_000, 3);
+        let mut y = vec![0u8; 2];
+        y.extend_from_slice(&x[..x.len() - 2]);
+        let forward = transfer_entropy_bits(&x, &y, 2, 2);
+        let backward = transfer_entropy_bits(&y, &x, 2, 2);
+        assert!(forward > 1.9, "forward = {forward}");
+        assert!(backward < 0.05, "backward = {backward}");
+    }
+
+    #[test]
+    fn test_short_input() {
+        assert_eq!(transfer_entropy(&[1], &[2], 1), 0.0);
+    }
+}
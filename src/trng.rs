@@ -0,0 +1,62 @@
+//! Combined assessment of a TRNG's raw and conditioned output.
+//!
+//! Follows the layout of an SP 800-90B entropy-source submission: the noise
+//! source (raw samples and their min-entropy estimate), the health tests run
+//! against that estimate, and the conditioned output.
+//!
+//! ```rust
+//! use ent_rs::trng::TrngReport;
+//! let raw: Vec<u8> = (0..1u32 << 16).map(|i| (i.wrapping_mul(2_654_435_761) >> 26) as u8).collect();
+//! let conditioned: Vec<u8> = (0..1u32 << 16).map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8).collect();
+//! let report = TrngReport::assess(&raw, &conditioned);
+//! assert!(report.raw_min_entropy < 6.5);
+//! assert!(report.health.passed());
+//! ```
+
+use crate::health::{self, HealthStatus};
+use crate::sp800_90b::most_common_value;
+use crate::EntStats;
+
+/// Raw and conditioned captures from the same entropy source, assessed together.
+#[derive(Debug, Clone)]
+pub struct TrngReport {
+    /// Statistics of the raw noise samples.
+    pub raw: EntStats,
+    /// 90B min-entropy estimate of the raw samples in bits per byte.
+    pub raw_min_entropy: f64,
+    /// Health tests on the raw samples, with cutoffs for `raw_min_entropy`.
+    pub health: HealthStatus,
+    /// Statistics of the conditioned output.
+    pub conditioned: EntStats,
+    /// 90B min-entropy estimate of the conditioned output in bits per byte.
+    pub conditioned_min_entropy: f64,
+}
+
+impl TrngReport {
+    /// Assess a raw capture and a conditioned capture of the same source.
+    pub fn assess(raw: &[u8], conditioned: &[u8]) -> Self {
+        let raw_min_entropy = most_common_value(raw);
+        TrngReport {
+            raw: EntStats::from_data(raw, false),
+            raw_min_entropy,
+            health: health::check(raw, raw_min_entropy),
+            conditioned: EntStats::from_data(conditioned, false),
+            conditioned_min_entropy: most_common_value(conditioned),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stuck_raw_source_fails_health() {
+        let mut raw: Vec<u8> = (0..8192u32).map(|i| (i * 37 % 64) as u8).collect();
+        raw.extend([0x11; 64]);
+        let conditioned: Vec<u8> = (0..8192u32).map(|i| (i * 167) as u8).collect();
+        let report = TrngReport::assess(&raw, &conditioned);
+        assert!(report.health.rct_failures > 0);
+        assert!(report.conditioned_min_entropy > report.raw_min_entropy);
+    }
+}
@@ -0,0 +1,621 @@
+//! Two-pass analyses of inputs larger than memory.
+//!
+//! Some analyses need global knowledge before they can process the data:
+//! binning words needs the value range, and change-point detection needs the
+//! noise level of the statistic it tracks. These functions read
+//! the input twice in chunks of [`TwoPassConfig::chunk_size`], so memory use
+//! is bounded regardless of input size.
+//!
+//! A [`Source::Path`] is simply read twice. A [`Source::Reader`] such as
+//! standard input can only be read once, so the first pass copies it to a
+//! temporary file in [`TwoPassConfig::temp_dir`], subject to
+//! [`TwoPassConfig::max_temp_bytes`]; the file is deleted afterwards.
+//!
+//! ```rust
+//! use ent_rs::binning::Binning;
+//! use ent_rs::twopass::{binned_words, Source, TwoPassConfig, Word};
+//! let words: Vec<u8> = (0..4096u16).flat_map(|w| w.to_le_bytes()).collect();
+//! let stats = binned_words(
+//!     Source::Reader(Box::new(&words[..])),
+//!     Word::U16,
+//!     &Binning::linear(16),
+//!     &TwoPassConfig::default(),
+//!     |_| {},
+//! )
+//! .unwrap();
+//! assert_eq!(stats.range, (0.0, 4095.0));
+//! assert_eq!(stats.counts, vec![256; 16]);
+//! ```
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::binning::{BinnedStats, Binning};
+use crate::entropy_from_counts;
+
+/// Where the data of a two-pass analysis comes from.
+pub enum Source<'a> {
+    /// A file, opened once per pass.
+    Path(PathBuf),
+    /// A stream, spooled to a temporary file during the first pass.
+    Reader(Box<dyn Read + 'a>),
+}
+
+/// Resource limits of a two-pass analysis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TwoPassConfig {
+    /// Bytes read per chunk; rounded up to a multiple of 8.
+    pub chunk_size: usize,
+    /// Directory for the spool file of a [`Source::Reader`]; `None` uses the
+    /// system temporary directory.
+    pub temp_dir: Option<PathBuf>,
+    /// Largest spool file allowed; a longer stream fails with an error
+    /// instead of filling the disk. `None` means unlimited.
+    pub max_temp_bytes: Option<u64>,
+}
+
+impl Default for TwoPassConfig {
+    fn default() -> Self {
+        TwoPassConfig {
+            chunk_size: 1 << 20,
+            temp_dir: None,
+            max_temp_bytes: None,
+        }
+    }
+}
+
+/// Progress of a two-pass analysis, reported after every chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// 1 or 2.
+    pub pass: u8,
+    /// Bytes processed in this pass.
+    pub bytes: u64,
+    /// Total input size, if known (always known in the second pass).
+    pub total: Option<u64>,
+}
+
+/// Fixed-width little-endian value type of a word stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Word {
+    /// Unsigned 16-bit integers.
+    U16,
+    /// Unsigned 32-bit integers.
+    U32,
+    /// Unsigned 64-bit integers.
+    U64,
+    /// IEEE 754 single precision.
+    F32,
+    /// IEEE 754 double precision.
+    F64,
+}
+
+impl Word {
+    /// Width in bytes.
+    pub fn width(self) -> usize {
+        match self {
+            Word::U16 => 2,
+            Word::U32 | Word::F32 => 4,
+            Word::U64 | Word::F64 => 8,
+        }
+    }
+
+    fn decode(self, bytes: &[u8]) -> f64 {
+        match self {
+            Word::U16 => u16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            Word::U32 => u32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            Word::U64 => u64::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            Word::F32 => f32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            Word::F64 => f64::from_le_bytes(bytes.try_into().unwrap()),
+        }
+    }
+}
+
+/// Bin a stream of words, taking the value range from a first pass over the
+/// data unless `binning` fixes it.
+///
+/// Equal to [`BinnedStats::from_values`] on the decoded words; a trailing
+/// partial word is ignored.
+pub fn binned_words<F>(
+    source: Source,
+    word: Word,
+    binning: &Binning,
+    config: &TwoPassConfig,
+    progress: F,
+) -> io::Result<BinnedStats>
+where
+    F: FnMut(Progress),
+{
+    let binning = Binning {
+        bins: binning.bins.max(1),
+        ..binning.clone()
+    };
+    let width = word.width();
+    let mut passes = Passes::new(source, config, progress)?;
+
+    let mut extent = None;
+    passes.run(|chunk| {
+        for bytes in chunk.chunks_exact(width) {
+            extent = binning.extend_range(extent, word.decode(bytes));
+        }
+    })?;
+    let range = binning.resolve_range_from(extent);
+
+    let mut counts = vec![0usize; binning.bins];
+    let mut out_of_range = 0;
+    passes.run(|chunk| {
+        for bytes in chunk.chunks_exact(width) {
+            match binning.bin_of(word.decode(bytes), range) {
+                Some(i) => counts[i] += 1,
+                None => out_of_range += 1,
+            }
+        }
+    })?;
+    Ok(BinnedStats::from_counts(range, counts, out_of_range))
+}
+
+/// Page's CUSUM parameters, in units of the block-to-block noise of the
+/// tracked statistic.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cusum {
+    /// Slack subtracted at every block; shifts smaller than this are ignored.
+    pub drift: f64,
+    /// Cumulative deviation at which a change is declared.
+    pub threshold: f64,
+}
+
+impl Default for Cusum {
+    /// Conservative defaults: about one false alarm in 10^8 blocks of
+    /// stationary data.
+    fn default() -> Self {
+        Cusum {
+            drift: 1.0,
+            threshold: 10.0,
+        }
+    }
+}
+
+/// A point where the block entropy shifts to a different level.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChangePoint {
+    /// Offset of the first block at the new level.
+    pub offset: u64,
+    /// Offset of the block at which the change was detected.
+    pub detected_at: u64,
+    /// Whether entropy rose rather than fell.
+    pub increase: bool,
+}
+
+/// Resolution of the block-to-block difference histogram, in bits.
+const NOISE_RESOLUTION: f64 = 1e-3;
+
+/// Detect shifts in the entropy of consecutive `block`-byte blocks.
+///
+/// The first pass estimates the block-to-block noise of the entropy over the
+/// whole input, from the median absolute difference between neighboring
+/// blocks, which level shifts barely affect. The second pass runs a
+/// two-sided CUSUM against the mean level of the current segment and reports
+/// where each detected shift began; the segment after a shift starts from the
+/// mean of the blocks that triggered it. A trailing partial block is ignored.
+pub fn change_points<F>(
+    source: Source,
+    block: usize,
+    cusum: Cusum,
+    config: &TwoPassConfig,
+    progress: F,
+) -> io::Result<Vec<ChangePoint>>
+where
+    F: FnMut(Progress),
+{
+    let block = block.max(1);
+    let mut passes = Passes::new(source, config, progress)?;
+
+    // Histogram of |h[i] - h[i-1]| over [0, 8] bits.
+    let bins = (8.0 / NOISE_RESOLUTION) as usize + 1;
+    let mut differences = vec![0usize; bins];
+    let mut previous: Option<f64> = None;
+    let mut blocks = Blocks::new(block);
+    passes.run(|chunk| {
+        blocks.feed(chunk, |h| {
+            if let Some(p) = previous.replace(h) {
+                let bin = ((h - p).abs() / NOISE_RESOLUTION) as usize;
+                differences[bin.min(bins - 1)] += 1;
+            }
+        })
+    })?;
+    let pairs: usize = differences.iter().sum();
+    if pairs == 0 {
+        return Ok(Vec::new());
+    }
+    let median_bin = differences
+        .iter()
+        .scan(0, |seen, &c| {
+            *seen += c;
+            Some(*seen)
+        })
+        .position(|seen| 2 * seen >= pairs)
+        .unwrap_or(0);
+    // For normal noise, median |X - Y| = 0.6745 * sqrt(2) * sigma.
+    let sigma = ((median_bin as f64 + 0.5) * NOISE_RESOLUTION) / (0.6745 * 2f64.sqrt());
+
+    let mut found = Vec::new();
+    let mut segment = Running::default();
+    let (mut up, mut down) = (Running::default(), Running::default());
+    let (mut up_score, mut down_score) = (0.0f64, 0.0f64);
+    let mut offset = 0u64;
+    let mut blocks = Blocks::new(block);
+    passes.run(|chunk| {
+        blocks.feed(chunk, |h| {
+            let z = match segment.mean() {
+                Some(level) => (h - level) / sigma,
+                None => 0.0,
+            };
+            if up_score == 0.0 {
+                up = Running::starting_at(offset);
+            }
+            if down_score == 0.0 {
+                down = Running::starting_at(offset);
+            }
+            up.add(h);
+            down.add(h);
+            segment.add(h);
+            up_score = (up_score + z - cusum.drift).max(0.0);
+            down_score = (down_score - z - cusum.drift).max(0.0);
+
+            let increase = up_score > cusum.threshold;
+            if increase || down_score > cusum.threshold {
+                let shifted = if increase { up } else { down };
+                found.push(ChangePoint {
+                    offset: shifted.start,
+                    detected_at: offset,
+                    increase,
+                });
+                segment = shifted;
+                up_score = 0.0;
+                down_score = 0.0;
+            }
+            offset += block as u64;
+        })
+    })?;
+    Ok(found)
+}
+
+/// Running mean of the blocks since `start`.
+#[derive(Debug, Clone, Copy, Default)]
+struct Running {
+    start: u64,
+    sum: f64,
+    n: usize,
+}
+
+impl Running {
+    fn starting_at(start: u64) -> Self {
+        Running {
+            start,
+            ..Running::default()
+        }
+    }
+
+    fn add(&mut self, h: f64) {
+        self.sum += h;
+        self.n += 1;
+    }
+
+    fn mean(&self) -> Option<f64> {
+        (self.n > 0).then(|| self.sum / self.n as f64)
+    }
+}
+
+/// Splits a chunked stream into fixed-size blocks and yields their entropy.
+struct Blocks {
+    block: usize,
+    counts: [usize; 256],
+    filled: usize,
+}
+
+impl Blocks {
+    fn new(block: usize) -> Self {
+        Blocks {
+            block,
+            counts: [0; 256],
+            filled: 0,
+        }
+    }
+
+    fn feed(&mut self, chunk: &[u8], mut on_block: impl FnMut(f64)) {
+        for &b in chunk {
+            self.counts[b as usize] += 1;
+            self.filled += 1;
+            if self.filled == self.block {
+                on_block(entropy_from_counts(&self.counts));
+                self.counts = [0; 256];
+                self.filled = 0;
+            }
+        }
+    }
+}
+
+/// Reads a [`Source`] once per call to [`run`](Passes::run), spooling a
+/// reader to a temporary file on the first pass.
+struct Passes<'a, F> {
+    source: Source<'a>,
+    spool: Option<Spool>,
+    chunk_size: usize,
+    temp_dir: PathBuf,
+    max_temp_bytes: Option<u64>,
+    pass: u8,
+    progress: F,
+}
+
+impl<'a, F: FnMut(Progress)> Passes<'a, F> {
+    fn new(source: Source<'a>, config: &TwoPassConfig, progress: F) -> io::Result<Self> {
+        Ok(Passes {
+            source,
+            spool: None,
+            chunk_size: config.chunk_size.max(1).next_multiple_of(8),
+            temp_dir: config.temp_dir.clone().unwrap_or_else(std::env::temp_dir),
+            max_temp_bytes: config.max_temp_bytes,
+            pass: 0,
+            progress,
+        })
+    }
+
+    fn run(&mut self, mut on_chunk: impl FnMut(&[u8])) -> io::Result<()> {
+        self.pass += 1;
+        let (mut input, total, mut writer): (Box<dyn Read + '_>, _, _) =
+            match (&mut self.source, &self.spool) {
+                (Source::Path(path), _) => {
+                    let file = File::open(path)?;
+                    let len = file.metadata()?.len();
+                    (Box::new(file), Some(len), None)
+                }
+                (Source::Reader(_), Some(spool)) => {
+                    let file = File::open(&spool.path)?;
+                    (Box::new(file), Some(spool.len), None)
+                }
+                (Source::Reader(reader), None) => {
+                    let (spool, file) = Spool::create(&self.temp_dir)?;
+                    self.spool = Some(spool);
+                    (Box::new(reader), None, Some(io::BufWriter::new(file)))
+                }
+            };
+
+        let mut buf = vec![0u8; self.chunk_size];
+        let mut bytes = 0u64;
+        loop {
+            let n = fill(&mut input, &mut buf)?;
+            if n == 0 {
+                break;
+            }
+            if let Some(writer) = &mut writer {
+                if self
+                    .max_temp_bytes
+                    .is_some_and(|max| bytes + n as u64 > max)
+                {
+                    return Err(io::Error::other("input exceeds the temporary file limit"));
+                }
+                writer.write_all(&buf[..n])?;
+            }
+            on_chunk(&buf[..n]);
+            bytes += n as u64;
+            (self.progress)(Progress {
+                pass: self.pass,
+                bytes,
+                total,
+            });
+        }
+        if let Some(mut writer) = writer {
+            writer.flush()?;
+            if let Some(spool) = &mut self.spool {
+                spool.len = bytes;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Temporary copy of a stream, deleted on drop.
+struct Spool {
+    path: PathBuf,
+    len: u64,
+}
+
+impl Spool {
+    /// A new spool file in `dir`, readable only by its owner.
+    fn create(dir: &Path) -> io::Result<(Self, File)> {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        Spool::create_with(dir, || NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Like [`Spool::create`], numbering candidate names with `next`.
+    ///
+    /// The file must not exist yet, so a file or symbolic link planted at a
+    /// predictable name is skipped rather than followed and truncated.
+    fn create_with(dir: &Path, mut next: impl FnMut() -> usize) -> io::Result<(Self, File)> {
+        const ATTEMPTS: usize = 100;
+        fs::create_dir_all(dir)?;
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        for _ in 0..ATTEMPTS {
+            let path = dir.join(format!("ent-rs-spool-{}-{}", std::process::id(), next()));
+            match options.open(&path) {
+                Ok(file) => return Ok((Spool { path, len: 0 }, file)),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "no unused temporary file name",
+        ))
+    }
+}
+
+impl Drop for Spool {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Read until `buf` is full or the input ends; returns the bytes read.
+fn fill<R: Read + ?Sized>(input: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut n = 0;
+    while n < buf.len() {
+        match input.read(&mut buf[n..]) {
+            Ok(0) => break,
+            Ok(k) => n += k,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noise(len: usize, seed: u64) -> Vec<u8> {
+        let mut x = seed;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_binned_words_match_in_memory_binning() {
+        let bytes = noise(40_003, 0x853C_49E6_748F_EA9B);
+        let words: Vec<f64> = bytes
+            .chunks_exact(4)
+            .map(|w| u32::from_le_bytes(w.try_into().unwrap()) as f64)
+            .collect();
+        let binning = Binning::log(12);
+        let expected = BinnedStats::from_values(&words, &binning);
+
+        let mut reports = Vec::new();
+        let config = TwoPassConfig {
+            chunk_size: 1000,
+            ..TwoPassConfig::default()
+        };
+        let stats = binned_words(
+            Source::Reader(Box::new(&bytes[..])),
+            Word::U32,
+            &binning,
+            &config,
+            |p| reports.push(p),
+        )
+        .unwrap();
+        assert_eq!(stats.range, expected.range);
+        assert_eq!(stats.counts, expected.counts);
+        assert_eq!(stats.entropy, expected.entropy);
+
+        // 41 chunks per pass; the spooled stream's size is known in pass 2.
+        assert_eq!(reports.len(), 82);
+        assert_eq!(reports[0].total, None);
+        let last = reports.last().unwrap();
+        assert_eq!(
+            (last.pass, last.bytes, last.total),
+            (2, 40_003, Some(40_003))
+        );
+    }
+
+    #[test]
+    fn test_change_points_in_file() {
+        let mut data = noise(64 * 1024, 7);
+        data.extend((0..32 * 1024).map(|i| (i % 16) as u8));
+        data.extend(noise(64 * 1024, 11));
+        let path = std::env::temp_dir().join(format!("ent-rs-twopass-{}", std::process::id()));
+        fs::write(&path, &data).unwrap();
+
+        let found = change_points(
+            Source::Path(path.clone()),
+            1024,
+            Cusum::default(),
+            &TwoPassConfig::default(),
+            |_| {},
+        )
+        .unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(found.len(), 2, "{found:?}");
+        assert_eq!(found[0].offset, 64 * 1024);
+        assert!(!found[0].increase);
+        assert_eq!(found[1].offset, 96 * 1024);
+        assert!(found[1].increase);
+
+        let stationary = noise(256 * 1024, 13);
+        let found = change_points(
+            Source::Reader(Box::new(&stationary[..])),
+            1024,
+            Cusum::default(),
+            &TwoPassConfig::default(),
+            |_| {},
+        )
+        .unwrap();
+        assert!(found.is_empty(), "{found:?}");
+    }
+
+    #[test]
+    fn test_spool_skips_existing_paths() {
+        let dir = std::env::temp_dir().join(format!("ent-rs-spool-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let name = |n: usize| dir.join(format!("ent-rs-spool-{}-{n}", std::process::id()));
+        fs::write(name(0), b"keep").unwrap();
+        #[cfg(unix)]
+        {
+            fs::write(dir.join("target"), b"keep").unwrap();
+            std::os::unix::fs::symlink(dir.join("target"), name(1)).unwrap();
+        }
+        #[cfg(not(unix))]
+        fs::write(name(1), b"keep").unwrap();
+
+        let mut n = 0;
+        let (spool, mut file) = Spool::create_with(&dir, || {
+            n += 1;
+            n - 1
+        })
+        .unwrap();
+        assert_eq!(spool.path, name(2));
+        file.write_all(b"spooled").unwrap();
+        assert_eq!(fs::read(name(0)).unwrap(), b"keep");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::read(dir.join("target")).unwrap(), b"keep");
+            let mode = fs::metadata(&spool.path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        drop(spool);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_spool_limit() {
+        let data = vec![0u8; 10_000];
+        let config = TwoPassConfig {
+            chunk_size: 4096,
+            max_temp_bytes: Some(5000),
+            ..TwoPassConfig::default()
+        };
+        let err = change_points(
+            Source::Reader(Box::new(&data[..])),
+            100,
+            Cusum::default(),
+            &config,
+            |_| {},
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Other);
+    }
+}
@@ -0,0 +1,71 @@
+//! WebAssembly bindings for browser tools.
+//!
+//! Built with
+//! `cargo rustc --release --lib --target wasm32-unknown-unknown --no-default-features --features wasm --crate-type cdylib`
+//! (the `statrs` dependency does not build for `wasm32-unknown-unknown`)
+//! and bound with
+//! `wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/ent_rs.wasm`,
+//! the functions below take a `Uint8Array` and return plain objects, so a
+//! page can analyze a dropped file without uploading it:
+//!
+//! ```js
+//! import init, { analyze, entropyProfile } from "./pkg/ent_rs.js";
+//! await init();
+//! const bytes = new Uint8Array(await file.arrayBuffer());
+//! console.log(analyze(bytes).entropy);
+//! const profile = entropyProfile(bytes, 1000);
+//! plot(profile.points.map((p) => [p.offset, p.entropy]));
+//! ```
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::profile;
+use crate::EntStats;
+
+/// Byte-mode statistics of `data`, with the fields of [`EntStats`].
+#[wasm_bindgen]
+pub fn analyze(data: &[u8]) -> Result<JsValue, JsError> {
+    to_js(&EntStats::from_data(data, false))
+}
+
+/// Bit-mode statistics of `data`, with the fields of [`EntStats`].
+#[wasm_bindgen(js_name = analyzeBits)]
+pub fn analyze_bits(data: &[u8]) -> Result<JsValue, JsError> {
+    to_js(&EntStats::from_data(data, true))
+}
+
+/// Entropy-vs-offset profile of `data` with at most `target_points` points,
+/// with the fields of [`EntropyProfile`](profile::EntropyProfile).
+#[wasm_bindgen(js_name = entropyProfile)]
+pub fn entropy_profile(data: &[u8], target_points: usize) -> Result<JsValue, JsError> {
+    to_js(&profile::entropy_profile(data, target_points))
+}
+
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsError> {
+    serde_wasm_bindgen::to_value(value).map_err(|e| JsError::new(&e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The bindings only run on wasm32; natively, check the values they
+    // serialize have the fields the JavaScript example reads.
+    #[test]
+    fn test_output_shape() {
+        let data: Vec<u8> = (0..=255).cycle().take(4096).collect();
+        let stats = serde_json::to_value(EntStats::from_data(&data, false)).unwrap();
+        assert_eq!(stats["entropy"], 8.0);
+        assert!(stats["bit_frequencies"].is_null());
+        let bits = serde_json::to_value(EntStats::from_data(&data, true)).unwrap();
+        assert_eq!(bits["bit_frequencies"][1][0], 4 * 4096);
+        assert_eq!(bits["bit_frequencies"][1][1], 0.5);
+
+        let profile = serde_json::to_value(profile::entropy_profile(&data, 8)).unwrap();
+        let points = profile["points"].as_array().unwrap();
+        assert_eq!(points.len(), 8);
+        assert_eq!(points[1]["offset"], 512);
+        assert_eq!(points[1]["entropy"], 8.0);
+    }
+}
@@ -0,0 +1,424 @@
+//! Live statistics over the most recent bytes of a stream, and fixed
+//! window placement over a buffer.
+//!
+//! ```rust
+//! use ent_rs::window::WindowAnalyzer;
+//! let mut live = WindowAnalyzer::new(4096);
+//! live.extend(&[0u8; 10_000]);
+//! assert_eq!(live.len(), 4096);
+//! assert_eq!(live.current_stats().entropy, 0.0);
+//! ```
+//!
+//! # Overlapping or disjoint windows
+//!
+//! [`Windowing`] selects between disjoint windows (each byte analyzed once)
+//! and overlapping windows (a step smaller than the window). Overlap gives
+//! finer offset resolution and smoother plots, but adjacent results share
+//! most of their bytes and are strongly correlated:
+//!
+//! - they are not independent samples, so they must not be fed to
+//!   goodness-of-fit tests over p-values or other second-level analyses;
+//! - one short anomaly shows up in up to `window / step` consecutive results,
+//!   which inflates event counts and false-alarm rates computed per window;
+//! - the number of results grows with `window / step`, so multiple-comparison
+//!   corrections must not treat them as independent tests.
+//!
+//! Use disjoint windows whenever results are counted or tested statistically,
+//! and overlapping windows for locating boundaries and for display.
+//!
+//! ```rust
+//! use ent_rs::window::{windowed_stats, Windowing};
+//! let data = vec![7u8; 4096];
+//! assert_eq!(windowed_stats(&data, Windowing::disjoint(1024), false).len(), 4);
+//! assert_eq!(windowed_stats(&data, Windowing::overlapping(1024, 256), false).len(), 13);
+//! ```
+
+use std::collections::VecDeque;
+
+use crate::{EntStats, Sums};
+
+/// Ring buffer holding the last `capacity` bytes pushed, with the running
+/// sums needed to report [`EntStats`] for exactly those bytes.
+///
+/// Each push and eviction updates the sums in constant time, so the analyzer
+/// can sit on a live stream and be queried at any point.
+#[derive(Debug, Clone)]
+pub struct WindowAnalyzer {
+    bit_mode: bool,
+    capacity: usize,
+    buf: VecDeque<u8>,
+    /// Absolute stream position of `buf[0]`.
+    start: u64,
+    counts: [usize; 256],
+    sum: u64,
+    /// Sums of x, y, xy, x², y² over adjacent pairs in the window.
+    scc: [u64; 5],
+    /// Monte Carlo hits and tries for complete 6-byte tuples, by start position mod 6.
+    pi_hits: [usize; 6],
+    pi_total: [usize; 6],
+}
+
+impl WindowAnalyzer {
+    /// Create an analyzer over the most recent `capacity` bytes (byte mode).
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        WindowAnalyzer {
+            bit_mode: false,
+            capacity,
+            buf: VecDeque::with_capacity(capacity),
+            start: 0,
+            counts: [0; 256],
+            sum: 0,
+            scc: [0; 5],
+            pi_hits: [0; 6],
+            pi_total: [0; 6],
+        }
+    }
+
+    /// Report statistics in bit mode instead of byte mode.
+    pub fn bit_mode(mut self, bit_mode: bool) -> Self {
+        self.bit_mode = bit_mode;
+        self
+    }
+
+    /// Maximum number of bytes kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of bytes currently in the window.
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Stream position of the oldest byte in the window.
+    pub fn start_offset(&self) -> u64 {
+        self.start
+    }
+
+    /// Whether no bytes have been pushed yet.
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    /// Append a byte, evicting the oldest one if the window is full.
+    pub fn push(&mut self, b: u8) {
+        if self.buf.len() == self.capacity {
+            self.evict();
+        }
+        if let Some(&prev) = self.buf.back() {
+            self.add_pair(prev, b, true);
+        }
+        self.buf.push_back(b);
+        self.counts[b as usize] += 1;
+        self.sum += b as u64;
+
+        let len = self.buf.len();
+        if len >= 6 {
+            let tuple_start = len - 6;
+            let phase = ((self.start + tuple_start as u64) % 6) as usize;
+            if self.tuple_hit(tuple_start) {
+                self.pi_hits[phase] += 1;
+            }
+            self.pi_total[phase] += 1;
+        }
+    }
+
+    /// Append every byte of `data`.
+    pub fn extend(&mut self, data: &[u8]) {
+        for &b in data {
+            self.push(b);
+        }
+    }
+
+    /// Statistics for the bytes currently in the window.
+    ///
+    /// Equal to [`EntStats::from_data`] on the window contents.
+    pub fn current_stats(&self) -> EntStats {
+        let phase = (self.start % 6) as usize;
+        Sums {
+            bit_mode: self.bit_mode,
+            counts: self.counts,
+            total: self.buf.len(),
+            sum: self.sum,
+            pi_hits: self.pi_hits[phase],
+            pi_total: self.pi_total[phase],
+            scc: self.scc,
+        }
+        .finalize()
+    }
+
+    fn evict(&mut self) {
+        if self.buf.len() >= 6 {
+            let phase = (self.start % 6) as usize;
+            if self.tuple_hit(0) {
+                self.pi_hits[phase] -= 1;
+            }
+            self.pi_total[phase] -= 1;
+        }
+        if self.buf.len() >= 2 {
+            self.add_pair(self.buf[0], self.buf[1], false);
+        }
+        if let Some(b) = self.buf.pop_front() {
+            self.counts[b as usize] -= 1;
+            self.sum -= b as u64;
+            self.start += 1;
+        }
+    }
+
+    fn add_pair(&mut self, x: u8, y: u8, add: bool) {
+        let (x, y) = (x as u64, y as u64);
+        let terms = [x, y, x * y, x * x, y * y];
+        for (acc, t) in self.scc.iter_mut().zip(terms) {
+            if add {
+                *acc += t;
+            } else {
+                *acc -= t;
+            }
+        }
+    }
+
+    fn tuple_hit(&self, index: usize) -> bool {
+        let mut tuple = [0u8; 6];
+        for (i, t) in tuple.iter_mut().enumerate() {
+            *t = self.buf[index + i];
+        }
+        crate::monte_carlo_hit(&tuple)
+    }
+}
+
+/// Placement of fixed-size windows over a buffer.
+///
+/// Only complete windows are produced; a trailing remainder shorter than the
+/// window is not analyzed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Windowing {
+    window: usize,
+    step: usize,
+}
+
+impl Windowing {
+    /// Back-to-back windows of `window` bytes.
+    pub fn disjoint(window: usize) -> Self {
+        let window = window.max(1);
+        Windowing {
+            window,
+            step: window,
+        }
+    }
+
+    /// Windows of `window` bytes starting every `step` bytes.
+    ///
+    /// A step of at least `window` makes the windows disjoint (leaving gaps
+    /// between them if larger).
+    pub fn overlapping(window: usize, step: usize) -> Self {
+        Windowing {
+            window: window.max(1),
+            step: step.max(1),
+        }
+    }
+
+    /// Bytes per window.
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Distance between the starts of consecutive windows.
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// Whether consecutive windows share bytes.
+    pub fn is_overlapping(&self) -> bool {
+        self.step < self.window
+    }
+
+    /// Number of windows over `len` bytes.
+    pub fn count(&self, len: usize) -> usize {
+        if len < self.window {
+            0
+        } else {
+            (len - self.window) / self.step + 1
+        }
+    }
+
+    /// Start offsets of the windows over `len` bytes.
+    pub fn offsets(&self, len: usize) -> impl Iterator<Item = usize> {
+        let step = self.step;
+        (0..self.count(len)).map(move |i| i * step)
+    }
+}
+
+/// Statistics of one window of a buffer.
+#[derive(Debug, Clone)]
+pub struct WindowStats {
+    /// Offset of the window's first byte.
+    pub offset: usize,
+    /// Statistics of the window.
+    pub stats: EntStats,
+}
+
+/// Compute statistics for each window of `data` placed by `windowing`.
+///
+/// Overlapping windows are computed by sliding a [`WindowAnalyzer`], so the
+/// cost stays proportional to the input size however small the step is.
+pub fn windowed_stats(data: &[u8], windowing: Windowing, bit_mode: bool) -> Vec<WindowStats> {
+    let window = windowing.window();
+    if !windowing.is_overlapping() {
+        return windowing
+            .offsets(data.len())
+            .map(|offset| WindowStats {
+                offset,
+                stats: EntStats::from_data(&data[offset..offset + window], bit_mode),
+            })
+            .collect();
+    }
+
+    let mut live = WindowAnalyzer::new(window).bit_mode(bit_mode);
+    let mut fed = 0;
+    windowing
+        .offsets(data.len())
+        .map(|offset| {
+            live.extend(&data[fed..offset + window]);
+            fed = offset + window;
+            WindowStats {
+                offset,
+                stats: live.current_stats(),
+            }
+        })
+        .collect()
+}
+
+/// Statistics of each block of a buffer and of the whole buffer.
+#[derive(Debug, Clone)]
+pub struct BlockStats {
+    /// One entry per block, in order.
+    pub blocks: Vec<WindowStats>,
+    /// Statistics of the whole buffer.
+    pub aggregate: EntStats,
+}
+
+impl EntStats {
+    /// Statistics of each `block_size`-byte block of `data`, and of all of
+    /// it.
+    ///
+    /// Unlike [`windowed_stats`], a trailing partial block is included, so
+    /// every byte belongs to exactly one block. A `block_size` of 0 is
+    /// treated as 1.
+    ///
+    /// ```rust
+    /// use ent_rs::EntStats;
+    /// let mut data = vec![0u8; 1000];
+    /// data.extend((0..=255u8).cycle().take(1500));
+    /// let per_block = EntStats::per_block(&data, 1024, false);
+    /// assert_eq!(per_block.blocks.len(), 3);
+    /// assert_eq!(per_block.blocks[2].offset, 2048);
+    /// assert!(per_block.blocks[0].stats.entropy < per_block.blocks[1].stats.entropy);
+    /// ```
+    pub fn per_block(data: &[u8], block_size: usize, bit_mode: bool) -> BlockStats {
+        let blocks = data
+            .chunks(block_size.max(1))
+            .zip((0..).step_by(block_size.max(1)))
+            .map(|(block, offset)| WindowStats {
+                offset,
+                stats: EntStats::from_data(block, bit_mode),
+            })
+            .collect();
+        BlockStats {
+            blocks,
+            aggregate: EntStats::from_data(data, bit_mode),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(len: usize) -> Vec<u8> {
+        let mut x = 0x2545_f491u32;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 17;
+                x ^= x << 5;
+                (x >> 24) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_window_matches_from_data() {
+        let data = sample(5000);
+        let mut live = WindowAnalyzer::new(1000);
+        for (i, &b) in data.iter().enumerate() {
+            live.push(b);
+            if i % 617 == 0 || i == data.len() - 1 {
+                let lo = (i + 1).saturating_sub(1000);
+                let expected = EntStats::from_data(&data[lo..=i], false);
+                let got = live.current_stats();
+                assert_eq!(got.entropy, expected.entropy);
+                assert_eq!(got.chisquare, expected.chisquare);
+                assert_eq!(got.mean, expected.mean);
+                assert_eq!(got.pi_estimate, expected.pi_estimate);
+                assert_eq!(got.serial_correlation, expected.serial_correlation);
+            }
+        }
+        assert_eq!(live.len(), 1000);
+    }
+
+    #[test]
+    fn test_per_block_covers_every_byte() {
+        let data = sample(2500);
+        for bit_mode in [false, true] {
+            let per_block = EntStats::per_block(&data, 1000, bit_mode);
+            let offsets: Vec<usize> = per_block.blocks.iter().map(|b| b.offset).collect();
+            assert_eq!(offsets, [0, 1000, 2000]);
+            let last = EntStats::from_data(&data[2000..], bit_mode);
+            assert_eq!(per_block.blocks[2].stats.entropy, last.entropy);
+            assert_eq!(
+                per_block.blocks[2].stats.bit_frequencies,
+                last.bit_frequencies
+            );
+            let whole = EntStats::from_data(&data, bit_mode);
+            assert_eq!(per_block.aggregate.entropy, whole.entropy);
+            assert_eq!(
+                per_block.aggregate.serial_correlation,
+                whole.serial_correlation
+            );
+        }
+        assert!(EntStats::per_block(&[], 16, false).blocks.is_empty());
+        assert_eq!(EntStats::per_block(&data[..3], 0, false).blocks.len(), 3);
+    }
+
+    #[test]
+    fn test_window_bit_mode() {
+        let mut live = WindowAnalyzer::new(64).bit_mode(true);
+        live.extend(&[0xFF; 100]);
+        live.extend(&[0x0F; 64]);
+        let stats = live.current_stats();
+        assert!((stats.entropy - 1.0).abs() < 1e-12);
+        assert!(stats.bit_frequencies.is_some());
+    }
+
+    #[test]
+    fn test_windowed_stats_modes_agree() {
+        let data = sample(10_000);
+        let overlapping = windowed_stats(&data, Windowing::overlapping(1000, 300), false);
+        assert_eq!(
+            overlapping.len(),
+            Windowing::overlapping(1000, 300).count(10_000)
+        );
+        for w in &overlapping {
+            let expected = EntStats::from_data(&data[w.offset..w.offset + 1000], false);
+            assert_eq!(w.stats.entropy, expected.entropy);
+            assert_eq!(w.stats.serial_correlation, expected.serial_correlation);
+        }
+
+        let disjoint = windowed_stats(&data, Windowing::disjoint(1000), false);
+        let offsets: Vec<usize> = disjoint.iter().map(|w| w.offset).collect();
+        assert_eq!(offsets, (0..10).map(|i| i * 1000).collect::<Vec<_>>());
+        assert!(!Windowing::overlapping(100, 150).is_overlapping());
+        assert_eq!(Windowing::overlapping(100, 150).count(400), 3);
+    }
+}